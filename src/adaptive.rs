@@ -0,0 +1,254 @@
+//! Contains adaptive difficulty, where the AI's strength follows the player's
+//! skill.
+//!
+//! The player's skill is tracked with an Elo style rating. Every AI difficulty
+//! has a fixed rating based on its chance of making a mistake, and after each
+//! game the player's rating moves towards the strength they played at. The next
+//! game is played against the AI the player is expected to score
+//! `TARGET_SCORE` against, counting a draw as half a win.
+use open_ttt_lib as ttt;
+use serde::{Deserialize, Serialize};
+
+use crate::daily_challenge::ChallengeResult;
+use crate::file_io;
+use crate::variants;
+
+/// The rating players start with, which is played against the easy AI.
+pub const STARTING_RATING: f64 = 1000.0;
+
+/// The score the player is expected to get each game, where a win scores 1
+/// and a draw scores 0.5.
+pub const TARGET_SCORE: f64 = 0.5;
+
+// The ratings of the AI that never makes a mistake and the one that always
+// does. The ratings of the other difficulties are spread evenly in between.
+const UNBEATABLE_AI_RATING: f64 = 1600.0;
+const RANDOM_AI_RATING: f64 = 400.0;
+
+// How far the rating can move after a single game. New players move further
+// so their rating quickly reaches their skill.
+const K_FACTOR: f64 = 32.0;
+const PROVISIONAL_K_FACTOR: f64 = 64.0;
+const PROVISIONAL_GAMES: u32 = 10;
+
+// The rating difference where the stronger player is expected to score ten
+// times as much as the weaker one.
+const RATING_SCALE: f64 = 400.0;
+
+/// The number of recent results kept.
+pub const NUM_RECENT_RESULTS: usize = 20;
+
+/// Gets the rating of an AI with the provided difficulty.
+pub fn ai_rating(difficulty: ttt::ai::Difficulty) -> f64 {
+    let probability = variants::mistake_probability(difficulty);
+    UNBEATABLE_AI_RATING - (UNBEATABLE_AI_RATING - RANDOM_AI_RATING) * probability
+}
+
+/// Gets the score the player is expected to get against an opponent, from 0
+/// for a certain loss to 1 for a certain win.
+pub fn expected_score(rating: f64, opponent_rating: f64) -> f64 {
+    1.0 / (1.0 + 10.0_f64.powf((opponent_rating - rating) / RATING_SCALE))
+}
+
+/// The player's adaptive difficulty rating, saved between runs of the game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerRating {
+    /// The player's current rating.
+    pub rating: f64,
+
+    /// The number of games played.
+    pub games: u32,
+
+    /// The results of the most recent games, oldest first.
+    pub recent_results: Vec<ChallengeResult>,
+
+    /// The player's rating after each of the most recent games, oldest first.
+    pub recent_ratings: Vec<f64>,
+}
+
+impl Default for PlayerRating {
+    fn default() -> Self {
+        Self {
+            rating: STARTING_RATING,
+            games: 0,
+            recent_results: Vec::new(),
+            recent_ratings: Vec::new(),
+        }
+    }
+}
+
+impl PlayerRating {
+    /// Reads the saved rating, logging any problems.
+    ///
+    /// The starting rating is used the first time an adaptive game is played
+    /// or if the rating cannot be read.
+    pub fn load() -> Self {
+        file_io::load_or_default(file_io::player_rating_file, "the player's rating")
+    }
+
+    /// Saves the rating, logging any problems.
+    pub fn save(&self) {
+        file_io::save(file_io::player_rating_file, self, "the player's rating");
+    }
+
+    /// Gets the AI difficulty for the next game.
+    ///
+    /// This is the AI the player is expected to get `TARGET_SCORE` against,
+    /// with its chance of a mistake rounded to a whole percent.
+    pub fn difficulty(&self) -> ttt::ai::Difficulty {
+        let opponent_rating = self.rating + RATING_SCALE * (1.0 / TARGET_SCORE - 1.0).log10();
+        let probability =
+            (UNBEATABLE_AI_RATING - opponent_rating) / (UNBEATABLE_AI_RATING - RANDOM_AI_RATING);
+        let percent = (probability * 100.0).round().clamp(0.0, 100.0);
+        ttt::ai::Difficulty::Custom(percent / 100.0)
+    }
+
+    /// Records the result of a game played against the AI with the provided
+    /// difficulty, updating the player's rating.
+    pub fn record_result(&mut self, result: ChallengeResult, difficulty: ttt::ai::Difficulty) {
+        let score = match result {
+            ChallengeResult::Win => 1.0,
+            ChallengeResult::Draw => 0.5,
+            ChallengeResult::Loss => 0.0,
+        };
+        let k_factor = if self.games < PROVISIONAL_GAMES {
+            PROVISIONAL_K_FACTOR
+        } else {
+            K_FACTOR
+        };
+        self.rating += k_factor * (score - expected_score(self.rating, ai_rating(difficulty)));
+        self.games += 1;
+
+        self.recent_results.push(result);
+        if self.recent_results.len() > NUM_RECENT_RESULTS {
+            self.recent_results.remove(0);
+        }
+        self.recent_ratings.push(self.rating);
+        if self.recent_ratings.len() > NUM_RECENT_RESULTS {
+            self.recent_ratings.remove(0);
+        }
+    }
+
+    /// Describes the recent results for showing to the player, such as
+    /// "Won 3, drew 5, lost 2".
+    pub fn recent_summary(&self) -> String {
+        let count = |result| {
+            self.recent_results
+                .iter()
+                .filter(|recent| **recent == result)
+                .count()
+        };
+        format!(
+            "Won {}, drew {}, lost {}",
+            count(ChallengeResult::Win),
+            count(ChallengeResult::Draw),
+            count(ChallengeResult::Loss)
+        )
+    }
+
+    /// Describes how the rating changed over the recent games for showing to
+    /// the player, such as "1000, 1032, 1018".
+    pub fn recent_history(&self) -> String {
+        self.recent_ratings
+            .iter()
+            .map(|rating| format!("{:.0}", rating))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mistake_percent(difficulty: ttt::ai::Difficulty) -> u32 {
+        (variants::mistake_probability(difficulty) * 100.0).round() as u32
+    }
+
+    #[test]
+    fn ai_rating_should_rate_harder_difficulties_higher() {
+        let difficulties = [
+            ttt::ai::Difficulty::None,
+            ttt::ai::Difficulty::Easy,
+            ttt::ai::Difficulty::Medium,
+            ttt::ai::Difficulty::Hard,
+            ttt::ai::Difficulty::Unbeatable,
+        ];
+
+        let ratings: Vec<f64> = difficulties.iter().map(|d| ai_rating(*d)).collect();
+
+        assert!(ratings.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn player_rating_difficulty_when_new_player_should_be_easy() {
+        let rating = PlayerRating::default();
+
+        let difficulty = rating.difficulty();
+
+        assert_eq!(
+            mistake_percent(difficulty),
+            mistake_percent(ttt::ai::Difficulty::Easy)
+        );
+    }
+
+    #[test]
+    fn player_rating_record_result_when_win_should_make_ai_harder() {
+        let mut rating = PlayerRating::default();
+        let difficulty = rating.difficulty();
+
+        rating.record_result(ChallengeResult::Win, difficulty);
+
+        assert!(rating.rating > STARTING_RATING);
+        assert!(mistake_percent(rating.difficulty()) < mistake_percent(difficulty));
+    }
+
+    #[test]
+    fn player_rating_record_result_when_draw_at_target_should_keep_rating() {
+        let mut rating = PlayerRating::default();
+        let difficulty = rating.difficulty();
+
+        rating.record_result(ChallengeResult::Draw, difficulty);
+
+        assert!((rating.rating - STARTING_RATING).abs() < 1e-9);
+    }
+
+    #[test]
+    fn player_rating_record_result_when_many_games_should_keep_recent_results() {
+        let mut rating = PlayerRating::default();
+        rating.record_result(ChallengeResult::Loss, ttt::ai::Difficulty::Easy);
+
+        for _ in 0..NUM_RECENT_RESULTS {
+            rating.record_result(ChallengeResult::Win, ttt::ai::Difficulty::Easy);
+        }
+
+        assert_eq!(rating.games, NUM_RECENT_RESULTS as u32 + 1);
+        assert_eq!(rating.recent_results.len(), NUM_RECENT_RESULTS);
+        assert_eq!(rating.recent_summary(), "Won 20, drew 0, lost 0");
+    }
+
+    #[test]
+    fn player_rating_record_result_when_many_games_should_keep_recent_ratings() {
+        let mut rating = PlayerRating::default();
+
+        for _ in 0..=NUM_RECENT_RESULTS {
+            rating.record_result(ChallengeResult::Win, ttt::ai::Difficulty::Easy);
+        }
+
+        assert_eq!(rating.recent_ratings.len(), NUM_RECENT_RESULTS);
+        assert_eq!(rating.recent_ratings.last(), Some(&rating.rating));
+    }
+
+    #[test]
+    fn player_rating_recent_history_should_list_ratings_oldest_first() {
+        let rating = PlayerRating {
+            recent_ratings: vec![1032.4, 1017.6],
+            ..PlayerRating::default()
+        };
+
+        let history = rating.recent_history();
+
+        assert_eq!(history, "1032, 1018");
+    }
+}
@@ -0,0 +1,393 @@
+//! Contains the bot arena, which runs tournaments between bots connected over
+//! TCP without opening the game window.
+//!
+//! Bots connect to the arena's port and exchange JSON objects, one per line,
+//! each with a `type` field naming the message. A bot joins by sending
+//! `{"type": "hello", "name": "<name>"}`, and the arena replies with
+//! `{"type": "welcome", "protocol": 1, "name": "<name>"}` once it has been
+//! accepted. When enough bots have joined the tournament starts, and for each
+//! game the arena sends:
+//!
+//! * `game_start`: the game and round numbers, the opponent's name, the bot's
+//!   `mark` (`"X"` or `"O"`), and the time control in `base_millis` and
+//!   `increment_millis`. X always moves first.
+//! * `position`: sent when it is the bot's turn, with the `board` as rows
+//!   from the top such as `["X..", ".O.", "..."]`, whose `turn` it is, and
+//!   the bot's `time_left_millis`. The bot replies with
+//!   `{"type": "move", "row": <row>, "column": <column>}`, counting from the
+//!   top left starting at 0.
+//! * `game_over`: the `result` from the bot's point of view, `win`, `loss`,
+//!   or `draw`, and the `reason` the game ended.
+//!
+//! A bot that runs out of time, makes an illegal move, or disconnects loses
+//! the game. Lines that are not valid messages are answered with an `error`
+//! message and otherwise ignored. Once every round has been played, each bot
+//! is sent `tournament_over` with its `rank` and `points`.
+//!
+//! Games are played one at a time using the same game logic as the game, and
+//! each is saved as a RON file alongside a Markdown table of the standings.
+mod protocol;
+mod tournament;
+
+pub use self::protocol::*;
+pub use self::tournament::*;
+
+use open_ttt_lib as ttt;
+use std::error::Error;
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+use std::{fs, path};
+
+use crate::engine;
+use crate::file_io;
+use crate::rules;
+use crate::variants;
+
+/// How long bots have to say hello after connecting.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Settings for running a tournament in the arena.
+#[derive(Clone, Debug)]
+pub struct ArenaOptions {
+    /// How bots are paired up.
+    pub format: TournamentFormat,
+
+    /// The number of bots to wait for before starting.
+    pub num_bots: usize,
+
+    /// The number of rounds in swiss tournaments, see `Tournament::new`.
+    pub num_rounds: Option<u32>,
+
+    /// The time each bot has to make its moves.
+    pub time_control: TimeControl,
+
+    /// The directory the standings and game records are written to.
+    pub records_dir: path::PathBuf,
+}
+
+/// Accepts bots on the listener, then plays a tournament between them.
+///
+/// The standings table is written to `standings.md` in the records directory
+/// after every round, and each game is written to `game-<number>.ron`.
+///
+/// # Errors
+/// An error is returned if the listener fails or the records cannot be
+/// written. Problems with individual bots are logged and cost them games.
+pub fn run_tournament(
+    listener: &TcpListener,
+    options: &ArenaOptions,
+) -> Result<Tournament, Box<dyn Error>> {
+    fs::create_dir_all(&options.records_dir)?;
+    let mut bots = accept_bots(listener, options.num_bots)?;
+    let names = bots.iter().map(|bot| bot.name.clone()).collect();
+    let mut tournament = Tournament::new(options.format, names, options.num_rounds);
+    log::info!(
+        "Starting a {:?} tournament of {} rounds between {} bots.",
+        options.format,
+        tournament.num_rounds(),
+        bots.len()
+    );
+
+    let mut num_games = 0;
+    while let Some(pairings) = tournament.next_round() {
+        log::info!("Starting round {}.", tournament.round());
+        for pairing in pairings {
+            let (first, second) = match pairing {
+                Pairing::Match(first, second) => (first, second),
+                Pairing::Bye(bot) => {
+                    log::info!("{} has a bye.", tournament.name(bot));
+                    continue;
+                }
+            };
+            for &(x, o) in &[(first, second), (second, first)] {
+                num_games += 1;
+                let record = {
+                    let (x_bot, o_bot) = two_bots(&mut bots, x, o);
+                    play_game(
+                        x_bot,
+                        o_bot,
+                        num_games,
+                        tournament.round(),
+                        options.time_control,
+                    )
+                };
+                log::info!(
+                    "Game {}: {} (X) vs {} (O), {:?} by {}.",
+                    record.game,
+                    record.x,
+                    record.o,
+                    record.result,
+                    record.reason
+                );
+                tournament.record_game(x, o, record.result);
+                let file = options
+                    .records_dir
+                    .join(format!("game-{:03}.ron", record.game));
+                file_io::write_ron_file(&file, &record)?;
+            }
+        }
+        fs::write(
+            options.records_dir.join("standings.md"),
+            tournament.standings_table(),
+        )?;
+    }
+
+    for (rank, standing) in tournament.standings().into_iter().enumerate() {
+        if let Some(bot) = bots.iter_mut().find(|bot| bot.name == standing.name) {
+            bot.send(&ServerMessage::TournamentOver {
+                rank: rank as u32 + 1,
+                points: standing.points(),
+            });
+        }
+    }
+    log::info!("Final standings:\n{}", tournament.standings_table());
+    Ok(tournament)
+}
+
+// Waits for the number of bots to join, giving each a name no other bot has.
+fn accept_bots(
+    listener: &TcpListener,
+    num_bots: usize,
+) -> Result<Vec<BotConnection>, Box<dyn Error>> {
+    log::info!(
+        "Waiting for {} bots on {}.",
+        num_bots,
+        listener.local_addr()?
+    );
+    let mut bots: Vec<BotConnection> = Vec::new();
+    while bots.len() < num_bots {
+        let (stream, address) = listener.accept()?;
+        let mut bot = match BotConnection::accept(stream, HELLO_TIMEOUT) {
+            Ok(bot) => bot,
+            Err(error) => {
+                log::warn!("Turned away bot from {}: {}", address, error);
+                continue;
+            }
+        };
+        let requested_name = if bot.name.is_empty() {
+            "Bot".to_string()
+        } else {
+            bot.name.clone()
+        };
+        bot.name = requested_name.clone();
+        let mut number = 1;
+        while bots.iter().any(|other| other.name == bot.name) {
+            number += 1;
+            bot.name = format!("{} {}", requested_name, number);
+        }
+
+        bot.send(&ServerMessage::Welcome {
+            protocol: PROTOCOL_VERSION,
+            name: bot.name.clone(),
+        });
+        log::info!("Bot {} joined from {}.", bot.name, address);
+        bots.push(bot);
+    }
+    Ok(bots)
+}
+
+// Borrows two different bots at once.
+fn two_bots(
+    bots: &mut [BotConnection],
+    first: usize,
+    second: usize,
+) -> (&mut BotConnection, &mut BotConnection) {
+    assert_ne!(first, second, "A bot cannot play itself.");
+    if first < second {
+        let (start, end) = bots.split_at_mut(second);
+        (&mut start[first], &mut end[0])
+    } else {
+        let (start, end) = bots.split_at_mut(first);
+        (&mut end[0], &mut start[second])
+    }
+}
+
+/// Plays a classic game between the bots, returning its record.
+///
+/// Each bot's clock only runs while the arena waits for its move.
+pub fn play_game(
+    x_bot: &mut BotConnection,
+    o_bot: &mut BotConnection,
+    game_number: u32,
+    round: u32,
+    time_control: TimeControl,
+) -> GameRecord {
+    let (x_name, o_name) = (x_bot.name.clone(), o_bot.name.clone());
+    let starts = vec![
+        (&mut *x_bot, rules::Player::X, o_name.clone()),
+        (&mut *o_bot, rules::Player::O, x_name.clone()),
+    ];
+    for (bot, mark, opponent) in starts {
+        bot.send(&ServerMessage::GameStart {
+            game: game_number,
+            round,
+            opponent,
+            mark,
+            base_millis: time_control.base.as_millis() as u64,
+            increment_millis: time_control.increment.as_millis() as u64,
+        });
+    }
+
+    let mut game_logic = rules::GameLogic::new(variants::Variant::Classic);
+    let mut clocks = [time_control.base, time_control.base];
+    let mut moves = Vec::new();
+    let (result, reason) = loop {
+        let player = match game_logic.game.state() {
+            ttt::game::State::PlayerXWin(_) => break (GameResult::XWins, "three in a row"),
+            ttt::game::State::PlayerOWin(_) => break (GameResult::OWins, "three in a row"),
+            ttt::game::State::CatsGame => break (GameResult::Draw, "cat's game"),
+            ttt::game::State::PlayerXMove => rules::Player::X,
+            ttt::game::State::PlayerOMove => rules::Player::O,
+        };
+        debug_assert!(game_logic.is_players_move(&player));
+        let (bot, clock, loss) = match player {
+            rules::Player::X => (&mut *x_bot, &mut clocks[0], GameResult::OWins),
+            rules::Player::O => (&mut *o_bot, &mut clocks[1], GameResult::XWins),
+        };
+        if bot.is_disconnected() {
+            break (loss, "disconnected");
+        }
+
+        bot.send(&ServerMessage::Position {
+            board: variants::board_to_rows(game_logic.game.board()),
+            turn: player,
+            time_left_millis: clock.as_millis() as u64,
+        });
+        let start = Instant::now();
+        let message = bot.receive(*clock);
+        let elapsed = start.elapsed();
+        let (row, column) = match message {
+            Ok(ClientMessage::Move { row, column }) => (row, column),
+            Ok(message) => {
+                log::warn!("Bot {} sent {:?} instead of a move.", bot.name, message);
+                break (loss, "illegal move");
+            }
+            Err(_) if bot.is_disconnected() => break (loss, "disconnected"),
+            Err(_) => break (loss, "out of time"),
+        };
+        if elapsed > *clock {
+            break (loss, "out of time");
+        }
+        // Moves are checked against the rules, so a bot that tries an
+        // illegal move loses the game.
+        let is_moved = engine::reply_position(&game_logic.game, row, column)
+            .and_then(|position| game_logic.try_move(&player, &variants::Move::Mark(position)))
+            .is_some();
+        if !is_moved {
+            log::warn!("Bot {} tried to move to {} {}.", bot.name, row, column);
+            break (loss, "illegal move");
+        }
+
+        *clock = clock
+            .saturating_sub(elapsed)
+            .saturating_add(time_control.increment);
+        game_logic.last_move_time += elapsed;
+        moves.push((row, column));
+    };
+
+    for (bot, wins) in [(x_bot, GameResult::XWins), (o_bot, GameResult::OWins)] {
+        let bot_result = match result {
+            GameResult::Draw => "draw",
+            _ if result == wins => "win",
+            _ => "loss",
+        };
+        bot.send(&ServerMessage::GameOver {
+            game: game_number,
+            result: bot_result.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    GameRecord {
+        game: game_number,
+        round,
+        x: x_name,
+        o: o_name,
+        moves,
+        result,
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{SocketAddr, TcpStream};
+    use std::thread;
+
+    // Connects a bot that plays the first free square, or always the top left
+    // square if it is cheating, returning its final rank.
+    fn spawn_bot(
+        address: SocketAddr,
+        name: &'static str,
+        is_cheating: bool,
+    ) -> thread::JoinHandle<u64> {
+        thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            writeln!(stream, r#"{{"type": "hello", "name": "{}"}}"#, name).unwrap();
+            for line in reader.lines() {
+                let message: serde_json::Value = serde_json::from_str(&line.unwrap()).unwrap();
+                match message["type"].as_str().unwrap() {
+                    "position" => {
+                        let rows = message["board"].as_array().unwrap();
+                        let (row, column) = rows
+                            .iter()
+                            .enumerate()
+                            .filter(|_| !is_cheating)
+                            .find_map(|(row, marks)| {
+                                let column = marks.as_str().unwrap().find('.')?;
+                                Some((row, column))
+                            })
+                            .unwrap_or((0, 0));
+                        writeln!(
+                            stream,
+                            r#"{{"type": "move", "row": {}, "column": {}}}"#,
+                            row, column
+                        )
+                        .unwrap();
+                    }
+                    "tournament_over" => return message["rank"].as_u64().unwrap(),
+                    _ => {}
+                }
+            }
+            panic!("The arena closed before the tournament was over.");
+        })
+    }
+
+    #[test]
+    fn run_tournament_should_play_bots_over_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let records_dir = std::env::temp_dir().join(format!("fossxo-arena-{}", std::process::id()));
+        let options = ArenaOptions {
+            format: TournamentFormat::RoundRobin,
+            num_bots: 3,
+            num_rounds: None,
+            time_control: TimeControl {
+                base: Duration::from_secs(5),
+                increment: Duration::default(),
+            },
+            records_dir: records_dir.clone(),
+        };
+        let bots = vec![
+            spawn_bot(address, "First Free", false),
+            spawn_bot(address, "First Free", false),
+            spawn_bot(address, "Cheater", true),
+        ];
+
+        let tournament = run_tournament(&listener, &options).unwrap();
+
+        let ranks: Vec<u64> = bots.into_iter().map(|bot| bot.join().unwrap()).collect();
+        let standings = tournament.standings();
+        assert_eq!(ranks[2], 3);
+        assert_eq!(standings[0].points(), "4");
+        assert_eq!(standings[1].name, "First Free 2");
+        assert_eq!(standings[2].name, "Cheater");
+        assert_eq!(standings[2].losses, 4);
+        assert!(records_dir.join("game-006.ron").exists());
+        assert!(records_dir.join("standings.md").exists());
+        fs::remove_dir_all(records_dir).unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufReader, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use crate::network;
+use crate::rules;
+
+/// The version of the protocol sent to bots when they join.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A message sent by a bot to the arena.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Joins the arena, which must be the bot's first message.
+    Hello { name: String },
+
+    /// Places a mark in the square, counting rows from the top and columns
+    /// from the left starting at 0.
+    Move { row: i32, column: i32 },
+}
+
+/// A message sent by the arena to a bot.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Accepts the bot into the arena with the name it will play under, which
+    /// has a number added if another bot already has the name.
+    Welcome { protocol: u32, name: String },
+
+    /// Starts a game against the opponent, with the bot playing the mark.
+    GameStart {
+        game: u32,
+        round: u32,
+        opponent: String,
+        mark: rules::Player,
+        base_millis: u64,
+        increment_millis: u64,
+    },
+
+    /// Asks for a move in the position. The rows are listed from the top
+    /// with `X`, `O`, or `.` for each square.
+    Position {
+        board: Vec<String>,
+        turn: rules::Player,
+        time_left_millis: u64,
+    },
+
+    /// Ends the game, giving the result from the bot's point of view: `win`,
+    /// `loss`, or `draw`.
+    GameOver {
+        game: u32,
+        result: String,
+        reason: String,
+    },
+
+    /// Ends the tournament, giving the bot's final rank and score.
+    TournamentOver { rank: u32, points: String },
+
+    /// Reports a message from the bot that could not be understood.
+    Error { message: String },
+}
+
+/// A bot connected to the arena, which sends and receives messages as JSON
+/// objects, one per line.
+pub struct BotConnection {
+    /// The name the bot plays under.
+    pub name: String,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    // Indicates the bot has disconnected, so its remaining games are lost.
+    is_disconnected: bool,
+}
+
+impl BotConnection {
+    /// Waits for the bot's hello message.
+    ///
+    /// # Errors
+    /// An error is returned if the bot does not say hello within the time
+    /// given or the connection fails.
+    pub fn accept(stream: TcpStream, timeout: Duration) -> io::Result<Self> {
+        let writer = stream.try_clone()?;
+        let mut connection = Self {
+            name: String::new(),
+            reader: BufReader::new(stream),
+            writer,
+            is_disconnected: false,
+        };
+        match connection.receive(timeout)? {
+            ClientMessage::Hello { name } => {
+                connection.name = name.trim().to_string();
+                Ok(connection)
+            }
+            message => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected hello, found {:?}", message),
+            )),
+        }
+    }
+
+    /// Indicates the bot has disconnected or stopped following the protocol.
+    pub fn is_disconnected(&self) -> bool {
+        self.is_disconnected
+    }
+
+    /// Sends the message to the bot.
+    ///
+    /// Problems are logged and mark the bot as disconnected.
+    pub fn send(&mut self, message: &ServerMessage) {
+        if self.is_disconnected {
+            return;
+        }
+        let write = |writer: &mut TcpStream| -> io::Result<()> {
+            let line = serde_json::to_string(message)?;
+            writeln!(writer, "{}", line)?;
+            writer.flush()
+        };
+        if let Err(error) = write(&mut self.writer) {
+            log::warn!("Unable to send to bot {}: {}", self.name, error);
+            self.is_disconnected = true;
+        }
+    }
+
+    /// Waits for the bot's next message.
+    ///
+    /// Lines that are not valid messages are reported back to the bot and
+    /// skipped.
+    ///
+    /// # Errors
+    /// An error with the kind `TimedOut` or `WouldBlock`, depending on the
+    /// platform, is returned if no message arrives in time. Other errors mean
+    /// the bot has disconnected, including sending a line longer than
+    /// `network::MAX_LINE_LENGTH`.
+    pub fn receive(&mut self, timeout: Duration) -> io::Result<ClientMessage> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let time_left = deadline.saturating_duration_since(Instant::now());
+            if time_left == Duration::default() {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "out of time"));
+            }
+            self.reader.get_ref().set_read_timeout(Some(time_left))?;
+
+            // Lines are limited in length so a bot cannot use up the arena's
+            // memory by never ending its line.
+            let result = network::read_line(&mut self.reader);
+            match result {
+                Ok(None) => {
+                    self.is_disconnected = true;
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                Ok(Some(line)) => match serde_json::from_str(&line) {
+                    Ok(message) => return Ok(message),
+                    Err(error) => {
+                        log::debug!("Invalid message from bot {}: {}", self.name, line.trim());
+                        self.send(&ServerMessage::Error {
+                            message: error.to_string(),
+                        });
+                    }
+                },
+                Err(error) => {
+                    if !matches!(
+                        error.kind(),
+                        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+                    ) {
+                        self.is_disconnected = true;
+                    }
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn client_message_should_parse_json_with_type() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type": "move", "row": 0, "column": 2}"#).unwrap();
+
+        assert_eq!(message, ClientMessage::Move { row: 0, column: 2 });
+    }
+
+    #[test]
+    fn server_message_should_write_json_with_type() {
+        let message = ServerMessage::Position {
+            board: vec!["X..".to_string(), ".O.".to_string(), "...".to_string()],
+            turn: rules::Player::X,
+            time_left_millis: 500,
+        };
+
+        let json = serde_json::to_string(&message).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"type":"position","board":["X..",".O.","..."],"turn":"X","time_left_millis":500}"#
+        );
+    }
+
+    #[test]
+    fn bot_connection_receive_when_line_is_too_long_should_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut bot = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        writeln!(bot, r#"{{"type": "hello", "name": "flood"}}"#).unwrap();
+        let mut connection = BotConnection::accept(stream, Duration::from_secs(1)).unwrap();
+
+        bot.write_all(&vec![b'x'; network::MAX_LINE_LENGTH + 1])
+            .unwrap();
+        let result = connection.receive(Duration::from_secs(1));
+
+        assert!(result.is_err());
+        assert!(connection.is_disconnected());
+    }
+}
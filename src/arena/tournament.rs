@@ -0,0 +1,517 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The ways bots can be paired up for a tournament.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TournamentFormat {
+    /// Every bot plays every other bot once.
+    RoundRobin,
+
+    /// Bots play a set number of rounds, each against a bot with a similar
+    /// score that they have not played yet.
+    Swiss,
+}
+
+impl FromStr for TournamentFormat {
+    type Err = String;
+
+    /// Parses the name of the format, ignoring case.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_lowercase().as_str() {
+            "round-robin" => Ok(Self::RoundRobin),
+            "swiss" => Ok(Self::Swiss),
+            _ => Err(format!(
+                "unknown tournament '{}', expected round-robin or swiss",
+                text
+            )),
+        }
+    }
+}
+
+/// How much time each bot has to make its moves in a game.
+///
+/// Each bot starts the game with the base time, which runs down while the bot
+/// is thinking. The increment is added after each of its moves. A bot that
+/// runs out of time loses the game.
+///
+/// Rooms on a game server time players the same way, see `server::Room`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeControl {
+    /// The time each bot starts the game with.
+    pub base: Duration,
+
+    /// The time added to a bot's clock after each of its moves.
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    /// The longest base time or increment allowed, which keeps clocks far
+    /// from overflowing.
+    pub const MAX_TIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Indicates both the base time and the increment are at most
+    /// `MAX_TIME`.
+    pub fn is_in_range(&self) -> bool {
+        self.base <= Self::MAX_TIME && self.increment <= Self::MAX_TIME
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = String;
+
+    /// Parses the base time and increment in seconds separated by `+`, such
+    /// as `10+0.5`. The increment can be left out. Neither can be longer than
+    /// `MAX_TIME`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let max_seconds = Self::MAX_TIME.as_secs_f64();
+        let seconds = |value: &str| -> Result<Duration, String> {
+            match value.trim().parse::<f64>() {
+                Ok(seconds) if seconds >= 0.0 && seconds <= max_seconds => {
+                    Ok(Duration::from_secs_f64(seconds))
+                }
+                _ => Err(format!(
+                    "expected a time control in seconds such as 10+0.5, found '{}'",
+                    text
+                )),
+            }
+        };
+        let mut parts = text.splitn(2, '+');
+        let base = seconds(parts.next().unwrap_or_default())?;
+        let increment = match parts.next() {
+            Some(increment) => seconds(increment)?,
+            None => Duration::default(),
+        };
+        Ok(Self { base, increment })
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}+{}",
+            self.base.as_secs_f64(),
+            self.increment.as_secs_f64()
+        )
+    }
+}
+
+/// How a game between two bots ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameResult {
+    XWins,
+    OWins,
+    Draw,
+}
+
+/// The record of a single game played in the arena.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// The number of the game in the tournament, counting from 1.
+    pub game: u32,
+
+    /// The round the game was played in, counting from 1.
+    pub round: u32,
+
+    /// The name of the bot that played X and moved first.
+    pub x: String,
+
+    /// The name of the bot that played O.
+    pub o: String,
+
+    /// The squares marked, in order, as rows and columns counted from the top
+    /// left as in the protocol.
+    pub moves: Vec<(i32, i32)>,
+
+    /// How the game ended.
+    pub result: GameResult,
+
+    /// Why the game ended, such as a bot running out of time.
+    pub reason: String,
+}
+
+/// A bot's results in a tournament.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Standing {
+    /// The bot's name.
+    pub name: String,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// The number of rounds the bot sat out because there was no one left to
+    /// play.
+    pub byes: u32,
+}
+
+impl Standing {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            byes: 0,
+        }
+    }
+
+    /// Gets the number of games played, not counting byes.
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Gets the bot's score in half points, to avoid rounding.
+    ///
+    /// Wins score a point and draws score half a point. Byes score a point,
+    /// the same as winning one game of the two-game match that was missed.
+    pub fn half_points(&self) -> u32 {
+        2 * (self.wins + self.byes) + self.draws
+    }
+
+    /// Gets the bot's score as text, such as `2.5`.
+    pub fn points(&self) -> String {
+        let half_points = self.half_points();
+        if half_points.is_multiple_of(2) {
+            format!("{}", half_points / 2)
+        } else {
+            format!("{}.5", half_points / 2)
+        }
+    }
+}
+
+/// The bots playing each other in a round.
+///
+/// Bots are given by their index in the order they joined the tournament.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pairing {
+    /// The bots play a match of two games, each playing X once. The first
+    /// bot plays X in the first game.
+    Match(usize, usize),
+
+    /// The bot sits out the round.
+    Bye(usize),
+}
+
+/// Decides who plays who and keeps track of the bots' results.
+///
+/// Each round is a set of pairings. The games of each pairing are played and
+/// recorded with `record_game` before asking for the next round.
+pub struct Tournament {
+    format: TournamentFormat,
+    num_rounds: u32,
+    round: u32,
+    standings: Vec<Standing>,
+    // The pairs of bots that have played, with the lower index first.
+    played: HashSet<(usize, usize)>,
+    // The round robin's rounds, made up front so every bot plays every other.
+    schedule: Vec<Vec<Pairing>>,
+}
+
+impl Tournament {
+    /// Creates a tournament for the bots with the provided names.
+    ///
+    /// Round robins have as many rounds as needed for every bot to play every
+    /// other bot. Swiss tournaments play the given number of rounds, or by
+    /// default enough rounds to find a clear winner.
+    pub fn new(format: TournamentFormat, names: Vec<String>, num_rounds: Option<u32>) -> Self {
+        let num_bots = names.len();
+        let schedule = match format {
+            TournamentFormat::RoundRobin => round_robin_schedule(num_bots),
+            TournamentFormat::Swiss => Vec::new(),
+        };
+        let num_rounds = match format {
+            TournamentFormat::RoundRobin => schedule.len() as u32,
+            TournamentFormat::Swiss => num_rounds.unwrap_or_else(|| default_swiss_rounds(num_bots)),
+        };
+        Self {
+            format,
+            num_rounds,
+            round: 0,
+            standings: names.into_iter().map(Standing::new).collect(),
+            played: HashSet::new(),
+            schedule,
+        }
+    }
+
+    /// Gets the number of rounds in the tournament.
+    pub fn num_rounds(&self) -> u32 {
+        self.num_rounds
+    }
+
+    /// Gets the round being played, counting from 1, or 0 before the first
+    /// round.
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// Gets the name of the bot at the index.
+    pub fn name(&self, bot: usize) -> &str {
+        &self.standings[bot].name
+    }
+
+    /// Starts the next round, returning who plays who.
+    ///
+    /// Byes are scored straight away. `None` is returned once every round has
+    /// been played.
+    pub fn next_round(&mut self) -> Option<Vec<Pairing>> {
+        if self.round >= self.num_rounds {
+            return None;
+        }
+        let pairings = match self.format {
+            TournamentFormat::RoundRobin => self.schedule[self.round as usize].clone(),
+            TournamentFormat::Swiss => self.swiss_pairings(),
+        };
+        self.round += 1;
+
+        for pairing in &pairings {
+            match *pairing {
+                Pairing::Match(first, second) => {
+                    self.played.insert((first.min(second), first.max(second)));
+                }
+                Pairing::Bye(bot) => self.standings[bot].byes += 1,
+            }
+        }
+        Some(pairings)
+    }
+
+    /// Records the result of a game between the bots.
+    pub fn record_game(&mut self, x: usize, o: usize, result: GameResult) {
+        match result {
+            GameResult::XWins => {
+                self.standings[x].wins += 1;
+                self.standings[o].losses += 1;
+            }
+            GameResult::OWins => {
+                self.standings[x].losses += 1;
+                self.standings[o].wins += 1;
+            }
+            GameResult::Draw => {
+                self.standings[x].draws += 1;
+                self.standings[o].draws += 1;
+            }
+        }
+    }
+
+    /// Gets the bots' results from first place to last.
+    ///
+    /// Bots are ranked by points, then by wins, then by the order they joined.
+    pub fn standings(&self) -> Vec<&Standing> {
+        self.ranking()
+            .into_iter()
+            .map(|bot| &self.standings[bot])
+            .collect()
+    }
+
+    /// Lays out the standings as a Markdown table.
+    pub fn standings_table(&self) -> String {
+        let mut table =
+            String::from("| Rank | Bot | Games | Wins | Draws | Losses | Byes | Points |\n");
+        table.push_str("|-----:|-----|------:|-----:|------:|-------:|-----:|-------:|\n");
+        for (rank, standing) in self.standings().into_iter().enumerate() {
+            table.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                rank + 1,
+                standing.name,
+                standing.games(),
+                standing.wins,
+                standing.draws,
+                standing.losses,
+                standing.byes,
+                standing.points()
+            ));
+        }
+        table
+    }
+
+    // Gets the indices of the bots from first place to last.
+    fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.standings.len()).collect();
+        ranking.sort_by_key(|bot| {
+            let standing = &self.standings[*bot];
+            (
+                std::cmp::Reverse(standing.half_points()),
+                std::cmp::Reverse(standing.wins),
+                *bot,
+            )
+        });
+        ranking
+    }
+
+    // Pairs each bot with the highest ranked bot it has not played yet.
+    //
+    // With an odd number of bots, the lowest ranked bot with the fewest byes
+    // sits out. Bots that have played everyone left are paired again.
+    fn swiss_pairings(&self) -> Vec<Pairing> {
+        let mut unpaired = self.ranking();
+        let mut pairings = Vec::new();
+        if unpaired.len() % 2 == 1 {
+            let fewest_byes = unpaired
+                .iter()
+                .map(|bot| self.standings[*bot].byes)
+                .min()
+                .unwrap_or_default();
+            let index = unpaired
+                .iter()
+                .rposition(|bot| self.standings[*bot].byes == fewest_byes)
+                .expect("Some bot has the fewest byes.");
+            pairings.push(Pairing::Bye(unpaired.remove(index)));
+        }
+
+        while !unpaired.is_empty() {
+            let first = unpaired.remove(0);
+            let index = unpaired
+                .iter()
+                .position(|second| {
+                    !self
+                        .played
+                        .contains(&(first.min(*second), first.max(*second)))
+                })
+                .unwrap_or(0);
+            let second = unpaired.remove(index);
+            pairings.push(Pairing::Match(first, second));
+        }
+        pairings
+    }
+}
+
+// Schedules the rounds of a round robin using the circle method: one bot stays
+// put while the rest rotate around it, so every bot meets every other bot
+// exactly once. With an odd number of bots, the bot paired with the empty
+// seat has a bye.
+fn round_robin_schedule(num_bots: usize) -> Vec<Vec<Pairing>> {
+    if num_bots < 2 {
+        return Vec::new();
+    }
+    let mut seats: Vec<Option<usize>> = (0..num_bots).map(Some).collect();
+    if num_bots % 2 == 1 {
+        seats.push(None);
+    }
+    let num_seats = seats.len();
+
+    let mut rounds = Vec::new();
+    for round in 0..num_seats - 1 {
+        let mut pairings = Vec::new();
+        for seat in 0..num_seats / 2 {
+            let pairing = match (seats[seat], seats[num_seats - 1 - seat]) {
+                // Alternate who plays X first so no bot always starts.
+                (Some(first), Some(second)) if (round + seat) % 2 == 0 => {
+                    Pairing::Match(first, second)
+                }
+                (Some(first), Some(second)) => Pairing::Match(second, first),
+                (Some(bot), None) | (None, Some(bot)) => Pairing::Bye(bot),
+                (None, None) => unreachable!("There is only one empty seat."),
+            };
+            pairings.push(pairing);
+        }
+        rounds.push(pairings);
+        seats[1..].rotate_right(1);
+    }
+    rounds
+}
+
+// Gets enough swiss rounds for a single bot to be the only one to win every
+// match, which is the number of times the bots can be halved.
+fn default_swiss_rounds(num_bots: usize) -> u32 {
+    let mut num_rounds = 0;
+    while (1 << num_rounds) < num_bots {
+        num_rounds += 1;
+    }
+    num_rounds.max(1).min(num_bots.saturating_sub(1) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(num_bots: usize) -> Vec<String> {
+        (0..num_bots).map(|bot| format!("Bot {}", bot)).collect()
+    }
+
+    #[test]
+    fn time_control_from_str_should_parse_base_and_increment() {
+        let time_control = TimeControl::from_str("10+0.5").unwrap();
+
+        assert_eq!(time_control.base, Duration::from_secs(10));
+        assert_eq!(time_control.increment, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn time_control_from_str_when_invalid_should_return_error() {
+        assert!(TimeControl::from_str("ten").is_err());
+        assert!(TimeControl::from_str("10+-1").is_err());
+    }
+
+    #[test]
+    fn time_control_from_str_when_too_long_should_return_error() {
+        assert!(TimeControl::from_str("1e30+0").is_err());
+        assert!(TimeControl::from_str("10+90000").is_err());
+        assert!(TimeControl::from_str("inf").is_err());
+    }
+
+    #[test]
+    fn tournament_when_round_robin_should_pair_every_bot_once() {
+        let mut tournament = Tournament::new(TournamentFormat::RoundRobin, names(5), None);
+        let mut matches = HashSet::new();
+        let mut byes = vec![0; 5];
+
+        while let Some(pairings) = tournament.next_round() {
+            for pairing in pairings {
+                match pairing {
+                    Pairing::Match(first, second) => {
+                        assert!(matches.insert((first.min(second), first.max(second))));
+                    }
+                    Pairing::Bye(bot) => byes[bot] += 1,
+                }
+            }
+        }
+
+        assert_eq!(tournament.num_rounds(), 5);
+        assert_eq!(matches.len(), 10);
+        assert_eq!(byes, vec![1; 5]);
+    }
+
+    #[test]
+    fn tournament_when_swiss_should_pair_leaders_with_new_opponents() {
+        let mut tournament = Tournament::new(TournamentFormat::Swiss, names(4), None);
+        tournament.next_round();
+        tournament.record_game(0, 1, GameResult::XWins);
+        tournament.record_game(1, 0, GameResult::OWins);
+        tournament.record_game(2, 3, GameResult::XWins);
+        tournament.record_game(3, 2, GameResult::Draw);
+
+        let pairings = tournament.next_round().unwrap();
+
+        assert_eq!(tournament.num_rounds(), 2);
+        assert_eq!(pairings, vec![Pairing::Match(0, 2), Pairing::Match(3, 1)]);
+        assert_eq!(tournament.next_round(), None);
+    }
+
+    #[test]
+    fn tournament_when_swiss_with_odd_bots_should_give_lowest_ranked_bye() {
+        let mut tournament = Tournament::new(TournamentFormat::Swiss, names(3), Some(2));
+        tournament.next_round();
+        tournament.record_game(0, 1, GameResult::XWins);
+        tournament.record_game(1, 0, GameResult::XWins);
+
+        let pairings = tournament.next_round().unwrap();
+
+        assert!(pairings.contains(&Pairing::Bye(1)));
+    }
+
+    #[test]
+    fn tournament_standings_should_rank_by_points_then_wins() {
+        let mut tournament = Tournament::new(TournamentFormat::RoundRobin, names(3), None);
+        tournament.record_game(0, 1, GameResult::Draw);
+        tournament.record_game(2, 1, GameResult::OWins);
+        tournament.record_game(0, 2, GameResult::Draw);
+
+        let standings = tournament.standings();
+
+        assert_eq!(standings[0].name, "Bot 1");
+        assert_eq!(standings[0].points(), "1.5");
+        assert_eq!(standings[1].name, "Bot 0");
+        assert_eq!(standings[2].name, "Bot 2");
+    }
+}
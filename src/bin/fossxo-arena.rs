@@ -0,0 +1,74 @@
+// Only safe code is allowed in this application.
+#![forbid(unsafe_code)]
+
+use fossxo::{arena, constants, file_io, logger};
+use std::error::Error;
+use structopt::StructOpt;
+
+fn main() {
+    let args = CliArgs::from_args();
+    logger::start_logger();
+    log::info!("Started FossXO bot arena v{}.", constants::FOSSXO_VERSION);
+
+    if let Err(error) = run_arena(&args) {
+        log::error!("Unable to run the arena: {}", error);
+        std::process::exit(1);
+    }
+}
+
+// Runs a bot arena tournament on the local port.
+fn run_arena(args: &CliArgs) -> Result<(), Box<dyn Error>> {
+    let records_dir = match &args.records {
+        Some(records_dir) => records_dir.clone(),
+        None => file_io::arena_records_dir()?,
+    };
+    let options = arena::ArenaOptions {
+        format: args.tournament,
+        num_bots: args.bots,
+        num_rounds: args.rounds,
+        time_control: args.time_control,
+        records_dir,
+    };
+    let listener = std::net::TcpListener::bind(("127.0.0.1", args.port))?;
+    arena::run_tournament(&listener, &options)?;
+    log::info!(
+        "Wrote the arena's records to {}.",
+        options.records_dir.display()
+    );
+    Ok(())
+}
+
+/// Runs a tournament between FossXO bots.
+///
+/// Bots connect to the local port and use the JSON protocol described in the
+/// player manual. The arena exits once the tournament is over.
+#[derive(StructOpt, Debug)]
+#[structopt(name = "fossxo-arena")]
+struct CliArgs {
+    /// The local port bots connect to.
+    #[structopt(value_name = "PORT")]
+    port: u16,
+
+    /// The number of bots the arena waits for before starting the tournament.
+    #[structopt(long, default_value = "2")]
+    bots: usize,
+
+    /// The arena's tournament: round-robin or swiss.
+    #[structopt(long, default_value = "round-robin")]
+    tournament: arena::TournamentFormat,
+
+    /// The number of rounds in swiss tournaments. By default there are enough
+    /// rounds for one bot to win every match.
+    #[structopt(long)]
+    rounds: Option<u32>,
+
+    /// Each bot's time for a game and the time added after each of its moves,
+    /// in seconds.
+    #[structopt(long, default_value = "10+0.1")]
+    time_control: arena::TimeControl,
+
+    /// The directory the arena writes its standings and game records to,
+    /// instead of the arena directory in the game's user data directory.
+    #[structopt(long, value_name = "DIR", parse(from_os_str))]
+    records: Option<std::path::PathBuf>,
+}
@@ -0,0 +1,58 @@
+// Only safe code is allowed in this application.
+#![forbid(unsafe_code)]
+
+use fossxo::{constants, logger, server};
+use std::error::Error;
+use std::time::Duration;
+use structopt::StructOpt;
+
+fn main() {
+    let args = CliArgs::from_args();
+    logger::start_logger();
+    log::info!("Started FossXO game server v{}.", constants::FOSSXO_VERSION);
+
+    if let Err(error) = run_server(&args) {
+        log::error!("Unable to run the game server: {}", error);
+        std::process::exit(1);
+    }
+}
+
+// Runs a game server on the port until the program is stopped.
+fn run_server(args: &CliArgs) -> Result<(), Box<dyn Error>> {
+    let options = server::ServerOptions {
+        room_timeout: Duration::from_secs(args.room_timeout * 60),
+        max_rooms: args.max_rooms,
+        max_lobby_players: args.max_lobby_players,
+    };
+    let listener = std::net::TcpListener::bind(("0.0.0.0", args.port))?;
+    let mut server = server::Server::new(listener, options)?;
+    log::info!("Running game server on port {}.", args.port);
+    server.run();
+    Ok(())
+}
+
+/// Runs a FossXO game server.
+///
+/// The server hosts rooms for players that connect to the port, so everyone
+/// on an office or club network can play online games without hosting them.
+#[derive(StructOpt, Debug)]
+#[structopt(name = "fossxo-server")]
+struct CliArgs {
+    /// The port players connect to.
+    #[structopt(value_name = "PORT")]
+    port: u16,
+
+    /// The minutes the server keeps a room no one has played in before
+    /// closing it.
+    #[structopt(long, value_name = "MINUTES", default_value = "10")]
+    room_timeout: u64,
+
+    /// The most rooms the server has open at once.
+    #[structopt(long, default_value = "1000")]
+    max_rooms: usize,
+
+    /// The most players that can be connected to the server without being in
+    /// a room. More players are turned away.
+    #[structopt(long, default_value = "100")]
+    max_lobby_players: usize,
+}
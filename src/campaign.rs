@@ -0,0 +1,193 @@
+//! Contains the campaign, a ladder of named AI opponents played in order.
+//!
+//! Each opponent plays a set variant at a set difficulty. Beating an opponent
+//! unlocks the next one, and the player's progress is saved between runs of
+//! the game.
+use open_ttt_lib as ttt;
+use serde::{Deserialize, Serialize};
+
+use crate::file_io;
+use crate::variants;
+
+/// A named AI opponent on the campaign ladder.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CampaignOpponent {
+    pub name: &'static str,
+    pub variant: variants::Variant,
+    pub difficulty: ttt::ai::Difficulty,
+}
+
+impl CampaignOpponent {
+    /// Describes what the opponent plays, such as "Numerical, Easy".
+    pub fn description(&self) -> String {
+        format!(
+            "{}, {}",
+            self.variant.name(),
+            variants::difficulty_name(self.difficulty)
+        )
+    }
+}
+
+/// The opponents in the order they are played.
+///
+/// New variants are brought in against easier opponents, so the player can
+/// learn the rules before facing a strong one.
+pub const OPPONENTS: [CampaignOpponent; 8] = [
+    CampaignOpponent {
+        name: "Pip the Beginner",
+        variant: variants::Variant::Classic,
+        difficulty: ttt::ai::Difficulty::Easy,
+    },
+    CampaignOpponent {
+        name: "Tally the Counter",
+        variant: variants::Variant::Numerical,
+        difficulty: ttt::ai::Difficulty::Easy,
+    },
+    CampaignOpponent {
+        name: "Rook the Rival",
+        variant: variants::Variant::Classic,
+        difficulty: ttt::ai::Difficulty::Medium,
+    },
+    CampaignOpponent {
+        name: "Miller the Mover",
+        variant: variants::Variant::ThreeMensMorris,
+        difficulty: ttt::ai::Difficulty::Easy,
+    },
+    CampaignOpponent {
+        name: "Nix the Trickster",
+        variant: variants::Variant::Notakto,
+        difficulty: ttt::ai::Difficulty::Medium,
+    },
+    CampaignOpponent {
+        name: "Rex of Order",
+        variant: variants::Variant::OrderAndChaos,
+        difficulty: ttt::ai::Difficulty::Medium,
+    },
+    CampaignOpponent {
+        name: "Sage the Wise",
+        variant: variants::Variant::ThreeMensMorris,
+        difficulty: ttt::ai::Difficulty::Hard,
+    },
+    CampaignOpponent {
+        name: "The Grandmaster",
+        variant: variants::Variant::Classic,
+        difficulty: ttt::ai::Difficulty::Hard,
+    },
+];
+
+/// How far the player has made it up the ladder.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CampaignProgress {
+    /// The number of opponents beaten. As opponents are beaten in order,
+    /// these are the first opponents on the ladder.
+    pub opponents_beaten: usize,
+}
+
+impl CampaignProgress {
+    /// Reads the saved progress, logging any problems.
+    ///
+    /// No opponents are beaten the first time the campaign is played or if
+    /// the progress cannot be read.
+    pub fn load() -> Self {
+        file_io::load_or_default(file_io::campaign_progress_file, "the campaign progress")
+    }
+
+    /// Saves the progress, logging any problems.
+    pub fn save(&self) {
+        file_io::save(
+            file_io::campaign_progress_file,
+            self,
+            "the campaign progress",
+        );
+    }
+
+    /// Indicates if the opponent at the index can be played.
+    ///
+    /// The first opponent is always unlocked, along with the opponent after
+    /// each one beaten.
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        index < OPPONENTS.len() && index <= self.opponents_beaten
+    }
+
+    /// Indicates every opponent has been beaten.
+    pub fn is_complete(&self) -> bool {
+        self.opponents_beaten >= OPPONENTS.len()
+    }
+
+    /// Gets the index of the opponent to play next, which is the last one
+    /// unlocked.
+    pub fn next_opponent(&self) -> usize {
+        self.opponents_beaten.min(OPPONENTS.len() - 1)
+    }
+
+    /// Records that the opponent at the index was beaten.
+    ///
+    /// `true` is returned if this unlocked the next opponent or completed the
+    /// campaign. Beating an opponent again does nothing.
+    pub fn record_win(&mut self, index: usize) -> bool {
+        if index == self.opponents_beaten && index < OPPONENTS.len() {
+            self.opponents_beaten += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn opponents_should_have_unique_names() {
+        let names: HashSet<&str> = OPPONENTS.iter().map(|opponent| opponent.name).collect();
+
+        assert_eq!(names.len(), OPPONENTS.len());
+    }
+
+    #[test]
+    fn campaign_progress_is_unlocked_when_new_should_only_unlock_first() {
+        let progress = CampaignProgress::default();
+
+        assert!(progress.is_unlocked(0));
+        assert!(!progress.is_unlocked(1));
+    }
+
+    #[test]
+    fn campaign_progress_record_win_when_next_opponent_should_unlock_another() {
+        let mut progress = CampaignProgress::default();
+
+        let unlocked = progress.record_win(0);
+
+        assert!(unlocked);
+        assert!(progress.is_unlocked(1));
+        assert_eq!(progress.next_opponent(), 1);
+    }
+
+    #[test]
+    fn campaign_progress_record_win_when_already_beaten_should_not_change() {
+        let mut progress = CampaignProgress {
+            opponents_beaten: 2,
+        };
+
+        let unlocked = progress.record_win(0);
+
+        assert!(!unlocked);
+        assert_eq!(progress.opponents_beaten, 2);
+    }
+
+    #[test]
+    fn campaign_progress_record_win_when_last_opponent_should_complete_campaign() {
+        let mut progress = CampaignProgress {
+            opponents_beaten: OPPONENTS.len() - 1,
+        };
+
+        progress.record_win(OPPONENTS.len() - 1);
+
+        assert!(progress.is_complete());
+        assert_eq!(progress.next_opponent(), OPPONENTS.len() - 1);
+        assert!(!progress.is_unlocked(OPPONENTS.len()));
+    }
+}
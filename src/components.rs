@@ -7,23 +7,14 @@
 
 use amethyst::{ecs, renderer::palette::Srgba};
 use open_ttt_lib as ttt;
+use std::{sync::Arc, time};
 
-/// The Player component stores if the player is playing as X or as O.
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum Player {
-    X,
-    O,
-}
+use crate::engine;
+use crate::network;
+use crate::variants;
 
-impl Player {
-    /// Gets the other, or opposite, of the current player.
-    pub fn opposite_player(&self) -> Self {
-        match self {
-            Self::X => Self::O,
-            Self::O => Self::X,
-        }
-    }
-}
+pub use crate::engine::ThinkTime;
+pub use crate::rules::Player;
 
 impl ecs::Component for Player {
     type Storage = ecs::DenseVecStorage<Self>;
@@ -38,24 +29,55 @@ impl ecs::Component for LocalPlayer {
     type Storage = ecs::NullStorage<Self>;
 }
 
-/// The AI component provides the underlying AI opponent to use when selecting positions.
+/// The AI component provides the underlying AI engine to use when selecting positions.
 pub struct AiPlayer {
-    /// The underlying AI opponent that performs the actual move logic.
-    pub ai_opponent: ttt::ai::Opponent,
+    /// The underlying AI engine that performs the actual move logic.
+    ///
+    /// The engine is shared with the worker thread searching for its move.
+    pub ai_opponent: Arc<dyn engine::Engine>,
 
     /// The delay to wait before trying to move into a position.
     ///
     /// The move delay allows the game to simulate the AI taking time to think
     /// of where to place its mark. Otherwise, the AI would instantly choose a
     /// location.
-    pub move_delay: std::time::Duration,
+    pub move_delay: time::Duration,
+
+    /// How long the AI thinks about each move. A new move delay is picked
+    /// from this at the start of each of the AI's turns.
+    pub think_time: ThinkTime,
+
+    /// The time of the last move when the move delay was picked, which tells
+    /// whether the delay is for the current turn.
+    pub move_delay_picked_at: Option<time::Duration>,
+
+    /// The move the engine is searching for on a worker thread, if any.
+    ///
+    /// The search is started at the beginning of the AI's turn and is
+    /// cancelled when the pending move is dropped, such as when the game is
+    /// restarted or left.
+    pub pending_move: Option<engine::PendingMove>,
 }
 
 impl AiPlayer {
-    pub fn new(difficulty: ttt::ai::Difficulty) -> Self {
+    /// Creates an AI player with the given difficulty for the provided variant.
+    pub fn new(variant: variants::Variant, difficulty: ttt::ai::Difficulty) -> Self {
+        Self::with_engine(engine::for_variant(variant, difficulty))
+    }
+
+    /// Creates an AI player that uses the provided opponent.
+    pub fn with_opponent(ai_opponent: variants::Opponent) -> Self {
+        Self::with_engine(Box::new(ai_opponent))
+    }
+
+    /// Creates an AI player that uses the provided engine.
+    pub fn with_engine(ai_opponent: Box<dyn engine::Engine>) -> Self {
         Self {
-            ai_opponent: ttt::ai::Opponent::new(difficulty),
-            move_delay: std::time::Duration::new(0, 0),
+            ai_opponent: Arc::from(ai_opponent),
+            move_delay: time::Duration::new(0, 0),
+            think_time: ThinkTime::default(),
+            move_delay_picked_at: None,
+            pending_move: None,
         }
     }
 }
@@ -64,6 +86,76 @@ impl ecs::Component for AiPlayer {
     type Storage = ecs::DenseVecStorage<Self>;
 }
 
+/// Component for a player whose moves are made by a bot running as a
+/// separate program, see `engine::ExternalEngine`.
+pub struct ExternalPlayer {
+    /// The running bot.
+    pub engine: engine::ExternalEngine,
+
+    /// The time of the last move when the bot was last asked for a move,
+    /// which tells whether it has been asked about the current position.
+    pub requested_at: Option<time::Duration>,
+
+    /// The number of illegal moves the bot has replied with this turn.
+    pub num_illegal_replies: u32,
+
+    /// The built-in AI that makes the bot's move when the bot runs out of
+    /// time or keeps replying with illegal moves.
+    pub fallback_engine: Arc<dyn engine::Engine>,
+
+    /// The move the built-in AI is searching for in place of the bot's, if
+    /// the bot did not make its move this turn.
+    pub fallback_move: Option<engine::PendingMove>,
+}
+
+impl ExternalPlayer {
+    /// Creates a player for the bot, whose moves are made by the fallback
+    /// engine when the bot fails to make them.
+    pub fn new(engine: engine::ExternalEngine, fallback_engine: Box<dyn engine::Engine>) -> Self {
+        Self {
+            engine,
+            requested_at: None,
+            num_illegal_replies: 0,
+            fallback_engine: Arc::from(fallback_engine),
+            fallback_move: None,
+        }
+    }
+}
+
+impl ecs::Component for ExternalPlayer {
+    type Storage = ecs::DenseVecStorage<Self>;
+}
+
+/// Component for a player on another computer, whose moves arrive over the
+/// network, see `network::Connection`.
+pub struct RemotePlayer {
+    /// The connection to the player's computer.
+    pub connection: network::Connection,
+
+    /// Indicates the host has started the next game, which the joining
+    /// player follows.
+    pub is_next_game_requested: bool,
+
+    /// The player a game server said ran out of time, ending the current
+    /// game, until the game state handles it.
+    pub timed_out_player: Option<Player>,
+}
+
+impl RemotePlayer {
+    /// Creates a player for the connection.
+    pub fn new(connection: network::Connection) -> Self {
+        Self {
+            connection,
+            is_next_game_requested: false,
+            timed_out_player: None,
+        }
+    }
+}
+
+impl ecs::Component for RemotePlayer {
+    type Storage = ecs::DenseVecStorage<Self>;
+}
+
 /// The Mark component indicates the owner of a given position on the board.
 pub struct Mark {
     /// The owner of the mark.
@@ -71,12 +163,34 @@ pub struct Mark {
 
     /// The position of the mark.
     pub position: ttt::game::Position,
+
+    /// The symbol drawn for the mark.
+    pub symbol: Symbol,
 }
 
 impl ecs::Component for Mark {
     type Storage = ecs::DenseVecStorage<Self>;
 }
 
+/// The symbols that can be drawn for a mark.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Symbol {
+    X,
+    O,
+    /// A number from 1 to 9, used by numerical tic-tac-toe.
+    Number(u8),
+}
+
+impl From<Player> for Symbol {
+    /// Gets the symbol players use for their marks in classic tic-tac-toe.
+    fn from(player: Player) -> Self {
+        match player {
+            Player::X => Self::X,
+            Player::O => Self::O,
+        }
+    }
+}
+
 /// Indicates a box should be drawn around the square at the current mouse position.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct MouseHoverDebugBox {
@@ -123,20 +237,8 @@ mod tests {
     use super::*;
 
     #[test]
-    fn player_opposite_player_when_x_should_be_o() {
-        let player = Player::X;
-
-        let opposite_player = player.opposite_player();
-
-        assert_eq!(opposite_player, Player::O);
-    }
-
-    #[test]
-    fn player_opposite_player_when_o_should_be_x() {
-        let player = Player::O;
-
-        let opposite_player = player.opposite_player();
-
-        assert_eq!(opposite_player, Player::X);
+    fn symbol_from_player_should_use_players_mark() {
+        assert_eq!(Symbol::from(Player::X), Symbol::X);
+        assert_eq!(Symbol::from(Player::O), Symbol::O);
     }
 }
@@ -0,0 +1,394 @@
+//! Contains the daily challenge, a single-player game that is the same for
+//! every player on a given date.
+//!
+//! The challenge's variant, starting position, AI difficulty, and environment
+//! are picked using a random number generator seeded from the date, see
+//! `seeded_rng`. This lets players compare their results with friends who
+//! played on the same day.
+use open_ttt_lib as ttt;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::file_io;
+use crate::rules::Player;
+use crate::variants;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// The variants a challenge can be played in. Custom variants are left out as
+// players can have different rule files.
+const CHALLENGE_VARIANTS: [variants::Variant; 5] = [
+    variants::Variant::Classic,
+    variants::Variant::Numerical,
+    variants::Variant::ThreeMensMorris,
+    variants::Variant::Notakto,
+    variants::Variant::OrderAndChaos,
+];
+
+const CHALLENGE_DIFFICULTIES: [ttt::ai::Difficulty; 2] =
+    [ttt::ai::Difficulty::Medium, ttt::ai::Difficulty::Hard];
+
+// The most random moves made before the player's first move.
+const MAX_OPENING_MOVES: usize = 2;
+
+/// A calendar date in UTC.
+///
+/// UTC is used so everyone plays the same challenge at the same time,
+/// regardless of their time zone.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChallengeDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl ChallengeDate {
+    /// Gets today's date.
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        Self::from_days_since_epoch((seconds / SECONDS_PER_DAY) as i64)
+    }
+
+    /// Gets the date the provided number of days after 1970-01-01.
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        // Converts the days to a date in the proleptic Gregorian calendar
+        // using Howard Hinnant's `civil_from_days` algorithm. The years are
+        // shifted to start in March so leap days fall at the end of the year.
+        let days = days + 719_468;
+        let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    /// Gets the seed for the random number generator that picks the date's
+    /// challenge.
+    pub fn seed(&self) -> u64 {
+        (self.year * 10_000) as u64 + u64::from(self.month * 100 + self.day)
+    }
+}
+
+/// Creates a random number generator that gives the same numbers for the
+/// seed on every computer and version of the game.
+///
+/// `ChaCha8Rng`'s numbers are fixed by its algorithm, unlike `StdRng`'s, and
+/// the seed is used as its key directly instead of being expanded by
+/// `SeedableRng::seed_from_u64`.
+pub fn seeded_rng(seed: u64) -> ChaCha8Rng {
+    let mut key = [0; 32];
+    key[..8].copy_from_slice(&seed.to_le_bytes());
+    ChaCha8Rng::from_seed(key)
+}
+
+impl fmt::Display for ChallengeDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The challenge for a particular date.
+#[derive(Clone)]
+pub struct DailyChallenge {
+    pub date: ChallengeDate,
+
+    /// The game the challenge starts from, which can have a few moves
+    /// already made.
+    pub game: variants::Game,
+
+    /// How well the AI opponent plays.
+    pub difficulty: ttt::ai::Difficulty,
+
+    /// The player whose move it is at the start of the challenge.
+    pub player: Player,
+
+    /// Seed for picking which environments are shown.
+    pub environment_seed: u64,
+}
+
+impl DailyChallenge {
+    /// Gets today's challenge.
+    pub fn today() -> Self {
+        Self::for_date(ChallengeDate::today())
+    }
+
+    /// Gets the challenge for the provided date.
+    ///
+    /// The same date always gives the same challenge.
+    pub fn for_date(date: ChallengeDate) -> Self {
+        let mut rng = seeded_rng(date.seed());
+        let variant = *CHALLENGE_VARIANTS
+            .choose(&mut rng)
+            .expect("There are variants to pick from.");
+        let difficulty = *CHALLENGE_DIFFICULTIES
+            .choose(&mut rng)
+            .expect("There are difficulties to pick from.");
+
+        let mut game = variants::Game::new(variant);
+        let num_opening_moves = rng.gen_range(0, MAX_OPENING_MOVES + 1);
+        for _ in 0..num_opening_moves {
+            if let Some(game_move) = game.moves().choose(&mut rng) {
+                game.do_move(game_move);
+            }
+        }
+        let player = match game.state() {
+            ttt::game::State::PlayerOMove => Player::O,
+            _ => Player::X,
+        };
+
+        Self {
+            date,
+            game,
+            difficulty,
+            player,
+            environment_seed: rng.gen(),
+        }
+    }
+
+    /// Describes the challenge for showing to the player, such as
+    /// "Notakto, Hard".
+    pub fn description(&self) -> String {
+        format!("{}, {:?}", self.game.variant().name(), self.difficulty)
+    }
+}
+
+/// The result of a daily challenge game from the player's point of view.
+///
+/// Results are ordered from worst to best.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ChallengeResult {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl ChallengeResult {
+    /// Gets the result of the finished game for the provided player.
+    ///
+    /// `None` is returned if the game is not over.
+    pub fn from_state(state: &ttt::game::State, player: Player) -> Option<Self> {
+        match (state, player) {
+            (ttt::game::State::PlayerXWin(_), Player::X)
+            | (ttt::game::State::PlayerOWin(_), Player::O) => Some(Self::Win),
+            (ttt::game::State::PlayerXWin(_), _) | (ttt::game::State::PlayerOWin(_), _) => {
+                Some(Self::Loss)
+            }
+            (ttt::game::State::CatsGame, _) => Some(Self::Draw),
+            _ => None,
+        }
+    }
+}
+
+/// How the player did on a single day's challenge.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChallengeRecord {
+    /// The number of games started, including unfinished ones.
+    pub attempts: u32,
+
+    /// The best result of the finished games, `None` if no game was finished.
+    pub best_result: Option<ChallengeResult>,
+
+    /// The attempt the best result was first reached on.
+    pub best_attempt: Option<u32>,
+}
+
+impl ChallengeRecord {
+    /// Describes the record for comparing with friends, such as
+    /// "Win on attempt 2 of 3".
+    pub fn summary(&self) -> String {
+        match (self.best_result, self.best_attempt) {
+            (Some(result), Some(attempt)) => {
+                format!("{:?} on attempt {} of {}", result, attempt, self.attempts)
+            }
+            _ => format!("Not finished after {} attempts", self.attempts),
+        }
+    }
+}
+
+/// The player's records of the daily challenges they have played.
+///
+/// This is saved to the user data directory so results can be compared by
+/// date.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChallengeRecords {
+    /// The records keyed by date, formatted as `YYYY-MM-DD`.
+    pub records: BTreeMap<String, ChallengeRecord>,
+}
+
+impl ChallengeRecords {
+    /// Reads the saved records, logging any problems.
+    ///
+    /// There are no records the first time a challenge is played or if the
+    /// records cannot be read.
+    pub fn load() -> Self {
+        file_io::load_or_default(
+            file_io::challenge_records_file,
+            "the daily challenge records",
+        )
+    }
+
+    /// Saves the records, logging any problems.
+    pub fn save(&self) {
+        file_io::save(
+            file_io::challenge_records_file,
+            self,
+            "the daily challenge records",
+        );
+    }
+
+    /// Gets the record for the date, if the challenge has been played.
+    pub fn get(&self, date: ChallengeDate) -> Option<&ChallengeRecord> {
+        self.records.get(&date.to_string())
+    }
+
+    /// Records that a game of the date's challenge was started.
+    pub fn record_attempt(&mut self, date: ChallengeDate) {
+        self.records.entry(date.to_string()).or_default().attempts += 1;
+    }
+
+    /// Records the result of a finished game of the date's challenge,
+    /// keeping it if it is the best result so far.
+    pub fn record_result(&mut self, date: ChallengeDate, result: ChallengeResult) {
+        let record = self.records.entry(date.to_string()).or_default();
+        if record.best_result.is_none_or(|best| result > best) {
+            record.best_result = Some(result);
+            record.best_attempt = Some(record.attempts);
+        }
+    }
+
+    /// Gets the dates and records of the most recently played challenges,
+    /// newest first.
+    pub fn recent(&self, count: usize) -> Vec<(&str, &ChallengeRecord)> {
+        self.records
+            .iter()
+            .rev()
+            .take(count)
+            .map(|(date, record)| (date.as_str(), record))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge_date_from_days_since_epoch_when_epoch_should_be_1970_01_01() {
+        let date = ChallengeDate::from_days_since_epoch(0);
+
+        assert_eq!(date.to_string(), "1970-01-01");
+    }
+
+    #[test]
+    fn challenge_date_from_days_since_epoch_when_leap_day_should_be_february_29() {
+        // 2024-02-29 is 19,782 days after the epoch.
+        let date = ChallengeDate::from_days_since_epoch(19_782);
+
+        assert_eq!(date.to_string(), "2024-02-29");
+    }
+
+    #[test]
+    fn challenge_date_from_days_since_epoch_when_end_of_year_should_be_december_31() {
+        // 2025-12-31 is 20,453 days after the epoch.
+        let date = ChallengeDate::from_days_since_epoch(20_453);
+
+        assert_eq!(date.to_string(), "2025-12-31");
+    }
+
+    #[test]
+    fn daily_challenge_for_date_when_same_date_should_be_same_challenge() {
+        let date = ChallengeDate::from_days_since_epoch(20_000);
+
+        let first = DailyChallenge::for_date(date);
+        let second = DailyChallenge::for_date(date);
+
+        assert_eq!(first.game.variant(), second.game.variant());
+        assert_eq!(
+            variants::board_to_rows(first.game.board()),
+            variants::board_to_rows(second.game.board())
+        );
+        assert_eq!(first.difficulty, second.difficulty);
+        assert_eq!(first.player, second.player);
+        assert_eq!(first.environment_seed, second.environment_seed);
+    }
+
+    #[test]
+    fn daily_challenge_for_date_should_be_same_on_every_version() {
+        // Changing how challenges are picked would give players on different
+        // versions different challenges for the same date.
+        let challenge = DailyChallenge::for_date(ChallengeDate::from_days_since_epoch(20_454));
+
+        assert_eq!(challenge.date.to_string(), "2026-01-01");
+        assert_eq!(challenge.game.variant(), variants::Variant::Numerical);
+        assert_eq!(
+            variants::board_to_rows(challenge.game.board()),
+            vec!["...", "...", "..."]
+        );
+        assert_eq!(challenge.difficulty, ttt::ai::Difficulty::Medium);
+        assert_eq!(challenge.player, Player::X);
+        assert_eq!(challenge.environment_seed, 16_076_022_717_317_238_836);
+    }
+
+    #[test]
+    fn daily_challenge_for_date_should_start_with_players_move() {
+        for days in 20_000..20_100 {
+            let challenge = DailyChallenge::for_date(ChallengeDate::from_days_since_epoch(days));
+
+            let expected_state = match challenge.player {
+                Player::X => ttt::game::State::PlayerXMove,
+                Player::O => ttt::game::State::PlayerOMove,
+            };
+            assert_eq!(challenge.game.state(), expected_state);
+        }
+    }
+
+    #[test]
+    fn challenge_records_record_result_when_worse_result_should_keep_best() {
+        let date = ChallengeDate::from_days_since_epoch(20_000);
+        let mut records = ChallengeRecords::default();
+        records.record_attempt(date);
+        records.record_result(date, ChallengeResult::Draw);
+        records.record_attempt(date);
+
+        records.record_result(date, ChallengeResult::Loss);
+
+        let record = records.get(date).unwrap();
+        assert_eq!(record.attempts, 2);
+        assert_eq!(record.best_result, Some(ChallengeResult::Draw));
+        assert_eq!(record.best_attempt, Some(1));
+    }
+
+    #[test]
+    fn challenge_records_recent_should_be_newest_first() {
+        let mut records = ChallengeRecords::default();
+        records.record_attempt(ChallengeDate::from_days_since_epoch(20_000));
+        records.record_attempt(ChallengeDate::from_days_since_epoch(20_002));
+        records.record_attempt(ChallengeDate::from_days_since_epoch(20_001));
+
+        let recent = records.recent(2);
+
+        let dates: Vec<&str> = recent.iter().map(|(date, _)| *date).collect();
+        assert_eq!(dates, vec!["2024-10-06", "2024-10-05"]);
+    }
+}
@@ -0,0 +1,281 @@
+//! Contains endless runs, where the player keeps playing classic games against
+//! an AI opponent that gets stronger after every game.
+//!
+//! A run ends on the player's first loss. The best runs are saved to a local
+//! leaderboard.
+use open_ttt_lib as ttt;
+use serde::{Deserialize, Serialize};
+
+use crate::daily_challenge::{ChallengeDate, ChallengeResult};
+use crate::file_io;
+
+// The chance the AI makes a mistake at each of the first levels, going from
+// easy through medium to hard.
+const LEVEL_MISTAKE_PROBABILITIES: [f64; 5] = [0.5, 0.35, 0.2, 0.1, 0.05];
+
+// How much less likely the AI is to make a mistake at each level past hard.
+const MISTAKE_DECAY_PAST_HARD: f64 = 0.5;
+
+// Points for each result at the first level. Later levels multiply these by
+// the level number.
+const WIN_POINTS: u32 = 3;
+const DRAW_POINTS: u32 = 1;
+
+/// The number of runs kept on the leaderboard.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Gets the AI difficulty at the provided level, starting from level 1.
+///
+/// Levels 1, 3, and 5 match the easy, medium, and hard difficulties. Past
+/// hard the AI keeps getting less likely to make a mistake, but never becomes
+/// unbeatable.
+pub fn level_difficulty(level: u32) -> ttt::ai::Difficulty {
+    match level {
+        0 | 1 => ttt::ai::Difficulty::Easy,
+        3 => ttt::ai::Difficulty::Medium,
+        5 => ttt::ai::Difficulty::Hard,
+        level => {
+            let index = level as usize - 1;
+            let probability = match LEVEL_MISTAKE_PROBABILITIES.get(index) {
+                Some(probability) => *probability,
+                None => {
+                    let levels_past_hard = (index + 1 - LEVEL_MISTAKE_PROBABILITIES.len()) as i32;
+                    LEVEL_MISTAKE_PROBABILITIES[LEVEL_MISTAKE_PROBABILITIES.len() - 1]
+                        * MISTAKE_DECAY_PAST_HARD.powi(levels_past_hard)
+                }
+            };
+            ttt::ai::Difficulty::Custom(probability)
+        }
+    }
+}
+
+/// A single endless run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Run {
+    streak: u32,
+    wins: u32,
+    score: u32,
+    is_over: bool,
+}
+
+impl Run {
+    /// Creates a new run starting at level 1.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the level of the next game, which goes up by one for every game
+    /// the player does not lose.
+    pub fn level(&self) -> u32 {
+        self.streak + 1
+    }
+
+    /// Gets the AI difficulty for the next game.
+    pub fn difficulty(&self) -> ttt::ai::Difficulty {
+        level_difficulty(self.level())
+    }
+
+    /// Gets the number of games in a row the player has won or drawn.
+    pub fn streak(&self) -> u32 {
+        self.streak
+    }
+
+    /// Gets the number of games the player has won.
+    pub fn wins(&self) -> u32 {
+        self.wins
+    }
+
+    /// Gets the points scored, with games at higher levels worth more.
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Indicates the player has lost, ending the run.
+    pub fn is_over(&self) -> bool {
+        self.is_over
+    }
+
+    /// Records the result of the game played at the current level.
+    ///
+    /// Wins and draws move the run to the next level, while a loss ends it.
+    /// Results after the run is over are ignored.
+    pub fn record_result(&mut self, result: ChallengeResult) {
+        if self.is_over {
+            return;
+        }
+
+        let level = self.level();
+        match result {
+            ChallengeResult::Win => {
+                self.wins += 1;
+                self.score += WIN_POINTS * level;
+            }
+            ChallengeResult::Draw => self.score += DRAW_POINTS * level,
+            ChallengeResult::Loss => {
+                self.is_over = true;
+                return;
+            }
+        }
+        self.streak += 1;
+    }
+}
+
+/// A finished run saved on the leaderboard.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub score: u32,
+    pub streak: u32,
+    pub wins: u32,
+    /// The date the run was played, formatted as `YYYY-MM-DD`.
+    pub date: String,
+}
+
+impl LeaderboardEntry {
+    /// Creates the entry for the run played on the provided date.
+    pub fn new(run: &Run, date: ChallengeDate) -> Self {
+        Self {
+            score: run.score(),
+            streak: run.streak(),
+            wins: run.wins(),
+            date: date.to_string(),
+        }
+    }
+
+    /// Describes the entry for showing on the leaderboard, such as
+    /// "42 points, streak 7 (2024-10-06)".
+    pub fn summary(&self) -> String {
+        format!(
+            "{} points, streak {} ({})",
+            self.score, self.streak, self.date
+        )
+    }
+}
+
+/// The best endless runs, saved between runs of the game.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    /// The entries from best to worst.
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    /// Reads the saved leaderboard, logging any problems.
+    ///
+    /// The leaderboard is empty the first time a run is played or if it
+    /// cannot be read.
+    pub fn load() -> Self {
+        file_io::load_or_default(file_io::leaderboard_file, "the endless leaderboard")
+    }
+
+    /// Saves the leaderboard, logging any problems.
+    pub fn save(&self) {
+        file_io::save(file_io::leaderboard_file, self, "the endless leaderboard");
+    }
+
+    /// Adds the entry if it is one of the best runs.
+    ///
+    /// Runs with higher scores rank first, then longer streaks. Ties go to
+    /// the run played first. The entry's position on the leaderboard, counting
+    /// from 1, is returned, or `None` if it did not make the leaderboard.
+    pub fn add(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let index = self
+            .entries
+            .iter()
+            .position(|other| (entry.score, entry.streak) > (other.score, other.streak))
+            .unwrap_or(self.entries.len());
+        if index >= LEADERBOARD_SIZE {
+            return None;
+        }
+
+        self.entries.insert(index, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(index + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32, streak: u32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            score,
+            streak,
+            wins: 0,
+            date: "2024-10-06".to_string(),
+        }
+    }
+
+    fn mistake_probability(difficulty: ttt::ai::Difficulty) -> f64 {
+        crate::variants::mistake_probability(difficulty)
+    }
+
+    #[test]
+    fn level_difficulty_should_make_fewer_mistakes_at_every_level() {
+        let probabilities: Vec<f64> = (1..12)
+            .map(|level| mistake_probability(level_difficulty(level)))
+            .collect();
+
+        assert!(probabilities.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(probabilities.iter().all(|probability| *probability > 0.0));
+    }
+
+    #[test]
+    fn level_difficulty_when_level_five_should_be_hard() {
+        let difficulty = level_difficulty(5);
+
+        assert_eq!(difficulty, ttt::ai::Difficulty::Hard);
+    }
+
+    #[test]
+    fn run_record_result_when_win_should_score_by_level() {
+        let mut run = Run::new();
+
+        run.record_result(ChallengeResult::Draw);
+        run.record_result(ChallengeResult::Win);
+
+        assert_eq!(run.score(), DRAW_POINTS + WIN_POINTS * 2);
+        assert_eq!(run.streak(), 2);
+        assert_eq!(run.level(), 3);
+    }
+
+    #[test]
+    fn run_record_result_when_loss_should_end_run() {
+        let mut run = Run::new();
+        run.record_result(ChallengeResult::Win);
+
+        run.record_result(ChallengeResult::Loss);
+        run.record_result(ChallengeResult::Win);
+
+        assert!(run.is_over());
+        assert_eq!(run.streak(), 1);
+        assert_eq!(run.score(), WIN_POINTS);
+    }
+
+    #[test]
+    fn leaderboard_add_when_tied_should_rank_earlier_run_first() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.add(entry(10, 3));
+
+        let rank = leaderboard.add(entry(10, 3));
+
+        assert_eq!(rank, Some(2));
+    }
+
+    #[test]
+    fn leaderboard_add_when_full_should_keep_best_runs() {
+        let mut leaderboard = Leaderboard::default();
+        for score in 1..=LEADERBOARD_SIZE as u32 {
+            leaderboard.add(entry(score, 1));
+        }
+
+        let low_rank = leaderboard.add(entry(0, 1));
+        let high_rank = leaderboard.add(entry(100, 1));
+
+        assert_eq!(low_rank, None);
+        assert_eq!(high_rank, Some(1));
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(leaderboard.entries.last(), Some(&entry(2, 1)));
+    }
+}
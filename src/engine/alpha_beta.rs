@@ -0,0 +1,512 @@
+use open_ttt_lib as ttt;
+use rand::prelude::*;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::variants;
+
+use super::symmetry::Symmetry;
+use super::{Cancellation, Engine};
+
+// How long the engine searches for a move. Deeper searches are started until
+// the time is up, so this bounds how long the game waits for the AI.
+const DEFAULT_TIME_BUDGET: Duration = Duration::from_millis(250);
+
+// The deepest search attempted, in moves. Games this long are only possible
+// in variants where marks move around the board.
+const MAX_DEPTH: u32 = 64;
+
+// The score for winning on the next move. Later wins score less, so the engine
+// takes the quickest win and puts off losses for as long as it can.
+const WIN_SCORE: i64 = 1_000_000_000;
+
+// Scores further from zero than this are wins or losses rather than estimates.
+const MIN_WIN_SCORE: i64 = WIN_SCORE - MAX_DEPTH as i64 - 1;
+
+// The number of positions searched between checks of the time and for
+// cancellation.
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+// The most positions kept in the transposition table before it is cleared.
+const MAX_TABLE_ENTRIES: usize = 1_000_000;
+
+/// AI engine that searches the moves ahead using minimax with alpha-beta
+/// pruning.
+///
+/// The engine searches one move deeper at a time until its time budget runs
+/// out or it has seen every possible ending. Positions already searched are
+/// kept in a transposition table, where rotated and mirrored copies of a
+/// position share one entry. When a search stops before the end of the game,
+/// positions are scored by the variant's `heuristic_score`.
+#[derive(Clone, Debug)]
+pub struct AlphaBetaEngine {
+    mistake_probability: f64,
+    time_budget: Duration,
+}
+
+impl AlphaBetaEngine {
+    /// Creates a new engine with the provided difficulty.
+    pub fn new(difficulty: ttt::ai::Difficulty) -> Self {
+        Self {
+            mistake_probability: variants::mistake_probability(difficulty),
+            time_budget: DEFAULT_TIME_BUDGET,
+        }
+    }
+
+    /// Gets the move the engine wishes to make.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_move(&self, game: &variants::Game) -> Option<variants::Move> {
+        self.get_move_with_cancellation(game, &Cancellation::new())
+    }
+
+    /// Gets the move the engine wishes to make, stopping the search early if
+    /// it is cancelled.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_move_with_cancellation(
+        &self,
+        game: &variants::Game,
+        cancellation: &Cancellation,
+    ) -> Option<variants::Move> {
+        let moves = game.moves();
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.mistake_probability) {
+            return moves.choose(&mut rng).copied();
+        }
+
+        let mut search = Search::new(
+            game.variant(),
+            Instant::now() + self.time_budget,
+            cancellation.clone(),
+        );
+        let mut best_moves = moves.clone();
+        for depth in 1..=MAX_DEPTH {
+            match search.root(game, &moves, &best_moves, depth) {
+                Some(moves) => best_moves = moves,
+                None => break,
+            }
+            log::debug!(
+                "Searched {} moves deep looking at {} positions.",
+                depth,
+                search.num_positions
+            );
+            if !search.is_depth_limited {
+                break;
+            }
+        }
+
+        best_moves.choose(&mut rng).copied()
+    }
+}
+
+impl Engine for AlphaBetaEngine {
+    fn get_move(&self, game: &variants::Game) -> Option<variants::Move> {
+        AlphaBetaEngine::get_move(self, game)
+    }
+
+    fn get_move_with_cancellation(
+        &self,
+        game: &variants::Game,
+        cancellation: &Cancellation,
+    ) -> Option<variants::Move> {
+        AlphaBetaEngine::get_move_with_cancellation(self, game, cancellation)
+    }
+}
+
+// Indicates how a score in the transposition table relates to the position's
+// true score.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    // The true score is at least this score.
+    Lower,
+    // The true score is at most this score.
+    Upper,
+}
+
+// A position already searched.
+#[derive(Clone, Debug)]
+struct TableEntry {
+    // How many moves ahead the position was searched.
+    depth: u32,
+    score: i64,
+    bound: Bound,
+    // Indicates the search stopped before the end of the game somewhere,
+    // so the score is only an estimate.
+    is_depth_limited: bool,
+    // The best move found, turned to match the position's key.
+    best_move: Option<variants::Move>,
+}
+
+// Holds the state of the search for a single move.
+struct Search {
+    symmetries: &'static [Symmetry],
+    board_size: i32,
+    table: HashMap<Vec<u8>, TableEntry>,
+    deadline: Instant,
+    cancellation: Cancellation,
+    // Indicates the search ran out of time or was cancelled.
+    is_out_of_time: bool,
+    // Indicates the search stopped before the end of the game somewhere.
+    is_depth_limited: bool,
+    num_positions: u64,
+}
+
+impl Search {
+    fn new(variant: variants::Variant, deadline: Instant, cancellation: Cancellation) -> Self {
+        Self {
+            symmetries: Symmetry::for_variant(variant),
+            board_size: variant.board_size(),
+            table: HashMap::new(),
+            deadline,
+            cancellation,
+            is_out_of_time: false,
+            is_depth_limited: false,
+            num_positions: 0,
+        }
+    }
+
+    // Searches every move the indicated number of moves ahead, returning the
+    // moves with the best score.
+    //
+    // The moves found best by the previous search are tried first. `None` is
+    // returned if the time ran out.
+    fn root(
+        &mut self,
+        game: &variants::Game,
+        moves: &[variants::Move],
+        previous_best_moves: &[variants::Move],
+        depth: u32,
+    ) -> Option<Vec<variants::Move>> {
+        self.is_depth_limited = false;
+        let is_x_move = game.state() == ttt::game::State::PlayerXMove;
+        let ordered_moves = previous_best_moves.iter().chain(
+            moves
+                .iter()
+                .filter(|game_move| !previous_best_moves.contains(game_move)),
+        );
+
+        let mut best_score = -WIN_SCORE * 2;
+        let mut best_moves = Vec::new();
+        for game_move in ordered_moves {
+            let mut next_game = game.clone();
+            next_game.do_move(game_move);
+            // Moves scoring one less than the best are ruled out, so moves as
+            // good as the best get exact scores.
+            let score = -self.negamax(
+                &next_game,
+                !is_x_move,
+                depth - 1,
+                1,
+                -WIN_SCORE * 2,
+                -(best_score - 1),
+            );
+            // The first search always finishes so there is a move to make.
+            if self.is_out_of_time && depth > 1 {
+                return None;
+            }
+            if score > best_score {
+                best_score = score;
+                best_moves.clear();
+            }
+            if score == best_score {
+                best_moves.push(*game_move);
+            }
+        }
+        Some(best_moves)
+    }
+
+    // Scores the game from the point of view of the player to move, searching
+    // the indicated number of moves ahead.
+    //
+    // The player to move is provided since it is not part of the state once
+    // the game is over. If the time runs out the score is incomplete and
+    // should not be used.
+    fn negamax(
+        &mut self,
+        game: &variants::Game,
+        is_x_move: bool,
+        depth: u32,
+        ply: u32,
+        mut alpha: i64,
+        beta: i64,
+    ) -> i64 {
+        let win_score = WIN_SCORE - i64::from(ply);
+        match game.state() {
+            ttt::game::State::PlayerXWin(_) if is_x_move => return win_score,
+            ttt::game::State::PlayerOWin(_) if !is_x_move => return win_score,
+            ttt::game::State::PlayerXWin(_) | ttt::game::State::PlayerOWin(_) => return -win_score,
+            ttt::game::State::CatsGame => return 0,
+            ttt::game::State::PlayerXMove | ttt::game::State::PlayerOMove => (),
+        }
+
+        self.num_positions += 1;
+        if self.num_positions.is_multiple_of(TIME_CHECK_INTERVAL)
+            && (Instant::now() >= self.deadline || self.cancellation.is_cancelled())
+        {
+            self.is_out_of_time = true;
+        }
+        if depth == 0 {
+            self.is_depth_limited = true;
+            let score = game.heuristic_score();
+            return if is_x_move { score } else { -score };
+        }
+
+        let (key, symmetry) = self.key(game);
+        let mut moves = game.moves();
+        if let Some(entry) = self.table.get(&key) {
+            if entry.depth >= depth || !entry.is_depth_limited {
+                let score = from_table_score(entry.score, ply);
+                let is_cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                if is_cutoff {
+                    self.is_depth_limited |= entry.is_depth_limited;
+                    return score;
+                }
+            }
+            // Try the move found best last time first.
+            if let Some(best_move) = entry.best_move {
+                let best_move = symmetry.inverse().apply_to_move(best_move, self.board_size);
+                if let Some(index) = moves.iter().position(|game_move| *game_move == best_move) {
+                    moves.swap(0, index);
+                }
+            }
+        }
+
+        // Track if this position's search hits the depth limit separately from
+        // the rest of the search, so it can be stored with the position.
+        let was_depth_limited = self.is_depth_limited;
+        self.is_depth_limited = false;
+        let original_alpha = alpha;
+        let mut best_score = -WIN_SCORE * 2;
+        let mut best_move = None;
+        for game_move in moves {
+            let mut next_game = game.clone();
+            next_game.do_move(&game_move);
+            let score = -self.negamax(&next_game, !is_x_move, depth - 1, ply + 1, -beta, -alpha);
+            if self.is_out_of_time {
+                // The score is incomplete, so it is not stored.
+                return best_score.max(score);
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(game_move);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        if self.table.len() >= MAX_TABLE_ENTRIES {
+            self.table.clear();
+        }
+        self.table.insert(
+            key,
+            TableEntry {
+                depth,
+                score: to_table_score(best_score, ply),
+                bound,
+                is_depth_limited: self.is_depth_limited,
+                best_move: best_move
+                    .map(|game_move| symmetry.apply_to_move(game_move, self.board_size)),
+            },
+        );
+        self.is_depth_limited |= was_depth_limited;
+
+        best_score
+    }
+
+    // Gets the key of the position in the transposition table along with the
+    // symmetry that turns the game's board into the key's board.
+    //
+    // Every turned or flipped copy of a position gets the same key.
+    fn key(&self, game: &variants::Game) -> (Vec<u8>, Symmetry) {
+        self.symmetries
+            .iter()
+            .map(|symmetry| (position_key(game, *symmetry, self.board_size), *symmetry))
+            .min_by(|(key, _), (other_key, _)| key.cmp(other_key))
+            .expect("There is always at least the identity symmetry.")
+    }
+}
+
+// Gets the key of the game's position after the symmetry is applied to it.
+//
+// The key holds the contents of every square followed by the details of the
+// game not shown on the board, such as the player to move.
+fn position_key(game: &variants::Game, symmetry: Symmetry, board_size: i32) -> Vec<u8> {
+    let board = game.board();
+    let columns = board.size().columns;
+    let mut key = vec![0; (board.size().rows * columns) as usize];
+    for (position, owner) in board.iter() {
+        let number = match game {
+            variants::Game::Numerical(game) => game.number(position).unwrap_or(0),
+            _ => 0,
+        };
+        let position = symmetry.apply(position, board_size);
+        key[(position.row * columns + position.column) as usize] = owner_code(owner) + 3 * number;
+    }
+
+    key.push((game.state() == ttt::game::State::PlayerXMove) as u8);
+    key.push(match game {
+        variants::Game::Numerical(game) => game.is_odd_players_turn() as u8,
+        variants::Game::OrderAndChaos(game) => game.is_x_order() as u8,
+        _ => 0,
+    });
+    key
+}
+
+fn owner_code(owner: ttt::board::Owner) -> u8 {
+    match owner {
+        ttt::board::Owner::None => 0,
+        ttt::board::Owner::PlayerX => 1,
+        ttt::board::Owner::PlayerO => 2,
+    }
+}
+
+// Wins are scored by how many moves away they are from the start of the
+// search. Positions in the table can be reached after any number of moves, so
+// their wins are stored counting from the position instead.
+fn to_table_score(score: i64, ply: u32) -> i64 {
+    if score >= MIN_WIN_SCORE {
+        score + i64::from(ply)
+    } else if score <= -MIN_WIN_SCORE {
+        score - i64::from(ply)
+    } else {
+        score
+    }
+}
+
+fn from_table_score(score: i64, ply: u32) -> i64 {
+    if score >= MIN_WIN_SCORE {
+        score - i64::from(ply)
+    } else if score <= -MIN_WIN_SCORE {
+        score + i64::from(ply)
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    fn custom_game(text: &str) -> variants::Game {
+        let rules = variants::CustomRules::from_ron(text).unwrap();
+        variants::Game::new(variants::Variant::Custom(Box::leak(Box::new(rules))))
+    }
+
+    fn play(game: &mut variants::Game, positions: &[(i32, i32)]) {
+        for (row, column) in positions {
+            game.do_move(&variants::Move::Mark(position(*row, *column)));
+        }
+    }
+
+    #[test]
+    fn search_key_when_rotated_position_should_match() {
+        let search = Search::new(
+            variants::Variant::Classic,
+            Instant::now(),
+            Cancellation::new(),
+        );
+        let mut game = variants::Game::new(variants::Variant::Classic);
+        let mut rotated_game = game.clone();
+        play(&mut game, &[(0, 0), (1, 1)]);
+        play(&mut rotated_game, &[(0, 2), (1, 1)]);
+
+        let (key, _) = search.key(&game);
+        let (rotated_key, _) = search.key(&rotated_game);
+
+        assert_eq!(key, rotated_key);
+    }
+
+    #[test]
+    fn search_key_when_gravity_and_mirrored_columns_should_match() {
+        let mut game = custom_game("(name: \"Drop\", board_size: 4, win_length: 3, gravity: true)");
+        let search = Search::new(game.variant(), Instant::now(), Cancellation::new());
+        let mut mirrored_game = game.clone();
+        play(&mut game, &[(0, 0)]);
+        play(&mut mirrored_game, &[(0, 3)]);
+
+        let (key, _) = search.key(&game);
+        let (mirrored_key, _) = search.key(&mirrored_game);
+
+        assert_eq!(key, mirrored_key);
+    }
+
+    #[test]
+    fn search_key_when_gravity_and_corner_on_other_side_should_not_match() {
+        let mut game = custom_game("(name: \"Drop\", board_size: 4, win_length: 3, gravity: true)");
+        let search = Search::new(game.variant(), Instant::now(), Cancellation::new());
+        let mut next_game = game.clone();
+        play(&mut game, &[(0, 0), (1, 0)]);
+        play(&mut next_game, &[(0, 0), (0, 1)]);
+
+        let (key, _) = search.key(&game);
+        let (next_key, _) = search.key(&next_game);
+
+        assert_ne!(key, next_key);
+    }
+
+    #[test]
+    fn alpha_beta_engine_get_move_when_can_win_should_win() {
+        let engine = AlphaBetaEngine::new(ttt::ai::Difficulty::Unbeatable);
+        let mut game = variants::Game::new(variants::Variant::Classic);
+        play(&mut game, &[(0, 0), (1, 0), (0, 1), (1, 1)]);
+
+        let game_move = engine.get_move(&game);
+
+        assert_eq!(game_move, Some(variants::Move::Mark(position(0, 2))));
+    }
+
+    #[test]
+    fn alpha_beta_engine_get_move_when_larger_board_should_block_three() {
+        let engine = AlphaBetaEngine::new(ttt::ai::Difficulty::Unbeatable);
+        let mut game = custom_game("(name: \"Big\", board_size: 7, win_length: 4)");
+        play(&mut game, &[(3, 1), (3, 0), (3, 2), (6, 6), (3, 3)]);
+
+        let game_move = engine.get_move(&game);
+
+        assert_eq!(game_move, Some(variants::Move::Mark(position(3, 4))));
+    }
+
+    #[test]
+    fn alpha_beta_engine_get_move_when_game_over_should_return_none() {
+        let engine = AlphaBetaEngine::new(ttt::ai::Difficulty::Unbeatable);
+        let mut game = variants::Game::new(variants::Variant::Classic);
+        play(&mut game, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]);
+
+        let game_move = engine.get_move(&game);
+
+        assert_eq!(game_move, None);
+    }
+
+    #[test]
+    fn alpha_beta_engine_get_move_when_cancelled_should_stop_searching() {
+        let engine = AlphaBetaEngine {
+            time_budget: Duration::from_secs(60),
+            ..AlphaBetaEngine::new(ttt::ai::Difficulty::Unbeatable)
+        };
+        let game = custom_game("(name: \"Big\", board_size: 7, win_length: 4)");
+        let cancellation = Cancellation::new();
+        let start = Instant::now();
+
+        cancellation.cancel();
+        let game_move = engine.get_move_with_cancellation(&game, &cancellation);
+
+        assert!(game_move.is_some());
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,373 @@
+//! Contains the protocol used to play against bots running as separate
+//! programs.
+//!
+//! The protocol is line based and inspired by the UCI protocol used by chess
+//! engines. The game writes commands to the bot's standard input and reads
+//! replies from its standard output, one per line. Anything the bot writes
+//! to its standard error is shown in the game's log.
+//!
+//! The game sends these commands:
+//!
+//! * `fossxo 1`: sent once when the bot is started, giving the protocol
+//!   version. The bot may reply with `id name <name>`.
+//! * `position <rows> <turn>`: the board to move on. The rows are listed from
+//!   the top row down and separated by `/`, with `X`, `O`, or `.` for each
+//!   square. The turn is `x` or `o`. For example, `position X../.O./... x`.
+//! * `go movetime <milliseconds>`: asks for a move for the last position,
+//!   which should be made within the time given. This is the time left for
+//!   the move, which is less than `MOVE_TIME` when the bot is asked again
+//!   after an illegal move.
+//! * `quit`: the bot should exit.
+//!
+//! The bot replies with:
+//!
+//! * `bestmove <row> <column>`: places a mark in the square. Rows are counted
+//!   from the top starting at 0, in the same order as the position's rows, and
+//!   columns are counted from the left.
+//! * `id name <name>`: the bot's name, which is logged.
+//! * `info <text>`: anything the bot wants logged.
+//!
+//! Other lines are ignored, so bots can be extended without breaking older
+//! versions of the game.
+use open_ttt_lib as ttt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::variants;
+
+/// The version of the protocol sent to bots.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// How long bots are given to make each move.
+///
+/// Bots that run out of time have their move made by the built-in AI.
+pub const MOVE_TIME: Duration = Duration::from_millis(5000);
+
+/// The number of illegal moves a bot can reply with before its move is made
+/// by the built-in AI.
+pub const MAX_ILLEGAL_REPLIES: u32 = 3;
+
+// How long a bot is given to exit after being told to quit before it is
+// stopped.
+const QUIT_TIME: Duration = Duration::from_millis(500);
+
+/// A reply from a bot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BotReply {
+    /// The square the bot wishes to place its mark in, counted from the top
+    /// row as sent in the position.
+    BestMove { row: i32, column: i32 },
+
+    /// The bot's name.
+    Name(String),
+
+    /// Information the bot wishes to be logged.
+    Info(String),
+}
+
+/// Parses a line written by a bot.
+///
+/// `None` is returned for lines that are not part of the protocol. An error
+/// is returned if the line is a known reply with missing or invalid values.
+pub fn parse_reply(line: &str) -> Option<Result<BotReply, String>> {
+    let line = line.trim();
+    let mut words = line.split_whitespace();
+    match words.next()? {
+        "bestmove" => Some(parse_best_move(line, words)),
+        "id" if words.next() == Some("name") => {
+            let name = words.collect::<Vec<&str>>().join(" ");
+            Some(Ok(BotReply::Name(name)))
+        }
+        "info" => Some(Ok(BotReply::Info(
+            line["info".len()..].trim_start().to_string(),
+        ))),
+        _ => None,
+    }
+}
+
+// Parses the row and column following `bestmove`.
+fn parse_best_move<'a>(
+    line: &str,
+    mut words: impl Iterator<Item = &'a str>,
+) -> Result<BotReply, String> {
+    let mut number = || -> Result<i32, String> {
+        let word = words
+            .next()
+            .ok_or_else(|| format!("'{}' is missing the row or column", line))?;
+        word.parse::<i32>()
+            .map_err(|_| format!("'{}' is not a row or column number", word))
+    };
+    let row = number()?;
+    let column = number()?;
+    Ok(BotReply::BestMove { row, column })
+}
+
+/// Creates the `position` command describing the game to a bot.
+///
+/// `None` is returned if the game is over.
+pub fn position_command(game: &variants::Game) -> Option<String> {
+    let turn = match game.state() {
+        ttt::game::State::PlayerXMove => "x",
+        ttt::game::State::PlayerOMove => "o",
+        _ => return None,
+    };
+    let rows = variants::board_to_rows(game.board()).join("/");
+    Some(format!("position {} {}", rows, turn))
+}
+
+/// Gets how long the bot has left to make its move, given the time it has
+/// already spent on it.
+pub fn time_left(time_spent: Duration) -> Duration {
+    MOVE_TIME.checked_sub(time_spent).unwrap_or_default()
+}
+
+/// Converts the row and column of a `bestmove` reply to a position on the
+/// game's board.
+///
+/// `None` is returned if the square is not on the board, which is an
+/// illegal reply.
+pub fn reply_position(game: &variants::Game, row: i32, column: i32) -> Option<ttt::game::Position> {
+    let size = game.board().size();
+    if !(0..size.rows).contains(&row) || !(0..size.columns).contains(&column) {
+        return None;
+    }
+    Some(ttt::game::Position {
+        row: size.rows - 1 - row,
+        column,
+    })
+}
+
+/// A bot running as a separate program that plays using the protocol.
+///
+/// The bot is started when created and told to quit when dropped.
+pub struct ExternalEngine {
+    // The bot's program, shown in log messages.
+    program: path::PathBuf,
+    child: Child,
+    stdin: ChildStdin,
+    // Lines the bot has written, read on a separate thread so waiting for the
+    // bot never holds up the game.
+    lines: Mutex<mpsc::Receiver<String>>,
+    // The number of moves asked for that the bot has not replied to. Bots
+    // reply to every request in order, so only the reply to the last request
+    // is for the current position.
+    num_unanswered: u32,
+    // Indicates the bot has closed its output, usually because it exited.
+    is_disconnected: bool,
+}
+
+impl ExternalEngine {
+    /// Starts the bot's program and sends it the protocol version.
+    ///
+    /// # Errors
+    /// An error is returned if the program cannot be started or written to.
+    pub fn launch(program: &path::Path) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("The bot's input is piped.");
+        let stdout = child.stdout.take().expect("The bot's output is piped.");
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => {
+                        if sender.send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let mut engine = Self {
+            program: program.to_path_buf(),
+            child,
+            stdin,
+            lines: Mutex::new(receiver),
+            num_unanswered: 0,
+            is_disconnected: false,
+        };
+        engine.send(&format!("fossxo {}", PROTOCOL_VERSION))?;
+        log::info!("Started bot {}.", program.display());
+        Ok(engine)
+    }
+
+    /// Asks the bot for its move in the game, which it has the given time to
+    /// make.
+    ///
+    /// The move is returned by `poll_move` once the bot replies.
+    ///
+    /// # Errors
+    /// An error is returned if the bot cannot be written to, usually because
+    /// it has exited.
+    pub fn request_move(&mut self, game: &variants::Game, time_left: Duration) -> io::Result<()> {
+        if let Some(position) = position_command(game) {
+            self.send(&position)?;
+            self.send(&format!("go movetime {}", time_left.as_millis()))?;
+            self.num_unanswered += 1;
+        }
+        Ok(())
+    }
+
+    /// Reads the bot's replies without waiting, returning the square of its
+    /// move as a row and column counted from the top once it has replied.
+    ///
+    /// Replies to earlier requests, such as for a game that has since been
+    /// restarted, are skipped.
+    ///
+    /// Names and information from the bot are logged, as are replies that
+    /// cannot be understood.
+    pub fn poll_move(&mut self) -> Option<(i32, i32)> {
+        loop {
+            let line = {
+                let lines = self
+                    .lines
+                    .lock()
+                    .expect("The bot's lines are only locked while being read.");
+                match lines.try_recv() {
+                    Ok(line) => line,
+                    Err(mpsc::TryRecvError::Empty) => return None,
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        if !self.is_disconnected {
+                            log::error!("Bot {} stopped responding.", self.program.display());
+                            self.is_disconnected = true;
+                        }
+                        return None;
+                    }
+                }
+            };
+            match parse_reply(&line) {
+                Some(Ok(BotReply::BestMove { row, column })) => match self.num_unanswered {
+                    0 => log::warn!("Ignored move the bot was not asked for: {}", line),
+                    1 => {
+                        self.num_unanswered = 0;
+                        return Some((row, column));
+                    }
+                    _ => self.num_unanswered -= 1,
+                },
+                Some(Ok(BotReply::Name(name))) => log::info!("Playing against bot {}.", name),
+                Some(Ok(BotReply::Info(text))) => log::debug!("Bot: {}", text),
+                Some(Err(error)) => log::warn!("Invalid reply from bot: {}", error),
+                None => log::debug!("Ignored line from bot: {}", line),
+            }
+        }
+    }
+
+    // Writes a command to the bot.
+    fn send(&mut self, command: &str) -> io::Result<()> {
+        log::debug!("Sending to bot: {}", command);
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()
+    }
+}
+
+impl Drop for ExternalEngine {
+    fn drop(&mut self) {
+        // Bots that ignore the quit command are stopped so they are not left
+        // running after the game.
+        let _ = self.send("quit");
+        let deadline = Instant::now() + QUIT_TIME;
+        while let Ok(None) = self.child.try_wait() {
+            if Instant::now() >= deadline {
+                let _ = self.child.kill();
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.wait();
+        log::info!("Stopped bot {}.", self.program.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reply_when_best_move_should_return_square() {
+        let reply = parse_reply("bestmove 0 2\n");
+
+        assert_eq!(reply, Some(Ok(BotReply::BestMove { row: 0, column: 2 })));
+    }
+
+    #[test]
+    fn parse_reply_when_best_move_missing_column_should_return_error() {
+        let reply = parse_reply("bestmove 1");
+
+        assert!(matches!(reply, Some(Err(_))));
+    }
+
+    #[test]
+    fn parse_reply_when_name_or_info_should_keep_text() {
+        let name = parse_reply("id name Random Bot");
+        let info = parse_reply("info searched 42 positions");
+
+        assert_eq!(name, Some(Ok(BotReply::Name("Random Bot".to_string()))));
+        assert_eq!(
+            info,
+            Some(Ok(BotReply::Info("searched 42 positions".to_string())))
+        );
+    }
+
+    #[test]
+    fn parse_reply_when_unknown_should_ignore_line() {
+        let reply = parse_reply("readyok");
+
+        assert_eq!(reply, None);
+    }
+
+    #[test]
+    fn position_command_should_list_rows_from_top() {
+        let mut game = variants::Game::new(variants::Variant::Classic);
+        game.do_move(&variants::Move::Mark(ttt::game::Position {
+            row: 2,
+            column: 0,
+        }));
+
+        let command = position_command(&game);
+
+        assert_eq!(command, Some("position X../.../... o".to_string()));
+    }
+
+    #[test]
+    fn time_left_when_part_of_move_time_spent_should_return_rest() {
+        let time_left = time_left(Duration::from_millis(1500));
+
+        assert_eq!(time_left, Duration::from_millis(3500));
+    }
+
+    #[test]
+    fn time_left_when_move_time_spent_should_return_zero() {
+        let time_left = time_left(MOVE_TIME + Duration::from_millis(1));
+
+        assert_eq!(time_left, Duration::from_millis(0));
+    }
+
+    #[test]
+    fn reply_position_should_count_rows_from_top() {
+        let game = variants::Game::new(variants::Variant::Classic);
+
+        let position = reply_position(&game, 0, 2);
+
+        assert_eq!(position, Some(ttt::game::Position { row: 2, column: 2 }));
+    }
+
+    #[test]
+    fn reply_position_when_off_the_board_should_be_none() {
+        let game = variants::Game::new(variants::Variant::Classic);
+
+        assert_eq!(reply_position(&game, i32::MIN, 0), None);
+        assert_eq!(reply_position(&game, 3, 0), None);
+        assert_eq!(reply_position(&game, 0, -1), None);
+        assert_eq!(reply_position(&game, 0, i32::MAX), None);
+    }
+}
@@ -0,0 +1,374 @@
+use open_ttt_lib as ttt;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::variants;
+
+use super::{Cancellation, Engine};
+
+// The most playouts used, by the unbeatable difficulty.
+const MAX_PLAYOUTS: u32 = 4000;
+
+// How quickly the number of playouts drops as the chance of a mistake goes up.
+// Higher powers leave the easier difficulties with far fewer playouts.
+const PLAYOUTS_POWER: i32 = 4;
+
+/// The usual exploration constant, the square root of two.
+pub const DEFAULT_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+// Random games longer than this are scored as a draw. Only variants where
+// marks move around the board can go on this long.
+const MAX_PLAYOUT_MOVES: usize = 200;
+
+// The reward for each result, from the point of view of the player who moved.
+const WIN_REWARD: f64 = 1.0;
+const DRAW_REWARD: f64 = 0.5;
+const LOSS_REWARD: f64 = 0.0;
+
+/// Options for the Monte Carlo tree search engine.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MctsOptions {
+    /// The number of random games played out for each move. No playouts means
+    /// the engine picks moves at random.
+    pub playouts: u32,
+
+    /// How much the search favors trying moves it has played out less over
+    /// moves that have done well so far.
+    pub exploration: f64,
+
+    /// Seeds the random number generator, so the engine always makes the same
+    /// move in the same position. Without a seed the moves vary.
+    pub seed: Option<u64>,
+}
+
+impl MctsOptions {
+    /// Creates the options for the provided difficulty.
+    ///
+    /// Harder difficulties play out more games, with unbeatable playing the
+    /// most and no difficulty playing none at all.
+    pub fn for_difficulty(difficulty: ttt::ai::Difficulty) -> Self {
+        let skill = 1.0 - variants::mistake_probability(difficulty);
+        Self {
+            playouts: (f64::from(MAX_PLAYOUTS) * skill.powi(PLAYOUTS_POWER)).round() as u32,
+            exploration: DEFAULT_EXPLORATION,
+            seed: None,
+        }
+    }
+}
+
+/// AI engine that uses Monte Carlo tree search.
+///
+/// Instead of scoring positions, the engine plays out games with random moves
+/// and picks the move that leads to the most wins. Each playout follows the
+/// moves that have done best so far, so the promising moves are looked at
+/// most. This gives reasonable play on boards far too large to search every
+/// move.
+#[derive(Clone, Debug)]
+pub struct MctsEngine {
+    options: MctsOptions,
+}
+
+impl MctsEngine {
+    /// Creates a new engine with the provided options.
+    pub fn new(options: MctsOptions) -> Self {
+        Self { options }
+    }
+
+    /// Gets the move the engine wishes to make.
+    ///
+    /// The move played out the most is picked. `None` is returned if the game
+    /// is over.
+    pub fn get_move(&self, game: &variants::Game) -> Option<variants::Move> {
+        self.get_move_with_cancellation(game, &Cancellation::new())
+    }
+
+    /// Gets the move the engine wishes to make, stopping the playouts early
+    /// if the search is cancelled.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_move_with_cancellation(
+        &self,
+        game: &variants::Game,
+        cancellation: &Cancellation,
+    ) -> Option<variants::Move> {
+        let mut rng = match self.options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        if self.options.playouts == 0 {
+            return game.moves().choose(&mut rng).copied();
+        }
+
+        let mut tree = Tree::new(game.clone());
+        let mut num_playouts = 0;
+        while num_playouts < self.options.playouts && !cancellation.is_cancelled() {
+            tree.playout(self.options.exploration, &mut rng);
+            num_playouts += 1;
+        }
+        log::debug!(
+            "Played out {} games using {} nodes.",
+            num_playouts,
+            tree.nodes.len()
+        );
+
+        tree.nodes[0]
+            .children
+            .iter()
+            .map(|index| &tree.nodes[*index])
+            .fold(None, |best: Option<&Node>, node| match best {
+                Some(best) if best.visits >= node.visits => Some(best),
+                _ => Some(node),
+            })
+            .and_then(|node| node.game_move)
+    }
+}
+
+impl Engine for MctsEngine {
+    fn get_move(&self, game: &variants::Game) -> Option<variants::Move> {
+        MctsEngine::get_move(self, game)
+    }
+
+    fn get_move_with_cancellation(
+        &self,
+        game: &variants::Game,
+        cancellation: &Cancellation,
+    ) -> Option<variants::Move> {
+        MctsEngine::get_move_with_cancellation(self, game, cancellation)
+    }
+}
+
+// A position in the search tree.
+struct Node {
+    game: variants::Game,
+    // The move that led to the position, which the root does not have.
+    game_move: Option<variants::Move>,
+    // Indicates player X made the move that led to the position.
+    is_x_mover: bool,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    // The moves that do not have a child yet.
+    untried_moves: Vec<variants::Move>,
+    visits: u32,
+    // The total reward from the playouts through the position, from the point
+    // of view of the player who made the move.
+    reward: f64,
+}
+
+impl Node {
+    fn new(
+        game: variants::Game,
+        game_move: Option<variants::Move>,
+        is_x_mover: bool,
+        parent: Option<usize>,
+    ) -> Self {
+        let untried_moves = game.moves();
+        Self {
+            game,
+            game_move,
+            is_x_mover,
+            parent,
+            children: Vec::new(),
+            untried_moves,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+
+    // Scores the node for picking which child to play out next, using the
+    // upper confidence bound applied to trees.
+    fn upper_confidence_bound(&self, parent_visits: u32, exploration: f64) -> f64 {
+        let average_reward = self.reward / f64::from(self.visits);
+        let log_visits = f64::from(parent_visits).ln();
+        average_reward + exploration * (log_visits / f64::from(self.visits)).sqrt()
+    }
+}
+
+// The search tree, with the nodes stored in the order they were added. The
+// first node is the position being searched.
+struct Tree {
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    fn new(game: variants::Game) -> Self {
+        let is_x_mover = game.state() != ttt::game::State::PlayerXMove;
+        Self {
+            nodes: vec![Node::new(game, None, is_x_mover, None)],
+        }
+    }
+
+    // Plays out a single game, adding one position to the tree.
+    fn playout(&mut self, exploration: f64, rng: &mut StdRng) {
+        let leaf = self.select(exploration);
+        let node = self.expand(leaf, rng);
+        let x_reward = simulate(&self.nodes[node].game, rng);
+        self.backpropagate(node, x_reward);
+    }
+
+    // Follows the best children down the tree until reaching a position that
+    // still has untried moves or where the game is over.
+    fn select(&self, exploration: f64) -> usize {
+        let mut index = 0;
+        loop {
+            let node = &self.nodes[index];
+            if !node.untried_moves.is_empty() || node.children.is_empty() {
+                return index;
+            }
+            index = *node
+                .children
+                .iter()
+                .max_by(|first, second| {
+                    let score = |child: usize| {
+                        self.nodes[child].upper_confidence_bound(node.visits, exploration)
+                    };
+                    score(**first)
+                        .partial_cmp(&score(**second))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .expect("The node was checked to have children.");
+        }
+    }
+
+    // Adds a child for one of the node's untried moves, picked at random.
+    //
+    // The node itself is returned if the game is over.
+    fn expand(&mut self, index: usize, rng: &mut StdRng) -> usize {
+        let child_index = self.nodes.len();
+        let node = &mut self.nodes[index];
+        if node.untried_moves.is_empty() {
+            return index;
+        }
+        let move_index = rng.gen_range(0, node.untried_moves.len());
+        let game_move = node.untried_moves.swap_remove(move_index);
+        let is_x_mover = node.game.state() == ttt::game::State::PlayerXMove;
+        let mut game = node.game.clone();
+        game.do_move(&game_move);
+        node.children.push(child_index);
+
+        self.nodes
+            .push(Node::new(game, Some(game_move), is_x_mover, Some(index)));
+        child_index
+    }
+
+    // Adds the result of the playout to the node and the nodes above it.
+    fn backpropagate(&mut self, index: usize, x_reward: f64) {
+        let mut next = Some(index);
+        while let Some(index) = next {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            node.reward += if node.is_x_mover {
+                x_reward
+            } else {
+                WIN_REWARD - x_reward
+            };
+            next = node.parent;
+        }
+    }
+}
+
+// Plays random moves until the game ends, returning the reward for player X.
+fn simulate(game: &variants::Game, rng: &mut StdRng) -> f64 {
+    let mut game = game.clone();
+    for _ in 0..MAX_PLAYOUT_MOVES {
+        match game.state() {
+            ttt::game::State::PlayerXWin(_) => return WIN_REWARD,
+            ttt::game::State::PlayerOWin(_) => return LOSS_REWARD,
+            ttt::game::State::CatsGame => return DRAW_REWARD,
+            ttt::game::State::PlayerXMove | ttt::game::State::PlayerOMove => {
+                let game_move = *game
+                    .moves()
+                    .choose(rng)
+                    .expect("There are always moves while the game is not over.");
+                game.do_move(&game_move);
+            }
+        }
+    }
+    DRAW_REWARD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    fn custom_game(text: &str) -> variants::Game {
+        let rules = variants::CustomRules::from_ron(text).unwrap();
+        variants::Game::new(variants::Variant::Custom(Box::leak(Box::new(rules))))
+    }
+
+    fn play(game: &mut variants::Game, positions: &[(i32, i32)]) {
+        for (row, column) in positions {
+            game.do_move(&variants::Move::Mark(position(*row, *column)));
+        }
+    }
+
+    fn seeded_options(seed: u64) -> MctsOptions {
+        MctsOptions {
+            seed: Some(seed),
+            ..MctsOptions::for_difficulty(ttt::ai::Difficulty::Medium)
+        }
+    }
+
+    #[test]
+    fn mcts_options_for_difficulty_should_play_out_more_games_when_harder() {
+        let difficulties = [
+            ttt::ai::Difficulty::None,
+            ttt::ai::Difficulty::Easy,
+            ttt::ai::Difficulty::Medium,
+            ttt::ai::Difficulty::Hard,
+            ttt::ai::Difficulty::Unbeatable,
+        ];
+
+        let playouts: Vec<u32> = difficulties
+            .iter()
+            .map(|difficulty| MctsOptions::for_difficulty(*difficulty).playouts)
+            .collect();
+
+        assert_eq!(playouts[0], 0);
+        assert!(playouts.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(playouts[4], MAX_PLAYOUTS);
+    }
+
+    #[test]
+    fn mcts_engine_get_move_when_same_seed_should_make_same_move() {
+        let mut game = custom_game("(name: \"Five\", board_size: 5, win_length: 4)");
+        play(&mut game, &[(2, 2), (1, 1)]);
+
+        let first = MctsEngine::new(seeded_options(7)).get_move(&game);
+        let second = MctsEngine::new(seeded_options(7)).get_move(&game);
+
+        assert!(first.is_some());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn mcts_engine_get_move_when_can_win_should_win() {
+        let engine = MctsEngine::new(seeded_options(1));
+        let mut game = custom_game("(name: \"Five\", board_size: 5, win_length: 4)");
+        play(&mut game, &[(0, 0), (4, 4), (0, 1), (4, 3), (0, 2), (2, 2)]);
+
+        let game_move = engine.get_move(&game);
+
+        assert_eq!(game_move, Some(variants::Move::Mark(position(0, 3))));
+    }
+
+    #[test]
+    fn mcts_engine_get_move_when_no_playouts_should_still_move() {
+        let engine = MctsEngine::new(MctsOptions::for_difficulty(ttt::ai::Difficulty::None));
+        let game = variants::Game::new(variants::Variant::OrderAndChaos);
+
+        let game_move = engine.get_move(&game);
+
+        assert!(game_move.is_some());
+    }
+
+    #[test]
+    fn mcts_engine_get_move_when_game_over_should_return_none() {
+        let engine = MctsEngine::new(seeded_options(1));
+        let mut game = variants::Game::new(variants::Variant::Classic);
+        play(&mut game, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]);
+
+        let game_move = engine.get_move(&game);
+
+        assert_eq!(game_move, None);
+    }
+}
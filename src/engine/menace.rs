@@ -0,0 +1,437 @@
+use open_ttt_lib as ttt;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::daily_challenge::ChallengeResult;
+use crate::file_io;
+use crate::rules::Player;
+use crate::variants;
+
+use super::symmetry::{Symmetry, ALL_SYMMETRIES};
+use super::Engine;
+
+// The beads put in a new matchbox for each free square, indexed by the
+// number of marks already on the board. Later moves start with fewer beads so
+// they are quicker to learn, as in Michie's original MENACE.
+const STARTING_BEADS: [u32; 9] = [4, 4, 3, 3, 2, 2, 1, 1, 1];
+
+// The beads added to each picked square after a win or draw, and taken away
+// after a loss.
+const WIN_BEADS: u32 = 3;
+const DRAW_BEADS: u32 = 1;
+const LOSS_BEADS: u32 = 1;
+
+// The classic board's size, which is the only board MENACE plays on.
+const BOARD_SIZE: i32 = 3;
+
+// The letters used for the squares in a matchbox's position: the player to
+// move's marks, the other player's marks, and free squares.
+const MOVER_MARK: char = 'M';
+const OTHER_MARK: char = 'T';
+const FREE_SQUARE: char = '.';
+
+/// The games won, drawn, and lost by the learning AI.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LearningRecord {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl LearningRecord {
+    /// Gets the number of games played.
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Describes the record for showing to the player, such as
+    /// "Won 3, drew 5, lost 12".
+    pub fn summary(&self) -> String {
+        format!(
+            "Won {}, drew {}, lost {}",
+            self.wins, self.draws, self.losses
+        )
+    }
+
+    /// Adds the result of a single game.
+    pub fn add(&mut self, result: ChallengeResult) {
+        match result {
+            ChallengeResult::Win => self.wins += 1,
+            ChallengeResult::Draw => self.draws += 1,
+            ChallengeResult::Loss => self.losses += 1,
+        }
+    }
+}
+
+/// A bead picked from a matchbox, which is rewarded or taken away once the
+/// game is over.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pick {
+    // The matchbox's position.
+    key: String,
+    // The square the bead was for, indexed by row then column of the
+    // matchbox's position.
+    index: usize,
+}
+
+/// The matchboxes of a MENACE style learning AI for classic games.
+///
+/// There is a matchbox for every position the AI has had to move in, with
+/// turned and flipped copies of a position sharing a box. Each box holds beads
+/// for the free squares. The AI picks a bead at random to decide where to move,
+/// so squares with more beads are picked more often. After the game, winning
+/// moves get more beads and losing moves lose some, so the AI gets better the
+/// more it plays.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Matchboxes {
+    /// The bead counts for each square, indexed by row then column, keyed by
+    /// the box's position.
+    pub boxes: BTreeMap<String, Vec<u32>>,
+
+    /// How the AI has done in every game it learned from.
+    pub record: LearningRecord,
+}
+
+impl Matchboxes {
+    /// Reads the saved matchboxes, logging any problems.
+    ///
+    /// The matchboxes are empty the first time the AI plays or if they
+    /// cannot be read.
+    pub fn load() -> Self {
+        file_io::load_or_default(file_io::matchboxes_file, "the learning AI's matchboxes")
+    }
+
+    /// Saves the matchboxes, logging any problems.
+    pub fn save(&self) {
+        file_io::save(
+            file_io::matchboxes_file,
+            self,
+            "the learning AI's matchboxes",
+        );
+    }
+
+    /// Picks a bead from the game's matchbox, returning the position to move
+    /// to along with the pick for learning from later.
+    ///
+    /// New boxes are filled with beads for every free square. A box that has
+    /// run out of beads is filled again, so the AI always has a move. `None`
+    /// is returned if the game is over.
+    pub fn pick(
+        &mut self,
+        game: &ttt::game::Game,
+        rng: &mut impl Rng,
+    ) -> Option<(ttt::game::Position, Pick)> {
+        if game.state().is_game_over() {
+            return None;
+        }
+
+        let (key, symmetry) = matchbox_key(game);
+        let beads = self
+            .boxes
+            .entry(key.clone())
+            .or_insert_with(|| starting_beads(&key));
+        if beads.iter().all(|count| *count == 0) {
+            *beads = starting_beads(&key);
+        }
+
+        let total: u32 = beads.iter().sum();
+        let mut bead = rng.gen_range(0, total);
+        let index = beads
+            .iter()
+            .position(|count| {
+                if bead < *count {
+                    true
+                } else {
+                    bead -= count;
+                    false
+                }
+            })
+            .expect("The bead is less than the total number of beads.");
+        let position = ttt::game::Position {
+            row: index as i32 / BOARD_SIZE,
+            column: index as i32 % BOARD_SIZE,
+        };
+
+        Some((
+            symmetry.inverse().apply(position, BOARD_SIZE),
+            Pick { key, index },
+        ))
+    }
+
+    /// Learns from the result of a game, adding beads for the picked squares
+    /// after a win or draw and taking them away after a loss.
+    pub fn learn(&mut self, picks: &[Pick], result: ChallengeResult) {
+        for pick in picks {
+            if let Some(count) = self
+                .boxes
+                .get_mut(&pick.key)
+                .and_then(|beads| beads.get_mut(pick.index))
+            {
+                *count = match result {
+                    ChallengeResult::Win => *count + WIN_BEADS,
+                    ChallengeResult::Draw => *count + DRAW_BEADS,
+                    ChallengeResult::Loss => count.saturating_sub(LOSS_BEADS),
+                };
+            }
+        }
+        self.record.add(result);
+    }
+}
+
+/// Learning AI engine for classic games, based on Donald Michie's MENACE.
+///
+/// The engine starts out playing almost at random and improves as it learns
+/// from each game, see `Matchboxes`. What it learns is saved after every game.
+pub struct MenaceEngine {
+    matchboxes: Mutex<Matchboxes>,
+    // The beads picked this game and the player they were picked for.
+    picks: Mutex<(Vec<Pick>, Option<Player>)>,
+}
+
+impl MenaceEngine {
+    /// Creates an engine using the saved matchboxes.
+    pub fn load() -> Self {
+        Self {
+            matchboxes: Mutex::new(Matchboxes::load()),
+            picks: Mutex::new((Vec::new(), None)),
+        }
+    }
+
+    /// Gets the move the engine wishes to make.
+    ///
+    /// `None` is returned if the game is over or is not a classic game.
+    pub fn get_move(&self, game: &variants::Game) -> Option<variants::Move> {
+        let game = match game {
+            variants::Game::Classic(game) => game,
+            _ => return None,
+        };
+        let player = match game.state() {
+            ttt::game::State::PlayerXMove => Player::X,
+            _ => Player::O,
+        };
+
+        let (position, pick) = self
+            .matchboxes
+            .lock()
+            .expect("The matchboxes are only locked while picking or learning.")
+            .pick(game, &mut rand::thread_rng())?;
+        let mut picks = self
+            .picks
+            .lock()
+            .expect("The picks are only locked while picking or learning.");
+        picks.0.push(pick);
+        picks.1 = Some(player);
+        Some(variants::Move::Mark(position))
+    }
+
+    /// Learns from the finished game and saves the matchboxes.
+    ///
+    /// Games the engine did not move in are ignored.
+    pub fn learn(&self, game: &variants::Game) {
+        let mut picks = self
+            .picks
+            .lock()
+            .expect("The picks are only locked while picking or learning.");
+        let (picked, player) = &mut *picks;
+        let result = match player {
+            Some(player) => ChallengeResult::from_state(&game.state(), *player),
+            None => None,
+        };
+        if let Some(result) = result {
+            let mut matchboxes = self
+                .matchboxes
+                .lock()
+                .expect("The matchboxes are only locked while picking or learning.");
+            matchboxes.learn(picked, result);
+            log::info!(
+                "The learning AI learned from a {:?}, after {} games.",
+                result,
+                matchboxes.record.games()
+            );
+            matchboxes.save();
+        }
+        picked.clear();
+        *player = None;
+    }
+}
+
+impl Engine for MenaceEngine {
+    fn get_move(&self, game: &variants::Game) -> Option<variants::Move> {
+        MenaceEngine::get_move(self, game)
+    }
+
+    fn learn(&self, game: &variants::Game) {
+        MenaceEngine::learn(self, game)
+    }
+}
+
+/// Trains the matchboxes by playing classic games against the computer.
+///
+/// The opponent's difficulty goes through the provided difficulties one game
+/// at a time, and the first player swaps every game. The record of the games
+/// played is returned.
+pub fn train(
+    matchboxes: &mut Matchboxes,
+    difficulties: &[ttt::ai::Difficulty],
+    num_games: u32,
+    rng: &mut impl Rng,
+) -> LearningRecord {
+    let mut record = LearningRecord::default();
+    let mut game = ttt::game::Game::new();
+    for (_, difficulty) in (0..num_games).zip(difficulties.iter().cycle()) {
+        let opponent = ttt::ai::Opponent::new(*difficulty);
+        let mut picks = Vec::new();
+        while !game.state().is_game_over() {
+            let position = if game.state() == ttt::game::State::PlayerXMove {
+                let (position, pick) = matchboxes.pick(&game, rng).expect("The game is not over.");
+                picks.push(pick);
+                position
+            } else {
+                opponent.get_move(&game).expect("The game is not over.")
+            };
+            game.do_move(position)
+                .expect("The AI only picks free squares.");
+        }
+
+        let result =
+            ChallengeResult::from_state(&game.state(), Player::X).expect("The game is over.");
+        matchboxes.learn(&picks, result);
+        record.add(result);
+        game.start_next_game();
+    }
+    record
+}
+
+// Gets the key of the game's matchbox along with the symmetry that turns the
+// game's board into the key's board.
+//
+// Every turned or flipped copy of a position gets the same key. The marks
+// are named by whose turn it is rather than X and O, so both players share the
+// matchboxes.
+fn matchbox_key(game: &ttt::game::Game) -> (String, Symmetry) {
+    let mover = match game.state() {
+        ttt::game::State::PlayerXMove => ttt::board::Owner::PlayerX,
+        _ => ttt::board::Owner::PlayerO,
+    };
+    ALL_SYMMETRIES
+        .iter()
+        .map(|symmetry| {
+            let mut squares = [FREE_SQUARE; (BOARD_SIZE * BOARD_SIZE) as usize];
+            for (position, owner) in game.board().iter() {
+                let position = symmetry.apply(position, BOARD_SIZE);
+                squares[(position.row * BOARD_SIZE + position.column) as usize] = match owner {
+                    ttt::board::Owner::None => FREE_SQUARE,
+                    owner if owner == mover => MOVER_MARK,
+                    _ => OTHER_MARK,
+                };
+            }
+            (squares.iter().collect::<String>(), *symmetry)
+        })
+        .min_by(|(key, _), (other_key, _)| key.cmp(other_key))
+        .expect("There are always symmetries.")
+}
+
+// Gets the beads for a new matchbox with the provided key.
+fn starting_beads(key: &str) -> Vec<u32> {
+    let num_marks = key.chars().filter(|square| *square != FREE_SQUARE).count();
+    let count = STARTING_BEADS[num_marks.min(STARTING_BEADS.len() - 1)];
+    key.chars()
+        .map(|square| if square == FREE_SQUARE { count } else { 0 })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    fn play(positions: &[(i32, i32)]) -> ttt::game::Game {
+        let mut game = ttt::game::Game::new();
+        for (row, column) in positions {
+            game.do_move(position(*row, *column)).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn matchbox_key_when_rotated_position_should_match() {
+        let game = play(&[(0, 0), (1, 1)]);
+        let rotated_game = play(&[(2, 2), (1, 1)]);
+
+        let (key, _) = matchbox_key(&game);
+        let (rotated_key, _) = matchbox_key(&rotated_game);
+
+        assert_eq!(key, rotated_key);
+    }
+
+    #[test]
+    fn matchboxes_pick_should_pick_free_square() {
+        let mut matchboxes = Matchboxes::default();
+        let game = play(&[(0, 0), (1, 1), (0, 2), (0, 1)]);
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let picks: Vec<ttt::game::Position> = (0..20)
+            .filter_map(|_| matchboxes.pick(&game, &mut rng))
+            .map(|(position, _)| position)
+            .collect();
+
+        assert_eq!(picks.len(), 20);
+        assert!(picks.iter().all(|position| game.can_move(*position)));
+    }
+
+    #[test]
+    fn matchboxes_learn_when_win_should_add_beads() {
+        let mut matchboxes = Matchboxes::default();
+        let game = play(&[]);
+        let (_, pick) = matchboxes
+            .pick(&game, &mut StdRng::seed_from_u64(1))
+            .unwrap();
+
+        matchboxes.learn(std::slice::from_ref(&pick), ChallengeResult::Win);
+
+        let beads = &matchboxes.boxes[&pick.key];
+        assert_eq!(beads[pick.index], STARTING_BEADS[0] + WIN_BEADS);
+        assert_eq!(matchboxes.record.wins, 1);
+    }
+
+    #[test]
+    fn matchboxes_pick_when_box_empty_should_fill_box_again() {
+        let mut matchboxes = Matchboxes::default();
+        let game = play(&[
+            (0, 0),
+            (1, 1),
+            (0, 2),
+            (0, 1),
+            (2, 1),
+            (1, 0),
+            (1, 2),
+            (2, 2),
+        ]);
+        let (key, _) = matchbox_key(&game);
+        matchboxes.boxes.insert(key.clone(), vec![0; 9]);
+
+        let pick = matchboxes.pick(&game, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(pick.map(|(position, _)| position), Some(position(2, 0)));
+        assert_eq!(matchboxes.boxes[&key], starting_beads(&key));
+    }
+
+    #[test]
+    fn train_should_record_every_game() {
+        let mut matchboxes = Matchboxes::default();
+
+        let record = train(
+            &mut matchboxes,
+            &[ttt::ai::Difficulty::Easy, ttt::ai::Difficulty::Hard],
+            10,
+            &mut StdRng::seed_from_u64(1),
+        );
+
+        assert_eq!(record.games(), 10);
+        assert_eq!(matchboxes.record, record);
+    }
+}
@@ -0,0 +1,87 @@
+//! Contains the AI engines that pick moves for computer players.
+//!
+//! The variants' own opponents, including `variants::ClassicOpponent` for
+//! classic games, are one kind of engine. The search based engines in this
+//! module play any variant, which helps on the larger boards where searching
+//! every possible move is too slow.
+
+mod alpha_beta;
+mod external;
+mod mcts;
+mod menace;
+mod personality;
+mod symmetry;
+mod worker;
+
+pub use self::alpha_beta::*;
+pub use self::external::*;
+pub use self::mcts::*;
+pub use self::menace::*;
+pub use self::personality::*;
+pub(crate) use self::symmetry::{square_bit, Symmetry, ALL_SYMMETRIES};
+pub use self::worker::*;
+
+use open_ttt_lib as ttt;
+
+use crate::variants;
+
+/// An AI that picks moves for a computer player.
+///
+/// Engines are held by the `AiPlayer` component and search for moves on worker
+/// threads, so they must be safe to share between threads.
+pub trait Engine: Send + Sync {
+    /// Gets the move the engine wishes to make.
+    ///
+    /// `None` is returned if the game is over or the engine does not play the
+    /// game's variant.
+    fn get_move(&self, game: &variants::Game) -> Option<variants::Move>;
+
+    /// Gets the move the engine wishes to make, stopping early if the search
+    /// is cancelled.
+    ///
+    /// The move returned after a cancellation is never made, so engines can
+    /// return whatever they have found so far. By default the search runs to
+    /// the end, which suits engines that move quickly.
+    fn get_move_with_cancellation(
+        &self,
+        game: &variants::Game,
+        _cancellation: &Cancellation,
+    ) -> Option<variants::Move> {
+        self.get_move(game)
+    }
+
+    /// Lets the engine learn from a finished game.
+    ///
+    /// Most engines do not learn, so by default nothing happens.
+    fn learn(&self, _game: &variants::Game) {}
+}
+
+impl Engine for variants::Opponent {
+    fn get_move(&self, game: &variants::Game) -> Option<variants::Move> {
+        variants::Opponent::get_move(self, game)
+    }
+}
+
+// The smallest board Monte Carlo tree search is used for. Smaller boards can
+// be searched deeply enough by the alpha-beta engine.
+const MCTS_MIN_BOARD_SIZE: i32 = 5;
+
+/// Creates the engine used for the provided variant and difficulty.
+///
+/// Order and chaos and the custom variants can be played on boards too large
+/// for their opponents to search deeply. The largest boards use Monte Carlo
+/// tree search and the rest use the alpha-beta engine. The other variants use
+/// their own opponents.
+pub fn for_variant(variant: variants::Variant, difficulty: ttt::ai::Difficulty) -> Box<dyn Engine> {
+    match variant {
+        variants::Variant::OrderAndChaos | variants::Variant::Custom(_)
+            if variant.board_size() >= MCTS_MIN_BOARD_SIZE =>
+        {
+            Box::new(MctsEngine::new(MctsOptions::for_difficulty(difficulty)))
+        }
+        variants::Variant::OrderAndChaos | variants::Variant::Custom(_) => {
+            Box::new(AlphaBetaEngine::new(difficulty))
+        }
+        _ => Box::new(variants::Opponent::new(variant, difficulty)),
+    }
+}
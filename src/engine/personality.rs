@@ -0,0 +1,349 @@
+use open_ttt_lib as ttt;
+use rand::prelude::*;
+use std::time;
+
+use crate::variants;
+
+use super::Engine;
+
+// The lines of three on the classic board.
+const LINES: [[(i32, i32); 3]; 8] = [
+    [(0, 0), (0, 1), (0, 2)],
+    [(1, 0), (1, 1), (1, 2)],
+    [(2, 0), (2, 1), (2, 2)],
+    [(0, 0), (1, 0), (2, 0)],
+    [(0, 1), (1, 1), (2, 1)],
+    [(0, 2), (1, 2), (2, 2)],
+    [(0, 0), (1, 1), (2, 2)],
+    [(0, 2), (1, 1), (2, 0)],
+];
+
+/// How long an AI player takes to think about its moves.
+///
+/// The time spent on each move is picked at random between the shortest and
+/// longest times, with times near the middle picked most often.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ThinkTime {
+    /// The shortest time spent on a move, in seconds.
+    pub min_seconds: f32,
+
+    /// The longest time spent on a move, in seconds.
+    pub max_seconds: f32,
+}
+
+impl ThinkTime {
+    /// Creates a think time that is the same for every move.
+    pub const fn fixed(seconds: f32) -> Self {
+        Self {
+            min_seconds: seconds,
+            max_seconds: seconds,
+        }
+    }
+
+    /// Picks how long to think about the next move.
+    pub fn sample(&self, rng: &mut impl Rng) -> time::Duration {
+        // The average of two random numbers is more likely to be near the
+        // middle of the range.
+        let fraction = (rng.gen::<f32>() + rng.gen::<f32>()) / 2.0;
+        let seconds = self.min_seconds + (self.max_seconds - self.min_seconds) * fraction;
+        time::Duration::from_secs_f32(seconds.max(0.0))
+    }
+}
+
+impl Default for ThinkTime {
+    /// A short, steady delay that gives the impression of "thinking".
+    fn default() -> Self {
+        Self::fixed(0.25)
+    }
+}
+
+/// How a personality picks between moves that are equally good.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlayStyle {
+    /// Prefers squares in the lines the other player has started.
+    Blocker,
+    /// Prefers squares that add to its own lines, setting up forks.
+    Forker,
+    /// Prefers the center, then the corners.
+    CenterLover,
+    /// Prefers the corners, then the center.
+    CornerLover,
+}
+
+impl PlayStyle {
+    // Scores how much the style likes placing a mark at the free position,
+    // with higher scores liked more.
+    fn preference(self, game: &ttt::game::Game, position: ttt::game::Position) -> i32 {
+        let mover = match game.state() {
+            ttt::game::State::PlayerXMove => ttt::board::Owner::PlayerX,
+            _ => ttt::board::Owner::PlayerO,
+        };
+        // Counts the owner's marks in the lines through the position, skipping
+        // lines the other player has marks in.
+        let open_line_marks = |owner| -> i32 {
+            LINES
+                .iter()
+                .filter(|line| line.contains(&(position.row, position.column)))
+                .map(|line| {
+                    line.iter()
+                        .filter_map(|(row, column)| {
+                            game.board().get(ttt::game::Position {
+                                row: *row,
+                                column: *column,
+                            })
+                        })
+                        .filter(|square| *square != ttt::board::Owner::None)
+                        .collect::<Vec<ttt::board::Owner>>()
+                })
+                .filter(|marks| marks.iter().all(|mark| *mark == owner))
+                .map(|marks| marks.len() as i32)
+                .sum()
+        };
+        let is_center = position.row == 1 && position.column == 1;
+        let is_corner = position.row != 1 && position.column != 1;
+
+        match self {
+            Self::Blocker => match mover {
+                ttt::board::Owner::PlayerX => open_line_marks(ttt::board::Owner::PlayerO),
+                _ => open_line_marks(ttt::board::Owner::PlayerX),
+            },
+            Self::Forker => open_line_marks(mover),
+            Self::CenterLover => match (is_center, is_corner) {
+                (true, _) => 2,
+                (_, true) => 1,
+                _ => 0,
+            },
+            Self::CornerLover => match (is_center, is_corner) {
+                (_, true) => 2,
+                (true, _) => 1,
+                _ => 0,
+            },
+        }
+    }
+}
+
+/// A named AI opponent for classic games with its own way of playing.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Personality {
+    /// The name shown to the player.
+    pub name: &'static str,
+
+    /// A short description of how the opponent plays.
+    pub description: &'static str,
+
+    /// How often the opponent makes mistakes.
+    pub difficulty: ttt::ai::Difficulty,
+
+    /// Where the opponent places its mark when it moves first.
+    pub opening: ttt::game::Position,
+
+    /// How the opponent picks between equally good moves.
+    pub style: PlayStyle,
+
+    /// How long the opponent takes to think about its moves.
+    pub think_time: ThinkTime,
+}
+
+/// The personalities the player can choose from.
+pub const PERSONALITIES: [Personality; 4] = [
+    Personality {
+        name: "Center-Lover",
+        description: "Can't resist the middle",
+        difficulty: ttt::ai::Difficulty::Easy,
+        opening: ttt::game::Position { row: 1, column: 1 },
+        style: PlayStyle::CenterLover,
+        think_time: ThinkTime {
+            min_seconds: 0.1,
+            max_seconds: 0.4,
+        },
+    },
+    Personality {
+        name: "The Blocker",
+        description: "Gets in your way",
+        difficulty: ttt::ai::Difficulty::Medium,
+        opening: ttt::game::Position { row: 0, column: 1 },
+        style: PlayStyle::Blocker,
+        think_time: ThinkTime {
+            min_seconds: 0.2,
+            max_seconds: 0.6,
+        },
+    },
+    Personality {
+        name: "Corner Cat",
+        description: "Stalks the corners",
+        difficulty: ttt::ai::Difficulty::Medium,
+        opening: ttt::game::Position { row: 2, column: 2 },
+        style: PlayStyle::CornerLover,
+        think_time: ThinkTime {
+            min_seconds: 0.8,
+            max_seconds: 2.0,
+        },
+    },
+    Personality {
+        name: "The Forker",
+        description: "Sets traps",
+        difficulty: ttt::ai::Difficulty::Hard,
+        opening: ttt::game::Position { row: 0, column: 0 },
+        style: PlayStyle::Forker,
+        think_time: ThinkTime {
+            min_seconds: 0.4,
+            max_seconds: 1.2,
+        },
+    },
+];
+
+/// AI engine that plays classic games with a personality.
+///
+/// Like `variants::ClassicOpponent`, the engine scores moves with the perfect
+/// play table and sometimes makes a random move instead based on the
+/// personality's difficulty. Openings and the choice between equally good moves follow the
+/// personality's preferences.
+#[derive(Clone, Debug)]
+pub struct PersonalityEngine {
+    personality: Personality,
+    mistake_probability: f64,
+}
+
+impl PersonalityEngine {
+    /// Creates an engine that plays with the provided personality.
+    pub fn new(personality: Personality) -> Self {
+        Self {
+            personality,
+            mistake_probability: variants::mistake_probability(personality.difficulty),
+        }
+    }
+
+    /// Gets the position the engine wishes to place its mark.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_classic_move(
+        &self,
+        game: &ttt::game::Game,
+        rng: &mut impl Rng,
+    ) -> Option<ttt::game::Position> {
+        let moves: Vec<ttt::game::Position> = game.free_positions().collect();
+        if game.state().is_game_over() || moves.is_empty() {
+            return None;
+        }
+        if moves.len() == 9 && game.can_move(self.personality.opening) {
+            return Some(self.personality.opening);
+        }
+        if rng.gen_bool(self.mistake_probability) {
+            return moves.choose(rng).copied();
+        }
+
+        let scored_moves: Vec<(ttt::game::Position, (i32, i32))> = moves
+            .iter()
+            .map(|position| {
+                let score = variants::classic_move_score(game, *position);
+                let preference = self.personality.style.preference(game, *position);
+                (*position, (score, preference))
+            })
+            .collect();
+        let best_score = scored_moves.iter().map(|(_, score)| *score).max()?;
+        let best_moves: Vec<ttt::game::Position> = scored_moves
+            .iter()
+            .filter(|(_, score)| *score == best_score)
+            .map(|(position, _)| *position)
+            .collect();
+        best_moves.choose(rng).copied()
+    }
+}
+
+impl Engine for PersonalityEngine {
+    fn get_move(&self, game: &variants::Game) -> Option<variants::Move> {
+        match game {
+            variants::Game::Classic(game) => self
+                .get_classic_move(game, &mut rand::thread_rng())
+                .map(variants::Move::Mark),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    fn play(positions: &[(i32, i32)]) -> ttt::game::Game {
+        let mut game = ttt::game::Game::new();
+        for (row, column) in positions {
+            game.do_move(position(*row, *column)).unwrap();
+        }
+        game
+    }
+
+    fn perfect_engine(style: PlayStyle) -> PersonalityEngine {
+        PersonalityEngine::new(Personality {
+            difficulty: ttt::ai::Difficulty::Unbeatable,
+            style,
+            ..PERSONALITIES[0]
+        })
+    }
+
+    #[test]
+    fn personality_engine_get_move_when_empty_board_should_play_opening() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let openings: Vec<Option<ttt::game::Position>> = PERSONALITIES
+            .iter()
+            .map(|personality| {
+                PersonalityEngine::new(*personality).get_classic_move(&play(&[]), &mut rng)
+            })
+            .collect();
+
+        let expected: Vec<Option<ttt::game::Position>> = PERSONALITIES
+            .iter()
+            .map(|personality| Some(personality.opening))
+            .collect();
+        assert_eq!(openings, expected);
+    }
+
+    #[test]
+    fn personality_engine_get_move_when_can_win_should_win() {
+        let engine = perfect_engine(PlayStyle::Blocker);
+        let game = play(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+
+        let ai_move = engine.get_classic_move(&game, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(ai_move, Some(position(0, 2)));
+    }
+
+    #[test]
+    fn personality_engine_get_move_when_center_lover_should_take_center() {
+        let engine = perfect_engine(PlayStyle::CenterLover);
+        let game = play(&[(0, 1)]);
+
+        let ai_move = engine.get_classic_move(&game, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(ai_move, Some(position(1, 1)));
+    }
+
+    #[test]
+    fn play_style_preference_when_forker_should_prefer_own_lines() {
+        let game = play(&[(1, 1), (0, 0), (2, 2)]);
+
+        let next_to_own_mark = PlayStyle::Forker.preference(&game, position(0, 1));
+        let away_from_own_mark = PlayStyle::Forker.preference(&game, position(1, 2));
+
+        assert!(next_to_own_mark > 0);
+        assert_eq!(away_from_own_mark, 0);
+    }
+
+    #[test]
+    fn think_time_sample_should_be_within_range() {
+        let think_time = ThinkTime {
+            min_seconds: 0.5,
+            max_seconds: 1.5,
+        };
+        let mut rng = rand::thread_rng();
+
+        let delays: Vec<time::Duration> = (0..100).map(|_| think_time.sample(&mut rng)).collect();
+
+        assert!(delays.iter().all(|delay| {
+            *delay >= time::Duration::from_secs_f32(0.5)
+                && *delay <= time::Duration::from_secs_f32(1.5)
+        }));
+    }
+}
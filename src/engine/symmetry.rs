@@ -0,0 +1,161 @@
+use open_ttt_lib as ttt;
+
+use crate::variants;
+
+// The ways a square board can be turned or flipped onto itself.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipRows,
+    FlipColumns,
+    FlipDiagonal,
+    FlipAntiDiagonal,
+}
+
+pub(crate) const ALL_SYMMETRIES: [Symmetry; 8] = [
+    Symmetry::Identity,
+    Symmetry::Rotate90,
+    Symmetry::Rotate180,
+    Symmetry::Rotate270,
+    Symmetry::FlipRows,
+    Symmetry::FlipColumns,
+    Symmetry::FlipDiagonal,
+    Symmetry::FlipAntiDiagonal,
+];
+
+// With gravity marks fall towards row 0, so only swapping the columns keeps
+// the rules the same.
+const GRAVITY_SYMMETRIES: [Symmetry; 2] = [Symmetry::Identity, Symmetry::FlipColumns];
+
+impl Symmetry {
+    // Gets the symmetries the variant's rules are unchanged by.
+    pub(super) fn for_variant(variant: variants::Variant) -> &'static [Self] {
+        match variant {
+            variants::Variant::Custom(rules) if rules.gravity => &GRAVITY_SYMMETRIES,
+            _ => &ALL_SYMMETRIES,
+        }
+    }
+
+    // Gets the symmetry that undoes this one.
+    pub(super) fn inverse(self) -> Self {
+        match self {
+            Self::Rotate90 => Self::Rotate270,
+            Self::Rotate270 => Self::Rotate90,
+            symmetry => symmetry,
+        }
+    }
+
+    // Moves the position to where it ends up on boards of the given size.
+    //
+    // Variants played on several boards have them side by side, so each board
+    // is turned or flipped in place.
+    pub(super) fn apply(
+        self,
+        position: ttt::game::Position,
+        board_size: i32,
+    ) -> ttt::game::Position {
+        let board_start = position.column / board_size * board_size;
+        let (row, column) = (position.row, position.column - board_start);
+        let last = board_size - 1;
+        let (row, column) = match self {
+            Self::Identity => (row, column),
+            Self::Rotate90 => (column, last - row),
+            Self::Rotate180 => (last - row, last - column),
+            Self::Rotate270 => (last - column, row),
+            Self::FlipRows => (last - row, column),
+            Self::FlipColumns => (row, last - column),
+            Self::FlipDiagonal => (column, row),
+            Self::FlipAntiDiagonal => (last - column, last - row),
+        };
+        ttt::game::Position {
+            row,
+            column: board_start + column,
+        }
+    }
+
+    // Moves the marked squares of a 3 x 3 board stored as a bit mask to where
+    // they end up.
+    pub(crate) fn apply_to_mask(self, mask: u16) -> u16 {
+        (0..9)
+            .filter(|square| mask & (1 << square) != 0)
+            .map(|square| {
+                let position = ttt::game::Position {
+                    row: square / 3,
+                    column: square % 3,
+                };
+                square_bit(self.apply(position, 3))
+            })
+            .fold(0, |moved, bit| moved | bit)
+    }
+
+    // Moves the positions of the move to where they end up.
+    pub(super) fn apply_to_move(
+        self,
+        game_move: variants::Move,
+        board_size: i32,
+    ) -> variants::Move {
+        let apply = |position| self.apply(position, board_size);
+        match game_move {
+            variants::Move::Mark(position) => variants::Move::Mark(apply(position)),
+            variants::Move::Number(position, number) => {
+                variants::Move::Number(apply(position), number)
+            }
+            variants::Move::Slide(from, to) => variants::Move::Slide(apply(from), apply(to)),
+            variants::Move::PlaceSymbol(position, symbol) => {
+                variants::Move::PlaceSymbol(apply(position), symbol)
+            }
+        }
+    }
+}
+
+// Gets the bit for the position on a 3 x 3 board, for boards stored as bit
+// masks.
+pub(crate) fn square_bit(position: ttt::game::Position) -> u16 {
+    1 << (position.row * 3 + position.column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    #[test]
+    fn symmetry_inverse_should_undo_symmetry() {
+        let board_size = 6;
+        let start = position(1, 4);
+
+        let results: Vec<ttt::game::Position> = ALL_SYMMETRIES
+            .iter()
+            .map(|symmetry| {
+                let moved = symmetry.apply(start, board_size);
+                symmetry.inverse().apply(moved, board_size)
+            })
+            .collect();
+
+        assert!(results.iter().all(|result| *result == start));
+    }
+
+    #[test]
+    fn symmetry_apply_when_several_boards_should_stay_on_same_board() {
+        let moved = Symmetry::Rotate90.apply(position(0, 3), 3);
+
+        assert_eq!(moved, position(0, 5));
+    }
+
+    #[test]
+    fn symmetry_apply_to_mask_should_match_apply() {
+        let start = position(0, 1);
+
+        let results: Vec<bool> = ALL_SYMMETRIES
+            .iter()
+            .map(|symmetry| {
+                symmetry.apply_to_mask(square_bit(start)) == square_bit(symmetry.apply(start, 3))
+            })
+            .collect();
+
+        assert!(results.iter().all(|matches| *matches));
+    }
+}
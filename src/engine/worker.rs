@@ -0,0 +1,245 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use crate::variants;
+
+use super::Engine;
+
+/// Tells a search running on another thread that its move is no longer
+/// needed.
+///
+/// Clones share the same flag, so cancelling one cancels them all.
+#[derive(Clone, Debug, Default)]
+pub struct Cancellation {
+    is_cancelled: Arc<AtomicBool>,
+}
+
+impl Cancellation {
+    /// Creates a cancellation that has not been cancelled yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the search.
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Indicates the search has been cancelled, so it should stop as soon as
+    /// it can.
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(Ordering::Relaxed)
+    }
+}
+
+// The result of a search: `None` while the search is running, then the move
+// found, which is `None` if the engine had no move to make.
+type SearchResult = Arc<Mutex<Option<Option<variants::Move>>>>;
+
+/// A move an engine is searching for on a worker thread.
+///
+/// Searching for moves on a worker thread keeps slow engines from holding up
+/// the game's frames. Dropping the pending move cancels the search, so moves
+/// for games that have restarted or been left are never made.
+pub struct PendingMove {
+    result: SearchResult,
+    cancellation: Cancellation,
+}
+
+impl PendingMove {
+    /// Creates a pending move for the engine's move in the game, along with
+    /// the job that searches for it.
+    ///
+    /// The job is meant to be run on a worker thread, such as one of the
+    /// `SearchWorkers`. It does nothing if the search is cancelled before it
+    /// starts.
+    pub fn new(
+        engine: Arc<dyn Engine>,
+        game: variants::Game,
+    ) -> (Self, impl FnOnce() + Send + 'static) {
+        let result = SearchResult::default();
+        let cancellation = Cancellation::new();
+        let pending_move = Self {
+            result: Arc::clone(&result),
+            cancellation: cancellation.clone(),
+        };
+
+        let job = move || {
+            if cancellation.is_cancelled() {
+                return;
+            }
+            let game_move = engine.get_move_with_cancellation(&game, &cancellation);
+            if !cancellation.is_cancelled() {
+                *result
+                    .lock()
+                    .expect("The result is only locked while being read or written.") =
+                    Some(game_move);
+            }
+        };
+        (pending_move, job)
+    }
+
+    /// Takes the move found by the search, if it has finished.
+    ///
+    /// `Some(None)` is returned if the engine had no move to make. Once taken,
+    /// the move is not returned again.
+    pub fn take(&self) -> Option<Option<variants::Move>> {
+        self.result
+            .lock()
+            .expect("The result is only locked while being read or written.")
+            .take()
+    }
+
+    /// Stops the search, so its move is never made.
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+}
+
+impl Drop for PendingMove {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// The number of threads searched on by default.
+pub const NUM_SEARCH_WORKERS: usize = 2;
+
+// A job run on one of the search workers.
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Threads that search for computer players' moves.
+///
+/// Searches run on their own threads instead of the game's thread pool, so a
+/// long search never holds up the systems drawing the game. Jobs run in the
+/// order they are spawned, and the threads exit once the workers are dropped.
+pub struct SearchWorkers {
+    jobs: Mutex<mpsc::Sender<Job>>,
+}
+
+impl SearchWorkers {
+    /// Starts the provided number of threads, at least one.
+    pub fn new(num_threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..num_threads.max(1) {
+            let receiver = Arc::clone(&receiver);
+            thread::spawn(move || loop {
+                let job = receiver
+                    .lock()
+                    .expect("The jobs are only locked while waiting for one.")
+                    .recv();
+                match job {
+                    // A panicking engine loses its move, not the worker.
+                    Ok(job) => {
+                        if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                            log::error!("A search for a move panicked.");
+                        }
+                    }
+                    Err(_) => break,
+                }
+            });
+        }
+        Self {
+            jobs: Mutex::new(sender),
+        }
+    }
+
+    /// Runs the job, such as the search for a `PendingMove`, on one of the
+    /// threads once it is free.
+    pub fn spawn(&self, job: impl FnOnce() + Send + 'static) {
+        let _ = self
+            .jobs
+            .lock()
+            .expect("The jobs are only locked while adding one.")
+            .send(Box::new(job));
+    }
+}
+
+impl Default for SearchWorkers {
+    fn default() -> Self {
+        Self::new(NUM_SEARCH_WORKERS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+    use open_ttt_lib as ttt;
+
+    fn engine() -> Arc<dyn Engine> {
+        Arc::new(variants::Opponent::new(
+            variants::Variant::Classic,
+            ttt::ai::Difficulty::Unbeatable,
+        ))
+    }
+
+    #[test]
+    fn pending_move_take_when_job_finished_should_return_move() {
+        let mut game = variants::Game::new(variants::Variant::Classic);
+        game.do_move(&variants::Move::Mark(position(1, 1)));
+        let (pending_move, job) = PendingMove::new(engine(), game);
+
+        let before_job = pending_move.take();
+        std::thread::spawn(job).join().unwrap();
+        let after_job = pending_move.take();
+
+        assert_eq!(before_job, None);
+        assert!(matches!(after_job, Some(Some(variants::Move::Mark(_)))));
+        assert_eq!(pending_move.take(), None);
+    }
+
+    #[test]
+    fn pending_move_take_when_cancelled_should_never_return_move() {
+        let game = variants::Game::new(variants::Variant::Classic);
+        let (pending_move, job) = PendingMove::new(engine(), game);
+
+        pending_move.cancel();
+        job();
+
+        assert_eq!(pending_move.take(), None);
+    }
+
+    #[test]
+    fn search_workers_spawn_should_run_job() {
+        let workers = SearchWorkers::new(1);
+        let (pending_move, job) =
+            PendingMove::new(engine(), variants::Game::new(variants::Variant::Classic));
+
+        workers.spawn(job);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let mut game_move = None;
+        while game_move.is_none() && std::time::Instant::now() < deadline {
+            game_move = pending_move.take();
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        assert!(matches!(game_move, Some(Some(variants::Move::Mark(_)))));
+    }
+
+    #[test]
+    fn search_workers_spawn_when_job_panics_should_keep_running_jobs() {
+        let workers = SearchWorkers::new(1);
+        let (sender, receiver) = mpsc::channel();
+
+        workers.spawn(|| panic!("The engine failed."));
+        workers.spawn(move || sender.send(42).unwrap());
+
+        let result = receiver.recv_timeout(std::time::Duration::from_secs(5));
+        assert_eq!(result, Ok(42));
+    }
+
+    #[test]
+    fn pending_move_drop_should_cancel_search() {
+        let game = variants::Game::new(variants::Variant::Classic);
+        let (pending_move, _job) = PendingMove::new(engine(), game);
+        let cancellation = pending_move.cancellation.clone();
+
+        drop(pending_move);
+
+        assert!(cancellation.is_cancelled());
+    }
+}
@@ -1,10 +1,11 @@
 use amethyst::{
-    core::ecs,
+    core::{ecs, math::Point3},
     prelude::*,
     renderer::{debug_drawing::DebugLinesComponent, palette::Srgba},
 };
 use contracts::*;
 use open_ttt_lib as ttt;
+use std::collections::HashMap;
 
 use crate::components;
 use crate::math::*;
@@ -73,6 +74,8 @@ impl Default for DebugOptions {
 
 // The size of the debug environment marks, relative to the square size.
 const MARK_SIZE_FACTOR: f32 = 0.8;
+// The width of number marks, relative to their height.
+const NUMBER_ASPECT_RATIO: f32 = 0.5;
 // Size of the center point graphic,  relative to the square size.
 const CENTER_POINT_SIZE_FACTOR: f32 = 0.0625;
 
@@ -82,8 +85,10 @@ pub struct DebugEnvironment {
     options: DebugOptions,
     // The main color to use when drawing the environment.
     color: Srgba,
-    // All the entities owned by this environment.
+    // All the entities owned by this environment, except for the marks.
     entities: Vec<ecs::Entity>,
+    // The entities for the marks, by their position on the board.
+    marks: HashMap<ttt::game::Position, ecs::Entity>,
 }
 
 impl DebugEnvironment {
@@ -93,6 +98,7 @@ impl DebugEnvironment {
             options,
             color: Srgba::new(0.3, 0.3, 0.3, 1.0),
             entities: Vec::new(),
+            marks: HashMap::new(),
         }
     }
 
@@ -153,28 +159,67 @@ impl DebugEnvironment {
         let points = 32;
         debug_lines.add_circle_2d(square.center(), radius, points, self.color);
     }
+
+    // Add a number mark to the debug lines component in the indicated square.
+    //
+    // The number is drawn using the segments of a seven-segment display.
+    fn add_number_mark(&self, debug_lines: &mut DebugLinesComponent, square: &Square, number: u8) {
+        let height = square.size() * MARK_SIZE_FACTOR;
+        let half_width = height * NUMBER_ASPECT_RATIO / 2.0;
+        let half_height = height / 2.0;
+        let center = square.center();
+        let corner = |x_direction: f32, y_direction: f32| {
+            Point3::new(
+                center.x + half_width * x_direction,
+                center.y + half_height * y_direction,
+                center.z,
+            )
+        };
+        let (top_left, top_right) = (corner(-1.0, 1.0), corner(1.0, 1.0));
+        let (middle_left, middle_right) = (corner(-1.0, 0.0), corner(1.0, 0.0));
+        let (bottom_left, bottom_right) = (corner(-1.0, -1.0), corner(1.0, -1.0));
+
+        // The segments in the order: top, top right, bottom right, bottom,
+        // bottom left, top left, and middle.
+        let segments = [
+            (top_left, top_right),
+            (top_right, middle_right),
+            (middle_right, bottom_right),
+            (bottom_left, bottom_right),
+            (middle_left, bottom_left),
+            (top_left, middle_left),
+            (middle_left, middle_right),
+        ];
+        for (segment, (start, end)) in segments.iter().enumerate() {
+            if seven_segment_digit(number) & (1 << segment) != 0 {
+                debug_lines.add_line(*start, *end, self.color);
+            }
+        }
+    }
 }
 
 impl Environment for DebugEnvironment {
     fn create(&mut self, world: &mut World) {
-        // Get a copy of the grid resource so we know where to place the various lines.
-        let grid = {
-            let grid_resource = world.read_resource::<resources::Grid>();
-            *grid_resource
+        // Get a copy of the grids resource so we know where to place the various lines.
+        let grids = {
+            let grids_resource = world.read_resource::<resources::Grids>();
+            grids_resource.clone()
         };
 
-        // Create the lines for the grid, border, and center points.
+        // Create the lines for the grid, border, and center points of each board.
         let mut debug_lines_component = DebugLinesComponent::new();
-        if self.options.grid {
-            self.add_grid(&mut debug_lines_component, &grid);
-        }
+        for grid in grids.iter() {
+            if self.options.grid {
+                self.add_grid(&mut debug_lines_component, grid);
+            }
 
-        if self.options.border {
-            self.add_border(&mut debug_lines_component, &grid);
-        }
+            if self.options.border {
+                self.add_border(&mut debug_lines_component, grid);
+            }
 
-        if self.options.center_points {
-            self.add_center_points(&mut debug_lines_component, &grid);
+            if self.options.center_points {
+                self.add_center_points(&mut debug_lines_component, grid);
+            }
         }
         self.entities
             .push(world.create_entity().with(debug_lines_component).build());
@@ -194,8 +239,11 @@ impl Environment for DebugEnvironment {
     }
 
     #[post(self.entities.is_empty())]
+    #[post(self.marks.is_empty())]
     fn delete(&mut self, world: &mut World) {
         // Delete all entities.
+        self.entities
+            .extend(self.marks.drain().map(|(_, entity)| entity));
         let result = world.delete_entities(self.entities.as_slice());
         if let Err(e) = result {
             log::error!("Unable to delete entities from environment. Details: {}", e);
@@ -210,17 +258,56 @@ impl Environment for DebugEnvironment {
 
         // Determine where to place the mark.
         let square_for_mark = {
-            let grid = world.read_resource::<resources::Grid>();
-            grid.position_to_square(mark.position)
+            let grids = world.read_resource::<resources::Grids>();
+            grids.position_to_square(mark.position)
         };
 
         // Add the corresponding mark.
         let mut debug_lines_component = DebugLinesComponent::new();
-        match mark.owner {
-            components::Player::X => self.add_x_mark(&mut debug_lines_component, &square_for_mark),
-            components::Player::O => self.add_o_mark(&mut debug_lines_component, &square_for_mark),
+        match mark.symbol {
+            components::Symbol::X => self.add_x_mark(&mut debug_lines_component, &square_for_mark),
+            components::Symbol::O => self.add_o_mark(&mut debug_lines_component, &square_for_mark),
+            components::Symbol::Number(number) => {
+                self.add_number_mark(&mut debug_lines_component, &square_for_mark, number)
+            }
         }
 
+        let entity = world.create_entity().with(debug_lines_component).build();
+        self.marks.insert(mark.position, entity);
+    }
+
+    fn remove_mark(&mut self, world: &mut World, position: ttt::game::Position) {
+        if let Some(entity) = self.marks.remove(&position) {
+            if let Err(e) = world.delete_entity(entity) {
+                log::error!("Unable to delete mark from environment. Details: {}", e);
+            }
+        }
+    }
+
+    fn board_dead(&mut self, world: &mut World, board_index: usize) {
+        if !self.options.marks {
+            return;
+        }
+
+        // Strike through the line that killed the board and put a border
+        // around the board to make it stand out from the boards in play.
+        let (dead_line, grid) = {
+            let game_logic = world.read_resource::<resources::GameLogic>();
+            let grids = world.read_resource::<resources::Grids>();
+            let dead_line = game_logic
+                .game
+                .dead_board_line(board_index)
+                .and_then(|positions| grids.wining_line(&positions));
+            (dead_line, grids.iter().nth(board_index).copied())
+        };
+
+        let mut debug_lines_component = DebugLinesComponent::new();
+        if let Some(line) = dead_line {
+            debug_lines_component.add_line(line.start(), line.end(), self.color);
+        }
+        if let Some(grid) = grid {
+            self.add_border(&mut debug_lines_component, &grid);
+        }
         self.entities
             .push(world.create_entity().with(debug_lines_component).build());
     }
@@ -233,13 +320,13 @@ impl Environment for DebugEnvironment {
         // Get the line that goes through the winning marks.
         let winning_line = {
             let game_logic = world.read_resource::<resources::GameLogic>();
-            let grid = world.read_resource::<resources::Grid>();
+            let grids = world.read_resource::<resources::Grids>();
             match game_logic.game.state() {
                 ttt::game::State::PlayerXWin(winning_positions) => {
-                    grid.wining_line(&winning_positions)
+                    grids.wining_line(&winning_positions)
                 }
                 ttt::game::State::PlayerOWin(winning_positions) => {
-                    grid.wining_line(&winning_positions)
+                    grids.wining_line(&winning_positions)
                 }
                 _ => None,
             }
@@ -258,3 +345,23 @@ impl Environment for DebugEnvironment {
         !self.entities.is_empty()
     }
 }
+
+// Gets the seven-segment display segments that are lit for the provided digit.
+//
+// Bit 0 is the top segment and the bits continue clockwise around the digit
+// with bit 6 being the middle segment.
+fn seven_segment_digit(digit: u8) -> u8 {
+    match digit {
+        0 => 0b011_1111,
+        1 => 0b000_0110,
+        2 => 0b101_1011,
+        3 => 0b100_1111,
+        4 => 0b110_0110,
+        5 => 0b110_1101,
+        6 => 0b111_1101,
+        7 => 0b000_0111,
+        8 => 0b111_1111,
+        9 => 0b110_1111,
+        _ => 0,
+    }
+}
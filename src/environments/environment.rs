@@ -1,4 +1,5 @@
 use amethyst::prelude::*;
+use open_ttt_lib as ttt;
 
 use crate::components;
 
@@ -16,6 +17,16 @@ pub trait Environment {
     /// Adds a mark to the environment.
     fn add_mark(&mut self, world: &mut World, mark: &components::Mark);
 
+    /// Removes the mark at the position from the environment.
+    ///
+    /// This is used by variants where marks move once placed.
+    fn remove_mark(&mut self, world: &mut World, position: ttt::game::Position);
+
+    /// Shows that the board has been put out of play.
+    ///
+    /// This is used by variants played on several boards, such as notakto.
+    fn board_dead(&mut self, world: &mut World, board_index: usize);
+
     /// Shows the game over related entities.
     fn game_over(&mut self, world: &mut World, outcome: OutcomeAffinity);
 
@@ -28,14 +39,20 @@ pub trait Environment {
 pub struct EnvironmentOptions {}
 
 /// Represents the outcome of the game from the player's perspective.
+///
+/// In variants where players take roles, such as order and chaos, the outcome
+/// follows the role the player had. For example, chaos filling the board is a
+/// win for the player playing chaos rather than a cat's game.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum OutcomeAffinity {
     /// The player won the game.
-    // //Win,
-    // /// The player lost the game.
-    //Loss,
-    // /// The game is a draw, no one has won.
-    //CatsGame,
+    Win,
+
+    /// The player lost the game.
+    Loss,
+
+    /// The game is a draw, no one has won.
+    CatsGame,
 
     /// The outcome is inconclusive.
     ///
@@ -13,10 +13,13 @@ use amethyst::{
     utils::ortho_camera::{CameraNormalizeMode, CameraOrtho},
     window::ScreenDimensions,
 };
-use rand::seq::SliceRandom;
+use open_ttt_lib as ttt;
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 use self::debug_environment::DebugEnvironment;
 use crate::components;
+use crate::daily_challenge;
 use crate::resources;
 
 /// Structure responsible for providing access and managing the all of the games environments.
@@ -24,10 +27,17 @@ pub struct Environments {
     // All the environments.
     environments: Vec<Box<dyn Environment + Send + Sync>>,
 
-    // Index into the environments vector of the currently active environment.
-    // When the index reaches the end the environments are re-shuffled.
+    // The order the environments are shown in, as indices into the
+    // environments vector.
+    order: Vec<usize>,
+
+    // Index into the order vector of the currently active environment.
+    // When the index reaches the end the order is re-shuffled.
     current_index: usize,
 
+    // Random number generator used for shuffling the order.
+    rng: ChaCha8Rng,
+
     // Environment used for showing on screen debug information. This is None if
     // the debug environment is not currently enabled.
     debug_environment: DebugEnvironment,
@@ -39,9 +49,12 @@ impl Environments {
         // For now, we use just the debug environment with all options enabled.
         // let environments :  = vec![Box::new(DebugEnvironment::new(DebugOptions::enable_all()))];
 
+        let environments = Self::create_environments();
         Self {
-            environments: Self::create_environments(),
+            order: (0..environments.len()).collect(),
+            environments,
             current_index: 0,
+            rng: ChaCha8Rng::from_entropy(),
             debug_environment: DebugEnvironment::new(DebugOptions::disable_all()),
         }
     }
@@ -67,6 +80,24 @@ impl Environments {
         self.debug_environment.create(world);
     }
 
+    /// Sets the seed used for picking the environments to show, or picks
+    /// them randomly again if the seed is `None`.
+    ///
+    /// The current environment is deleted. After setting a seed, the same
+    /// environments are shown in the same order on every computer. This is
+    /// used by the daily challenge.
+    pub fn set_seed(&mut self, world: &mut World, seed: Option<u64>) {
+        self.delete_current(world);
+        self.rng = match seed {
+            Some(seed) => daily_challenge::seeded_rng(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+        // Start from a known order so the seed alone decides the shuffle. The
+        // index is left at the end so the next environment shown re-shuffles.
+        self.order = (0..self.environments.len()).collect();
+        self.current_index = self.order.len() - 1;
+    }
+
     /// Deletes the current environment and all its owned entities.
     ///
     /// This is useful when switching to the game menu. It is safe to call this
@@ -82,6 +113,18 @@ impl Environments {
         self.debug_environment.add_mark(world, mark);
     }
 
+    /// Removes the mark at the position from the current environment.
+    pub fn remove_mark(&mut self, world: &mut World, position: ttt::game::Position) {
+        self.current_environment().remove_mark(world, position);
+        self.debug_environment.remove_mark(world, position);
+    }
+
+    /// Shows that the board has been put out of play in the current environment.
+    pub fn board_dead(&mut self, world: &mut World, board_index: usize) {
+        self.current_environment().board_dead(world, board_index);
+        self.debug_environment.board_dead(world, board_index);
+    }
+
     /// Shows the game over related entities.
     pub fn game_over(&mut self, world: &mut World, outcome: OutcomeAffinity) {
         self.current_environment().game_over(world, outcome);
@@ -107,11 +150,11 @@ impl Environments {
 
     // Gets a mutable reference to the current environment.
     fn current_environment(&mut self) -> &mut dyn Environment {
-        self.environments[self.current_index].as_mut()
+        self.environments[self.order[self.current_index]].as_mut()
     }
 
     // Advances the `current_index` to the next environment, shuffling the
-    // environments vector if necessary.
+    // order if necessary.
     fn select_next_environment(&mut self) {
         // TODO: Perform a check to ensure the next environment is not the same
         // as the last one. That would look weird. The trick is figuring out
@@ -119,10 +162,9 @@ impl Environments {
         // compare the pointer value).
         self.current_index += 1;
         // If the end of the environments have been reached, shuffle the
-        // environments and start gain at the beginning of the collection.
-        if self.current_index >= self.environments.len() {
-            let mut rng = rand::thread_rng();
-            self.environments.shuffle(&mut rng);
+        // order and start gain at the beginning of the collection.
+        if self.current_index >= self.order.len() {
+            self.order.shuffle(&mut self.rng);
             self.current_index = 0;
         }
     }
@@ -152,6 +194,7 @@ impl Environments {
         let grid_center_point = Point3::new(0.0, 0.0, 0.0);
         let grid_size = 0.8;
         let grid = resources::Grid::new(grid_center_point, grid_size);
+        world.insert(resources::Grids::new(&grid, 1, grid.board_size()));
         world.insert(grid);
     }
 
@@ -20,6 +20,7 @@ use open_ttt_lib as ttt;
 use serde::{Deserialize, Serialize};
 
 use crate::components::Player;
+use crate::variants;
 
 /// Holds all the events that are sent to states.
 #[derive(Clone, Debug, EventReader)]
@@ -45,6 +46,33 @@ where
 #[derive(Clone, Debug, PartialEq)]
 pub enum PlayerEvent {
     RequestMark(Player, ttt::game::Position),
+
+    /// Requests the number be placed at the position, used by numerical tic-tac-toe.
+    RequestNumber(Player, ttt::game::Position, u8),
+
+    /// Requests the player's mark be moved from the first position to the
+    /// second, used by three men's morris.
+    RequestMove(Player, ttt::game::Position, ttt::game::Position),
+
+    /// Requests the X or O symbol be placed at the position regardless of
+    /// which player is moving, used by order and chaos.
+    RequestSymbol(Player, ttt::game::Position, ttt::board::Owner),
+}
+
+impl PlayerEvent {
+    /// Creates the event requesting the player's move.
+    pub fn new(player: Player, game_move: variants::Move) -> Self {
+        match game_move {
+            variants::Move::Mark(position) => Self::RequestMark(player, position),
+            variants::Move::Number(position, number) => {
+                Self::RequestNumber(player, position, number)
+            }
+            variants::Move::Slide(from, to) => Self::RequestMove(player, from, to),
+            variants::Move::PlaceSymbol(position, symbol) => {
+                Self::RequestSymbol(player, position, symbol)
+            }
+        }
+    }
 }
 
 /// Inputs for controller axis.
@@ -65,6 +93,17 @@ pub enum ActionBinding {
 
     /// Requests a mark be placed at the current mouse pointer position.
     PlaceMarkAtMouse,
+
+    /// Selects the number to place when playing numerical tic-tac-toe.
+    SelectNumber(u8),
+
+    /// Selects which board the keys for placing marks use, for variants
+    /// played on several boards.
+    SelectBoard(usize),
+
+    /// While held, an O is placed instead of an X in variants where players
+    /// place either symbol.
+    AlternateSymbol,
 }
 
 impl fmt::Display for ActionBinding {
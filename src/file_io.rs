@@ -1,7 +1,10 @@
 //! Holds functionality related to opening, reading, and writing game files.
-use amethyst::utils::application_dir;
+use serde::{de::DeserializeOwned, Serialize};
 use std::error::Error;
-use std::{env, io, path};
+use std::{env, fs, io, path};
+
+// The name of the game's directory within the user data directory.
+const USER_DATA_DIR_NAME: &str = "fossxo";
 
 /// Gets the path to the game's asset directory.
 pub fn assets_dir() -> Result<path::PathBuf, io::Error> {
@@ -13,6 +16,162 @@ pub fn input_bindings_file() -> Result<path::PathBuf, io::Error> {
     Ok(assets_dir()?.join("data").join("input.ron"))
 }
 
+/// Gets the paths to the files defining custom variants.
+///
+/// The files are the RON files in the `data/variants` asset directory, sorted
+/// by name. If the directory does not exist there are no custom variants.
+///
+/// # Errors
+/// An error is returned if there was a problem reading the directory.
+pub fn custom_variant_files() -> Result<Vec<path::PathBuf>, io::Error> {
+    let variants_dir = assets_dir()?.join("data").join("variants");
+    if !variants_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(variants_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "ron") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Gets the path to the puzzle pack file played in puzzle mode.
+pub fn puzzle_pack_file() -> Result<path::PathBuf, io::Error> {
+    Ok(assets_dir()?.join("data").join("puzzles.ron"))
+}
+
+/// Gets the path to the file recording which puzzles the player has solved.
+pub fn solved_puzzles_file() -> Result<path::PathBuf, io::Error> {
+    Ok(user_data_dir()?.join("solved-puzzles.ron"))
+}
+
+/// Gets the path to the file the player's settings are saved in.
+pub fn settings_file() -> Result<path::PathBuf, io::Error> {
+    Ok(user_data_dir()?.join("settings.ron"))
+}
+
+/// Gets the path to the puzzle pack file positions from the position editor
+/// are saved to.
+pub fn saved_positions_file() -> Result<path::PathBuf, io::Error> {
+    Ok(user_data_dir()?.join("saved-positions.ron"))
+}
+
+/// Gets the path to the file recording the player's daily challenge results.
+pub fn challenge_records_file() -> Result<path::PathBuf, io::Error> {
+    Ok(user_data_dir()?.join("daily-challenges.ron"))
+}
+
+/// Gets the path to the file the best endless runs are saved in.
+pub fn leaderboard_file() -> Result<path::PathBuf, io::Error> {
+    Ok(user_data_dir()?.join("endless-leaderboard.ron"))
+}
+
+/// Gets the path to the file recording the player's campaign progress.
+pub fn campaign_progress_file() -> Result<path::PathBuf, io::Error> {
+    Ok(user_data_dir()?.join("campaign-progress.ron"))
+}
+
+/// Gets the path to the file the player's adaptive difficulty rating is saved
+/// in.
+pub fn player_rating_file() -> Result<path::PathBuf, io::Error> {
+    Ok(user_data_dir()?.join("player-rating.ron"))
+}
+
+/// Gets the path to the file the learning AI's matchboxes are saved in.
+pub fn matchboxes_file() -> Result<path::PathBuf, io::Error> {
+    Ok(user_data_dir()?.join("learning-ai-matchboxes.ron"))
+}
+
+/// Gets the path to the directory the bot arena writes its standings and
+/// game records to by default. The directory is created if it does not exist.
+pub fn arena_records_dir() -> Result<path::PathBuf, io::Error> {
+    let arena_records_dir = user_data_dir()?.join("arena");
+    fs::create_dir_all(&arena_records_dir)?;
+    Ok(arena_records_dir)
+}
+
+/// Gets the path to the directory the player's data is saved in.
+///
+/// This is the game's directory within the platform's user data directory,
+/// for example `~/.local/share/fossxo` on Linux. The directory is created if
+/// it does not exist.
+///
+/// # Errors
+/// An error is returned if the platform does not have a user data directory
+/// or the game's directory could not be created.
+pub fn user_data_dir() -> Result<path::PathBuf, io::Error> {
+    let data_dir = dirs::data_dir().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Unable to find the user data directory.",
+        )
+    })?;
+    let user_data_dir = data_dir.join(USER_DATA_DIR_NAME);
+    fs::create_dir_all(&user_data_dir)?;
+    Ok(user_data_dir)
+}
+
+/// Reads a value from the RON file at the provided path.
+///
+/// # Errors
+/// An error is returned if the file could not be read or does not contain
+/// the expected value.
+pub fn read_ron_file<T: DeserializeOwned>(path: &path::Path) -> Result<T, Box<dyn Error>> {
+    let text = fs::read_to_string(path)?;
+    Ok(ron::de::from_str(&text)?)
+}
+
+/// Writes the value to a RON file at the provided path, replacing any
+/// existing file.
+///
+/// # Errors
+/// An error is returned if the value could not be converted to RON or the
+/// file could not be written.
+pub fn write_ron_file<T: Serialize>(path: &path::Path, value: &T) -> Result<(), Box<dyn Error>> {
+    let text = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Reads the value saved in the RON file at the path the provided function
+/// gets, logging any problems.
+///
+/// The default value is used if the file has not been saved yet or cannot be
+/// read. The value is described by `what` in the log, such as "the settings".
+pub fn load_or_default<T: DeserializeOwned + Default>(
+    file: fn() -> Result<path::PathBuf, io::Error>,
+    what: &str,
+) -> T {
+    let read = || -> Result<T, Box<dyn Error>> {
+        let file = file()?;
+        if file.exists() {
+            read_ron_file(&file)
+        } else {
+            Ok(T::default())
+        }
+    };
+    read().unwrap_or_else(|error| {
+        log::error!("Unable to read {}: {}", what, error);
+        T::default()
+    })
+}
+
+/// Saves the value to the RON file at the path the provided function gets,
+/// logging any problems.
+///
+/// The value is described by `what` in the log, such as "the settings".
+pub fn save<T: Serialize>(file: fn() -> Result<path::PathBuf, io::Error>, value: &T, what: &str) {
+    let write = || -> Result<(), Box<dyn Error>> { write_ron_file(&file()?, value) };
+    if let Err(error) = write() {
+        log::error!("Unable to save {}: {}", what, error);
+    }
+}
+
 /// Opens the game's player manual in the default browser.
 ///
 /// # Errors
@@ -52,3 +211,21 @@ fn player_manual_dir() -> Result<path::PathBuf, io::Error> {
         application_dir("player-manual")
     }
 }
+
+// Gets the path within the game's application directory.
+//
+// When run by cargo this is the crate's root directory, otherwise it is the
+// directory containing the game's executable.
+fn application_dir(path: impl AsRef<path::Path>) -> Result<path::PathBuf, io::Error> {
+    if let Some(manifest_dir) = env::var_os("CARGO_MANIFEST_DIR") {
+        return Ok(path::PathBuf::from(manifest_dir).join(path));
+    }
+    let executable = fs::canonicalize(env::current_exe()?)?;
+    let executable_dir = executable.parent().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Unable to find the game's application directory.",
+        )
+    })?;
+    Ok(executable_dir.join(path))
+}
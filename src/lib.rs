@@ -0,0 +1,53 @@
+//! Free and open-source tic-tac-toe.
+//!
+//! The rules, AI opponents, and networking are shared by the game and by the
+//! programs that run without its window, such as the bot arena. The modules
+//! that need amethyst are only built with the `client` feature, which is on
+//! by default and needed by the `fossxo` program.
+
+// Only safe code is allowed in this application.
+#![forbid(unsafe_code)]
+
+pub mod adaptive;
+pub mod arena;
+pub mod campaign;
+#[cfg(feature = "client")]
+mod components;
+pub mod constants;
+pub mod daily_challenge;
+pub mod endless;
+pub mod engine;
+#[cfg(feature = "client")]
+mod environments;
+#[cfg(feature = "client")]
+mod events;
+pub mod file_io;
+pub mod logger;
+#[cfg(feature = "client")]
+mod math;
+pub mod network;
+pub mod position_editor;
+pub mod puzzles;
+#[cfg(feature = "client")]
+mod resources;
+pub mod rules;
+pub mod server;
+pub mod settings;
+#[cfg(feature = "client")]
+mod states;
+#[cfg(feature = "client")]
+mod systems;
+#[cfg(test)]
+mod test_util;
+pub mod tutorial;
+#[cfg(feature = "client")]
+mod ui;
+pub mod variants;
+
+/// The parts of the game that open its window, used by the `fossxo` program.
+#[cfg(feature = "client")]
+pub mod client {
+    pub use crate::events::{InputBindingTypes, StateEvent, StateEventReader};
+    pub use crate::states::Loading;
+    pub use crate::systems::{EnvironmentsBundle, GameBundle};
+}
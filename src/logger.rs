@@ -0,0 +1,36 @@
+//! Contains the logger used by the programs that run without the game's
+//! window, which have no amethyst logger.
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+// Writes log messages to standard error.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "[{}][{}] {}",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Starts writing info, warning, and error messages to standard error.
+///
+/// Nothing happens if a logger has already been started.
+pub fn start_logger() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
@@ -4,38 +4,34 @@
 // Note: this also suppresses output from the unit tests.
 #![windows_subsystem = "windows"]
 
-mod components;
-mod constants;
-mod environments;
-mod events;
-mod file_io;
-mod math;
-mod resources;
-mod states;
-mod systems;
-mod ui;
-
 use amethyst::{
     core::frame_limiter, core::transform::TransformBundle, input, prelude::*, renderer, window,
 };
+use fossxo::{client, constants, engine, file_io, settings};
+use open_ttt_lib as ttt;
 
 use structopt::StructOpt;
 
 fn main() -> amethyst::Result<()> {
-    let _args = CliArgs::from_args();
+    let args = CliArgs::from_args();
     amethyst::start_logger(Default::default());
     log::info!("Started FossXO v{}.", constants::FOSSXO_VERSION);
     log::info!("Operating system: {}", os_info::get());
 
+    if let Some(num_games) = args.train_menace {
+        train_menace(num_games, &args);
+        return Ok(());
+    }
+
     let game_data = GameDataBuilder::default()
         .with_bundle(TransformBundle::new())?
         .with_bundle(
-            input::InputBundle::<events::InputBindingTypes>::new()
+            input::InputBundle::<client::InputBindingTypes>::new()
                 .with_bindings_from_file(file_io::input_bindings_file()?)?,
         )?
-        .with_bundle(amethyst::ui::UiBundle::<events::InputBindingTypes>::new())?
-        .with_bundle(systems::GameBundle)?
-        .with_bundle(systems::EnvironmentsBundle)?
+        .with_bundle(amethyst::ui::UiBundle::<client::InputBindingTypes>::new())?
+        .with_bundle(client::GameBundle)?
+        .with_bundle(client::EnvironmentsBundle)?
         .with_bundle(
             renderer::RenderingBundle::<renderer::types::DefaultBackend>::new()
                 .with_plugin(
@@ -47,9 +43,16 @@ fn main() -> amethyst::Result<()> {
                 .with_plugin(renderer::RenderDebugLines::default()),
         )?;
 
-    let mut game = CoreApplication::<_, events::StateEvent, events::StateEventReader>::build(
+    let mut game = CoreApplication::<_, client::StateEvent, client::StateEventReader>::build(
         file_io::assets_dir()?,
-        states::Loading,
+        client::Loading::new(
+            args.difficulty,
+            args.mistakes,
+            args.engine,
+            args.join,
+            args.name,
+            args.room,
+        ),
     )?
     .with_frame_limit(frame_limiter::FrameRateLimitStrategy::Sleep, 60)
     .build(game_data)?;
@@ -60,6 +63,45 @@ fn main() -> amethyst::Result<()> {
     Ok(())
 }
 
+// Trains the learning AI against the computer instead of starting the game.
+//
+// The opponent plays at the difficulty given on the command line, or takes
+// turns being easy, medium, and hard if none was given.
+fn train_menace(num_games: u32, args: &CliArgs) {
+    let mut settings = settings::Settings::load();
+    if let Some(percent) = args.mistakes {
+        settings.custom_mistake_percent = percent;
+    }
+    let difficulties = match args.difficulty {
+        Some(choice) => vec![choice.difficulty(&settings)],
+        None => vec![
+            ttt::ai::Difficulty::Easy,
+            ttt::ai::Difficulty::Medium,
+            ttt::ai::Difficulty::Hard,
+        ],
+    };
+
+    log::info!(
+        "Training the learning AI for {} games against {:?}.",
+        num_games,
+        difficulties
+    );
+    let mut matchboxes = engine::Matchboxes::load();
+    let record = engine::train(
+        &mut matchboxes,
+        &difficulties,
+        num_games,
+        &mut rand::thread_rng(),
+    );
+    matchboxes.save();
+    log::info!("Finished training the learning AI: {}.", record.summary());
+    log::info!(
+        "The learning AI has played {} games: {}.",
+        matchboxes.record.games(),
+        matchboxes.record.summary()
+    );
+}
+
 fn display_configuration() -> window::DisplayConfig {
     let mut config = window::DisplayConfig::default();
     config.title = "FossXO".to_string();
@@ -73,4 +115,42 @@ fn display_configuration() -> window::DisplayConfig {
 /// For information on how to play FossXO, select *Help* from the game's
 /// main menu.
 #[derive(StructOpt, Debug)]
-struct CliArgs {}
+struct CliArgs {
+    /// Starts a single-player game against the AI at this difficulty: easy,
+    /// medium, hard, or custom.
+    #[structopt(long)]
+    difficulty: Option<settings::DifficultyChoice>,
+
+    /// Sets the chance, from 0 to 100 percent, the AI makes a mistake at the
+    /// custom difficulty. The value is saved for future games.
+    #[structopt(long, parse(try_from_str = settings::parse_mistake_percent))]
+    mistakes: Option<u8>,
+
+    /// Trains the learning AI by playing this many games against the computer,
+    /// then exits without opening the game window. Use --difficulty to pick
+    /// the computer's difficulty.
+    #[structopt(long, value_name = "GAMES")]
+    train_menace: Option<u32>,
+
+    /// Starts a classic game against a bot, a program that plays using the
+    /// FossXO engine protocol described in the player manual. The program is
+    /// saved for future games, so it can be left out next time.
+    #[structopt(long, value_name = "PROGRAM", parse(from_os_str))]
+    engine: Option<Option<std::path::PathBuf>>,
+
+    /// Opens the multiplayer menu, ready to join the game hosted at this host
+    /// name or IP address, optionally followed by a colon and port. Game
+    /// servers are joined the same way. The address is saved for future games.
+    #[structopt(long, value_name = "ADDRESS")]
+    join: Option<String>,
+
+    /// The name shown to players on the local network looking for games you
+    /// host. The name is saved for future games.
+    #[structopt(long)]
+    name: Option<String>,
+
+    /// Opens the game server menu, ready to join the room with this code on
+    /// the server given with --join. The code is saved for future games.
+    #[structopt(long, value_name = "CODE")]
+    room: Option<String>,
+}
@@ -0,0 +1,397 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{Message, PROTOCOL_VERSION};
+
+/// How often a ping is sent when there is nothing else to send.
+pub const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait to hear from the other side before giving up on it.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait for a message to be written before giving up on the
+/// other side, which has most likely stopped reading.
+pub const WRITE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// The longest line the other side can send, in bytes. The connection is
+/// closed if a longer line is sent.
+pub const MAX_LINE_LENGTH: usize = 4096;
+
+/// The most messages handled each time the connection is polled, so a
+/// flood of messages from the other side cannot hold up the game.
+pub const MAX_MESSAGES_PER_POLL: usize = 16;
+
+/// The number of messages that cannot be understood the other side can send
+/// before the connection is closed.
+pub const MAX_INVALID_MESSAGES: u32 = 10;
+
+// The most messages read from the other side but not yet polled. Reading
+// stops until more are polled, so the other side cannot use up memory.
+const MAX_QUEUED_MESSAGES: usize = 64;
+
+/// A connection to the other player's computer.
+///
+/// Messages are read on a separate thread so waiting for the other side never
+/// holds up the game. The connection keeps itself alive with pings, and closes
+/// if the other side leaves, stops responding, or uses another version. A
+/// goodbye is sent when the connection is dropped.
+///
+/// Writes give up after `WRITE_TIMEOUT`, so another side that stops reading
+/// is disconnected instead of holding up the game. Likewise, at most
+/// `MAX_MESSAGES_PER_POLL` messages are handled each poll, and another side
+/// that keeps sending invalid messages is disconnected.
+pub struct Connection {
+    writer: TcpStream,
+    // Messages from the other side, or the line that could not be read.
+    messages: Mutex<mpsc::Receiver<Result<Message, String>>>,
+    last_sent: Instant,
+    last_received: Instant,
+    timeout: Duration,
+    is_hello_received: bool,
+    num_invalid_messages: u32,
+    // Messages already received that are returned first by the next poll.
+    unread: Vec<Message>,
+    disconnect_reason: Option<String>,
+}
+
+impl Connection {
+    /// Starts using the stream to talk to the other side, sending it a hello.
+    ///
+    /// # Errors
+    /// An error is returned if the stream cannot be set up or written to.
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let (sender, receiver) = mpsc::sync_channel(MAX_QUEUED_MESSAGES);
+        thread::spawn(move || loop {
+            let line = match read_line(&mut reader) {
+                Ok(Some(line)) => line,
+                Ok(None) => break,
+                Err(error) => {
+                    log::warn!("Stopped reading from the other side: {}", error);
+                    break;
+                }
+            };
+            let message = serde_json::from_str(&line).map_err(|_| line);
+            if sender.send(message).is_err() {
+                break;
+            }
+        });
+
+        let now = Instant::now();
+        let mut connection = Self {
+            writer: stream,
+            messages: Mutex::new(receiver),
+            last_sent: now,
+            last_received: now,
+            timeout: TIMEOUT,
+            is_hello_received: false,
+            num_invalid_messages: 0,
+            unread: Vec::new(),
+            disconnect_reason: None,
+        };
+        connection.write(&Message::Hello {
+            version: PROTOCOL_VERSION,
+        })?;
+        Ok(connection)
+    }
+
+    /// Waits the provided time to hear from the other side, instead of
+    /// `TIMEOUT`, before giving up on it.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Gets why the connection closed, or `None` if it is still open.
+    pub fn disconnect_reason(&self) -> Option<&str> {
+        self.disconnect_reason.as_deref()
+    }
+
+    /// Sends the message to the other side.
+    ///
+    /// The connection is closed if the message cannot be sent.
+    pub fn send(&mut self, message: &Message) {
+        if self.disconnect_reason.is_some() {
+            return;
+        }
+        if let Err(error) = self.write(message) {
+            log::warn!("Unable to send {:?}: {}", message, error);
+            self.close("The connection was lost.");
+        }
+    }
+
+    /// Gets the messages that have arrived from the other side, without
+    /// waiting.
+    ///
+    /// Hellos, pings, and goodbyes are handled by the connection and not
+    /// returned. Pings are sent to keep the connection alive. Messages beyond
+    /// `MAX_MESSAGES_PER_POLL` are left for the next poll.
+    pub fn poll(&mut self) -> Vec<Message> {
+        let mut messages: Vec<Message> = self.unread.drain(..).collect();
+        for _ in messages.len()..MAX_MESSAGES_PER_POLL {
+            if self.disconnect_reason.is_some() {
+                break;
+            }
+            let received = {
+                let receiver = self
+                    .messages
+                    .lock()
+                    .expect("The messages are only locked while being read.");
+                receiver.try_recv()
+            };
+            let message = match received {
+                Ok(Ok(message)) => message,
+                Ok(Err(line)) => {
+                    log::warn!("Ignored invalid message: {}", line);
+                    self.num_invalid_messages += 1;
+                    if self.num_invalid_messages >= MAX_INVALID_MESSAGES {
+                        self.close("The other side sent too many invalid messages.");
+                    }
+                    continue;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.close("The other player left.");
+                    break;
+                }
+            };
+            self.last_received = Instant::now();
+            match message {
+                Message::Hello { version } if version == PROTOCOL_VERSION => {
+                    self.is_hello_received = true
+                }
+                Message::Hello { version } => {
+                    log::warn!(
+                        "The other player uses protocol version {}, not {}.",
+                        version,
+                        PROTOCOL_VERSION
+                    );
+                    self.close("The other player has a different version of FossXO.");
+                }
+                Message::Ping => (),
+                Message::Goodbye => self.close("The other player left."),
+                _ if !self.is_hello_received => {
+                    self.close("The other side did not say hello.");
+                }
+                message => messages.push(message),
+            }
+        }
+
+        if self.disconnect_reason.is_none() {
+            if self.last_received.elapsed() > self.timeout {
+                self.close("The other player stopped responding.");
+            } else if self.last_sent.elapsed() > PING_INTERVAL {
+                self.send(&Message::Ping);
+            }
+        }
+        messages
+    }
+
+    /// Keeps messages that were polled but not handled, so they are returned
+    /// first by the next poll.
+    pub(crate) fn keep_unread(&mut self, messages: impl IntoIterator<Item = Message>) {
+        let mut unread: Vec<Message> = messages.into_iter().collect();
+        unread.append(&mut self.unread);
+        self.unread = unread;
+    }
+
+    /// Closes the connection, saying goodbye to the other side.
+    pub fn close(&mut self, reason: &str) {
+        if self.disconnect_reason.is_some() {
+            return;
+        }
+        log::info!("Closed the connection: {}", reason);
+        let _ = self.write(&Message::Goodbye);
+        let _ = self.writer.shutdown(Shutdown::Both);
+        self.disconnect_reason = Some(reason.to_string());
+    }
+
+    // Writes the message as a line of JSON.
+    fn write(&mut self, message: &Message) -> io::Result<()> {
+        let line = serde_json::to_string(message)?;
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+}
+
+/// Reads the next line without its line ending, or `None` once the other
+/// side has closed the stream.
+///
+/// At most `MAX_LINE_LENGTH` bytes are read, so the other side cannot use up
+/// memory by never ending its line. Longer lines are an error.
+pub(crate) fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    reader
+        .take(MAX_LINE_LENGTH as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+    if line.is_empty() {
+        return Ok(None);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+    } else if line.len() > MAX_LINE_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("a line is longer than {} bytes", MAX_LINE_LENGTH),
+        ));
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.close("Left the game.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    // Connects to a peer that is driven by the test through its raw stream.
+    fn connect_to_raw_peer() -> (Connection, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (peer, _) = listener.accept().unwrap();
+        (Connection::new(stream).unwrap(), peer)
+    }
+
+    // Polls the connection until it closes or a second passes.
+    fn poll_until_closed(connection: &mut Connection) {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while connection.disconnect_reason().is_none() && Instant::now() < deadline {
+            connection.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn connection_poll_when_version_differs_should_disconnect() {
+        let (mut connection, mut peer) = connect_to_raw_peer();
+
+        writeln!(
+            peer,
+            r#"{{"Hello":{{"version":{}}}}}"#,
+            PROTOCOL_VERSION + 1
+        )
+        .unwrap();
+        poll_until_closed(&mut connection);
+
+        assert_eq!(
+            connection.disconnect_reason(),
+            Some("The other player has a different version of FossXO.")
+        );
+    }
+
+    #[test]
+    fn connection_poll_when_other_side_is_silent_should_time_out() {
+        let (connection, mut peer) = connect_to_raw_peer();
+        let mut connection = connection.with_timeout(Duration::from_millis(100));
+
+        writeln!(peer, r#"{{"Hello":{{"version":{}}}}}"#, PROTOCOL_VERSION).unwrap();
+        poll_until_closed(&mut connection);
+
+        assert_eq!(
+            connection.disconnect_reason(),
+            Some("The other player stopped responding.")
+        );
+    }
+
+    #[test]
+    fn connection_poll_should_skip_invalid_messages() {
+        let (mut connection, mut peer) = connect_to_raw_peer();
+
+        writeln!(peer, r#"{{"Hello":{{"version":{}}}}}"#, PROTOCOL_VERSION).unwrap();
+        writeln!(peer, "not a message").unwrap();
+        writeln!(peer, r#""NextGame""#).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let mut messages = Vec::new();
+        while messages.is_empty() && Instant::now() < deadline {
+            messages = connection.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(messages, vec![Message::NextGame]);
+        assert_eq!(connection.disconnect_reason(), None);
+    }
+
+    #[test]
+    fn connection_poll_when_many_invalid_messages_should_disconnect() {
+        let (mut connection, mut peer) = connect_to_raw_peer();
+
+        writeln!(peer, r#"{{"Hello":{{"version":{}}}}}"#, PROTOCOL_VERSION).unwrap();
+        for _ in 0..MAX_INVALID_MESSAGES {
+            writeln!(peer, "not a message").unwrap();
+        }
+        poll_until_closed(&mut connection);
+
+        assert_eq!(
+            connection.disconnect_reason(),
+            Some("The other side sent too many invalid messages.")
+        );
+    }
+
+    #[test]
+    fn connection_poll_should_handle_at_most_max_messages_per_poll() {
+        let (mut connection, mut peer) = connect_to_raw_peer();
+
+        writeln!(peer, r#"{{"Hello":{{"version":{}}}}}"#, PROTOCOL_VERSION).unwrap();
+        for _ in 0..MAX_MESSAGES_PER_POLL * 2 {
+            writeln!(peer, r#""NextGame""#).unwrap();
+        }
+        thread::sleep(Duration::from_millis(100));
+        let first = connection.poll();
+        let second = connection.poll();
+
+        assert_eq!(first.len(), MAX_MESSAGES_PER_POLL - 1);
+        assert_eq!(second.len(), MAX_MESSAGES_PER_POLL);
+    }
+
+    #[test]
+    fn connection_poll_when_line_is_too_long_should_disconnect() {
+        let (mut connection, mut peer) = connect_to_raw_peer();
+
+        writeln!(peer, r#"{{"Hello":{{"version":{}}}}}"#, PROTOCOL_VERSION).unwrap();
+        peer.write_all(&vec![b'x'; MAX_LINE_LENGTH + 1]).unwrap();
+        poll_until_closed(&mut connection);
+
+        assert!(connection.disconnect_reason().is_some());
+    }
+
+    #[test]
+    fn connection_send_when_other_side_never_reads_should_disconnect() {
+        let (mut connection, _peer) = connect_to_raw_peer();
+        let message = Message::Refused {
+            reason: "x".repeat(1000),
+        };
+
+        // Keep sending until the other side's buffers are full and a write
+        // gives up, checking no send waits much longer than the timeout.
+        let deadline = Instant::now() + Duration::from_secs(30);
+        let mut slowest_send = Duration::from_secs(0);
+        while connection.disconnect_reason().is_none() && Instant::now() < deadline {
+            let started = Instant::now();
+            connection.send(&message);
+            slowest_send = slowest_send.max(started.elapsed());
+        }
+
+        assert_eq!(
+            connection.disconnect_reason(),
+            Some("The connection was lost.")
+        );
+        assert!(slowest_send < WRITE_TIMEOUT * 4);
+    }
+}
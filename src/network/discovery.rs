@@ -0,0 +1,368 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// The port hosts announce their games to and players look for them on.
+pub const DISCOVERY_PORT: u16 = 4748;
+
+/// The version of the announcements, which must match for a game to be found.
+pub const DISCOVERY_VERSION: u32 = 1;
+
+/// How often a host announces its game.
+pub const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a game is listed after its last announcement.
+pub const LAN_GAME_TIMEOUT: Duration = Duration::from_secs(4);
+
+// Identifies announcements from FossXO among other programs' packets.
+const DISCOVERY_GAME: &str = "FossXO";
+
+// The largest announcement read, which is plenty for a name and variant.
+const MAX_PACKET_SIZE: usize = 1024;
+
+/// What a host tells the local network about its game.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Announcement {
+    /// The name of the player hosting the game.
+    pub name: String,
+
+    /// The name of the variant being played.
+    pub variant: String,
+
+    /// The port the game is joined on.
+    pub port: u16,
+
+    /// Indicates no one has joined the game yet.
+    pub is_open: bool,
+}
+
+// An announcement as sent over the network, marked with the game and version.
+#[derive(Serialize, Deserialize)]
+struct Packet {
+    game: String,
+    version: u32,
+    #[serde(flatten)]
+    announcement: Announcement,
+}
+
+impl Announcement {
+    /// Encodes the announcement as a packet of JSON.
+    pub fn to_packet(&self) -> Vec<u8> {
+        let packet = Packet {
+            game: DISCOVERY_GAME.to_string(),
+            version: DISCOVERY_VERSION,
+            announcement: self.clone(),
+        };
+        serde_json::to_vec(&packet).expect("Announcements can always be written as JSON.")
+    }
+
+    /// Decodes the announcement from a packet.
+    ///
+    /// `None` is returned for packets from other programs or other versions.
+    pub fn from_packet(bytes: &[u8]) -> Option<Self> {
+        let packet: Packet = serde_json::from_slice(bytes).ok()?;
+        if packet.game == DISCOVERY_GAME && packet.version == DISCOVERY_VERSION {
+            Some(packet.announcement)
+        } else {
+            None
+        }
+    }
+}
+
+/// Announces a hosted game to the local network by UDP broadcast.
+///
+/// The announcement is sent every `ANNOUNCE_INTERVAL`, and straight away
+/// when whether the game is open changes.
+pub struct Announcer {
+    socket: UdpSocket,
+    target: SocketAddr,
+    announcement: Announcement,
+    last_sent: Option<Instant>,
+}
+
+impl Announcer {
+    /// Starts announcing the game to every computer on the local network.
+    ///
+    /// # Errors
+    /// An error is returned if the announcements cannot be broadcast.
+    pub fn new(announcement: Announcement) -> io::Result<Self> {
+        Self::with_target(
+            announcement,
+            SocketAddr::from((Ipv4Addr::BROADCAST, DISCOVERY_PORT)),
+        )
+    }
+
+    /// Starts announcing the game to the provided address instead of the
+    /// whole local network.
+    ///
+    /// # Errors
+    /// An error is returned if the socket cannot be set up.
+    pub fn with_target(announcement: Announcement, target: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_broadcast(true)?;
+        Ok(Self {
+            socket,
+            target,
+            announcement,
+            last_sent: None,
+        })
+    }
+
+    /// Sets if the game is open for a player to join.
+    pub fn set_open(&mut self, is_open: bool) {
+        if self.announcement.is_open != is_open {
+            self.announcement.is_open = is_open;
+            self.last_sent = None;
+        }
+    }
+
+    /// Sends the announcement if it is time to.
+    ///
+    /// Problems sending are logged; the next announcement may get through.
+    pub fn update(&mut self) {
+        let is_due = self
+            .last_sent
+            .is_none_or(|last_sent| last_sent.elapsed() >= ANNOUNCE_INTERVAL);
+        if is_due {
+            self.last_sent = Some(Instant::now());
+            let packet = self.announcement.to_packet();
+            if let Err(error) = self.socket.send_to(&packet, self.target) {
+                log::warn!("Unable to announce the game: {}", error);
+            }
+        }
+    }
+}
+
+/// A game found on the local network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanGame {
+    /// What the host said about the game.
+    pub announcement: Announcement,
+
+    /// The address the game is joined at.
+    pub address: SocketAddr,
+
+    // When the host last announced the game.
+    last_seen: Instant,
+}
+
+/// Listens for games announced on the local network without holding up the
+/// game.
+///
+/// Games are forgotten once their host stops announcing them for
+/// `LAN_GAME_TIMEOUT`. Packets from other programs are ignored.
+pub struct Browser {
+    socket: UdpSocket,
+    games: Vec<LanGame>,
+    timeout: Duration,
+}
+
+impl Browser {
+    /// Starts listening for games announced to `DISCOVERY_PORT`.
+    ///
+    /// # Errors
+    /// An error is returned if the port cannot be listened on, such as when
+    /// another copy of the game is already looking for games.
+    pub fn bind() -> io::Result<Self> {
+        Self::bind_to((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT))
+    }
+
+    /// Starts listening for games announced to the provided address.
+    ///
+    /// # Errors
+    /// An error is returned if the address cannot be listened on.
+    pub fn bind_to(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            games: Vec::new(),
+            timeout: LAN_GAME_TIMEOUT,
+        })
+    }
+
+    /// Forgets games after the provided time without an announcement,
+    /// instead of `LAN_GAME_TIMEOUT`.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Gets the address being listened on.
+    ///
+    /// # Errors
+    /// An error is returned if the address cannot be read from the socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Gets the games found, in the order they were first announced.
+    pub fn games(&self) -> &[LanGame] {
+        &self.games
+    }
+
+    /// Reads the announcements that have arrived and forgets games that are
+    /// no longer announced.
+    ///
+    /// Returns `true` if the games found have changed.
+    pub fn poll(&mut self) -> bool {
+        let mut is_changed = false;
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            let (length, source) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    log::warn!("Unable to read announcements: {}", error);
+                    break;
+                }
+            };
+            let announcement = match Announcement::from_packet(&buffer[..length]) {
+                Some(announcement) => announcement,
+                None => continue,
+            };
+
+            let address = SocketAddr::new(source.ip(), announcement.port);
+            let now = Instant::now();
+            match self.games.iter_mut().find(|game| game.address == address) {
+                Some(game) => {
+                    is_changed |= game.announcement != announcement;
+                    game.announcement = announcement;
+                    game.last_seen = now;
+                }
+                None => {
+                    log::info!("Found {} game at {}.", announcement.variant, address);
+                    self.games.push(LanGame {
+                        announcement,
+                        address,
+                        last_seen: now,
+                    });
+                    is_changed = true;
+                }
+            }
+        }
+
+        let timeout = self.timeout;
+        let num_games = self.games.len();
+        self.games.retain(|game| game.last_seen.elapsed() < timeout);
+        is_changed || self.games.len() != num_games
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn announcement() -> Announcement {
+        Announcement {
+            name: "Alice".to_string(),
+            variant: "Classic".to_string(),
+            port: 4747,
+            is_open: true,
+        }
+    }
+
+    // Listens for announcements on a free loopback port.
+    fn loopback_browser() -> Browser {
+        Browser::bind_to((Ipv4Addr::LOCALHOST, 0)).unwrap()
+    }
+
+    // Polls the browser until it finds a game or a second passes.
+    fn poll_until_found(browser: &mut Browser) {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while browser.games().is_empty() && Instant::now() < deadline {
+            browser.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn announcement_from_packet_should_round_trip() {
+        let packet = announcement().to_packet();
+
+        let decoded = Announcement::from_packet(&packet);
+
+        assert_eq!(decoded, Some(announcement()));
+    }
+
+    #[test]
+    fn announcement_from_packet_when_foreign_should_be_none() {
+        let other_version = format!(
+            r#"{{"game":"FossXO","version":{},"name":"Alice","variant":"Classic","port":4747,"is_open":true}}"#,
+            DISCOVERY_VERSION + 1
+        );
+        let other_game = r#"{"game":"Chess","version":1,"name":"Alice","variant":"Classic","port":4747,"is_open":true}"#;
+
+        assert_eq!(Announcement::from_packet(other_version.as_bytes()), None);
+        assert_eq!(Announcement::from_packet(other_game.as_bytes()), None);
+        assert_eq!(Announcement::from_packet(b"\x00\x01 not json"), None);
+    }
+
+    #[test]
+    fn browser_poll_should_find_announced_game() {
+        let mut browser = loopback_browser();
+        let mut announcer =
+            Announcer::with_target(announcement(), browser.local_addr().unwrap()).unwrap();
+
+        announcer.update();
+        poll_until_found(&mut browser);
+
+        let games = browser.games();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].announcement, announcement());
+        assert_eq!(
+            games[0].address,
+            SocketAddr::from((Ipv4Addr::LOCALHOST, 4747))
+        );
+    }
+
+    #[test]
+    fn browser_poll_should_ignore_foreign_packets() {
+        let mut browser = loopback_browser();
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+
+        sender
+            .send_to(b"hello from another program", browser.local_addr().unwrap())
+            .unwrap();
+        thread::sleep(Duration::from_millis(50));
+        let is_changed = browser.poll();
+
+        assert!(!is_changed);
+        assert!(browser.games().is_empty());
+    }
+
+    #[test]
+    fn browser_poll_when_game_closes_should_update_game() {
+        let mut browser = loopback_browser();
+        let mut announcer =
+            Announcer::with_target(announcement(), browser.local_addr().unwrap()).unwrap();
+        announcer.update();
+        poll_until_found(&mut browser);
+
+        announcer.set_open(false);
+        announcer.update();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while browser.games()[0].announcement.is_open && Instant::now() < deadline {
+            browser.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(!browser.games()[0].announcement.is_open);
+    }
+
+    #[test]
+    fn browser_poll_when_host_stops_announcing_should_forget_game() {
+        let mut browser = loopback_browser().with_timeout(Duration::from_millis(100));
+        let mut announcer =
+            Announcer::with_target(announcement(), browser.local_addr().unwrap()).unwrap();
+        announcer.update();
+        poll_until_found(&mut browser);
+
+        thread::sleep(Duration::from_millis(150));
+        let is_changed = browser.poll();
+
+        assert!(is_changed);
+        assert!(browser.games().is_empty());
+    }
+}
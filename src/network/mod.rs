@@ -0,0 +1,589 @@
+//! Contains the messages and connections used to play against a player on
+//! another computer.
+//!
+//! One player hosts the game and the other joins it over TCP. Both sides send
+//! messages as JSON, one per line, starting with a `Hello` giving the protocol
+//! version; connections from other versions are closed. The host then sends
+//! `Start` with the variant to play and the joining player's mark.
+//!
+//! The host is in charge of the game: moves from the joining player are only
+//! accepted if the host's game allows them, and only the host can start the
+//! next game. Each side sends the moves its own player makes, so both games
+//! stay the same. A move the other side does not allow means the games no
+//! longer match, and the connection is closed.
+//!
+//! Hosts also announce their games to the local network by UDP broadcast, so
+//! players can find them without knowing the host's address, see `Browser`.
+//!
+//! Players can also meet in a room on a game server, see `server`. The server
+//! takes the place of the host: one player creates a room and is given its
+//! code, the other joins the room with the code, and the server sends both
+//! players `Start`. The player who created the room starts the next games.
+//!
+//! Joining a game and creating or joining a room wait for the other side, so
+//! the game runs them with `Handshake` to keep responding meanwhile.
+mod connection;
+mod discovery;
+
+pub use self::connection::*;
+pub use self::discovery::*;
+
+use open_ttt_lib as ttt;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::arena;
+use crate::rules;
+use crate::variants;
+
+/// The version of the messages, which must match for players to connect.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The port games are hosted on, and joined on if no port is given.
+pub const DEFAULT_PORT: u16 = 4747;
+
+/// How long to wait for the host when joining a game.
+pub const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A message sent between the host and the joining player.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Message {
+    /// Sent first by both sides, giving the protocol version.
+    Hello { version: u32 },
+
+    /// Sent by the host to start playing, giving the name of the variant and
+    /// the mark the joining player plays.
+    Start {
+        variant: String,
+        player: rules::Player,
+    },
+
+    /// A move made by the player with the mark.
+    Move {
+        player: rules::Player,
+        game_move: NetworkMove,
+    },
+
+    /// Sent by the host when it starts the next game.
+    NextGame,
+
+    /// Sent to a game server to create a room for games of the variant, which
+    /// are timed if a time control is given.
+    CreateRoom {
+        variant: String,
+        time_control: Option<arena::TimeControl>,
+    },
+
+    /// Sent by a game server with the code of the room that was created.
+    RoomCreated { code: String },
+
+    /// Sent to a game server to join the room with the code.
+    JoinRoom { code: String },
+
+    /// Sent by a game server when a room cannot be created or joined, or is
+    /// being closed.
+    Refused { reason: String },
+
+    /// Sent by a game server when the player runs out of time, which ends the
+    /// game.
+    TimeOut { player: rules::Player },
+
+    /// Sent regularly so each side knows the other is still there.
+    Ping,
+
+    /// Sent when leaving the game.
+    Goodbye,
+}
+
+/// A move that can be sent over the network, with positions given as rows
+/// and columns like `ttt::game::Position`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NetworkMove {
+    Mark { row: i32, column: i32 },
+    Number { row: i32, column: i32, number: u8 },
+    Slide { from: (i32, i32), to: (i32, i32) },
+    Symbol { row: i32, column: i32, is_o: bool },
+}
+
+impl NetworkMove {
+    /// Gets the network move for the move made in the game.
+    pub fn from_move(game_move: &variants::Move) -> Self {
+        match *game_move {
+            variants::Move::Mark(position) => Self::Mark {
+                row: position.row,
+                column: position.column,
+            },
+            variants::Move::Number(position, number) => Self::Number {
+                row: position.row,
+                column: position.column,
+                number,
+            },
+            variants::Move::Slide(from, to) => Self::Slide {
+                from: (from.row, from.column),
+                to: (to.row, to.column),
+            },
+            variants::Move::PlaceSymbol(position, symbol) => Self::Symbol {
+                row: position.row,
+                column: position.column,
+                is_o: symbol == ttt::board::Owner::PlayerO,
+            },
+        }
+    }
+
+    /// Gets the move to make in the game.
+    pub fn to_move(self) -> variants::Move {
+        let position = |row, column| ttt::game::Position { row, column };
+        match self {
+            Self::Mark { row, column } => variants::Move::Mark(position(row, column)),
+            Self::Number {
+                row,
+                column,
+                number,
+            } => variants::Move::Number(position(row, column), number),
+            Self::Slide { from, to } => {
+                variants::Move::Slide(position(from.0, from.1), position(to.0, to.1))
+            }
+            Self::Symbol { row, column, is_o } => {
+                let symbol = if is_o {
+                    ttt::board::Owner::PlayerO
+                } else {
+                    ttt::board::Owner::PlayerX
+                };
+                variants::Move::PlaceSymbol(position(row, column), symbol)
+            }
+        }
+    }
+}
+
+/// Waits for a player to join a hosted game without holding up the game.
+pub struct Host {
+    listener: TcpListener,
+}
+
+impl Host {
+    /// Starts listening for a player on the port on every network the
+    /// computer is connected to.
+    ///
+    /// # Errors
+    /// An error is returned if the port cannot be listened on, such as when
+    /// another program is using it.
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener })
+    }
+
+    /// Gets the port being listened on.
+    pub fn port(&self) -> u16 {
+        self.listener
+            .local_addr()
+            .map(|address| address.port())
+            .unwrap_or_default()
+    }
+
+    /// Accepts a player if one has connected, sending them the variant and
+    /// the mark they play.
+    ///
+    /// # Errors
+    /// An error is returned if listening fails. Problems with a connecting
+    /// player are logged and the player is turned away.
+    pub fn accept(
+        &self,
+        variant: variants::Variant,
+        player: rules::Player,
+    ) -> io::Result<Option<Connection>> {
+        let (stream, address) = match self.listener.accept() {
+            Ok(accepted) => accepted,
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        match Connection::new(stream) {
+            Ok(mut connection) => {
+                log::info!("Player joined from {}.", address);
+                connection.send(&Message::Start {
+                    variant: variant.name().to_string(),
+                    player,
+                });
+                Ok(Some(connection))
+            }
+            Err(error) => {
+                log::warn!("Turned away player from {}: {}", address, error);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Joins the game hosted at the address, waiting for the host to say which
+/// variant to play and the mark to play it with.
+///
+/// The address is a host name or IP address, optionally followed by a colon
+/// and port. IPv6 addresses are put in brackets when given a port, such as
+/// `[::1]:4747`. `DEFAULT_PORT` is used if no port is given.
+///
+/// # Errors
+/// An error is returned if the host cannot be reached, uses another version,
+/// or does not start the game in time.
+pub fn join(address: &str) -> Result<(Connection, String, rules::Player), Box<dyn Error>> {
+    let mut connection = connect(address)?;
+    let (variant, player) = wait_for(&mut connection, |message| match message {
+        Message::Start { variant, player } => Some((variant, player)),
+        _ => None,
+    })
+    .map_err(|error| error.unwrap_or_else(|| "the host did not start the game".to_string()))?;
+    log::info!("Joined game of {} at {}.", variant, address);
+    Ok((connection, variant, player))
+}
+
+/// Creates a room for games of the variant on the game server at the
+/// address, returning the room's code for the other player to join with.
+///
+/// The server sends `Start` on the connection once the other player joins.
+///
+/// # Errors
+/// An error is returned if the server cannot be reached or refuses to create
+/// the room.
+pub fn create_room(
+    address: &str,
+    variant: variants::Variant,
+    time_control: Option<arena::TimeControl>,
+) -> Result<(Connection, String), Box<dyn Error>> {
+    let mut connection = connect(address)?;
+    connection.send(&Message::CreateRoom {
+        variant: variant.name().to_string(),
+        time_control,
+    });
+    let code = wait_for(&mut connection, |message| match message {
+        Message::RoomCreated { code } => Some(code),
+        _ => None,
+    })
+    .map_err(|error| error.unwrap_or_else(|| "the server did not create the room".to_string()))?;
+    log::info!("Created room {} on {}.", code, address);
+    Ok((connection, code))
+}
+
+/// Joins the room with the code on the game server at the address, waiting
+/// for the server to say which variant to play and the mark to play it with.
+///
+/// # Errors
+/// An error is returned if the server cannot be reached or the room cannot be
+/// joined, such as when there is no room with the code.
+pub fn join_room(
+    address: &str,
+    code: &str,
+) -> Result<(Connection, String, rules::Player), Box<dyn Error>> {
+    let mut connection = connect(address)?;
+    connection.send(&Message::JoinRoom {
+        code: code.to_string(),
+    });
+    let (variant, player) = wait_for(&mut connection, |message| match message {
+        Message::Start { variant, player } => Some((variant, player)),
+        _ => None,
+    })
+    .map_err(|error| error.unwrap_or_else(|| "the server did not start the game".to_string()))?;
+    log::info!("Joined room {} on {} to play {}.", code, address, variant);
+    Ok((connection, variant, player))
+}
+
+// Connects to the host or game server at the address, adding
+// `DEFAULT_PORT` if the address has no port.
+fn connect(address: &str) -> Result<Connection, Box<dyn Error>> {
+    let address = with_default_port(address);
+    let socket_address = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| format!("'{}' is not a known address", address))?;
+    let stream = TcpStream::connect_timeout(&socket_address, JOIN_TIMEOUT)?;
+    Ok(Connection::new(stream)?)
+}
+
+// Adds `DEFAULT_PORT` to the address if it has no port.
+//
+// IPv6 addresses contain colons, so they are only given a port when written
+// in brackets, such as `[::1]:4747`.
+fn with_default_port(address: &str) -> String {
+    if address.parse::<SocketAddr>().is_ok() {
+        return address.to_string();
+    }
+    let ip_address = address
+        .strip_prefix('[')
+        .and_then(|address| address.strip_suffix(']'))
+        .unwrap_or(address);
+    if let Ok(ip_address) = ip_address.parse::<IpAddr>() {
+        return SocketAddr::new(ip_address, DEFAULT_PORT).to_string();
+    }
+    if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, DEFAULT_PORT)
+    }
+}
+
+// Waits up to `JOIN_TIMEOUT` for the message picked out by `find`. Messages
+// after it are kept in the connection for the next poll.
+//
+// The error is why the other side refused or left, or `None` if it did not
+// answer in time.
+fn wait_for<T>(
+    connection: &mut Connection,
+    mut find: impl FnMut(Message) -> Option<T>,
+) -> Result<T, Option<String>> {
+    let deadline = Instant::now() + JOIN_TIMEOUT;
+    while Instant::now() < deadline {
+        let mut messages = connection.poll().into_iter();
+        while let Some(message) = messages.next() {
+            if let Message::Refused { reason } = message {
+                return Err(Some(reason));
+            }
+            if let Some(found) = find(message) {
+                // Messages sent right after, such as `Start` following
+                // `RoomCreated`, are left for the game to handle.
+                connection.keep_unread(messages);
+                return Ok(found);
+            }
+        }
+        if let Some(reason) = connection.disconnect_reason() {
+            return Err(Some(reason.to_string()));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    Err(None)
+}
+
+/// Joining a game or creating a room, run on a separate thread so waiting for
+/// the host or game server never holds up the game.
+///
+/// Dropping the handshake before it finishes closes the connection once it
+/// is made.
+pub struct Handshake<T> {
+    result: Mutex<mpsc::Receiver<Result<T, String>>>,
+}
+
+impl<T: Send + 'static> Handshake<T> {
+    /// Starts the handshake, such as `join`, on a separate thread.
+    pub fn start(handshake: impl FnOnce() -> Result<T, Box<dyn Error>> + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let result = handshake().map_err(|error| error.to_string());
+            let _ = sender.send(result);
+        });
+        Self {
+            result: Mutex::new(receiver),
+        }
+    }
+
+    /// Gets the result once the handshake has finished, without waiting.
+    ///
+    /// `None` is returned while the handshake is still running.
+    pub fn poll(&self) -> Option<Result<T, String>> {
+        let receiver = self
+            .result
+            .lock()
+            .expect("The result is only locked while being read.");
+        match receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Some(Err("the handshake stopped unexpectedly".to_string()))
+            }
+        }
+    }
+}
+
+/// Finds the variant with the name sent by the host, which may be one of the
+/// custom variants.
+pub fn find_variant(
+    name: &str,
+    custom_variants: &[variants::Variant],
+) -> Option<variants::Variant> {
+    [
+        variants::Variant::Classic,
+        variants::Variant::Numerical,
+        variants::Variant::ThreeMensMorris,
+        variants::Variant::Notakto,
+        variants::Variant::OrderAndChaos,
+    ]
+    .iter()
+    .chain(custom_variants)
+    .find(|variant| variant.name() == name)
+    .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hosts a game on a free port and joins it over loopback.
+    fn host_and_join() -> (Connection, Connection) {
+        let host = Host::bind(0).unwrap();
+        let port = host.port();
+        let joining = std::thread::spawn(move || join(&format!("127.0.0.1:{}", port)).unwrap());
+        let host_connection = loop {
+            if let Some(connection) = host
+                .accept(variants::Variant::Classic, rules::Player::O)
+                .unwrap()
+            {
+                break connection;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let (join_connection, variant, player) = joining.join().unwrap();
+        assert_eq!(variant, "Classic");
+        assert_eq!(player, rules::Player::O);
+        (host_connection, join_connection)
+    }
+
+    // Waits for the connection's next game message.
+    fn next_message(connection: &mut Connection) -> Option<Message> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline && connection.disconnect_reason().is_none() {
+            if let Some(message) = connection.poll().into_iter().next() {
+                return Some(message);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        None
+    }
+
+    #[test]
+    fn network_move_from_move_should_round_trip() {
+        let game_move = variants::Move::PlaceSymbol(
+            ttt::game::Position { row: 4, column: 1 },
+            ttt::board::Owner::PlayerO,
+        );
+
+        let network_move = NetworkMove::from_move(&game_move);
+
+        assert_eq!(network_move.to_move(), game_move);
+    }
+
+    #[test]
+    fn join_should_send_moves_over_loopback() {
+        let (mut host_connection, mut join_connection) = host_and_join();
+        let message = Message::Move {
+            player: rules::Player::O,
+            game_move: NetworkMove::Mark { row: 1, column: 2 },
+        };
+
+        join_connection.send(&message);
+
+        assert_eq!(next_message(&mut host_connection), Some(message));
+    }
+
+    #[test]
+    fn connection_when_other_side_leaves_should_disconnect() {
+        let (mut host_connection, join_connection) = host_and_join();
+
+        drop(join_connection);
+
+        assert_eq!(next_message(&mut host_connection), None);
+        assert_eq!(
+            host_connection.disconnect_reason(),
+            Some("The other player left.")
+        );
+    }
+
+    #[test]
+    fn join_when_no_host_should_return_error() {
+        let port = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+
+        let result = join(&format!("127.0.0.1:{}", port));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn create_room_when_start_arrives_with_room_code_should_keep_start() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let start = Message::Start {
+            variant: "Classic".to_string(),
+            player: rules::Player::X,
+        };
+        let server_start = start.clone();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let messages = [
+                Message::Hello {
+                    version: PROTOCOL_VERSION,
+                },
+                Message::RoomCreated {
+                    code: "ABCD".to_string(),
+                },
+                server_start,
+            ];
+            let lines: String = messages
+                .iter()
+                .map(|message| serde_json::to_string(message).unwrap() + "\n")
+                .collect();
+            std::io::Write::write_all(&mut stream, lines.as_bytes()).unwrap();
+            stream
+        });
+
+        let (mut connection, code) =
+            create_room(&address, variants::Variant::Classic, None).unwrap();
+        let _stream = server.join().unwrap();
+
+        assert_eq!(code, "ABCD");
+        assert_eq!(next_message(&mut connection), Some(start));
+    }
+
+    #[test]
+    fn handshake_poll_when_finished_should_return_result() {
+        let handshake = Handshake::start(|| Ok(42));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut result = None;
+        while result.is_none() && Instant::now() < deadline {
+            result = handshake.poll();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(result, Some(Ok(42)));
+    }
+
+    #[test]
+    fn handshake_poll_when_failed_should_return_error() {
+        let handshake: Handshake<()> = Handshake::start(|| Err("refused".into()));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut result = None;
+        while result.is_none() && Instant::now() < deadline {
+            result = handshake.poll();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(result, Some(Err("refused".to_string())));
+    }
+
+    #[test]
+    fn with_default_port_when_no_port_should_add_default_port() {
+        assert_eq!(with_default_port("localhost"), "localhost:4747");
+        assert_eq!(with_default_port("192.168.1.2"), "192.168.1.2:4747");
+        assert_eq!(with_default_port("::1"), "[::1]:4747");
+        assert_eq!(with_default_port("[fe80::1]"), "[fe80::1]:4747");
+    }
+
+    #[test]
+    fn with_default_port_when_port_given_should_keep_port() {
+        assert_eq!(with_default_port("localhost:80"), "localhost:80");
+        assert_eq!(with_default_port("192.168.1.2:80"), "192.168.1.2:80");
+        assert_eq!(with_default_port("[::1]:80"), "[::1]:80");
+    }
+
+    #[test]
+    fn find_variant_should_find_built_in_variants_by_name() {
+        let variant = find_variant("Three Men's Morris", &[]);
+
+        assert_eq!(variant, Some(variants::Variant::ThreeMensMorris));
+        assert_eq!(find_variant("Chess", &[]), None);
+    }
+}
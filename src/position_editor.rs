@@ -0,0 +1,181 @@
+//! Contains the position being set up in the position editor.
+//!
+//! Positions are classic tic-tac-toe boards along with the player who moves
+//! next. They can be played from, saved as puzzles, or written out as notation
+//! such as `X../.O./... X` for sharing in bug reports.
+use open_ttt_lib as ttt;
+
+use crate::puzzles;
+use crate::rules::Player;
+use crate::variants;
+
+/// A classic board being set up square by square.
+///
+/// The position does not have to be legal while it is being edited. It is
+/// checked when it is played or saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EditedPosition {
+    board: ttt::board::Board,
+    to_move: Player,
+}
+
+impl EditedPosition {
+    /// Creates an empty board with X to move.
+    pub fn new() -> Self {
+        Self {
+            board: ttt::board::Board::new(ttt::board::Size {
+                rows: 3,
+                columns: 3,
+            }),
+            to_move: Player::X,
+        }
+    }
+
+    /// Gets the board being edited.
+    pub fn board(&self) -> &ttt::board::Board {
+        &self.board
+    }
+
+    /// Gets the player who moves next from the position.
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    /// Switches which player moves next.
+    pub fn toggle_to_move(&mut self) {
+        self.to_move = self.to_move.opposite_player();
+    }
+
+    /// Changes the square at the position from empty to X, from X to O, and
+    /// from O back to empty.
+    ///
+    /// The new owner of the square is returned, or `None` if the position is
+    /// not on the board.
+    pub fn cycle_square(&mut self, position: ttt::game::Position) -> Option<ttt::board::Owner> {
+        let square = self.board.get_mut(position)?;
+        *square = match *square {
+            ttt::board::Owner::None => ttt::board::Owner::PlayerX,
+            ttt::board::Owner::PlayerX => ttt::board::Owner::PlayerO,
+            ttt::board::Owner::PlayerO => ttt::board::Owner::None,
+        };
+        Some(*square)
+    }
+
+    /// Removes every mark from the board.
+    pub fn clear(&mut self) {
+        *self = Self {
+            to_move: self.to_move,
+            ..Self::new()
+        };
+    }
+
+    /// Gets the position's notation, the rows from the top of the board down
+    /// separated by `/` followed by the player to move, such as `X../.O./... X`.
+    pub fn notation(&self) -> String {
+        format!(
+            "{} {}",
+            variants::board_to_rows(&self.board).join("/"),
+            self.to_move
+        )
+    }
+
+    /// Creates a game starting from the position.
+    ///
+    /// # Errors
+    /// An error describing why the position could not occur in a game is
+    /// returned, for example if the number of marks does not allow the player
+    /// to move next or both players have three in a row.
+    pub fn game(&self) -> Result<ttt::game::Game, String> {
+        variants::classic_game_from_board(&self.board, self.to_move == Player::X)
+    }
+
+    /// Creates a puzzle with the provided name from the position.
+    ///
+    /// # Errors
+    /// An error is returned if the position is not legal or the player to move
+    /// cannot avoid losing.
+    pub fn to_puzzle(&self, name: String) -> Result<puzzles::Puzzle, String> {
+        self.game()?;
+        puzzles::Puzzle::from_position(name, variants::board_to_rows(&self.board), self.to_move)
+    }
+}
+
+impl Default for EditedPosition {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    #[test]
+    fn edited_position_cycle_square_should_go_from_x_to_o_to_empty() {
+        let mut edited_position = EditedPosition::new();
+
+        let owners: Vec<Option<ttt::board::Owner>> = (0..3)
+            .map(|_| edited_position.cycle_square(position(1, 1)))
+            .collect();
+
+        assert_eq!(
+            owners,
+            vec![
+                Some(ttt::board::Owner::PlayerX),
+                Some(ttt::board::Owner::PlayerO),
+                Some(ttt::board::Owner::None),
+            ]
+        );
+    }
+
+    #[test]
+    fn edited_position_cycle_square_when_off_board_should_return_none() {
+        let mut edited_position = EditedPosition::new();
+
+        let owner = edited_position.cycle_square(position(3, 0));
+
+        assert_eq!(owner, None);
+    }
+
+    #[test]
+    fn edited_position_notation_should_list_rows_from_top_then_player_to_move() {
+        let mut edited_position = EditedPosition::new();
+        edited_position.cycle_square(position(2, 0));
+        edited_position.cycle_square(position(1, 1));
+        edited_position.cycle_square(position(1, 1));
+        edited_position.toggle_to_move();
+
+        let notation = edited_position.notation();
+
+        assert_eq!(notation, "X../.O./... O");
+    }
+
+    #[test]
+    fn edited_position_game_when_too_many_o_marks_should_be_error() {
+        let mut edited_position = EditedPosition::new();
+        for _ in 0..2 {
+            edited_position.cycle_square(position(0, 0));
+            edited_position.cycle_square(position(0, 1));
+        }
+
+        let result = edited_position.game();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn edited_position_to_puzzle_when_x_can_win_should_have_win_goal() {
+        let mut edited_position = EditedPosition::new();
+        for &(row, column, times) in &[(0, 0, 1), (1, 0, 1), (1, 1, 2), (2, 2, 2)] {
+            for _ in 0..times {
+                edited_position.cycle_square(position(row, column));
+            }
+        }
+
+        let puzzle = edited_position.to_puzzle("Test".to_string()).unwrap();
+
+        assert_eq!(puzzle.position, vec!["..O", "XO.", "X.."]);
+        assert_eq!(puzzle.goal, puzzles::Goal::WinIn(1));
+    }
+}
@@ -0,0 +1,509 @@
+//! Contains the puzzles the player can solve, such as "X to move and win in 2".
+//!
+//! Puzzles are classic tic-tac-toe positions played against an opponent that
+//! defends perfectly. They are read from a puzzle pack file and checked when
+//! loaded, so every puzzle can be solved.
+use open_ttt_lib as ttt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::{error, fmt};
+
+use crate::rules::Player;
+use crate::variants;
+
+/// A collection of puzzles read from a puzzle pack file.
+///
+/// Puzzle packs are RON files, for example:
+/// ```text
+/// (
+///     puzzles: [
+///         (
+///             name: "Fork",
+///             position: [
+///                 "X..",
+///                 ".O.",
+///                 "..X",
+///             ],
+///             to_move: O,
+///             goal: Draw,
+///         ),
+///     ],
+/// )
+/// ```
+///
+/// The position lists the rows from the top of the board down, using `X`,
+/// `O`, and `.` for an empty square. The goal is either `WinIn(moves)` or
+/// `Draw`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PuzzlePack {
+    /// The puzzles, in the order they are played.
+    pub puzzles: Vec<Puzzle>,
+}
+
+impl PuzzlePack {
+    /// Reads the puzzle pack from the provided RON text.
+    ///
+    /// # Errors
+    /// An error is returned if the text is not valid RON, does not have the
+    /// expected fields, or a puzzle cannot be solved as described.
+    pub fn from_ron(text: &str) -> Result<Self, PuzzleError> {
+        let pack: Self = ron::de::from_str(text).map_err(PuzzleError::Parse)?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Checks every puzzle in the pack can be solved as described.
+    ///
+    /// # Errors
+    /// An error is returned if the pack is empty, or for the first puzzle that
+    /// is not valid.
+    pub fn validate(&self) -> Result<(), PuzzleError> {
+        if self.puzzles.is_empty() {
+            return Err(PuzzleError::NoPuzzles);
+        }
+
+        for (index, puzzle) in self.puzzles.iter().enumerate() {
+            let invalid = |message| PuzzleError::InvalidPuzzle {
+                number: index + 1,
+                name: puzzle.name.clone(),
+                message,
+            };
+            let earlier_puzzles = &self.puzzles[..index];
+            if let Some(earlier_index) = earlier_puzzles
+                .iter()
+                .position(|earlier_puzzle| earlier_puzzle.name == puzzle.name)
+            {
+                return Err(invalid(format!(
+                    "`name` is already used by puzzle {}",
+                    earlier_index + 1
+                )));
+            }
+            puzzle.validate().map_err(invalid)?;
+        }
+        Ok(())
+    }
+
+    /// Gets the index of the next puzzle that has not been solved.
+    ///
+    /// The search starts after the provided index, or at the first puzzle if
+    /// there is none, and wraps around to the start of the pack. Once every
+    /// puzzle is solved, the puzzle the search started at is returned.
+    pub fn next_unsolved(&self, solved: &SolvedPuzzles, after: Option<usize>) -> usize {
+        let num_puzzles = self.puzzles.len().max(1);
+        let start = after.map_or(0, |index| index + 1) % num_puzzles;
+        (0..num_puzzles)
+            .map(|offset| (start + offset) % num_puzzles)
+            .find(|index| {
+                self.puzzles
+                    .get(*index)
+                    .is_some_and(|puzzle| !solved.names.contains(&puzzle.name))
+            })
+            .unwrap_or(start)
+    }
+
+    /// Gets a puzzle name starting with the prefix that no puzzle in the pack
+    /// uses, such as "Position 3".
+    pub fn unused_name(&self, prefix: &str) -> String {
+        (1..)
+            .map(|number| format!("{} {}", prefix, number))
+            .find(|name| self.puzzles.iter().all(|puzzle| puzzle.name != *name))
+            .expect("There are more names than puzzles.")
+    }
+}
+
+/// The names of the puzzles the player has solved.
+///
+/// This is saved to the user data directory so solved puzzles are remembered
+/// between runs of the game.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolvedPuzzles {
+    /// The names of the solved puzzles.
+    pub names: BTreeSet<String>,
+}
+
+/// A position along with the goal the player must reach from it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Puzzle {
+    /// The name of the puzzle as shown to the player.
+    pub name: String,
+
+    /// The marks on the board, top row first.
+    pub position: Vec<String>,
+
+    /// The player who moves first, which is the player solving the puzzle.
+    pub to_move: Player,
+
+    /// What the player must do to solve the puzzle.
+    pub goal: Goal,
+}
+
+impl Puzzle {
+    /// Creates a puzzle from the position, with the best result the player to
+    /// move can force as the goal.
+    ///
+    /// # Errors
+    /// An error is returned if the position is not valid or the player to move
+    /// cannot avoid losing, as there is no goal they could reach.
+    pub fn from_position(
+        name: String,
+        position: Vec<String>,
+        to_move: Player,
+    ) -> Result<Self, String> {
+        let mut puzzle = Self {
+            name,
+            position,
+            to_move,
+            goal: Goal::Draw,
+        };
+        let game = puzzle.classic_game()?;
+        let score = variants::classic_score(&game);
+        puzzle.goal = match moves_to_win(&game, score) {
+            Some(moves) => Goal::WinIn(moves),
+            None if score == 0 => Goal::Draw,
+            None => return Err(format!("{} cannot avoid losing", to_move)),
+        };
+        Ok(puzzle)
+    }
+
+    /// Checks the position could occur in a game and the goal can be reached
+    /// against an opponent that defends perfectly.
+    ///
+    /// # Errors
+    /// An error describing the first problem found is returned.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("`name` must not be empty".to_string());
+        }
+
+        let game = self.classic_game()?;
+        let score = variants::classic_score(&game);
+        let player = self.to_move;
+        match self.goal {
+            Goal::WinIn(0) => Err("`goal` must give at least one move to win in".to_string()),
+            Goal::WinIn(moves) => match moves_to_win(&game, score) {
+                Some(fewest_moves) if fewest_moves == moves => Ok(()),
+                Some(fewest_moves) => Err(format!(
+                    "`goal` {} can force a win in {}, expected {}",
+                    player, fewest_moves, moves
+                )),
+                None => Err(format!("`goal` {} cannot force a win", player)),
+            },
+            Goal::Draw if score > 0 => Err(format!(
+                "`goal` {} can force a win, which is more than a draw",
+                player
+            )),
+            Goal::Draw if score < 0 => Err(format!("`goal` {} cannot avoid losing", player)),
+            Goal::Draw => Ok(()),
+        }
+    }
+
+    /// Creates a game starting from the puzzle's position.
+    ///
+    /// # Errors
+    /// An error is returned if the position is not valid.
+    pub fn game(&self) -> Result<variants::Game, String> {
+        self.classic_game().map(variants::Game::Classic)
+    }
+
+    /// Gets the puzzle's goal as shown to the player, such as
+    /// "X to move and win in 2".
+    pub fn description(&self) -> String {
+        let player = self.to_move;
+        match self.goal {
+            Goal::WinIn(moves) => format!("{} to move and win in {}", player, moves),
+            Goal::Draw => format!("{} to move and force a draw", player),
+        }
+    }
+
+    /// Checks how close the player is to solving the puzzle in the provided
+    /// game, which must have started from the puzzle's position.
+    pub fn progress(&self, game: &variants::Game) -> PuzzleProgress {
+        let state = game.state();
+        let is_players_win = matches!(
+            (&state, self.to_move),
+            (ttt::game::State::PlayerXWin(_), Player::X)
+                | (ttt::game::State::PlayerOWin(_), Player::O)
+        );
+        let is_opponents_win = match &state {
+            ttt::game::State::PlayerXWin(_) | ttt::game::State::PlayerOWin(_) => !is_players_win,
+            _ => false,
+        };
+
+        match self.goal {
+            Goal::WinIn(moves) => {
+                let moves_made = self.moves_made(game);
+                if is_players_win && moves_made <= moves {
+                    PuzzleProgress::Solved
+                } else if state.is_game_over() || moves_made >= moves {
+                    PuzzleProgress::Failed
+                } else {
+                    PuzzleProgress::Playing
+                }
+            }
+            Goal::Draw if is_opponents_win => PuzzleProgress::Failed,
+            Goal::Draw if state.is_game_over() => PuzzleProgress::Solved,
+            Goal::Draw => PuzzleProgress::Playing,
+        }
+    }
+
+    // Creates the classic game starting from the puzzle's position.
+    fn classic_game(&self) -> Result<ttt::game::Game, String> {
+        let board = variants::board_from_rows(&self.position, 3)
+            .map_err(|message| format!("`position` {}", message))?;
+        variants::classic_game_from_board(&board, self.to_move == Player::X)
+            .map_err(|message| format!("`position` {}", message))
+    }
+
+    // Counts the marks the player has placed since the puzzle started.
+    fn moves_made(&self, game: &variants::Game) -> u32 {
+        let owner = match self.to_move {
+            Player::X => ttt::board::Owner::PlayerX,
+            Player::O => ttt::board::Owner::PlayerO,
+        };
+        let count = |board: &ttt::board::Board| {
+            board
+                .iter()
+                .filter(|(_position, square)| *square == owner)
+                .count() as u32
+        };
+        let starting_count = variants::board_from_rows(&self.position, 3)
+            .map(|board| count(&board))
+            .unwrap_or(0);
+        count(game.board()).saturating_sub(starting_count)
+    }
+}
+
+/// What the player must do to solve a puzzle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Goal {
+    /// Win the game using no more than the given number of moves.
+    WinIn(u32),
+
+    /// Finish the game without losing.
+    Draw,
+}
+
+/// How close the player is to solving a puzzle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PuzzleProgress {
+    /// The goal has not been reached yet, but still can be.
+    Playing,
+
+    /// The goal has been reached.
+    Solved,
+
+    /// The goal can no longer be reached.
+    Failed,
+}
+
+/// Errors that can occur when reading a puzzle pack.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PuzzleError {
+    /// The text is not valid RON or its fields do not match a puzzle pack.
+    Parse(ron::de::Error),
+
+    /// The pack does not have any puzzles.
+    NoPuzzles,
+
+    /// One of the puzzles is not valid.
+    InvalidPuzzle {
+        /// The number of the puzzle in the pack, starting from 1.
+        number: usize,
+        /// The name of the puzzle.
+        name: String,
+        /// Describes what is wrong with the puzzle.
+        message: String,
+    },
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "{}", error),
+            Self::NoPuzzles => write!(f, "the pack does not have any puzzles"),
+            Self::InvalidPuzzle {
+                number,
+                name,
+                message,
+            } => write!(f, "puzzle {} ({}): {}", number, name, message),
+        }
+    }
+}
+
+impl error::Error for PuzzleError {}
+
+// Gets the fewest moves the player to move needs to win against perfect
+// defense, given the game's score from `variants::classic_score`.
+fn moves_to_win(game: &ttt::game::Game, score: i32) -> Option<u32> {
+    if score <= 0 {
+        return None;
+    }
+
+    // The score of a win is one more than the free squares left at the end,
+    // and the player makes every other move starting with the first.
+    let free_squares = game.free_positions().count() as i32;
+    let moves_until_end = free_squares - (score - 1);
+    Some(((moves_until_end + 1) / 2) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle(position: &[&str], to_move: Player, goal: Goal) -> Puzzle {
+        Puzzle {
+            name: "Test".to_string(),
+            position: position.iter().map(|row| row.to_string()).collect(),
+            to_move,
+            goal,
+        }
+    }
+
+    #[test]
+    fn puzzle_pack_from_ron_should_read_puzzles() {
+        let text = r#"(
+            puzzles: [
+                (
+                    name: "Finish it",
+                    position: ["XX.", "OO.", "..."],
+                    to_move: X,
+                    goal: WinIn(1),
+                ),
+            ],
+        )"#;
+
+        let pack = PuzzlePack::from_ron(text).unwrap();
+
+        assert_eq!(pack.puzzles.len(), 1);
+        assert_eq!(pack.puzzles[0].goal, Goal::WinIn(1));
+    }
+
+    #[test]
+    fn puzzle_pack_validate_when_names_repeat_should_name_puzzle() {
+        let first = puzzle(&["XX.", "OO.", "..."], Player::X, Goal::WinIn(1));
+        let pack = PuzzlePack {
+            puzzles: vec![first.clone(), first],
+        };
+
+        let error = pack.validate().unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "puzzle 2 (Test): `name` is already used by puzzle 1"
+        );
+    }
+
+    #[test]
+    fn puzzle_pack_next_unsolved_should_skip_solved_puzzles_and_wrap() {
+        let mut pack = PuzzlePack {
+            puzzles: vec![puzzle(&["X..", ".O.", "..X"], Player::O, Goal::Draw); 3],
+        };
+        for (index, puzzle) in pack.puzzles.iter_mut().enumerate() {
+            puzzle.name = index.to_string();
+        }
+        let mut solved = SolvedPuzzles::default();
+        solved.names.insert("0".to_string());
+        solved.names.insert("2".to_string());
+
+        let index = pack.next_unsolved(&solved, Some(1));
+
+        assert_eq!(index, 1);
+    }
+
+    #[test]
+    fn puzzle_pack_unused_name_should_skip_names_in_use() {
+        let mut first = puzzle(&["XX.", "OO.", "..."], Player::X, Goal::WinIn(1));
+        first.name = "Position 1".to_string();
+        let pack = PuzzlePack {
+            puzzles: vec![first],
+        };
+
+        let name = pack.unused_name("Position");
+
+        assert_eq!(name, "Position 2");
+    }
+
+    #[test]
+    fn puzzle_from_position_when_fork_available_should_win_in_two() {
+        let position = vec!["X..".to_string(), "OOX".to_string(), "...".to_string()];
+
+        let puzzle = Puzzle::from_position("Fork".to_string(), position, Player::X).unwrap();
+
+        assert_eq!(puzzle.goal, Goal::WinIn(2));
+        assert_eq!(puzzle.validate(), Ok(()));
+    }
+
+    #[test]
+    fn puzzle_from_position_when_player_cannot_avoid_losing_should_be_error() {
+        let position = vec!["X.X".to_string(), ".O.".to_string(), "O.X".to_string()];
+
+        let result = Puzzle::from_position("Lost".to_string(), position, Player::O);
+
+        assert_eq!(result.unwrap_err(), "O cannot avoid losing");
+    }
+
+    #[test]
+    fn puzzle_validate_when_fork_wins_in_two_should_be_valid() {
+        // X can take the top right corner, threatening two lines at once.
+        let puzzle = puzzle(&["X..", "OOX", "..."], Player::X, Goal::WinIn(2));
+
+        let result = puzzle.validate();
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn puzzle_validate_when_win_takes_longer_should_be_invalid_goal() {
+        let puzzle = puzzle(&["X..", ".O.", "..X"], Player::O, Goal::WinIn(2));
+
+        let message = puzzle.validate().unwrap_err();
+
+        assert_eq!(message, "`goal` O cannot force a win");
+    }
+
+    #[test]
+    fn puzzle_validate_when_quicker_win_available_should_be_invalid_goal() {
+        let puzzle = puzzle(&["XX.", "OO.", "..."], Player::X, Goal::WinIn(2));
+
+        let message = puzzle.validate().unwrap_err();
+
+        assert_eq!(message, "`goal` X can force a win in 1, expected 2");
+    }
+
+    #[test]
+    fn puzzle_progress_when_move_used_without_winning_should_fail() {
+        let puzzle = puzzle(&["XX.", "OO.", "..."], Player::X, Goal::WinIn(1));
+        let mut game = puzzle.game().unwrap();
+
+        game.do_move(&variants::Move::Mark(ttt::game::Position {
+            row: 0,
+            column: 0,
+        }));
+
+        assert_eq!(puzzle.progress(&game), PuzzleProgress::Failed);
+    }
+
+    #[test]
+    fn puzzle_progress_when_player_wins_should_be_solved() {
+        let puzzle = puzzle(&["XX.", "OO.", "..."], Player::X, Goal::WinIn(1));
+        let mut game = puzzle.game().unwrap();
+
+        game.do_move(&variants::Move::Mark(ttt::game::Position {
+            row: 2,
+            column: 2,
+        }));
+
+        assert_eq!(puzzle.progress(&game), PuzzleProgress::Solved);
+    }
+
+    #[test]
+    fn puzzle_description_should_include_player_and_goal() {
+        let puzzle = puzzle(&["X..", ".O.", "..X"], Player::O, Goal::Draw);
+
+        let description = puzzle.description();
+
+        assert_eq!(description, "O to move and force a draw");
+    }
+}
@@ -2,9 +2,12 @@
 use amethyst::core::math::*;
 use open_ttt_lib as ttt;
 
-use crate::components;
 use crate::math::*;
-use std::collections::{HashMap, HashSet};
+use crate::variants;
+use std::collections::HashSet;
+
+pub use crate::engine::SearchWorkers;
+pub use crate::rules::GameLogic;
 
 /// Holds the current mouse position in various coordinate systems.
 #[derive(Debug)]
@@ -33,38 +36,64 @@ impl Default for MousePosition {
     }
 }
 
-/// Provides access to the tic-tac-toe game logic.
+/// Holds the variants defined by data files.
 ///
-/// This includes the current state of the game and the last time a move was made.
-#[derive(Default)]
-pub struct GameLogic {
-    /// The tic-tac-toe game that contains the current game state.
-    pub game: ttt::game::Game,
-
-    /// Holds the time the game was last updated.
-    pub last_move_time: std::time::Duration,
+/// These are loaded once when the game starts, see `variants::CustomRules`.
+#[derive(Debug, Default)]
+pub struct CustomVariants {
+    /// The loaded variants, in the order they are shown to the player.
+    pub variants: Vec<variants::Variant>,
 }
 
-impl GameLogic {
-    /// Helper function for knowing if it is the provided player's turn.
-    pub fn is_players_move(&self, player: &components::Player) -> bool {
-        match self.game.state() {
-            ttt::game::State::PlayerXMove => *player == components::Player::X,
-            ttt::game::State::PlayerOMove => *player == components::Player::O,
-            _ => false,
-        }
-    }
+/// Holds the number a local player has picked to place next.
+///
+/// This is only used when playing numerical tic-tac-toe.
+#[derive(Debug, Default)]
+pub struct NumberPicker {
+    /// The selected number, if any.
+    pub selected: Option<u8>,
 }
 
-// The size of tic-tac-toe boards that the grid works with.
+/// Holds the mark a local player has picked up to move.
+///
+/// This is only used in variants where marks move once placed, such as
+/// three men's morris.
+#[derive(Debug, Default)]
+pub struct SelectedMark {
+    /// The position of the selected mark, if any.
+    pub position: Option<ttt::game::Position>,
+}
+
+/// Holds the board the keys for placing marks use.
+///
+/// This is only used for variants played on several boards, such as notakto.
+#[derive(Debug, Default)]
+pub struct BoardPicker {
+    /// The index of the selected board.
+    pub selected: usize,
+}
+
+/// Holds the squares to draw the player's attention to, such as the squares
+/// the tutorial asks the player to move in.
+#[derive(Debug, Default)]
+pub struct HighlightedSquares {
+    /// The positions of the highlighted squares.
+    pub positions: Vec<ttt::game::Position>,
+}
+
+// The size of tic-tac-toe boards that the grid works with by default.
 const TTT_BOARD_SIZE: i32 = 3;
 
+// The fewest positions in a row that a winning line is drawn through.
+const MIN_WINNING_LINE_LENGTH: usize = 3;
+
 /// Represents the game's grid.
 ///
 /// The grid is made up of a `#` mark. Various helper methods provide access to
 /// the different points in the grid.
 ///
-/// The grid assumes a square 3x3 game board.
+/// The grid assumes a square game board, 3x3 unless created with
+/// `Grid::with_board_size`.
 #[derive(Copy, Clone)]
 pub struct Grid {
     // The bottom left point of the grid. It is easier to do calculations from
@@ -72,6 +101,8 @@ pub struct Grid {
     origin: Point3<f32>,
     // Grids are square. This is the length / width of the grid.
     size: f32,
+    // The number of rows and columns of squares in the grid.
+    board_size: i32,
 }
 
 impl Grid {
@@ -82,10 +113,23 @@ impl Grid {
     /// # Panics
     /// The size must be greater than 0.0.
     pub fn new(center_point: Point3<f32>, size: f32) -> Self {
+        Self::with_board_size(center_point, size, TTT_BOARD_SIZE)
+    }
+
+    /// Creates a grid like `Grid::new` but with the indicated number of rows and
+    /// columns of squares.
+    ///
+    /// # Panics
+    /// The size must be greater than 0.0 and the board size must be at least 1.
+    pub fn with_board_size(center_point: Point3<f32>, size: f32, board_size: i32) -> Self {
         assert!(
             size > 0.0,
             "The size of the grid must be greater than zero."
         );
+        assert!(
+            board_size > 0,
+            "The grid must have at least one row and column."
+        );
 
         let offset = size / 2.0;
         let origin = Point3::new(
@@ -93,7 +137,11 @@ impl Grid {
             center_point.y - offset,
             center_point.z,
         );
-        Self { origin, size }
+        Self {
+            origin,
+            size,
+            board_size,
+        }
     }
 
     /// Gets the point at the center of the grid.
@@ -111,25 +159,30 @@ impl Grid {
         self.size
     }
 
+    /// Gets the number of rows and columns of squares in the grid.
+    pub fn board_size(&self) -> i32 {
+        self.board_size
+    }
+
     /// Gets an iterator over all the squares in the grid.
     pub fn squares(&self) -> Squares {
         Squares {
             grid: &self,
             current_position: ttt::game::Position { row: 0, column: 0 },
             board_size: ttt::board::Size {
-                rows: TTT_BOARD_SIZE,
-                columns: TTT_BOARD_SIZE,
+                rows: self.board_size,
+                columns: self.board_size,
             },
         }
     }
 
     /// Gets the lines that make up the grid's hash marks.
     ///
-    /// The vertical lines are first in the array followed by the horizontal lines.
+    /// The vertical lines are first followed by the horizontal lines.
     /// The points in the lines start at the bottom or left of the grid and end at
     /// the top or right of the grid.
     ///
-    /// For example the array contains `[a-b, c-d, e-f, g-h]` as shown below:
+    /// For example a 3x3 grid has the lines `[a-b, c-d, e-f, g-h]` as shown below:
     /// ```text
     ///       b   d
     ///       |   |
@@ -139,70 +192,55 @@ impl Grid {
     ///       |   |
     ///       a   c
     /// ```
-    #[allow(clippy::many_single_char_names)] // Diagram above describes the single character names.
-    pub fn lines(&self) -> [Line; 4] {
+    pub fn lines(&self) -> Vec<Line> {
         let square_size = self.square_size();
-        let a = Point3::new(self.origin.x + square_size, self.origin.y, self.origin.z);
-        let b = Point3::new(
-            self.origin.x + square_size,
-            self.origin.y + self.size,
-            self.origin.z,
-        );
-        let c = Point3::new(
-            self.origin.x + square_size * 2.0,
-            self.origin.y,
-            self.origin.z,
-        );
-        let d = Point3::new(
-            self.origin.x + square_size * 2.0,
-            self.origin.y + self.size,
-            self.origin.z,
-        );
-        let e = Point3::new(self.origin.x, self.origin.y + square_size, self.origin.z);
-        let f = Point3::new(
-            self.origin.x + self.size,
-            self.origin.y + square_size,
-            self.origin.z,
-        );
-        let g = Point3::new(
-            self.origin.x,
-            self.origin.y + square_size * 2.0,
-            self.origin.z,
-        );
-        let h = Point3::new(
-            self.origin.x + self.size,
-            self.origin.y + square_size * 2.0,
-            self.origin.z,
-        );
+        let vertical_lines = (1..self.board_size).map(|index| {
+            let x = self.origin.x + square_size * index as f32;
+            Line::new(
+                Point3::new(x, self.origin.y, self.origin.z),
+                Point3::new(x, self.origin.y + self.size, self.origin.z),
+            )
+        });
+        let horizontal_lines = (1..self.board_size).map(|index| {
+            let y = self.origin.y + square_size * index as f32;
+            Line::new(
+                Point3::new(self.origin.x, y, self.origin.z),
+                Point3::new(self.origin.x + self.size, y, self.origin.z),
+            )
+        });
 
-        [
-            Line::new(a, b),
-            Line::new(c, d),
-            Line::new(e, f),
-            Line::new(g, h),
-        ]
+        vertical_lines.chain(horizontal_lines).collect()
     }
 
     /// Returns a line that can be drawn through the provided winning positions.
     ///
     /// If multiple lines can be drawn through the positions, only one line is selected.
-    /// The endpoints of the line are along the grid's edges. Lines start left or bottom
-    /// of the grid. The top-left to bottom-right diagonal starts at the top left.
+    /// The line is drawn through the longest run of at least three positions in a row.
+    /// The endpoints of the line are along the edges of the run's end squares. Lines
+    /// start left or bottom of the grid. The top-left to bottom-right diagonal starts
+    /// at the top left.
     ///
     /// `None` is returned if the winning positions is empty or a line cannot be
     /// drawn through them.
     pub fn wining_line(&self, winning_positions: &HashSet<ttt::game::Position>) -> Option<Line> {
         // Check the provided winning positions to see what diagonal, vertical, or horizontal
-        // line to pick.
-        if let Some(line) = self.bottom_left_to_top_right_winning_line(&winning_positions) {
-            Some(line)
-        } else if let Some(line) = self.top_left_to_bottom_right_winning_line(&winning_positions) {
-            Some(line)
-        } else if let Some(line) = self.vertical_or_horizontal_winning_line(&winning_positions) {
-            Some(line)
-        } else {
-            None
+        // line to pick. The first direction wins when runs are the same length.
+        let directions = [
+            WinningLineDirection::BottomLeftToTopRight,
+            WinningLineDirection::TopLeftToBottomRight,
+            WinningLineDirection::Vertical,
+            WinningLineDirection::Horizontal,
+        ];
+        let mut longest_run: Option<(usize, Line)> = None;
+        for direction in directions.iter() {
+            if let Some((length, line)) = self.longest_winning_run(winning_positions, *direction) {
+                if longest_run.is_none_or(|(longest, _)| length > longest) {
+                    longest_run = Some((length, line));
+                }
+            }
         }
+
+        longest_run.map(|(_, line)| line)
     }
 
     /// Converts the provided point to a grid position.
@@ -238,114 +276,100 @@ impl Grid {
 
     // Gets the size of an and individual square.
     fn square_size(&self) -> f32 {
-        self.size / 3.0
+        self.size / self.board_size as f32
     }
 
-    // Gets the bottom-left to top-right line.
-    fn bottom_left_to_top_right_winning_line(
+    // Gets the length of the longest run of winning positions in the direction
+    // and the line drawn through the run.
+    fn longest_winning_run(
         &self,
         winning_positions: &HashSet<ttt::game::Position>,
-    ) -> Option<Line> {
-        for index in 0..TTT_BOARD_SIZE {
-            let position = ttt::game::Position {
-                row: index,
-                column: index,
+        direction: WinningLineDirection,
+    ) -> Option<(usize, Line)> {
+        let (row_step, column_step) = direction.step();
+        let mut longest_run: Option<(usize, ttt::game::Position, ttt::game::Position)> = None;
+        for start in winning_positions {
+            // Only count runs from their first position.
+            let previous = ttt::game::Position {
+                row: start.row - row_step,
+                column: start.column - column_step,
             };
-            if !winning_positions.contains(&position) {
-                return None;
+            if winning_positions.contains(&previous) {
+                continue;
             }
-        }
-        let start_square = self.position_to_square(ttt::game::Position { row: 0, column: 0 });
-        let end_square = self.position_to_square(ttt::game::Position {
-            row: TTT_BOARD_SIZE - 1,
-            column: TTT_BOARD_SIZE - 1,
-        });
-        Some(Line::new(
-            start_square.bottom_left(),
-            end_square.top_right(),
-        ))
-    }
 
-    // Gets the top-left to bottom-right line.
-    fn top_left_to_bottom_right_winning_line(
-        &self,
-        winning_positions: &HashSet<ttt::game::Position>,
-    ) -> Option<Line> {
-        for index in 0..TTT_BOARD_SIZE {
-            let position = ttt::game::Position {
-                row: TTT_BOARD_SIZE - index - 1,
-                column: index,
+            let mut end = *start;
+            let mut length = 1;
+            loop {
+                let next = ttt::game::Position {
+                    row: end.row + row_step,
+                    column: end.column + column_step,
+                };
+                if !winning_positions.contains(&next) {
+                    break;
+                }
+                end = next;
+                length += 1;
+            }
+
+            // Prefer the run closest to the bottom left so the line picked does not
+            // depend on the set's iteration order.
+            let is_longer = match longest_run {
+                None => true,
+                Some((longest, longest_start, _)) => {
+                    length > longest
+                        || (length == longest
+                            && (start.column, start.row)
+                                < (longest_start.column, longest_start.row))
+                }
             };
-            if !winning_positions.contains(&position) {
-                return None;
+            if is_longer {
+                longest_run = Some((length, *start, end));
             }
         }
-        let start_square = self.position_to_square(ttt::game::Position {
-            row: TTT_BOARD_SIZE - 1,
-            column: 0,
-        });
-        let end_square = self.position_to_square(ttt::game::Position {
-            row: 0,
-            column: TTT_BOARD_SIZE - 1,
-        });
-        Some(Line::new(
-            start_square.top_left(),
-            end_square.bottom_right(),
-        ))
-    }
 
-    fn vertical_or_horizontal_winning_line(
-        &self,
-        winning_positions: &HashSet<ttt::game::Position>,
-    ) -> Option<Line> {
-        // Build a mapping of rows and columns to how many times the row or column
-        // appeared in the winning positions. If the same row / column appears
-        // the same number as the board size then we know we can draw a line
-        // through it.
-        let mut rows_found = HashMap::new();
-        let mut columns_found = HashMap::new();
-        for position in winning_positions {
-            *rows_found.entry(position.row).or_insert(0) += 1;
-            *columns_found.entry(position.column).or_insert(0) += 1;
+        let (length, start, end) = longest_run?;
+        if length < MIN_WINNING_LINE_LENGTH {
+            return None;
         }
+        let start_square = self.position_to_square(start);
+        let end_square = self.position_to_square(end);
+        let line = match direction {
+            WinningLineDirection::BottomLeftToTopRight => {
+                Line::new(start_square.bottom_left(), end_square.top_right())
+            }
+            WinningLineDirection::TopLeftToBottomRight => {
+                Line::new(start_square.top_left(), end_square.bottom_right())
+            }
+            WinningLineDirection::Vertical => {
+                Line::new(start_square.bottom_center(), end_square.top_center())
+            }
+            WinningLineDirection::Horizontal => {
+                Line::new(start_square.center_left(), end_square.center_right())
+            }
+        };
+        Some((length, line))
+    }
+}
 
-        // Using the rows and columns found, build a horizontal or vertical line for the first one
-        // that has the same number (or more) as the board size. None is returned if a line could
-        // not be built.
-        if let Some((column, _)) = columns_found
-            .iter()
-            .find(|(_col, num)| **num >= TTT_BOARD_SIZE)
-        {
-            let start_square = self.position_to_square(ttt::game::Position {
-                row: 0,
-                column: *column,
-            });
-            let end_square = self.position_to_square(ttt::game::Position {
-                row: TTT_BOARD_SIZE - 1,
-                column: *column,
-            });
-            Some(Line::new(
-                start_square.bottom_center(),
-                end_square.top_center(),
-            ))
-        } else if let Some((row, _)) = rows_found
-            .iter()
-            .find(|(_row, num)| **num >= TTT_BOARD_SIZE)
-        {
-            let start_square = self.position_to_square(ttt::game::Position {
-                row: *row,
-                column: 0,
-            });
-            let end_square = self.position_to_square(ttt::game::Position {
-                row: *row,
-                column: TTT_BOARD_SIZE - 1,
-            });
-            Some(Line::new(
-                start_square.center_left(),
-                end_square.center_right(),
-            ))
-        } else {
-            None
+// The directions a winning line can be drawn in.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum WinningLineDirection {
+    BottomLeftToTopRight,
+    TopLeftToBottomRight,
+    Vertical,
+    Horizontal,
+}
+
+impl WinningLineDirection {
+    // Gets the change in row and column when moving along the line, starting from
+    // the line's first point.
+    fn step(self) -> (i32, i32) {
+        match self {
+            Self::BottomLeftToTopRight => (1, 1),
+            Self::TopLeftToBottomRight => (-1, 1),
+            Self::Vertical => (1, 0),
+            Self::Horizontal => (0, 1),
         }
     }
 }
@@ -388,6 +412,154 @@ impl Iterator for Squares<'_> {
     }
 }
 
+// The space between boards, relative to the size of each board.
+const BOARD_GAP_FACTOR: f32 = 0.25;
+
+/// Holds a grid for each board being played on.
+///
+/// Most variants are played on a single board, whose grid fills the play
+/// area. Variants played on several boards, such as notakto, place smaller
+/// grids side by side across the play area. Positions work the same way as
+/// for the boards: each board takes up the next set of columns, see
+/// `variants::BOARD_COLUMNS`.
+#[derive(Clone)]
+pub struct Grids {
+    grids: Vec<Grid>,
+}
+
+impl Grids {
+    /// Lays out grids for the number of boards inside the provided play area.
+    ///
+    /// Each board has the indicated number of rows and columns.
+    ///
+    /// # Panics
+    /// The number of boards must be greater than 0 and the board size must be at
+    /// least 1.
+    pub fn new(play_area: &Grid, num_boards: usize, board_size: i32) -> Self {
+        assert!(num_boards > 0, "There must be at least one board.");
+
+        let num_boards = num_boards as f32;
+        let grid_size = play_area.size() / (num_boards + (num_boards - 1.0) * BOARD_GAP_FACTOR);
+        let grid_spacing = grid_size * (1.0 + BOARD_GAP_FACTOR);
+        let first_center_x = play_area.center_point().x - play_area.size() / 2.0 + grid_size / 2.0;
+        let grids = (0..num_boards as usize)
+            .map(|board_index| {
+                let mut center_point = play_area.center_point();
+                center_point.x = first_center_x + grid_spacing * board_index as f32;
+                Grid::with_board_size(center_point, grid_size, board_size)
+            })
+            .collect();
+
+        Self { grids }
+    }
+
+    /// Gets an iterator over the grid of each board.
+    pub fn iter(&self) -> impl Iterator<Item = &Grid> {
+        self.grids.iter()
+    }
+
+    /// Gets the grid of the board the position is on.
+    ///
+    /// Positions past the last board use the last board's grid.
+    pub fn grid(&self, position: ttt::game::Position) -> &Grid {
+        &self.grids[self.board_index(position)]
+    }
+
+    /// Converts the provided point to a position on one of the boards.
+    ///
+    /// Like `Grid::point_to_position`, the returned position can be outside the
+    /// playable area, such as when the point is in the space between boards.
+    pub fn point_to_position(&self, point: Point3<f32>) -> ttt::game::Position {
+        // Use the board whose center is closest to the point.
+        let (board_index, grid) = self
+            .grids
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                let a_distance = (a.center_point().x - point.x).abs();
+                let b_distance = (b.center_point().x - point.x).abs();
+                a_distance.partial_cmp(&b_distance).unwrap()
+            })
+            .unwrap();
+
+        let position = grid.point_to_position(point);
+        if self.grids.len() == 1 || (0..grid.board_size()).contains(&position.column) {
+            self.to_shared_position(board_index, position)
+        } else {
+            // The point is beside the board, make sure this does not end up
+            // as a position on the neighboring board.
+            ttt::game::Position {
+                row: position.row,
+                column: -1,
+            }
+        }
+    }
+
+    /// Gets a rectangle containing the bounds of the position.
+    pub fn position_to_square(&self, position: ttt::game::Position) -> Square {
+        let board_index = self.board_index(position);
+        self.grids[board_index].position_to_square(self.to_board_position(board_index, position))
+    }
+
+    /// Returns a line that can be drawn through the provided winning positions.
+    ///
+    /// The positions must be on the same board. See `Grid::wining_line` for details.
+    pub fn wining_line(&self, winning_positions: &HashSet<ttt::game::Position>) -> Option<Line> {
+        let board_index = self.board_index(*winning_positions.iter().next()?);
+        let board_positions = winning_positions
+            .iter()
+            .map(|position| self.to_board_position(board_index, *position))
+            .collect();
+        self.grids[board_index].wining_line(&board_positions)
+    }
+
+    // Gets the index of the board the position is on, clamped to the boards available.
+    fn board_index(&self, position: ttt::game::Position) -> usize {
+        if self.grids.len() == 1 {
+            0
+        } else {
+            let board_size = self.grids[0].board_size();
+            let board_index = position.column.div_euclid(board_size).max(0) as usize;
+            board_index.min(self.grids.len() - 1)
+        }
+    }
+
+    // Converts a position shared by all boards to the position on the indicated board.
+    fn to_board_position(
+        &self,
+        board_index: usize,
+        position: ttt::game::Position,
+    ) -> ttt::game::Position {
+        ttt::game::Position {
+            row: position.row,
+            column: position.column - self.board_column_offset(board_index),
+        }
+    }
+
+    // Converts a position on the indicated board to the position shared by all boards.
+    fn to_shared_position(
+        &self,
+        board_index: usize,
+        position: ttt::game::Position,
+    ) -> ttt::game::Position {
+        ttt::game::Position {
+            row: position.row,
+            column: position.column + self.board_column_offset(board_index),
+        }
+    }
+
+    // Gets the column the indicated board starts at.
+    fn board_column_offset(&self, board_index: usize) -> i32 {
+        board_index as i32 * self.grids[board_index].board_size()
+    }
+}
+
+impl Default for Grids {
+    fn default() -> Self {
+        Self::new(&Grid::default(), 1, TTT_BOARD_SIZE)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -652,6 +824,36 @@ mod tests {
         assert_ne!(actual_line, None);
     }
 
+    #[test]
+    fn grid_lines_when_board_size_6_should_provide_5_lines_each_way() {
+        let center_point = Point3::new(0.0, 0.0, 0.0);
+        let size = 6.0;
+        let grid = Grid::with_board_size(center_point, size, 6);
+
+        let lines = grid.lines();
+
+        assert_eq!(lines.len(), 10);
+    }
+
+    #[test]
+    fn grid_wining_line_when_run_shorter_than_board_should_span_run() {
+        // To make calculations easier, place the grid's bottom left corner at (0, 0, 0).
+        let size = 6.0;
+        let center_point = Point3::new(size / 2.0, size / 2.0, 0.0);
+        let grid = Grid::with_board_size(center_point, size, 6);
+        let mut winning_positions = HashSet::new();
+        for column in 1..6 {
+            winning_positions.insert(ttt::game::Position { row: 2, column });
+        }
+        let expected_start_point = Point3::new(1.0, 2.5, 0.0);
+        let expected_end_point = Point3::new(size, 2.5, 0.0);
+
+        let actual_line = grid.wining_line(&winning_positions).unwrap();
+
+        assert_ulps_eq!(actual_line.start(), expected_start_point);
+        assert_ulps_eq!(actual_line.end(), expected_end_point);
+    }
+
     #[test]
     fn grid_point_to_position_center_point_should_be_at_row_1_column_1() {
         let center_point = Point3::new(0.0, 0.0, 0.0);
@@ -693,4 +895,73 @@ mod tests {
         assert_ulps_eq!(actual_square.center(), expected_square.center());
         assert_ulps_eq!(actual_square.size(), expected_square.size());
     }
+
+    #[test]
+    fn grids_new_when_one_board_should_fill_play_area() {
+        let play_area = Grid::new(Point3::new(1.0, 2.0, 3.0), 3.0);
+
+        let grids = Grids::new(&play_area, 1, 3);
+
+        let grid = grids.iter().next().unwrap();
+        assert_ulps_eq!(grid.center_point(), play_area.center_point());
+        assert_ulps_eq!(grid.size(), play_area.size());
+    }
+
+    #[test]
+    fn grids_new_should_fit_boards_side_by_side_in_play_area() {
+        let play_area = Grid::new(Point3::new(0.0, 0.0, 0.0), 3.5);
+
+        let grids = Grids::new(&play_area, 3, 3);
+
+        // Three boards plus two gaps of a quarter board fit exactly.
+        let centers: Vec<f32> = grids.iter().map(|grid| grid.center_point().x).collect();
+        assert_eq!(grids.iter().count(), 3);
+        assert_ulps_eq!(grids.iter().next().unwrap().size(), 1.0);
+        assert_ulps_eq!(centers[0], -1.25);
+        assert_ulps_eq!(centers[1], 0.0);
+        assert_ulps_eq!(centers[2], 1.25);
+    }
+
+    #[test]
+    fn grids_point_to_position_when_on_second_board_should_offset_column() {
+        let play_area = Grid::new(Point3::new(0.0, 0.0, 0.0), 3.5);
+        let grids = Grids::new(&play_area, 3, 3);
+        let expected_position = ttt::game::Position { row: 1, column: 4 };
+
+        let position = grids.point_to_position(Point3::new(0.0, 0.0, 0.0));
+
+        assert_eq!(position, expected_position);
+    }
+
+    #[test]
+    fn grids_point_to_position_when_between_boards_should_be_outside_boards() {
+        let play_area = Grid::new(Point3::new(0.0, 0.0, 0.0), 3.5);
+        let grids = Grids::new(&play_area, 3, 3);
+
+        let position = grids.point_to_position(Point3::new(0.55, 0.0, 0.0));
+
+        assert_eq!(position.column, -1);
+    }
+
+    #[test]
+    fn grids_position_to_square_should_use_board_of_position() {
+        let play_area = Grid::new(Point3::new(0.0, 0.0, 0.0), 3.5);
+        let grids = Grids::new(&play_area, 3, 3);
+        let position = ttt::game::Position { row: 1, column: 7 };
+
+        let square = grids.position_to_square(position);
+
+        assert_ulps_eq!(square.center(), Point3::new(1.25, 0.0, 0.0));
+    }
+
+    #[test]
+    fn grids_point_to_position_when_one_large_board_should_not_offset_column() {
+        let play_area = Grid::new(Point3::new(0.0, 0.0, 0.0), 6.0);
+        let grids = Grids::new(&play_area, 1, 6);
+        let expected_position = ttt::game::Position { row: 2, column: 5 };
+
+        let position = grids.point_to_position(Point3::new(2.5, -0.5, 0.0));
+
+        assert_eq!(position, expected_position);
+    }
 }
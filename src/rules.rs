@@ -0,0 +1,199 @@
+//! Contains the players and game logic shared by the game and the programs
+//! that run without its window, such as the game server and bot arena.
+use open_ttt_lib as ttt;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::variants;
+
+/// Stores if the player is playing as X or as O.
+///
+/// In variants where players take roles, such as order and chaos, X and O
+/// only identify whose turn it is; either player can place either symbol. The
+/// role the player has for the current game is provided by
+/// `GameLogic::players_role`.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Player {
+    X,
+    O,
+}
+
+impl Player {
+    /// Gets the other, or opposite, of the current player.
+    pub fn opposite_player(&self) -> Self {
+        match self {
+            Self::X => Self::O,
+            Self::O => Self::X,
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::X => write!(f, "X"),
+            Self::O => write!(f, "O"),
+        }
+    }
+}
+
+/// Provides access to the tic-tac-toe game logic.
+///
+/// This includes the current state of the game and the last time a move was made.
+#[derive(Default)]
+pub struct GameLogic {
+    /// The tic-tac-toe game that contains the current game state.
+    pub game: variants::Game,
+
+    /// Holds the time the game was last updated.
+    pub last_move_time: std::time::Duration,
+
+    /// The squares each player is not allowed to place marks in, such as
+    /// when the computer plays with a handicap.
+    pub blocked_squares: Vec<(Player, ttt::game::Position)>,
+}
+
+impl GameLogic {
+    /// Creates the game logic for playing the provided variant.
+    pub fn new(variant: variants::Variant) -> Self {
+        Self::with_game(variants::Game::new(variant))
+    }
+
+    /// Creates the game logic for continuing the provided game.
+    ///
+    /// This allows playing from any position, such as a puzzle's, instead of
+    /// an empty board.
+    pub fn with_game(game: variants::Game) -> Self {
+        Self {
+            game,
+            last_move_time: std::time::Duration::default(),
+            blocked_squares: Vec::new(),
+        }
+    }
+
+    /// Helper function for knowing if it is the provided player's turn.
+    pub fn is_players_move(&self, player: &Player) -> bool {
+        match self.game.state() {
+            ttt::game::State::PlayerXMove => *player == Player::X,
+            ttt::game::State::PlayerOMove => *player == Player::O,
+            _ => false,
+        }
+    }
+
+    /// Indicates if the provided player is not allowed to move into the position.
+    pub fn is_blocked(&self, player: &Player, position: ttt::game::Position) -> bool {
+        self.blocked_squares
+            .iter()
+            .any(|(blocked_player, blocked_position)| {
+                blocked_player == player && *blocked_position == position
+            })
+    }
+
+    /// Makes the player's move if it is their turn, the move is valid, and
+    /// the player is not blocked from the move's position.
+    ///
+    /// The new state of the game is returned if the move was made.
+    pub fn try_move(
+        &mut self,
+        player: &Player,
+        game_move: &variants::Move,
+    ) -> Option<ttt::game::State> {
+        if self.game.is_valid_move(game_move)
+            && self.is_players_move(player)
+            && !self.is_blocked(player, game_move.position())
+        {
+            Some(self.game.do_move(game_move))
+        } else {
+            None
+        }
+    }
+
+    /// Gets the role the provided player has in the current game.
+    ///
+    /// `None` is returned for variants where players do not take roles.
+    pub fn players_role(&self, player: &Player) -> Option<variants::Role> {
+        let x_role = self.game.x_role()?;
+        match player {
+            Player::X => Some(x_role),
+            Player::O => Some(x_role.opposite_role()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn player_opposite_player_when_x_should_be_o() {
+        let player = Player::X;
+
+        let opposite_player = player.opposite_player();
+
+        assert_eq!(opposite_player, Player::O);
+    }
+
+    #[test]
+    fn player_opposite_player_when_o_should_be_x() {
+        let player = Player::O;
+
+        let opposite_player = player.opposite_player();
+
+        assert_eq!(opposite_player, Player::X);
+    }
+
+    #[test]
+    fn game_logic_with_game_when_o_to_move_should_be_o_players_move() {
+        let rows: Vec<String> = vec!["X..".into(), "...".into(), "...".into()];
+        let board = variants::board_from_rows(&rows, 3).unwrap();
+        let game = variants::classic_game_from_board(&board, false).unwrap();
+
+        let game_logic = GameLogic::with_game(variants::Game::Classic(game));
+
+        assert!(game_logic.is_players_move(&Player::O));
+        assert!(!game_logic.is_players_move(&Player::X));
+    }
+
+    #[test]
+    fn game_logic_players_role_when_classic_should_return_none() {
+        let game_logic = GameLogic::new(variants::Variant::Classic);
+
+        let role = game_logic.players_role(&Player::X);
+
+        assert_eq!(role, None);
+    }
+
+    #[test]
+    fn game_logic_players_role_when_next_order_and_chaos_game_should_swap_roles() {
+        let mut game_logic = GameLogic::new(variants::Variant::OrderAndChaos);
+
+        game_logic.game.start_next_game();
+
+        let x_role = game_logic.players_role(&Player::X);
+        let o_role = game_logic.players_role(&Player::O);
+        assert_eq!(x_role, Some(variants::Role::Chaos));
+        assert_eq!(o_role, Some(variants::Role::Order));
+    }
+
+    #[test]
+    fn game_logic_is_blocked_should_only_block_the_squares_player() {
+        let center = ttt::game::Position { row: 1, column: 1 };
+        let mut game_logic = GameLogic::new(variants::Variant::Classic);
+
+        game_logic.blocked_squares.push((Player::O, center));
+
+        assert!(game_logic.is_blocked(&Player::O, center));
+        assert!(!game_logic.is_blocked(&Player::X, center));
+    }
+
+    #[test]
+    fn game_logic_try_move_when_not_players_turn_should_not_move() {
+        let center = ttt::game::Position { row: 1, column: 1 };
+        let mut game_logic = GameLogic::new(variants::Variant::Classic);
+
+        let state = game_logic.try_move(&Player::O, &variants::Move::Mark(center));
+
+        assert_eq!(state, None);
+        assert!(game_logic.is_players_move(&Player::X));
+    }
+}
@@ -0,0 +1,425 @@
+//! Contains the dedicated game server, which hosts many online games at once
+//! without opening the game window.
+//!
+//! Players connect to the server over TCP using the same messages used
+//! between a host and a joining player, see `network`. After the hellos, a
+//! player either sends `CreateRoom` and is sent `RoomCreated` with the room's
+//! short code, or sends `JoinRoom` with a code. Requests that cannot be met
+//! are answered with `Refused`, and the player can try again.
+//!
+//! Once a room has two players the server sends both `Start` and plays the
+//! part of the host, see `Room`. Rooms where no one has done anything for the
+//! room timeout are closed, as are rooms a player leaves. Players are sent
+//! `Refused` with the reason before a room is closed.
+mod room;
+
+pub use self::room::*;
+
+use std::collections::HashMap;
+use std::io;
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use crate::arena;
+use crate::network::{self, Connection, Message};
+use crate::variants;
+
+/// The options for running a game server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerOptions {
+    /// How long a room is kept without any moves before it is closed.
+    pub room_timeout: Duration,
+
+    /// The most rooms open at once. Creating more rooms is refused.
+    pub max_rooms: usize,
+
+    /// The most players connected without being in a room. More players are
+    /// turned away.
+    pub max_lobby_players: usize,
+}
+
+/// Hosts rooms for players that connect to it.
+pub struct Server {
+    listener: TcpListener,
+    options: ServerOptions,
+    // Players that have connected but are not in a room, and when they
+    // connected.
+    lobby: Vec<(Connection, Instant)>,
+    rooms: HashMap<String, Room>,
+}
+
+impl Server {
+    /// Creates a server for players that connect to the listener.
+    ///
+    /// # Errors
+    /// An error is returned if the listener cannot be set up.
+    pub fn new(listener: TcpListener, options: ServerOptions) -> io::Result<Self> {
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            options,
+            lobby: Vec::new(),
+            rooms: HashMap::new(),
+        })
+    }
+
+    /// Runs the server until the program is stopped.
+    pub fn run(&mut self) {
+        loop {
+            self.poll();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Accepts players that have connected and handles the messages sent by
+    /// players in the lobby and in rooms, without waiting.
+    pub fn poll(&mut self) {
+        self.accept_players();
+        self.poll_lobby();
+
+        let timeout = self.options.room_timeout;
+        for room in self.rooms.values_mut() {
+            room.poll();
+            if room.is_idle(timeout) {
+                room.close("The room was closed after being idle.");
+            }
+        }
+        self.rooms.retain(|_, room| room.close_reason().is_none());
+    }
+
+    // Adds the players that have connected to the lobby.
+    fn accept_players(&mut self) {
+        loop {
+            let (stream, address) = match self.listener.accept() {
+                Ok(accepted) => accepted,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return,
+                Err(error) => {
+                    log::error!("Unable to accept players: {}", error);
+                    return;
+                }
+            };
+            match Connection::new(stream) {
+                Ok(mut connection) if self.lobby.len() >= self.options.max_lobby_players => {
+                    log::warn!("Turned away player from {}: the lobby is full.", address);
+                    connection.send(&Message::Refused {
+                        reason: "the server is full".to_string(),
+                    });
+                    connection.close("The server is full.");
+                }
+                Ok(connection) => {
+                    log::info!("Player connected from {}.", address);
+                    self.lobby.push((connection, Instant::now()));
+                }
+                Err(error) => log::warn!("Turned away player from {}: {}", address, error),
+            }
+        }
+    }
+
+    // Creates and joins rooms for the players in the lobby. Players that
+    // leave or do not pick a room in time are removed.
+    fn poll_lobby(&mut self) {
+        let lobby = std::mem::take(&mut self.lobby);
+        for (connection, connected_at) in lobby {
+            let mut connection = match self.seat_player(connection) {
+                Some(connection) => connection,
+                None => continue,
+            };
+            if connection.disconnect_reason().is_some() {
+                continue;
+            }
+            if connected_at.elapsed() > self.options.room_timeout {
+                connection.close("No room was created or joined.");
+            } else {
+                self.lobby.push((connection, connected_at));
+            }
+        }
+    }
+
+    // Handles the messages from a player in the lobby, creating or joining
+    // the room they ask for.
+    //
+    // The connection is returned if the player is still in the lobby.
+    fn seat_player(&mut self, mut connection: Connection) -> Option<Connection> {
+        for message in connection.poll() {
+            match message {
+                Message::CreateRoom {
+                    variant,
+                    time_control,
+                } => match self.room_variant(&variant, time_control) {
+                    Ok(variant) => {
+                        let rooms = &self.rooms;
+                        let code =
+                            new_room_code(&mut rand::thread_rng(), |code| rooms.contains_key(code));
+                        log::info!("Created {} room {}.", variant.name(), code);
+                        connection.send(&Message::RoomCreated { code: code.clone() });
+                        let room = Room::new(code.clone(), variant, time_control, connection);
+                        self.rooms.insert(code, room);
+                        return None;
+                    }
+                    Err(reason) => connection.send(&Message::Refused { reason }),
+                },
+                Message::JoinRoom { code } => {
+                    let code = code.trim().to_uppercase();
+                    match self.rooms.get_mut(&code) {
+                        Some(room) if room.is_open() => {
+                            log::info!("Player joined room {}.", room.code());
+                            room.join(connection);
+                            return None;
+                        }
+                        Some(_) => connection.send(&Message::Refused {
+                            reason: format!("room {} is full", code),
+                        }),
+                        None => connection.send(&Message::Refused {
+                            reason: format!("there is no room {}", code),
+                        }),
+                    }
+                }
+                message => log::warn!("Ignored unexpected message: {:?}", message),
+            }
+        }
+        Some(connection)
+    }
+
+    // Gets the variant with the name for a new room with the time control,
+    // if another room can be created.
+    fn room_variant(
+        &self,
+        variant: &str,
+        time_control: Option<arena::TimeControl>,
+    ) -> Result<variants::Variant, String> {
+        if self.rooms.len() >= self.options.max_rooms {
+            return Err("the server has no more rooms".to_string());
+        }
+        // Players choose the time control, so it is checked before clocks are
+        // run with it.
+        if time_control.is_some_and(|time_control| !time_control.is_in_range()) {
+            return Err(format!(
+                "the time control is longer than {} hours",
+                arena::TimeControl::MAX_TIME.as_secs() / 3600
+            ));
+        }
+        // Custom variants are left out since players' rule files may differ.
+        network::find_variant(variant, &[])
+            .ok_or_else(|| format!("the server does not have the {} variant", variant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Player;
+    use std::net::TcpStream;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    // Runs a server on a free loopback port until the flag is dropped.
+    struct TestServer {
+        address: String,
+        is_stopped: Arc<AtomicBool>,
+    }
+
+    impl TestServer {
+        fn start(options: ServerOptions) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let mut server = Server::new(listener, options).unwrap();
+            let is_stopped = Arc::new(AtomicBool::new(false));
+            let is_server_stopped = Arc::clone(&is_stopped);
+            thread::spawn(move || {
+                while !is_server_stopped.load(Ordering::SeqCst) {
+                    server.poll();
+                    thread::sleep(Duration::from_millis(5));
+                }
+            });
+            Self {
+                address,
+                is_stopped,
+            }
+        }
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            self.is_stopped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn options() -> ServerOptions {
+        ServerOptions {
+            room_timeout: Duration::from_secs(60),
+            max_rooms: 10,
+            max_lobby_players: 10,
+        }
+    }
+
+    // Waits for the connection's next game message.
+    fn next_message(connection: &mut Connection) -> Option<Message> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline && connection.disconnect_reason().is_none() {
+            if let Some(message) = connection.poll().into_iter().next() {
+                return Some(message);
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        None
+    }
+
+    // Creates a room and joins it, returning the creator's and joiner's
+    // connections.
+    fn create_and_join(
+        server: &TestServer,
+        time_control: Option<arena::TimeControl>,
+    ) -> (Connection, Connection) {
+        let (mut creator, code) =
+            network::create_room(&server.address, variants::Variant::Classic, time_control)
+                .unwrap();
+        let (joiner, variant, player) = network::join_room(&server.address, &code).unwrap();
+        assert_eq!(variant, "Classic");
+        assert_eq!(player, Player::O);
+        assert_eq!(
+            next_message(&mut creator),
+            Some(Message::Start {
+                variant: "Classic".to_string(),
+                player: Player::X
+            })
+        );
+        (creator, joiner)
+    }
+
+    #[test]
+    fn server_should_pass_moves_between_players_in_a_room() {
+        let server = TestServer::start(options());
+        let (mut creator, mut joiner) = create_and_join(&server, None);
+        let message = Message::Move {
+            player: Player::X,
+            game_move: network::NetworkMove::Mark { row: 1, column: 1 },
+        };
+
+        creator.send(&message);
+
+        assert_eq!(next_message(&mut joiner), Some(message));
+    }
+
+    #[test]
+    fn server_when_move_is_not_allowed_should_close_room() {
+        let server = TestServer::start(options());
+        let (_creator, mut joiner) = create_and_join(&server, None);
+
+        // It is X's turn, so O cannot move.
+        joiner.send(&Message::Move {
+            player: Player::O,
+            game_move: network::NetworkMove::Mark { row: 1, column: 1 },
+        });
+
+        assert_eq!(
+            next_message(&mut joiner),
+            Some(Message::Refused {
+                reason: "A player made a move that is not allowed.".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn join_room_when_no_room_should_return_error() {
+        let server = TestServer::start(options());
+
+        let result = network::join_room(&server.address, "ZZZZ");
+
+        assert_eq!(
+            result.map(|_| ()).unwrap_err().to_string(),
+            "there is no room ZZZZ"
+        );
+    }
+
+    #[test]
+    fn server_when_player_runs_out_of_time_should_send_time_out() {
+        let server = TestServer::start(options());
+        let time_control = "0.1+0".parse().unwrap();
+        let (mut creator, mut joiner) = create_and_join(&server, Some(time_control));
+
+        let timed_out = Message::TimeOut { player: Player::X };
+
+        assert_eq!(next_message(&mut creator), Some(timed_out.clone()));
+        assert_eq!(next_message(&mut joiner), Some(timed_out));
+    }
+
+    #[test]
+    fn server_when_room_is_idle_should_close_room() {
+        let server = TestServer::start(ServerOptions {
+            room_timeout: Duration::from_millis(100),
+            ..options()
+        });
+        let (mut creator, _code) =
+            network::create_room(&server.address, variants::Variant::Classic, None).unwrap();
+
+        assert_eq!(
+            next_message(&mut creator),
+            Some(Message::Refused {
+                reason: "The room was closed after being idle.".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn server_when_full_should_refuse_to_create_room() {
+        let server = TestServer::start(ServerOptions {
+            max_rooms: 1,
+            ..options()
+        });
+        let _first = network::create_room(&server.address, variants::Variant::Classic, None);
+
+        let second = network::create_room(&server.address, variants::Variant::Classic, None);
+
+        assert!(second.is_err());
+    }
+
+    #[test]
+    fn server_when_time_control_is_too_long_should_refuse_to_create_room() {
+        let server = TestServer::start(options());
+        let time_control = arena::TimeControl {
+            base: Duration::from_secs(10),
+            increment: Duration::from_secs(u64::MAX),
+        };
+
+        let result = network::create_room(
+            &server.address,
+            variants::Variant::Classic,
+            Some(time_control),
+        );
+
+        assert_eq!(
+            result.map(|_| ()).unwrap_err().to_string(),
+            "the time control is longer than 24 hours"
+        );
+    }
+
+    #[test]
+    fn join_room_when_code_has_spaces_should_refuse_with_normalized_code() {
+        let server = TestServer::start(options());
+
+        let result = network::join_room(&server.address, " zzzz ");
+
+        assert_eq!(
+            result.map(|_| ()).unwrap_err().to_string(),
+            "there is no room ZZZZ"
+        );
+    }
+
+    #[test]
+    fn server_when_lobby_is_full_should_turn_away_players() {
+        let server = TestServer::start(ServerOptions {
+            max_lobby_players: 1,
+            ..options()
+        });
+        let _first = TcpStream::connect(&server.address).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        let second = network::create_room(&server.address, variants::Variant::Classic, None);
+
+        assert_eq!(
+            second.map(|_| ()).unwrap_err().to_string(),
+            "the server is full"
+        );
+    }
+}
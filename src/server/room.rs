@@ -0,0 +1,391 @@
+use rand::{seq::SliceRandom, Rng};
+use std::time::{Duration, Instant};
+
+use crate::arena;
+use crate::network::{Connection, Message};
+use crate::rules::{self, Player};
+use crate::variants;
+
+/// The number of letters in a room code.
+pub const ROOM_CODE_LENGTH: usize = 4;
+
+// The letters room codes are made of, leaving out I and O which are easily
+// mistaken for 1 and 0.
+const ROOM_CODE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+/// Picks a new room code that is not already taken.
+pub fn new_room_code(rng: &mut impl Rng, is_taken: impl Fn(&str) -> bool) -> String {
+    loop {
+        let code: String = (0..ROOM_CODE_LENGTH)
+            .map(|_| *ROOM_CODE_LETTERS.choose(rng).expect("There are letters.") as char)
+            .collect();
+        if !is_taken(&code) {
+            return code;
+        }
+    }
+}
+
+/// A room on the game server where two players play games of a variant.
+///
+/// The player who creates the room plays X and starts the next games, and the
+/// player who joins plays O. The room checks every move against the rules
+/// before passing it on, and runs the players' clocks if the games are timed.
+pub struct Room {
+    code: String,
+    variant: variants::Variant,
+    time_control: Option<arena::TimeControl>,
+    creator: Connection,
+    joiner: Option<Connection>,
+    game_logic: rules::GameLogic,
+    // The time X and O have left in the current game.
+    clocks: [Duration; 2],
+    // When the player to move started thinking.
+    turn_started: Instant,
+    // Indicates the player to move ran out of time, ending the game.
+    is_timed_out: bool,
+    // When a player last did something, used to close idle rooms.
+    last_activity: Instant,
+    // Why the room was closed, or `None` if it is still open.
+    close_reason: Option<String>,
+}
+
+impl Room {
+    /// Creates a room for the player with the connection.
+    pub fn new(
+        code: String,
+        variant: variants::Variant,
+        time_control: Option<arena::TimeControl>,
+        creator: Connection,
+    ) -> Self {
+        let base = time_control.map_or(Duration::default(), |time_control| time_control.base);
+        let now = Instant::now();
+        Self {
+            code,
+            variant,
+            time_control,
+            creator,
+            joiner: None,
+            game_logic: rules::GameLogic::new(variant),
+            clocks: [base, base],
+            turn_started: now,
+            is_timed_out: false,
+            last_activity: now,
+            close_reason: None,
+        }
+    }
+
+    /// Gets the room's code.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Indicates a player can still join the room.
+    pub fn is_open(&self) -> bool {
+        self.joiner.is_none()
+    }
+
+    /// Gets why the room was closed, or `None` if it is still open.
+    pub fn close_reason(&self) -> Option<&str> {
+        self.close_reason.as_deref()
+    }
+
+    /// Indicates no player has done anything for the provided time.
+    pub fn is_idle(&self, timeout: Duration) -> bool {
+        self.last_activity.elapsed() > timeout
+    }
+
+    /// Adds the second player to the room and starts the first game.
+    pub fn join(&mut self, mut joiner: Connection) {
+        let variant = self.variant.name().to_string();
+        self.creator.send(&Message::Start {
+            variant: variant.clone(),
+            player: Player::X,
+        });
+        joiner.send(&Message::Start {
+            variant,
+            player: Player::O,
+        });
+        self.joiner = Some(joiner);
+        self.start_clocks();
+        log::info!(
+            "Started {} game in room {}.",
+            self.variant.name(),
+            self.code
+        );
+    }
+
+    /// Handles the messages the players have sent and checks their clocks.
+    ///
+    /// The room is closed if either player leaves or sends a move the rules
+    /// do not allow.
+    pub fn poll(&mut self) {
+        if self.close_reason.is_some() {
+            return;
+        }
+
+        let mut messages: Vec<(Player, Message)> = self
+            .creator
+            .poll()
+            .into_iter()
+            .map(|message| (Player::X, message))
+            .collect();
+        if let Some(joiner) = self.joiner.as_mut() {
+            messages.extend(
+                joiner
+                    .poll()
+                    .into_iter()
+                    .map(|message| (Player::O, message)),
+            );
+        }
+        for (player, message) in messages {
+            if self.close_reason.is_some() {
+                return;
+            }
+            self.handle_message(player, message);
+        }
+
+        let has_left = self.creator.disconnect_reason().is_some()
+            || self
+                .joiner
+                .as_ref()
+                .is_some_and(|joiner| joiner.disconnect_reason().is_some());
+        if has_left {
+            self.close("A player left.");
+        } else {
+            self.check_clock();
+        }
+    }
+
+    /// Closes the room, telling the players why.
+    pub fn close(&mut self, reason: &str) {
+        if self.close_reason.is_some() {
+            return;
+        }
+        log::info!("Closed room {}: {}", self.code, reason);
+        let refused = Message::Refused {
+            reason: reason.to_string(),
+        };
+        self.creator.send(&refused);
+        self.creator.close(reason);
+        if let Some(joiner) = self.joiner.as_mut() {
+            joiner.send(&refused);
+            joiner.close(reason);
+        }
+        self.close_reason = Some(reason.to_string());
+    }
+
+    // Handles a message from the player.
+    //
+    // Only moves and starting the next game count as activity, so a room
+    // where players send nothing else is still closed when idle.
+    fn handle_message(&mut self, player: Player, message: Message) {
+        match message {
+            Message::Move {
+                player: mover,
+                game_move,
+            } if mover == player && !self.is_timed_out => {
+                // Moves that arrive after the player's time ran out lose on
+                // time, even if the clock has not been checked since.
+                if self.is_out_of_time(player) {
+                    self.time_out(player);
+                    return;
+                }
+                if self
+                    .game_logic
+                    .try_move(&player, &game_move.to_move())
+                    .is_none()
+                {
+                    log::warn!("Rejected move {:?} in room {}.", game_move, self.code);
+                    self.close("A player made a move that is not allowed.");
+                    return;
+                }
+                self.last_activity = Instant::now();
+                if let Some(time_control) = self.time_control {
+                    let clock = &mut self.clocks[clock_index(player)];
+                    *clock = clock
+                        .saturating_sub(self.turn_started.elapsed())
+                        .saturating_add(time_control.increment);
+                }
+                self.turn_started = Instant::now();
+                self.send_to(
+                    player.opposite_player(),
+                    &Message::Move {
+                        player: mover,
+                        game_move,
+                    },
+                );
+            }
+            Message::NextGame if player == Player::X && self.is_game_over() => {
+                self.last_activity = Instant::now();
+                self.game_logic.game.start_next_game();
+                self.start_clocks();
+                self.send_to(Player::O, &Message::NextGame);
+            }
+            message => log::warn!(
+                "Ignored unexpected message in room {}: {:?}",
+                self.code,
+                message
+            ),
+        }
+    }
+
+    // Ends the game if the player to move has run out of time.
+    fn check_clock(&mut self) {
+        if self.time_control.is_none() || self.joiner.is_none() || self.is_game_over() {
+            return;
+        }
+        let player = if self.game_logic.is_players_move(&Player::X) {
+            Player::X
+        } else {
+            Player::O
+        };
+        if self.is_out_of_time(player) {
+            self.time_out(player);
+        }
+    }
+
+    // Indicates the player to move has used up their clock this turn.
+    fn is_out_of_time(&self, player: Player) -> bool {
+        self.time_control.is_some()
+            && self.turn_started.elapsed() > self.clocks[clock_index(player)]
+    }
+
+    // Ends the game because the player ran out of time, telling both players.
+    fn time_out(&mut self, player: Player) {
+        log::info!("Player {} ran out of time in room {}.", player, self.code);
+        self.is_timed_out = true;
+        for receiver in [Player::X, Player::O].iter() {
+            self.send_to(*receiver, &Message::TimeOut { player });
+        }
+    }
+
+    // Gives both players the full base time for a new game.
+    fn start_clocks(&mut self) {
+        if let Some(time_control) = self.time_control {
+            self.clocks = [time_control.base, time_control.base];
+        }
+        self.is_timed_out = false;
+        self.turn_started = Instant::now();
+    }
+
+    // Indicates the current game has finished, by the rules or the clock.
+    fn is_game_over(&self) -> bool {
+        self.is_timed_out || self.game_logic.game.state().is_game_over()
+    }
+
+    // Sends the message to the player.
+    fn send_to(&mut self, player: Player, message: &Message) {
+        let connection = match player {
+            Player::X => Some(&mut self.creator),
+            Player::O => self.joiner.as_mut(),
+        };
+        if let Some(connection) = connection {
+            connection.send(message);
+        }
+    }
+}
+
+// Gets the index of the player's clock.
+fn clock_index(player: Player) -> usize {
+    match player {
+        Player::X => 0,
+        Player::O => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::NetworkMove;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    // Connects a connection for the room to one driven by the test.
+    fn connect_to_peer() -> (Connection, Connection) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (peer, _) = listener.accept().unwrap();
+        (
+            Connection::new(stream).unwrap(),
+            Connection::new(peer).unwrap(),
+        )
+    }
+
+    // Gets the messages the connection receives within a short time.
+    fn received_messages(connection: &mut Connection) -> Vec<Message> {
+        let mut messages = Vec::new();
+        for _ in 0..20 {
+            messages.extend(connection.poll());
+            thread::sleep(Duration::from_millis(10));
+        }
+        messages
+    }
+
+    #[test]
+    fn new_room_code_should_use_room_code_letters() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let code = new_room_code(&mut rng, |_| false);
+
+        assert_eq!(code.len(), ROOM_CODE_LENGTH);
+        assert!(code
+            .bytes()
+            .all(|letter| ROOM_CODE_LETTERS.contains(&letter)));
+    }
+
+    #[test]
+    fn new_room_code_when_taken_should_pick_another() {
+        let taken = new_room_code(&mut StdRng::seed_from_u64(7), |_| false);
+
+        let code = new_room_code(&mut StdRng::seed_from_u64(7), |code| code == taken);
+
+        assert_ne!(code, taken);
+    }
+
+    #[test]
+    fn room_poll_when_move_arrives_after_time_ran_out_should_time_out() {
+        let (creator, mut creator_peer) = connect_to_peer();
+        let (joiner, mut joiner_peer) = connect_to_peer();
+        let time_control = "0.05+0".parse().unwrap();
+        let mut room = Room::new(
+            "ABCD".to_string(),
+            variants::Variant::Classic,
+            Some(time_control),
+            creator,
+        );
+        room.join(joiner);
+        thread::sleep(Duration::from_millis(100));
+
+        // The move reaches the room before its clock is checked.
+        creator_peer.send(&Message::Move {
+            player: Player::X,
+            game_move: NetworkMove::Mark { row: 1, column: 1 },
+        });
+        thread::sleep(Duration::from_millis(50));
+        room.poll();
+
+        let messages = received_messages(&mut joiner_peer);
+        assert!(messages.contains(&Message::TimeOut { player: Player::X }));
+        assert!(!messages
+            .iter()
+            .any(|message| matches!(message, Message::Move { .. })));
+    }
+
+    #[test]
+    fn room_is_idle_when_only_unexpected_messages_arrive_should_be_idle() {
+        let (creator, mut creator_peer) = connect_to_peer();
+        let mut room = Room::new(
+            "ABCD".to_string(),
+            variants::Variant::Classic,
+            None,
+            creator,
+        );
+
+        creator_peer.send(&Message::NextGame);
+        thread::sleep(Duration::from_millis(50));
+        room.poll();
+
+        assert!(room.is_idle(Duration::from_millis(10)));
+    }
+}
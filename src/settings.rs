@@ -0,0 +1,175 @@
+//! Contains the player's settings, which are saved between runs of the game.
+use open_ttt_lib as ttt;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path;
+use std::str::FromStr;
+
+use crate::file_io;
+use crate::variants;
+
+/// The player's settings.
+///
+/// Settings missing from the settings file, such as those added in newer
+/// versions of the game, take their default values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Indicates the player has finished or skipped the tutorial, so it is
+    /// not started automatically.
+    pub tutorial_completed: bool,
+
+    /// The handicaps used in single-player classic games.
+    pub handicaps: variants::Handicaps,
+
+    /// The chance, from 0 to 100 percent, the AI makes a mistake in custom
+    /// difficulty games.
+    pub custom_mistake_percent: u8,
+
+    /// The program of the bot played with the `--engine` command line
+    /// option, if any.
+    pub external_engine: Option<path::PathBuf>,
+
+    /// The address of the host or game server joined for online games, set
+    /// with the `--join` command line option.
+    pub join_address: String,
+
+    /// The code of the room joined on a game server, set with the `--room`
+    /// command line option.
+    pub room_code: String,
+
+    /// The name other players on the local network see for games this player
+    /// hosts, set with the `--name` command line option.
+    pub player_name: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            tutorial_completed: false,
+            handicaps: variants::Handicaps::default(),
+            // Between the easy and medium difficulties.
+            custom_mistake_percent: 35,
+            external_engine: None,
+            // Lets two copies of the game on the same computer play.
+            join_address: "localhost".to_string(),
+            room_code: String::new(),
+            // The name the player logs in to the computer with, if known.
+            player_name: env::var("USER")
+                .or_else(|_| env::var("USERNAME"))
+                .unwrap_or_else(|_| "Player".to_string()),
+        }
+    }
+}
+
+impl Settings {
+    /// Gets the custom difficulty the player has set up.
+    pub fn custom_difficulty(&self) -> ttt::ai::Difficulty {
+        ttt::ai::Difficulty::Custom(f64::from(self.custom_mistake_percent.min(100)) / 100.0)
+    }
+
+    /// Reads the saved settings, logging any problems.
+    ///
+    /// The default settings are used the first time the game is played or
+    /// if the settings cannot be read.
+    pub fn load() -> Self {
+        file_io::load_or_default(file_io::settings_file, "the settings")
+    }
+
+    /// Saves the settings, logging any problems.
+    pub fn save(&self) {
+        file_io::save(file_io::settings_file, self, "the settings");
+    }
+}
+
+/// The difficulties the player can pick for single-player games.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DifficultyChoice {
+    Easy,
+    Medium,
+    Hard,
+    /// Uses the player's custom mistake percentage, see
+    /// `Settings::custom_difficulty`.
+    Custom,
+}
+
+impl DifficultyChoice {
+    /// Gets the AI difficulty for the choice using the provided settings.
+    pub fn difficulty(self, settings: &Settings) -> ttt::ai::Difficulty {
+        match self {
+            Self::Easy => ttt::ai::Difficulty::Easy,
+            Self::Medium => ttt::ai::Difficulty::Medium,
+            Self::Hard => ttt::ai::Difficulty::Hard,
+            Self::Custom => settings.custom_difficulty(),
+        }
+    }
+}
+
+impl FromStr for DifficultyChoice {
+    type Err = String;
+
+    /// Parses the name of the difficulty, ignoring case.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_lowercase().as_str() {
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            "custom" => Ok(Self::Custom),
+            _ => Err(format!(
+                "unknown difficulty '{}', expected easy, medium, hard, or custom",
+                text
+            )),
+        }
+    }
+}
+
+/// Parses a mistake percentage, which must be from 0 to 100.
+pub fn parse_mistake_percent(text: &str) -> Result<u8, String> {
+    match text.parse::<u8>() {
+        Ok(percent) if percent <= 100 => Ok(percent),
+        _ => Err(format!(
+            "expected a percentage from 0 to 100, found '{}'",
+            text
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_when_field_missing_should_use_default() {
+        let text = "()";
+
+        let settings: Settings = ron::de::from_str(text).unwrap();
+
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn settings_custom_difficulty_should_use_mistake_percent() {
+        let settings = Settings {
+            custom_mistake_percent: 25,
+            ..Settings::default()
+        };
+
+        let difficulty = settings.custom_difficulty();
+
+        assert_eq!(difficulty, ttt::ai::Difficulty::Custom(0.25));
+    }
+
+    #[test]
+    fn difficulty_choice_from_str_when_mixed_case_should_parse() {
+        let choice = "Custom".parse::<DifficultyChoice>();
+
+        assert_eq!(choice, Ok(DifficultyChoice::Custom));
+    }
+
+    #[test]
+    fn parse_mistake_percent_when_over_100_should_be_error() {
+        let percent = parse_mistake_percent("101");
+
+        assert!(percent.is_err());
+    }
+}
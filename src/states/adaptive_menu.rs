@@ -0,0 +1,148 @@
+use amethyst::{input, prelude::*};
+use contracts::*;
+
+use crate::adaptive;
+use crate::events;
+use crate::ui;
+use crate::variants;
+
+use super::{ChallengesMenu, Game};
+
+/// Lets the player start adaptive games and shows their rating.
+///
+/// Games are played on top of this state, so the rating shown is updated when
+/// the player comes back.
+pub struct AdaptiveMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+}
+
+impl<'a, 'b> AdaptiveMenu {
+    pub fn new() -> Self {
+        Self { menu: None }
+    }
+
+    // Creates the menu, reading the latest rating.
+    fn create_menu(&mut self, world: &mut World) {
+        let rating = adaptive::PlayerRating::load();
+        let mistake_percent = variants::mistake_probability(rating.difficulty()) * 100.0;
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, "Adaptive");
+        menu.set_close_button(world, "Back", |_, _| NextState::ChallengesMenu);
+        menu.add_button(world, "Play", |_, _| NextState::Play);
+        menu.add_separator(world);
+        menu.add_paragraph(world, &format!("Your rating: {:.0}", rating.rating));
+        menu.add_paragraph(world, &format!("Games played: {}", rating.games));
+        if !rating.recent_results.is_empty() {
+            menu.add_paragraph(
+                world,
+                &format!(
+                    "Last {} games: {}",
+                    rating.recent_results.len(),
+                    rating.recent_summary()
+                ),
+            );
+        }
+        menu.add_paragraph(world, &format!("Next AI mistakes: {:.0}%", mistake_percent));
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            NextState::ChallengesMenu.as_trans()
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for AdaptiveMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened adaptive menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+
+        log::info!("Closed adaptive menu.");
+    }
+
+    fn on_pause(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.create_menu(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    Play,
+    ChallengesMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::Play => Trans::Push(Box::new(Game::adaptive())),
+            Self::ChallengesMenu => Trans::Switch(Box::new(ChallengesMenu::new())),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use amethyst::{core::ecs, input, prelude::*};
+use contracts::*;
+
+use crate::campaign;
+use crate::events;
+use crate::ui;
+
+use super::{ChallengesMenu, Game};
+
+/// Shows the campaign ladder one opponent at a time, letting the player play
+/// any opponent they have unlocked.
+///
+/// Games are played on top of this state. When the player comes back after
+/// unlocking an opponent, the new opponent is shown.
+pub struct CampaignMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+    progress: campaign::CampaignProgress,
+    // The index of the opponent shown.
+    selected: usize,
+}
+
+impl<'a, 'b> CampaignMenu {
+    pub fn new() -> Self {
+        let progress = campaign::CampaignProgress::load();
+        let selected = progress.next_opponent();
+        Self {
+            menu: None,
+            progress,
+            selected,
+        }
+    }
+
+    // Creates the menu for the selected opponent, replacing any existing menu.
+    fn create_menu(&mut self, world: &mut World) {
+        self.delete_menu(world);
+
+        let opponent = &campaign::OPPONENTS[self.selected];
+        let is_unlocked = self.progress.is_unlocked(self.selected);
+        let details = if is_unlocked {
+            opponent.description()
+        } else {
+            format!(
+                "Locked: beat {} first",
+                campaign::OPPONENTS[self.selected - 1].name
+            )
+        };
+        let result = if self.selected < self.progress.opponents_beaten {
+            "Beaten"
+        } else {
+            "Not beaten yet"
+        };
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, "Campaign");
+        menu.set_close_button(world, "Back", |_, _| NextState::ChallengesMenu);
+        menu.add_paragraph(
+            world,
+            &format!(
+                "Opponent {} of {}: {}",
+                self.selected + 1,
+                campaign::OPPONENTS.len(),
+                opponent.name
+            ),
+        );
+        menu.add_paragraph(world, &details);
+        menu.add_paragraph(world, result);
+        if is_unlocked {
+            menu.add_button(world, "Play", |_, _| NextState::Play);
+        }
+        if self.selected > 0 {
+            menu.add_button(world, "Previous", Self::on_previous_button_click);
+        }
+        if self.selected + 1 < campaign::OPPONENTS.len() {
+            menu.add_button(world, "Next", Self::on_next_button_click);
+        }
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+    }
+
+    fn on_previous_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        self.selected = self.selected.saturating_sub(1);
+        self.create_menu(world);
+        NextState::None
+    }
+
+    fn on_next_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        self.selected = (self.selected + 1).min(campaign::OPPONENTS.len() - 1);
+        self.create_menu(world);
+        NextState::None
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            NextState::ChallengesMenu.as_trans(self.selected)
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans(self.selected);
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for CampaignMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened campaign menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+
+        log::info!("Closed campaign menu.");
+    }
+
+    fn on_pause(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        // Move on to the newly unlocked opponent, if the player unlocked one.
+        let progress = campaign::CampaignProgress::load();
+        if progress.opponents_beaten > self.progress.opponents_beaten {
+            self.selected = progress.next_opponent();
+        }
+        self.progress = progress;
+        self.create_menu(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    Play,
+    ChallengesMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self, selected: usize) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::Play => {
+                log::info!(
+                    "Started campaign game against {}.",
+                    campaign::OPPONENTS[selected].name
+                );
+                Trans::Push(Box::new(Game::campaign(selected)))
+            }
+            Self::ChallengesMenu => Trans::Switch(Box::new(ChallengesMenu::new())),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use amethyst::{input, prelude::*};
+use contracts::*;
+
+use crate::events;
+use crate::ui;
+
+use super::{
+    AdaptiveMenu, CampaignMenu, DailyChallengeMenu, EndlessMenu, OpponentsMenu, SinglePlayerMenu,
+};
+
+/// Lets the player choose between the single-player modes played against
+/// set challenges rather than a fixed difficulty.
+pub struct ChallengesMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+}
+
+impl<'a, 'b> ChallengesMenu {
+    pub fn new() -> Self {
+        Self { menu: None }
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            Trans::Switch(Box::new(SinglePlayerMenu::new()))
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for ChallengesMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened challenges menu.");
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(data.world, "Challenges");
+        menu.set_close_button(data.world, "Back", |_, _| NextState::SinglePlayerMenu);
+        menu.add_button(data.world, "Campaign", |_, _| NextState::CampaignMenu);
+        menu.add_button(data.world, "Daily Challenge", |_, _| {
+            NextState::DailyChallengeMenu
+        });
+        menu.add_button(data.world, "Endless Run", |_, _| NextState::EndlessMenu);
+        menu.add_button(data.world, "Adaptive", |_, _| NextState::AdaptiveMenu);
+        menu.add_button(data.world, "Opponents", |_, _| NextState::OpponentsMenu);
+        self.menu = Some(menu);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+
+        log::info!("Closed challenges menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    AdaptiveMenu,
+    CampaignMenu,
+    DailyChallengeMenu,
+    EndlessMenu,
+    OpponentsMenu,
+    SinglePlayerMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::AdaptiveMenu => Trans::Switch(Box::new(AdaptiveMenu::new())),
+            Self::CampaignMenu => Trans::Switch(Box::new(CampaignMenu::new())),
+            Self::DailyChallengeMenu => Trans::Switch(Box::new(DailyChallengeMenu::new())),
+            Self::EndlessMenu => Trans::Switch(Box::new(EndlessMenu::new())),
+            Self::OpponentsMenu => Trans::Switch(Box::new(OpponentsMenu::new())),
+            Self::SinglePlayerMenu => Trans::Switch(Box::new(SinglePlayerMenu::new())),
+        }
+    }
+}
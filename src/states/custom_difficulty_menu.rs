@@ -0,0 +1,210 @@
+use amethyst::{core::ecs, input, prelude::*};
+use contracts::*;
+use open_ttt_lib as ttt;
+
+use crate::components::Player;
+use crate::events;
+use crate::settings;
+use crate::ui;
+use crate::variants::{Handicaps, Variant};
+
+use super::{Game, GameStateOptions, HandicapsMenu, SinglePlayerMenu};
+
+// How much each button press changes the chance of a mistake, in percent.
+const MISTAKE_PERCENT_STEP: u8 = 5;
+
+/// Lets the player set how often the AI makes mistakes and start a game
+/// against it.
+///
+/// The buttons act like a slider from 0 to 100 percent. The setting is saved
+/// straight away, so it is kept for next time.
+pub struct CustomDifficultyMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+    // The variant to play.
+    variant: Variant,
+}
+
+impl<'a, 'b> CustomDifficultyMenu {
+    /// Creates the menu for playing the provided variant.
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            menu: None,
+            variant,
+        }
+    }
+
+    // Creates the menu showing the current mistake percentage, replacing any
+    // existing menu.
+    fn create_menu(&mut self, world: &mut World) {
+        self.delete_menu(world);
+
+        let percent = world
+            .read_resource::<settings::Settings>()
+            .custom_mistake_percent;
+        let title = match self.variant {
+            Variant::Classic => "Custom Difficulty".to_string(),
+            variant => format!("{}: Custom Difficulty", variant.name()),
+        };
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, &title);
+        menu.set_close_button(world, "Back", |_, _| NextState::SinglePlayerMenu);
+        menu.add_paragraph(world, &format!("AI mistakes: {}%", percent));
+        menu.add_button(world, "More Mistakes", Self::on_more_mistakes_button_click);
+        menu.add_button(
+            world,
+            "Fewer Mistakes",
+            Self::on_fewer_mistakes_button_click,
+        );
+        menu.add_button(world, "Play", Self::on_play_button_click);
+        // Handicaps are only used in classic games.
+        if self.variant == Variant::Classic {
+            menu.add_button(world, "Handicaps", |_, _| NextState::HandicapsMenu);
+        }
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+    }
+
+    // Changes the mistake percentage, saves it, and shows the new value.
+    fn update_mistake_percent(
+        &mut self,
+        world: &mut World,
+        update: impl FnOnce(u8) -> u8,
+    ) -> NextState {
+        {
+            let mut settings = world.write_resource::<settings::Settings>();
+            settings.custom_mistake_percent = update(settings.custom_mistake_percent).min(100);
+            log::info!(
+                "Changed custom difficulty to {}% mistakes.",
+                settings.custom_mistake_percent
+            );
+            settings.save();
+        }
+        self.create_menu(world);
+        NextState::None
+    }
+
+    fn on_more_mistakes_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        self.update_mistake_percent(world, |percent| {
+            percent.saturating_add(MISTAKE_PERCENT_STEP)
+        })
+    }
+
+    fn on_fewer_mistakes_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        self.update_mistake_percent(world, |percent| {
+            percent.saturating_sub(MISTAKE_PERCENT_STEP)
+        })
+    }
+
+    fn on_play_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        let settings = world.read_resource::<settings::Settings>();
+        let handicaps = match self.variant {
+            Variant::Classic => settings.handicaps,
+            _ => Handicaps::default(),
+        };
+        NextState::SinglePlayerGame(settings.custom_difficulty(), handicaps)
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            NextState::SinglePlayerMenu.as_trans(self.variant)
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans(self.variant);
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for CustomDifficultyMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened custom difficulty menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+
+        log::info!("Closed custom difficulty menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    SinglePlayerGame(ttt::ai::Difficulty, Handicaps),
+    HandicapsMenu,
+    SinglePlayerMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self, variant: Variant) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::SinglePlayerGame(difficulty, handicaps) => {
+                let game_state = Game::new(
+                    GameStateOptions::SinglePlayer(*difficulty, Player::X),
+                    variant,
+                )
+                .with_handicaps(*handicaps);
+                Trans::Switch(Box::new(game_state))
+            }
+            Self::HandicapsMenu => Trans::Switch(Box::new(HandicapsMenu::new())),
+            Self::SinglePlayerMenu => {
+                Trans::Switch(Box::new(SinglePlayerMenu::with_variant(variant)))
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use amethyst::{input, prelude::*};
+use contracts::*;
+
+use crate::daily_challenge;
+use crate::events;
+use crate::ui;
+
+use super::{ChallengesMenu, Game};
+
+// How many of the previous days' results are shown.
+const NUM_RECENT_RECORDS: usize = 3;
+
+/// Shows today's daily challenge along with the player's recent results.
+///
+/// The challenge is played on top of this state, so the results shown are
+/// updated when the player comes back.
+pub struct DailyChallengeMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+    challenge: daily_challenge::DailyChallenge,
+}
+
+impl<'a, 'b> DailyChallengeMenu {
+    pub fn new() -> Self {
+        Self {
+            menu: None,
+            challenge: daily_challenge::DailyChallenge::today(),
+        }
+    }
+
+    // Creates the menu, reading the latest results.
+    fn create_menu(&mut self, world: &mut World) {
+        // The date can change while the menu is open.
+        self.challenge = daily_challenge::DailyChallenge::today();
+        let records = daily_challenge::ChallengeRecords::load();
+        let date = self.challenge.date;
+        let today_summary = records
+            .get(date)
+            .map_or("Not played yet".to_string(), |record| record.summary());
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, "Daily Challenge");
+        menu.set_close_button(world, "Back", |_, _| NextState::ChallengesMenu);
+        menu.add_paragraph(
+            world,
+            &format!("{}: {}", date, self.challenge.description()),
+        );
+        menu.add_paragraph(world, &today_summary);
+        menu.add_button(world, "Play", |_, _| NextState::Play);
+        menu.add_separator(world);
+        let today = date.to_string();
+        for (record_date, record) in records
+            .recent(NUM_RECENT_RECORDS + 1)
+            .into_iter()
+            .filter(|(record_date, _)| *record_date != today)
+            .take(NUM_RECENT_RECORDS)
+        {
+            menu.add_paragraph(world, &format!("{}: {}", record_date, record.summary()));
+        }
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            NextState::ChallengesMenu.as_trans(&self.challenge)
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans(&self.challenge);
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for DailyChallengeMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened daily challenge menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+
+        log::info!("Closed daily challenge menu.");
+    }
+
+    fn on_pause(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.create_menu(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    Play,
+    ChallengesMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(
+        &self,
+        challenge: &daily_challenge::DailyChallenge,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::Play => {
+                log::info!("Started daily challenge {}.", challenge.date);
+                Trans::Push(Box::new(Game::daily_challenge(challenge.clone())))
+            }
+            Self::ChallengesMenu => Trans::Switch(Box::new(ChallengesMenu::new())),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use amethyst::{input, prelude::*};
+use contracts::*;
+
+use crate::endless;
+use crate::events;
+use crate::ui;
+
+use super::{ChallengesMenu, Game};
+
+// How many of the best runs are shown. The whole leaderboard does not fit in
+// the menu.
+const NUM_SHOWN_ENTRIES: usize = 5;
+
+/// Lets the player start an endless run and shows the best runs so far.
+///
+/// Runs are played on top of this state, so the leaderboard shown is updated
+/// when the player comes back.
+pub struct EndlessMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+}
+
+impl<'a, 'b> EndlessMenu {
+    pub fn new() -> Self {
+        Self { menu: None }
+    }
+
+    // Creates the menu, reading the latest leaderboard.
+    fn create_menu(&mut self, world: &mut World) {
+        let leaderboard = endless::Leaderboard::load();
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, "Endless Run");
+        menu.set_close_button(world, "Back", |_, _| NextState::ChallengesMenu);
+        menu.add_button(world, "Start Run", |_, _| NextState::Play);
+        menu.add_separator(world);
+        if leaderboard.entries.is_empty() {
+            menu.add_paragraph(world, "No runs finished yet");
+        } else {
+            menu.add_paragraph(world, "Best runs:");
+        }
+        for (index, entry) in leaderboard
+            .entries
+            .iter()
+            .take(NUM_SHOWN_ENTRIES)
+            .enumerate()
+        {
+            menu.add_paragraph(world, &format!("{}. {}", index + 1, entry.summary()));
+        }
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            NextState::ChallengesMenu.as_trans()
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for EndlessMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened endless run menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+
+        log::info!("Closed endless run menu.");
+    }
+
+    fn on_pause(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.create_menu(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    Play,
+    ChallengesMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::Play => Trans::Push(Box::new(Game::endless_run())),
+            Self::ChallengesMenu => Trans::Switch(Box::new(ChallengesMenu::new())),
+        }
+    }
+}
@@ -0,0 +1,858 @@
+mod online;
+mod single_player;
+
+use amethyst::{core::ecs, core::ecs::Join, core::timing::Time, input, prelude::*};
+use contracts::*;
+use open_ttt_lib as ttt;
+
+use crate::components;
+use crate::daily_challenge;
+use crate::environments::*;
+use crate::events;
+use crate::network;
+use crate::resources;
+use crate::ui;
+use crate::variants;
+
+use self::online::{send_to_remote_players, OnlineGame, OnlineRole};
+use self::single_player::{SinglePlayerGame, SinglePlayerMode};
+use super::MainMenu;
+
+/// Holds the options for the game state.
+pub enum GameStateOptions {
+    /// Play a single player game with the provided difficulty and player mark.
+    SinglePlayer(ttt::ai::Difficulty, components::Player),
+
+    /// Play a multiplayer game.
+    Multiplayer,
+
+    /// Play against a player on another computer, with the provided player
+    /// mark being played on this computer.
+    Online(components::Player),
+}
+
+impl Default for GameStateOptions {
+    fn default() -> Self {
+        Self::SinglePlayer(ttt::ai::Difficulty::Medium, components::Player::X)
+    }
+}
+
+// What is being played, along with the state only that kind of game needs.
+enum Mode {
+    SinglePlayer(SinglePlayerGame),
+    Multiplayer,
+    Online(OnlineGame),
+}
+
+impl Mode {
+    // Creates a single-player game of the provided kind.
+    fn single_player(
+        difficulty: ttt::ai::Difficulty,
+        player: components::Player,
+        kind: SinglePlayerMode,
+    ) -> Self {
+        Self::SinglePlayer(SinglePlayerGame {
+            difficulty,
+            player,
+            kind,
+        })
+    }
+}
+
+impl From<GameStateOptions> for Mode {
+    fn from(options: GameStateOptions) -> Self {
+        match options {
+            GameStateOptions::SinglePlayer(difficulty, player) => Self::single_player(
+                difficulty,
+                player,
+                SinglePlayerMode::Standard(variants::Handicaps::default()),
+            ),
+            GameStateOptions::Multiplayer => Self::Multiplayer,
+            GameStateOptions::Online(player) => Self::Online(OnlineGame::new(
+                player,
+                OnlineRole::Host {
+                    host: None,
+                    announcer: None,
+                },
+            )),
+        }
+    }
+}
+
+/// Responsible for managing single-player and multiplayer games.
+pub struct Game {
+    mode: Mode,
+    variant: variants::Variant,
+    // The game to start from instead of an empty board, if any.
+    starting_game: Option<variants::Game>,
+    // Indicates the player can take over the computer's opening mark.
+    is_swap_offered: bool,
+    // Indicates the opening mark changed hands in the current game.
+    is_opening_swapped: bool,
+    // Entities the game state owns that need deleted when the state exits.
+    owned_entities: Vec<ecs::Entity>,
+    game_controls: Option<ui::GameControls<Self, NextState>>,
+}
+
+impl<'a, 'b> Game {
+    /// Creates a new game of the provided variant using the given the options.
+    pub fn new(options: GameStateOptions, variant: variants::Variant) -> Self {
+        Self::with_mode(options.into(), variant)
+    }
+
+    // Creates a new game of the variant in the provided mode.
+    fn with_mode(mode: Mode, variant: variants::Variant) -> Self {
+        Self {
+            mode,
+            variant,
+            starting_game: None,
+            is_swap_offered: false,
+            is_opening_swapped: false,
+            owned_entities: Vec::new(),
+            game_controls: None,
+        }
+    }
+
+    /// Creates a game that starts from the provided game's position rather
+    /// than an empty board, such as a position set up in the position editor.
+    ///
+    /// Playing again returns to the starting position. Leaving the game pops
+    /// this state, so it must be pushed on top of the state to return to.
+    pub fn from_position(options: GameStateOptions, game: variants::Game) -> Self {
+        let variant = game.variant();
+        Self {
+            starting_game: Some(game),
+            ..Self::new(options, variant)
+        }
+    }
+
+    // Gets the handicaps used in the current game, which are none outside of
+    // standard single-player games.
+    fn handicaps(&self) -> variants::Handicaps {
+        match &self.mode {
+            Mode::SinglePlayer(SinglePlayerGame {
+                kind: SinglePlayerMode::Standard(handicaps),
+                ..
+            }) => *handicaps,
+            _ => variants::Handicaps::default(),
+        }
+    }
+
+    // Gets the daily challenge being played, if any.
+    fn challenge(&self) -> Option<&daily_challenge::DailyChallenge> {
+        match &self.mode {
+            Mode::SinglePlayer(SinglePlayerGame {
+                kind: SinglePlayerMode::DailyChallenge(challenge),
+                ..
+            }) => Some(challenge),
+            _ => None,
+        }
+    }
+
+    // Gets the online game being played, if any.
+    fn online(&self) -> Option<&OnlineGame> {
+        match &self.mode {
+            Mode::Online(online) => Some(online),
+            _ => None,
+        }
+    }
+
+    // Gets the online game being played, if any, so it can be changed.
+    fn online_mut(&mut self) -> Option<&mut OnlineGame> {
+        match &mut self.mode {
+            Mode::Online(online) => Some(online),
+            _ => None,
+        }
+    }
+
+    // Indicates the online game was joined, so the host starts the next games.
+    fn is_joined(&self) -> bool {
+        matches!(self.online(), Some(online) if online.is_joined())
+    }
+
+    // Gets the player the game server said ran out of time in the current
+    // game, if any.
+    fn timed_out_player(&self) -> Option<components::Player> {
+        self.online().and_then(|online| online.timed_out_player)
+    }
+
+    // Gets the mark played using this computer's keyboard and mouse, or
+    // `None` if both marks are.
+    fn local_player(&self) -> Option<components::Player> {
+        match &self.mode {
+            Mode::SinglePlayer(game) => Some(game.player),
+            Mode::Multiplayer => None,
+            Mode::Online(online) => Some(online.player),
+        }
+    }
+
+    // Adds the game's players to the world. Online games that are waiting
+    // for the other player add them once the other player connects.
+    fn create_players(&mut self, world: &mut World) {
+        match self.mode {
+            Mode::SinglePlayer(_) => self.create_single_player_players(world),
+            Mode::Multiplayer => {
+                log::info!("Started {} multiplayer game.", self.variant.name());
+                self.create_local_player(world, components::Player::X);
+                self.create_local_player(world, components::Player::O);
+            }
+            Mode::Online(_) => self.create_online_players(world),
+        }
+    }
+
+    // Adds a local player to the world.
+    fn create_local_player(&mut self, world: &mut World, player: components::Player) {
+        let player_entity = world
+            .create_entity()
+            .with(player)
+            .with(components::LocalPlayer)
+            .build();
+
+        self.owned_entities.push(player_entity);
+    }
+
+    // Adds a player on another computer to the world.
+    fn create_remote_player(
+        &mut self,
+        world: &mut World,
+        player: components::Player,
+        connection: network::Connection,
+    ) {
+        let remote_player_entity = world
+            .create_entity()
+            .with(player)
+            .with(components::RemotePlayer::new(connection))
+            .build();
+
+        self.owned_entities.push(remote_player_entity);
+    }
+
+    fn game_state_extra_information(&self) -> Vec<String> {
+        match &self.mode {
+            Mode::SinglePlayer(game) => game.status().unwrap_or_else(|| {
+                let mut extra_information = self.starting_information();
+                extra_information.push("Single-player".to_string());
+                extra_information.push(variants::difficulty_name(game.difficulty));
+                extra_information.extend(self.handicaps().descriptions());
+                extra_information
+            }),
+            Mode::Multiplayer => {
+                let mut extra_information = self.starting_information();
+                extra_information.push(String::from("Multiplayer"));
+                extra_information
+            }
+            Mode::Online(online) => {
+                let mut extra_information = self.starting_information();
+                extra_information.extend(online.status());
+                extra_information
+            }
+        }
+    }
+
+    // Gets the status for the variant and what the game started from, which
+    // is shown in modes where it is not taken up by the opponent.
+    fn starting_information(&self) -> Vec<String> {
+        let mut extra_information = Vec::new();
+        if self.variant != variants::Variant::Classic {
+            extra_information.push(self.variant.name().to_string());
+        }
+        if let Some(challenge) = self.challenge() {
+            extra_information.push(format!("Daily challenge {}", challenge.date));
+        } else if self.starting_game.is_some() {
+            extra_information.push("Set up position".to_string());
+        }
+        extra_information
+    }
+
+    // Creates the game controls, replacing any existing controls.
+    //
+    // The controls are created again to show or hide the button for taking
+    // over the computer's opening mark.
+    fn create_game_controls(&mut self, world: &mut World) {
+        if let Some(mut game_controls) = self.game_controls.take() {
+            game_controls.delete(world);
+        }
+
+        let mut game_controls = ui::GameControls::new();
+        game_controls.set_menu_button(world, Self::on_leave);
+        if self.is_swap_offered {
+            game_controls.add_side_button(world, "Swap", Self::on_swap);
+        }
+        game_controls.set_status(world, self.game_state_extra_information());
+        self.game_controls = Some(game_controls);
+    }
+
+    // Gets the state to go to when the player leaves the game.
+    //
+    // Games other than standard single-player games and games against a bot
+    // are pushed on top of the menu they were started from, which they
+    // return to.
+    fn on_leave(&mut self, _world: &mut World) -> NextState {
+        let is_pushed = match &self.mode {
+            Mode::SinglePlayer(game) => game.is_pushed(),
+            Mode::Multiplayer | Mode::Online(_) => false,
+        };
+        if is_pushed || self.starting_game.is_some() {
+            NextState::Previous
+        } else {
+            NextState::MainMenu
+        }
+    }
+
+    // Indicates if it is the turn of a player using this computer's keyboard and mouse.
+    fn is_local_players_turn(&self, game_logic: &resources::GameLogic) -> bool {
+        match self.local_player() {
+            Some(player) => game_logic.is_players_move(&player),
+            None => !game_logic.game.state().is_game_over(),
+        }
+    }
+
+    // Clears the picked number and selected mark, then shows the number picker
+    // if a local player needs to pick a number to place.
+    fn reset_move_selection(&mut self, world: &mut World) {
+        world.write_resource::<resources::NumberPicker>().selected = None;
+        world.write_resource::<resources::SelectedMark>().position = None;
+
+        let numbers = {
+            let game_logic = world.read_resource::<resources::GameLogic>();
+            match &game_logic.game {
+                variants::Game::Numerical(game) if self.is_local_players_turn(&game_logic) => {
+                    Some(game.available_numbers())
+                }
+                _ => None,
+            }
+        };
+
+        if let Some(game_controls) = self.game_controls.as_mut() {
+            match numbers {
+                Some(numbers) => game_controls.show_number_picker(world, &numbers),
+                None => game_controls.hide_number_picker(world),
+            }
+        }
+    }
+
+    // Gets the outcome of the finished game from the local player's perspective.
+    fn outcome_affinity(&self, state: &ttt::game::State) -> OutcomeAffinity {
+        match self.local_player() {
+            Some(player) => outcome_affinity(state, player),
+            None => OutcomeAffinity::Neutral,
+        }
+    }
+
+    // Deletes all owned entities from the world.
+    fn delete_owned_entities(&mut self, world: &mut World) {
+        world
+            .delete_entities(self.owned_entities.as_slice())
+            .expect("Unable to game state entities.");
+
+        self.owned_entities.clear();
+    }
+
+    // Shows the next environment, which for the daily challenge is the same
+    // every attempt.
+    fn show_game_environment(&self, world: &mut World) {
+        if let Some(challenge) = self.challenge() {
+            set_environments_seed(world, Some(challenge.environment_seed));
+        }
+        show_next_environment(world);
+    }
+
+    // Lets the AI players learn from the finished game.
+    fn teach_ai_players(&self, world: &World) {
+        let game_logic = world.read_resource::<resources::GameLogic>();
+        let ai_players = world.read_storage::<components::AiPlayer>();
+        for ai_player in ai_players.join() {
+            ai_player.ai_opponent.learn(&game_logic.game);
+        }
+    }
+
+    // Updates the game based on the player event.
+    fn handle_player_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        player_event: &events::PlayerEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // The game server ended the game on time.
+        if self.timed_out_player().is_some() {
+            return Trans::None;
+        }
+
+        let (player, game_move, mark) = requested_move(player_event, self.variant);
+
+        if let Some(state) = do_move(data.world, player, &game_move) {
+            // Keep the other computer's game the same.
+            if let Some(online) = self.online() {
+                if player == online.player {
+                    send_to_remote_players(
+                        data.world,
+                        &network::Message::Move {
+                            player,
+                            game_move: network::NetworkMove::from_move(&game_move),
+                        },
+                    );
+                }
+            }
+
+            show_move(
+                data.world,
+                &game_move,
+                &mark,
+                &state,
+                self.outcome_affinity(&state),
+            );
+
+            if state.is_game_over() {
+                self.teach_ai_players(data.world);
+                let is_status_changed = match &mut self.mode {
+                    Mode::SinglePlayer(game) => game.record_result(&state),
+                    Mode::Multiplayer | Mode::Online(_) => false,
+                };
+                if is_status_changed {
+                    self.create_game_controls(data.world);
+                }
+            } else {
+                self.check_swap_rule(data.world, player);
+            }
+
+            // Show the game over button if the game is complete.
+            let game_over_text = match &self.mode {
+                Mode::SinglePlayer(game) => game.game_over_text(),
+                Mode::Multiplayer | Mode::Online(_) => "Play Again?",
+            };
+            let is_joined = self.is_joined();
+            if let Some(game_controls) = self.game_controls.as_mut() {
+                if state.is_game_over() && !is_joined {
+                    game_controls.show_game_over_button(
+                        data.world,
+                        game_over_text,
+                        Self::on_start_next_game,
+                    );
+                }
+            }
+
+            self.reset_move_selection(data.world);
+        } else if let Some(online) = self.online() {
+            // The other computer allowed the move, so the games differ.
+            if player != online.player {
+                log::warn!("Rejected move {:?} from the other player.", game_move);
+                let mut remote_players = data.world.write_storage::<components::RemotePlayer>();
+                for remote_player in (&mut remote_players).join() {
+                    remote_player.connection.close("The games no longer match.");
+                }
+            }
+        }
+
+        Trans::None
+    }
+
+    fn is_start_next_game_key_down(&self, window_event: &events::WindowEvent) -> bool {
+        // TODO: NumpadEnter does not seem to be working on Debian, try on Windows.
+        input::is_key_down(window_event, input::VirtualKeyCode::Return)
+            || input::is_key_down(window_event, input::VirtualKeyCode::NumpadEnter)
+            || input::is_key_down(window_event, input::VirtualKeyCode::Space)
+    }
+
+    fn can_start_next_game(&self, world: &World) -> bool {
+        let game_logic = world.read_resource::<resources::GameLogic>();
+        // The host of a joined game starts the next game.
+        (game_logic.game.state().is_game_over() || self.timed_out_player().is_some())
+            && !self.is_joined()
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            self.on_leave(data.world).as_trans()
+        } else if self.is_start_next_game_key_down(window_event)
+            && self.can_start_next_game(data.world)
+        {
+            self.on_start_next_game(data.world).as_trans()
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(game_controls) = self.game_controls.as_mut() {
+            if let Some(callback) = game_controls.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+
+    // Called when the user wishes to start the next game.
+    fn on_start_next_game(&mut self, world: &mut World) -> NextState {
+        if let Some(online) = self.online_mut() {
+            online.timed_out_player = None;
+        }
+
+        // Tell the game logic to start the next game.
+        {
+            let mut game_logic = world.fetch_mut::<resources::GameLogic>();
+            game_logic.last_move_time = world.fetch::<Time>().absolute_time();
+            // Games started from a position go back to that position.
+            if let Some(starting_game) = &self.starting_game {
+                game_logic.game = starting_game.clone();
+            } else {
+                game_logic.game.start_next_game();
+                // The swapped game counts the taken over mark as the first
+                // move, so it is started again to keep alternating who opens.
+                if self.is_opening_swapped {
+                    game_logic.game.start_next_game();
+                }
+            }
+        }
+
+        // The joined player follows the host into the next game.
+        if matches!(self.online(), Some(online) if !online.is_joined()) {
+            send_to_remote_players(world, &network::Message::NextGame);
+        }
+
+        self.update_ai_difficulty(world);
+        self.start_handicaps(world);
+        self.show_game_environment(world);
+        self.record_challenge_attempt();
+
+        // Hide the game over button, along with the swap button if the last
+        // game ended while it was offered.
+        self.create_game_controls(world);
+        self.reset_move_selection(world);
+
+        NextState::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for Game {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        // Create the game's players based on the game's mode.
+        self.create_players(data.world);
+
+        // New game data is created ensuring any leftover in progress games are
+        // destroyed.
+        let game_logic = match &self.starting_game {
+            Some(starting_game) => resources::GameLogic::with_game(starting_game.clone()),
+            None => resources::GameLogic::new(self.variant),
+        };
+        insert_game_resources(data.world, game_logic);
+        self.start_handicaps(data.world);
+
+        // Show the next environment. Note, this has to occur after replacing the game
+        // resource as this is used by the created environment.
+        self.show_game_environment(data.world);
+        self.record_challenge_attempt();
+
+        // Create the UI elements.
+        self.create_game_controls(data.world);
+        self.reset_move_selection(data.world);
+    }
+
+    #[post(self.owned_entities.is_empty())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        // Remove entities we created from the world, which closes the
+        // connection to the other player.
+        self.delete_owned_entities(data.world);
+        if let Some(online) = self.online_mut() {
+            online.role.disconnect();
+        }
+
+        // Delete the game controls.
+        if let Some(mut game_controls) = self.game_controls.take() {
+            game_controls.delete(data.world);
+        }
+
+        delete_current_environment(data.world);
+        // Go back to showing random environments after the daily challenge.
+        if self.challenge().is_some() {
+            set_environments_seed(data.world, None);
+        }
+
+        // Make a log entry of the game stopping.
+        match &self.mode {
+            Mode::SinglePlayer(game) => {
+                game.leave();
+                log::info!(
+                    "Ended {:?} difficulty single-player game for player {:?}.",
+                    game.difficulty,
+                    game.player
+                );
+            }
+            Mode::Multiplayer => {
+                log::info!("Ended multiplayer game.");
+            }
+            Mode::Online(online) => {
+                log::info!("Ended online game for player {:?}.", online.player);
+            }
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Player(player_event) => {
+                self.handle_player_event(data, &player_event)
+            }
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+        self.update_online_game(data.world);
+
+        Trans::None
+    }
+}
+
+/// Inserts the resources for playing the provided game, replacing those of
+/// any previous game.
+///
+/// This includes a grid for each board the game's variant is played on.
+pub(super) fn insert_game_resources(world: &mut World, game_logic: resources::GameLogic) {
+    let variant = game_logic.game.variant();
+    world.insert(game_logic);
+    world.insert(resources::NumberPicker::default());
+    world.insert(resources::SelectedMark::default());
+    world.insert(resources::BoardPicker::default());
+
+    let grids = {
+        let play_area = world.read_resource::<resources::Grid>();
+        resources::Grids::new(&play_area, variant.num_boards(), variant.board_size())
+    };
+    world.insert(grids);
+}
+
+/// Shows the next environment along with any marks already on the board.
+///
+/// Games started from a position, such as puzzles, have marks before either
+/// player moves.
+pub(super) fn show_next_environment(world: &mut World) {
+    let marks: Vec<components::Mark> = {
+        let game_logic = world.read_resource::<resources::GameLogic>();
+        let variant = game_logic.game.variant();
+        game_logic
+            .game
+            .board()
+            .iter()
+            .filter_map(|(position, owner)| {
+                let player = match owner {
+                    ttt::board::Owner::PlayerX => components::Player::X,
+                    ttt::board::Owner::PlayerO => components::Player::O,
+                    ttt::board::Owner::None => return None,
+                };
+                Some(components::Mark {
+                    position,
+                    owner: player,
+                    symbol: mark_symbol(variant, player),
+                })
+            })
+            .collect()
+    };
+
+    let environments = { world.write_resource::<Option<Environments>>().take() };
+    if let Some(mut environments) = environments {
+        environments.show_random(world);
+        for mark in &marks {
+            environments.add_mark(world, mark);
+        }
+        // Be sure to return the environment when done.
+        world
+            .write_resource::<Option<Environments>>()
+            .replace(environments);
+    }
+}
+
+/// Deletes the environment being shown.
+pub(super) fn delete_current_environment(world: &mut World) {
+    let environments = { world.write_resource::<Option<Environments>>().take() };
+    if let Some(mut environments) = environments {
+        environments.delete_current(world);
+        world
+            .write_resource::<Option<Environments>>()
+            .replace(environments);
+    }
+}
+
+// Sets the seed used to pick the environments shown, see
+// `Environments::set_seed`.
+fn set_environments_seed(world: &mut World, seed: Option<u64>) {
+    let environments = { world.write_resource::<Option<Environments>>().take() };
+    if let Some(mut environments) = environments {
+        environments.set_seed(world, seed);
+        world
+            .write_resource::<Option<Environments>>()
+            .replace(environments);
+    }
+}
+
+/// Gets the player, move, and mark to add for the player event.
+pub(super) fn requested_move(
+    player_event: &events::PlayerEvent,
+    variant: variants::Variant,
+) -> (components::Player, variants::Move, components::Mark) {
+    match *player_event {
+        events::PlayerEvent::RequestMark(player, position) => (
+            player,
+            variants::Move::Mark(position),
+            components::Mark {
+                position,
+                owner: player,
+                symbol: mark_symbol(variant, player),
+            },
+        ),
+        events::PlayerEvent::RequestNumber(player, position, number) => (
+            player,
+            variants::Move::Number(position, number),
+            components::Mark {
+                position,
+                owner: player,
+                symbol: components::Symbol::Number(number),
+            },
+        ),
+        events::PlayerEvent::RequestMove(player, from, to) => (
+            player,
+            variants::Move::Slide(from, to),
+            components::Mark {
+                position: to,
+                owner: player,
+                symbol: components::Symbol::from(player),
+            },
+        ),
+        events::PlayerEvent::RequestSymbol(player, position, symbol) => (
+            player,
+            variants::Move::PlaceSymbol(position, symbol),
+            components::Mark {
+                position,
+                owner: player,
+                // The symbol placed does not depend on who placed it.
+                symbol: match symbol {
+                    ttt::board::Owner::PlayerO => components::Symbol::O,
+                    _ => components::Symbol::X,
+                },
+            },
+        ),
+    }
+}
+
+/// Makes the player's move if it is their turn and the move is valid.
+///
+/// The new state of the game is returned if the move was made.
+pub(super) fn do_move(
+    world: &mut World,
+    player: components::Player,
+    game_move: &variants::Move,
+) -> Option<ttt::game::State> {
+    let mut game_logic = world.fetch_mut::<resources::GameLogic>();
+
+    // The move is only made if it is the player's turn and the move selected is valid.
+    let state = game_logic.try_move(&player, game_move)?;
+
+    // Let systems know the time of this update.
+    game_logic.last_move_time = world.fetch::<Time>().absolute_time();
+    log::debug!("player: {:?} made move {:?}", player, game_move);
+    log::debug!("game state: {:?}", state);
+
+    Some(state)
+}
+
+/// Shows the move that was made in the current environment.
+///
+/// When the move ends the game, the outcome is shown as well.
+pub(super) fn show_move(
+    world: &mut World,
+    game_move: &variants::Move,
+    mark: &components::Mark,
+    state: &ttt::game::State,
+    outcome: OutcomeAffinity,
+) {
+    let (board_index, is_board_dead) = {
+        let game_logic = world.read_resource::<resources::GameLogic>();
+        let board_index = game_logic.game.board_index(mark.position);
+        let is_board_dead = game_logic.game.dead_board_line(board_index).is_some();
+        (board_index, is_board_dead)
+    };
+
+    let environments = { world.write_resource::<Option<Environments>>().take() };
+    if let Some(mut environments) = environments {
+        if let variants::Move::Slide(from, _) = *game_move {
+            environments.remove_mark(world, from);
+        }
+        environments.add_mark(world, mark);
+        if is_board_dead {
+            environments.board_dead(world, board_index);
+        }
+        if state.is_game_over() {
+            environments.game_over(world, outcome);
+        }
+        // Be sure to return the environment when done.
+        world
+            .write_resource::<Option<Environments>>()
+            .replace(environments);
+    }
+}
+
+/// Gets the outcome of the finished game from the provided player's perspective.
+///
+/// The state reports which player won, so in variants with roles the winner
+/// is whichever player had the winning role.
+pub(super) fn outcome_affinity(
+    state: &ttt::game::State,
+    player: components::Player,
+) -> OutcomeAffinity {
+    match daily_challenge::ChallengeResult::from_state(state, player) {
+        Some(daily_challenge::ChallengeResult::Win) => OutcomeAffinity::Win,
+        Some(daily_challenge::ChallengeResult::Loss) => OutcomeAffinity::Loss,
+        Some(daily_challenge::ChallengeResult::Draw) => OutcomeAffinity::CatsGame,
+        None => OutcomeAffinity::Neutral,
+    }
+}
+
+// Gets the symbol drawn for the player's marks.
+fn mark_symbol(variant: variants::Variant, player: components::Player) -> components::Symbol {
+    match variant {
+        // Both players place X marks in notakto.
+        variants::Variant::Notakto => components::Symbol::X,
+        _ => components::Symbol::from(player),
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    MainMenu,
+    Previous,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::MainMenu => Trans::Switch(Box::new(MainMenu::new())),
+            Self::Previous => Trans::Pop,
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! Contains the online games, played against a player on another computer.
+
+use amethyst::{core::ecs::Join, prelude::*};
+
+use crate::components;
+use crate::network;
+use crate::settings;
+use crate::variants;
+
+use super::{Game, GameStateOptions, Mode};
+
+impl Game {
+    /// Creates games of the variant for a player on another computer to join.
+    ///
+    /// The host plays X and waits for the other player on
+    /// `network::DEFAULT_PORT`, announcing the game to the local network.
+    pub fn host(variant: variants::Variant) -> Self {
+        Self::new(GameStateOptions::Online(components::Player::X), variant)
+    }
+
+    /// Creates games of the variant hosted on another computer, which has
+    /// been joined with the provided connection.
+    ///
+    /// The player plays the provided mark, and the host starts the next games.
+    pub fn join(
+        variant: variants::Variant,
+        player: components::Player,
+        connection: network::Connection,
+    ) -> Self {
+        let role = OnlineRole::Joined {
+            connection: Some(connection),
+        };
+        Self::with_mode(Mode::Online(OnlineGame::new(player, role)), variant)
+    }
+
+    /// Creates games of the variant in a room created on a game server with
+    /// the provided connection.
+    ///
+    /// The player who created the room plays X and starts the next games once
+    /// another player joins the room with its code.
+    pub fn room(variant: variants::Variant, connection: network::Connection, code: String) -> Self {
+        let role = OnlineRole::Room {
+            code,
+            connection: Some(connection),
+        };
+        let online_game = OnlineGame::new(components::Player::X, role);
+        Self::with_mode(Mode::Online(online_game), variant)
+    }
+
+    // Adds the players of an online game to the world, or starts waiting for
+    // the other player to connect.
+    pub(super) fn create_online_players(&mut self, world: &mut World) {
+        let variant = self.variant;
+        let online = match &mut self.mode {
+            Mode::Online(online) => online,
+            _ => return,
+        };
+
+        let player = online.player;
+        let connection = match &mut online.role {
+            OnlineRole::Joined { connection } => connection.take(),
+            // The players are created once the other player joins.
+            OnlineRole::Room { .. } => {
+                log::info!("Created {} game server room.", variant.name());
+                online.status = Some("Waiting for a player to join".to_string());
+                None
+            }
+            OnlineRole::Host { host, announcer } => {
+                match network::Host::bind(network::DEFAULT_PORT) {
+                    Ok(bound_host) => {
+                        log::info!("Hosting {} online game.", variant.name());
+                        online.status = Some(format!(
+                            "Waiting for a player on port {}",
+                            bound_host.port()
+                        ));
+                        *announcer = start_announcing(world, variant, bound_host.port());
+                        *host = Some(bound_host);
+                    }
+                    Err(error) => {
+                        log::error!("Unable to host a game: {}", error);
+                        online.status = Some("Unable to host the game.".to_string());
+                    }
+                }
+                None
+            }
+        };
+        if let Some(connection) = connection {
+            log::info!(
+                "Started joined {} online game for player {:?}.",
+                variant.name(),
+                player
+            );
+            self.create_local_player(world, player);
+            self.create_remote_player(world, player.opposite_player(), connection);
+        }
+    }
+
+    // Waits for the other player to connect, and keeps the online game in
+    // step with the other computer.
+    pub(super) fn update_online_game(&mut self, world: &mut World) {
+        let variant = self.variant;
+        let online = match self.online_mut() {
+            Some(online) => online,
+            None => return,
+        };
+
+        // The other player's connection once they have joined, or why they
+        // cannot.
+        let joined = match &mut online.role {
+            OnlineRole::Host { host, announcer } => {
+                let accepted = match host.as_ref() {
+                    Some(host) => host.accept(variant, online.player.opposite_player()),
+                    None => Ok(None),
+                };
+                let joined = match accepted {
+                    Ok(Some(connection)) => {
+                        log::info!("Started {} online game as the host.", variant.name());
+                        *host = None;
+                        Some(Ok((online.player, connection)))
+                    }
+                    Ok(None) => None,
+                    Err(error) => {
+                        log::error!("Unable to wait for a player: {}", error);
+                        *host = None;
+                        Some(Err("Unable to host the game.".to_string()))
+                    }
+                };
+                if let Some(announcer) = announcer.as_mut() {
+                    announcer.set_open(host.is_some());
+                    announcer.update();
+                }
+                joined
+            }
+            OnlineRole::Room { connection, .. } => wait_for_room(connection, variant),
+            OnlineRole::Joined { .. } => None,
+        };
+        match joined {
+            Some(Ok((player, connection))) => self.start_online_game(world, player, connection),
+            Some(Err(reason)) => {
+                online.stop(reason);
+                self.create_game_controls(world);
+            }
+            None => (),
+        }
+
+        let (is_next_game_requested, timed_out_player, disconnect_reason) = {
+            let mut remote_players = world.write_storage::<components::RemotePlayer>();
+            let mut is_next_game_requested = false;
+            let mut timed_out_player = None;
+            let mut disconnect_reason = None;
+            for remote_player in (&mut remote_players).join() {
+                is_next_game_requested |= remote_player.is_next_game_requested;
+                remote_player.is_next_game_requested = false;
+                timed_out_player = timed_out_player.or(remote_player.timed_out_player.take());
+                if let Some(reason) = remote_player.connection.disconnect_reason() {
+                    disconnect_reason = Some(reason.to_string());
+                }
+            }
+            (is_next_game_requested, timed_out_player, disconnect_reason)
+        };
+
+        if is_next_game_requested {
+            self.on_start_next_game(world);
+        }
+        if let Some(player) = timed_out_player {
+            log::info!("Player {:?} ran out of time.", player);
+            if let Some(online) = self.online_mut() {
+                online.timed_out_player = Some(player);
+            }
+            // Show who ran out of time, and the game over button to the
+            // player who starts the next game.
+            self.create_game_controls(world);
+            let is_joined = self.is_joined();
+            if let Some(game_controls) = self.game_controls.as_mut() {
+                if !is_joined {
+                    game_controls.show_game_over_button(
+                        world,
+                        "Play Again?",
+                        Self::on_start_next_game,
+                    );
+                }
+            }
+            self.reset_move_selection(world);
+        }
+        if let Some(reason) = disconnect_reason {
+            if let Some(online) = self.online_mut().filter(|online| online.status.is_none()) {
+                log::info!("The online game ended: {}", reason);
+                online.stop(reason);
+                self.create_game_controls(world);
+            }
+        }
+    }
+
+    // Starts the online game once the other player has connected, with the
+    // provided mark played on this computer.
+    fn start_online_game(
+        &mut self,
+        world: &mut World,
+        player: components::Player,
+        connection: network::Connection,
+    ) {
+        if let Some(online) = self.online_mut() {
+            online.player = player;
+            online.status = None;
+        }
+        self.create_local_player(world, player);
+        self.create_remote_player(world, player.opposite_player(), connection);
+        self.create_game_controls(world);
+        self.reset_move_selection(world);
+    }
+}
+
+// A game against a player on another computer.
+pub(super) struct OnlineGame {
+    // The mark played on this computer.
+    pub(super) player: components::Player,
+    pub(super) role: OnlineRole,
+    // The player the game server said ran out of time in the current game.
+    pub(super) timed_out_player: Option<components::Player>,
+    // Describes the game's connection when it is not being played, such as
+    // while waiting for a player or after they leave.
+    pub(super) status: Option<String>,
+}
+
+impl OnlineGame {
+    pub(super) fn new(player: components::Player, role: OnlineRole) -> Self {
+        Self {
+            player,
+            role,
+            timed_out_player: None,
+            status: None,
+        }
+    }
+
+    // Indicates the game was joined, so the host starts the next games.
+    pub(super) fn is_joined(&self) -> bool {
+        matches!(self.role, OnlineRole::Joined { .. })
+    }
+
+    // Stops waiting for the other player and announcing the game, showing
+    // the reason the game is not being played.
+    fn stop(&mut self, reason: String) {
+        self.role.disconnect();
+        self.status = Some(reason);
+    }
+
+    // Gets the status shown for the online game, such as who ran out of time.
+    pub(super) fn status(&self) -> Vec<String> {
+        let mut status = vec!["Online".to_string(), format!("You are {}", self.player)];
+        if let OnlineRole::Room { code, .. } = &self.role {
+            status.push(format!("Room {}", code));
+        }
+        if let Some(timed_out_player) = self.timed_out_player {
+            status.push(format!("{} ran out of time", timed_out_player));
+        }
+        status.extend(self.status.clone());
+        status
+    }
+}
+
+// How an online game was started, along with what it needs until both
+// players are connected.
+pub(super) enum OnlineRole {
+    // Hosting the game, waiting for the other player and telling the local
+    // network about the game.
+    Host {
+        host: Option<network::Host>,
+        announcer: Option<network::Announcer>,
+    },
+    // Joined a game hosted on another computer, holding the connection to the
+    // host until the players are created.
+    Joined {
+        connection: Option<network::Connection>,
+    },
+    // Created a room with the code on a game server, holding the connection
+    // to the server until another player joins the room.
+    Room {
+        code: String,
+        connection: Option<network::Connection>,
+    },
+}
+
+impl OnlineRole {
+    // Stops waiting for the other player and announcing the game.
+    pub(super) fn disconnect(&mut self) {
+        match self {
+            Self::Host { host, announcer } => {
+                *host = None;
+                *announcer = None;
+            }
+            Self::Joined { connection } | Self::Room { connection, .. } => *connection = None,
+        }
+    }
+}
+
+// Starts announcing the hosted game of the variant to the local network,
+// logging any problems since the game can still be joined by its address.
+fn start_announcing(
+    world: &World,
+    variant: variants::Variant,
+    port: u16,
+) -> Option<network::Announcer> {
+    let announcement = network::Announcement {
+        name: world
+            .read_resource::<settings::Settings>()
+            .player_name
+            .clone(),
+        variant: variant.name().to_string(),
+        port,
+        is_open: true,
+    };
+    match network::Announcer::new(announcement) {
+        Ok(announcer) => Some(announcer),
+        Err(error) => {
+            log::warn!("Unable to announce the game: {}", error);
+            None
+        }
+    }
+}
+
+// Waits for a player to join the game server room that was created.
+//
+// Once they have, the mark to play and the room's connection are returned,
+// taking the connection along with any messages received after the start. If the room closed instead, the reason is returned.
+fn wait_for_room(
+    room_connection: &mut Option<network::Connection>,
+    variant: variants::Variant,
+) -> Option<Result<(components::Player, network::Connection), String>> {
+    let connection = room_connection.as_mut()?;
+    let mut messages = connection.poll().into_iter();
+    while let Some(message) = messages.next() {
+        match message {
+            network::Message::Start { player, .. } => {
+                log::info!(
+                    "Started {} game server game for player {:?}.",
+                    variant.name(),
+                    player
+                );
+                // Moves that arrived with the start are handled by the game.
+                connection.keep_unread(messages);
+                let connection = room_connection.take().expect("The room is connected.");
+                return Some(Ok((player, connection)));
+            }
+            network::Message::Refused { reason } => connection.close(&reason),
+            message => log::warn!("Ignored unexpected message: {:?}", message),
+        }
+    }
+
+    let reason = connection.disconnect_reason()?.to_string();
+    log::info!("The game server room closed: {}", reason);
+    Some(Err(reason))
+}
+
+// Sends the message to the players on other computers.
+pub(super) fn send_to_remote_players(world: &World, message: &network::Message) {
+    let mut remote_players = world.write_storage::<components::RemotePlayer>();
+    for remote_player in (&mut remote_players).join() {
+        remote_player.connection.send(message);
+    }
+}
@@ -0,0 +1,590 @@
+//! Contains the single-player games, played against the computer.
+
+use amethyst::{core::ecs::Join, core::timing::Time, prelude::*};
+use open_ttt_lib as ttt;
+use std::path;
+
+use crate::adaptive;
+use crate::campaign;
+use crate::components;
+use crate::daily_challenge;
+use crate::endless;
+use crate::engine;
+use crate::environments::*;
+use crate::resources;
+use crate::variants;
+
+use super::{Game, Mode, NextState};
+
+impl Game {
+    /// Creates a game of the provided daily challenge.
+    ///
+    /// Every game started counts as an attempt at the challenge, and the
+    /// results are saved. Like games started from a position, leaving the
+    /// game pops this state.
+    pub fn daily_challenge(challenge: daily_challenge::DailyChallenge) -> Self {
+        let game = challenge.game.clone();
+        let variant = game.variant();
+        let mode = Mode::single_player(
+            challenge.difficulty,
+            challenge.player,
+            SinglePlayerMode::DailyChallenge(challenge),
+        );
+        Self {
+            starting_game: Some(game),
+            ..Self::with_mode(mode, variant)
+        }
+    }
+
+    /// Creates a new endless run of classic games.
+    ///
+    /// The AI gets stronger after every game the player wins or draws, and
+    /// the run ends on the first loss. Leaving the game pops this state and
+    /// ends the run.
+    pub fn endless_run() -> Self {
+        let run = endless::Run::new();
+        let mode = Mode::single_player(
+            run.difficulty(),
+            components::Player::X,
+            SinglePlayerMode::EndlessRun(run),
+        );
+        Self::with_mode(mode, variants::Variant::Classic)
+    }
+
+    /// Creates a game against the campaign opponent at the provided index
+    /// into `campaign::OPPONENTS`.
+    ///
+    /// Winning unlocks the next opponent. Leaving the game pops this state.
+    pub fn campaign(index: usize) -> Self {
+        let opponent = campaign::OPPONENTS[index];
+        let is_beaten = index < campaign::CampaignProgress::load().opponents_beaten;
+        let mode = Mode::single_player(
+            opponent.difficulty,
+            components::Player::X,
+            SinglePlayerMode::Campaign { index, is_beaten },
+        );
+        Self::with_mode(mode, opponent.variant)
+    }
+
+    /// Creates a game of classic against an AI with the provided personality.
+    ///
+    /// Leaving the game pops this state.
+    pub fn personality(personality: engine::Personality) -> Self {
+        let mode = Mode::single_player(
+            personality.difficulty,
+            components::Player::X,
+            SinglePlayerMode::Personality(personality),
+        );
+        Self::with_mode(mode, variants::Variant::Classic)
+    }
+
+    /// Creates a game of classic against the learning AI.
+    ///
+    /// The AI learns from every game played and saves what it learned, so it
+    /// gets better the more it is played. Leaving the game pops this state.
+    pub fn learning_opponent() -> Self {
+        let mode = Mode::single_player(
+            ttt::ai::Difficulty::None,
+            components::Player::X,
+            SinglePlayerMode::LearningOpponent(engine::Matchboxes::load().record),
+        );
+        Self::with_mode(mode, variants::Variant::Classic)
+    }
+
+    /// Creates classic games against an AI whose difficulty follows the
+    /// player's rating.
+    ///
+    /// The rating is updated and saved after every game, and the next game is
+    /// played at the new difficulty. Leaving the game pops this state.
+    pub fn adaptive() -> Self {
+        let rating = adaptive::PlayerRating::load();
+        let mode = Mode::single_player(
+            rating.difficulty(),
+            components::Player::X,
+            SinglePlayerMode::Adaptive(rating),
+        );
+        Self::with_mode(mode, variants::Variant::Classic)
+    }
+
+    /// Creates classic games against a bot running as a separate program.
+    ///
+    /// The bot plays O using the protocol described in `engine::external`.
+    /// If the bot cannot be started, the medium AI is played instead.
+    pub fn external_engine(program: path::PathBuf) -> Self {
+        let mode = Mode::single_player(
+            ttt::ai::Difficulty::Medium,
+            components::Player::X,
+            SinglePlayerMode::ExternalEngine(program),
+        );
+        Self::with_mode(mode, variants::Variant::Classic)
+    }
+
+    /// Plays single-player games with the provided handicaps.
+    ///
+    /// The handicaps are only used against the AI at a chosen difficulty,
+    /// not in the other single-player modes.
+    pub fn with_handicaps(mut self, handicaps: variants::Handicaps) -> Self {
+        if let Mode::SinglePlayer(SinglePlayerGame {
+            kind: SinglePlayerMode::Standard(standard_handicaps),
+            ..
+        }) = &mut self.mode
+        {
+            *standard_handicaps = handicaps;
+        }
+        self
+    }
+
+    // Adds the player using this computer and the computer's player to the
+    // world.
+    pub(super) fn create_single_player_players(&mut self, world: &mut World) {
+        let variant = self.variant;
+        let game = match &self.mode {
+            Mode::SinglePlayer(game) => game,
+            _ => return,
+        };
+        let (difficulty, player) = (game.difficulty, game.player);
+        let program = match &game.kind {
+            SinglePlayerMode::ExternalEngine(program) => Some(program.clone()),
+            _ => None,
+        };
+        log::info!(
+            "Started {:?} difficulty {} single-player game for player {:?}.",
+            difficulty,
+            variant.name(),
+            player
+        );
+        self.create_local_player(world, player);
+        match program {
+            Some(program) => {
+                self.create_external_player(world, player.opposite_player(), difficulty, &program)
+            }
+            None => self.create_ai_player(world, player.opposite_player(), difficulty),
+        }
+    }
+
+    // Adds an AI player to the world.
+    fn create_ai_player(
+        &mut self,
+        world: &mut World,
+        player: components::Player,
+        difficulty: ttt::ai::Difficulty,
+    ) {
+        let blocked = self.handicaps().computer_blocked_squares();
+        let kind = match &self.mode {
+            Mode::SinglePlayer(game) => Some(&game.kind),
+            _ => None,
+        };
+        let ai_player_component = match kind {
+            Some(SinglePlayerMode::Personality(personality)) => components::AiPlayer {
+                think_time: personality.think_time,
+                ..components::AiPlayer::with_engine(Box::new(engine::PersonalityEngine::new(
+                    *personality,
+                )))
+            },
+            Some(SinglePlayerMode::LearningOpponent(_)) => {
+                components::AiPlayer::with_engine(Box::new(engine::MenaceEngine::load()))
+            }
+            _ if blocked.is_empty() => components::AiPlayer::new(self.variant, difficulty),
+            _ => components::AiPlayer::with_opponent(variants::Opponent::Handicap(
+                variants::HandicapOpponent::new(difficulty, blocked),
+            )),
+        };
+        let ai_player_entity = world
+            .create_entity()
+            .with(player)
+            .with(ai_player_component)
+            .build();
+
+        self.owned_entities.push(ai_player_entity);
+    }
+
+    // Adds a player for the bot to the world, or an AI player if the bot
+    // cannot be started.
+    fn create_external_player(
+        &mut self,
+        world: &mut World,
+        player: components::Player,
+        difficulty: ttt::ai::Difficulty,
+        program: &path::Path,
+    ) {
+        match engine::ExternalEngine::launch(program) {
+            Ok(external_engine) => {
+                let external_player_entity = world
+                    .create_entity()
+                    .with(player)
+                    .with(components::ExternalPlayer::new(
+                        external_engine,
+                        engine::for_variant(self.variant, difficulty),
+                    ))
+                    .build();
+                self.owned_entities.push(external_player_entity);
+            }
+            Err(error) => {
+                log::error!("Unable to start bot {}: {}", program.display(), error);
+                if let Mode::SinglePlayer(game) = &mut self.mode {
+                    game.kind = SinglePlayerMode::Standard(variants::Handicaps::default());
+                }
+                self.create_ai_player(world, player, difficulty);
+            }
+        }
+    }
+
+    // Sets up the handicaps for a new game, which includes making the random
+    // opening moves.
+    pub(super) fn start_handicaps(&mut self, world: &mut World) {
+        self.is_swap_offered = false;
+        self.is_opening_swapped = false;
+
+        let computer = match &self.mode {
+            Mode::SinglePlayer(game) => game.player.opposite_player(),
+            Mode::Multiplayer | Mode::Online(_) => return,
+        };
+        let handicaps = self.handicaps();
+        let blocked = handicaps.computer_blocked_squares();
+        let mut game_logic = world.write_resource::<resources::GameLogic>();
+        game_logic.blocked_squares = blocked
+            .iter()
+            .map(|position| (computer, *position))
+            .collect();
+        handicaps.play_random_opening(&mut game_logic.game, &blocked);
+    }
+
+    // Applies the swap rule after the first mark is placed: the computer
+    // decides straight away, while the player is offered a button to swap.
+    //
+    // Once the next move is made, the chance to swap has passed.
+    pub(super) fn check_swap_rule(&mut self, world: &mut World, player: components::Player) {
+        let was_swap_offered = self.is_swap_offered;
+        self.is_swap_offered = false;
+
+        let local_player = match &self.mode {
+            Mode::SinglePlayer(game) => game.player,
+            Mode::Multiplayer | Mode::Online(_) => return,
+        };
+        let can_swap = {
+            let game_logic = world.read_resource::<resources::GameLogic>();
+            let is_first_mark = game_logic
+                .game
+                .board()
+                .iter()
+                .filter(|(_position, owner)| *owner != ttt::board::Owner::None)
+                .count()
+                == 1;
+            self.handicaps().swap_rule && !self.is_opening_swapped && is_first_mark
+        };
+
+        if can_swap && player == local_player {
+            // The computer does not take over marks in squares it is blocked
+            // from.
+            let computer_swaps = {
+                let game_logic = world.read_resource::<resources::GameLogic>();
+                let computer = local_player.opposite_player();
+                match &game_logic.game {
+                    variants::Game::Classic(game) => {
+                        variants::should_swap_opening(game)
+                            && !game_logic.is_blocked(&computer, variants::CENTER)
+                    }
+                    _ => false,
+                }
+            };
+            if computer_swaps {
+                log::info!("The computer took over the opening mark.");
+                self.swap_opening(world);
+            }
+        } else if can_swap {
+            self.is_swap_offered = true;
+        }
+
+        if self.is_swap_offered != was_swap_offered {
+            self.create_game_controls(world);
+        }
+    }
+
+    // Gives the opening mark to the other player and shows the change.
+    fn swap_opening(&mut self, world: &mut World) {
+        let mark = {
+            let mut game_logic = world.write_resource::<resources::GameLogic>();
+            let swapped = match &game_logic.game {
+                variants::Game::Classic(game) => variants::swap_opening(game),
+                _ => None,
+            };
+            let game = match swapped {
+                Some(game) => game,
+                None => return,
+            };
+            let mark = game.board().iter().find_map(|(position, owner)| {
+                let player = match owner {
+                    ttt::board::Owner::PlayerX => components::Player::X,
+                    ttt::board::Owner::PlayerO => components::Player::O,
+                    ttt::board::Owner::None => return None,
+                };
+                Some(components::Mark {
+                    position,
+                    owner: player,
+                    symbol: components::Symbol::from(player),
+                })
+            });
+            game_logic.game = variants::Game::Classic(game);
+            game_logic.last_move_time = world.fetch::<Time>().absolute_time();
+            mark
+        };
+        self.is_opening_swapped = true;
+
+        let environments = { world.write_resource::<Option<Environments>>().take() };
+        if let Some(mut environments) = environments {
+            if let Some(mark) = &mark {
+                environments.remove_mark(world, mark.position);
+                environments.add_mark(world, mark);
+            }
+            world
+                .write_resource::<Option<Environments>>()
+                .replace(environments);
+        }
+    }
+
+    // Called when the player takes over the computer's opening mark.
+    pub(super) fn on_swap(&mut self, world: &mut World) -> NextState {
+        if self.is_swap_offered {
+            log::info!("The player took over the opening mark.");
+            self.is_swap_offered = false;
+            self.swap_opening(world);
+            self.create_game_controls(world);
+        }
+        NextState::None
+    }
+
+    // Records the start of another attempt at the daily challenge, if one is
+    // being played.
+    pub(super) fn record_challenge_attempt(&self) {
+        if let Some(challenge) = self.challenge() {
+            let mut records = daily_challenge::ChallengeRecords::load();
+            records.record_attempt(challenge.date);
+            records.save();
+        }
+    }
+
+    // Sets up the next game of the endless run or adaptive games, whose
+    // difficulty changes between games. A new run is started if the last one
+    // is over.
+    //
+    // The AI players are given the difficulty of the run's new level or the
+    // player's new rating.
+    pub(super) fn update_ai_difficulty(&mut self, world: &mut World) {
+        let game = match &mut self.mode {
+            Mode::SinglePlayer(game) => game,
+            Mode::Multiplayer | Mode::Online(_) => return,
+        };
+        game.difficulty = match &mut game.kind {
+            SinglePlayerMode::EndlessRun(run) => {
+                if run.is_over() {
+                    log::info!("Started a new endless run.");
+                    *run = endless::Run::new();
+                }
+                run.difficulty()
+            }
+            SinglePlayerMode::Adaptive(rating) => rating.difficulty(),
+            _ => return,
+        };
+
+        let difficulty = game.difficulty;
+        let mut ai_players = world.write_storage::<components::AiPlayer>();
+        for ai_player in (&mut ai_players).join() {
+            ai_player.ai_opponent = engine::for_variant(self.variant, difficulty).into();
+            ai_player.pending_move = None;
+        }
+    }
+}
+
+// A game against the computer.
+pub(super) struct SinglePlayerGame {
+    pub(super) difficulty: ttt::ai::Difficulty,
+    // The mark played using this computer's keyboard and mouse.
+    pub(super) player: components::Player,
+    pub(super) kind: SinglePlayerMode,
+}
+
+impl SinglePlayerGame {
+    // Gets the status shown for the kind of game, or `None` if the variant,
+    // difficulty, and handicaps are shown instead.
+    pub(super) fn status(&self) -> Option<Vec<String>> {
+        let status = match &self.kind {
+            // The run's level takes the place of the difficulty.
+            SinglePlayerMode::EndlessRun(run) => vec![
+                "Endless run".to_string(),
+                format!("Level {}", run.level()),
+                format!("Streak {}", run.streak()),
+                format!("Score {}", run.score()),
+            ],
+            // The opponent's name takes the place of the difficulty.
+            SinglePlayerMode::Campaign { index, is_beaten } => {
+                let opponent = &campaign::OPPONENTS[*index];
+                let mut status = vec![
+                    "Campaign".to_string(),
+                    opponent.name.to_string(),
+                    opponent.description(),
+                ];
+                if *is_beaten {
+                    status.push("Beaten".to_string());
+                }
+                status
+            }
+            // The player's rating is shown along with the difficulty it
+            // picked.
+            SinglePlayerMode::Adaptive(rating) => vec![
+                "Adaptive".to_string(),
+                format!("Rating {:.0}", rating.rating),
+                format!(
+                    "AI mistakes {:.0}%",
+                    variants::mistake_probability(self.difficulty) * 100.0
+                ),
+            ],
+            // The bot's program takes the place of the difficulty.
+            SinglePlayerMode::ExternalEngine(program) => {
+                let name = program.file_stem().unwrap_or_else(|| program.as_os_str());
+                vec!["Bot".to_string(), name.to_string_lossy().to_string()]
+            }
+            // The personality's name takes the place of the difficulty.
+            SinglePlayerMode::Personality(personality) => vec![
+                personality.name.to_string(),
+                personality.description.to_string(),
+            ],
+            // The AI's record takes the place of the difficulty.
+            SinglePlayerMode::LearningOpponent(record) => vec![
+                "Learning AI".to_string(),
+                format!("Games played {}", record.games()),
+                record.summary(),
+            ],
+            SinglePlayerMode::Standard(_) | SinglePlayerMode::DailyChallenge(_) => return None,
+        };
+        Some(status)
+    }
+
+    // Gets the text of the button that starts the next game.
+    pub(super) fn game_over_text(&self) -> &'static str {
+        match &self.kind {
+            SinglePlayerMode::EndlessRun(run) if run.is_over() => "New Run?",
+            SinglePlayerMode::EndlessRun(_) => "Next Level",
+            _ => "Play Again?",
+        }
+    }
+
+    // Indicates the game was pushed on top of the menu it was started from,
+    // which it returns to. Only standard games and games against a bot are
+    // not.
+    pub(super) fn is_pushed(&self) -> bool {
+        !matches!(
+            self.kind,
+            SinglePlayerMode::Standard(_) | SinglePlayerMode::ExternalEngine(_)
+        )
+    }
+
+    // Called when the player leaves the game.
+    pub(super) fn leave(&self) {
+        // Leaving during a run ends it like a loss.
+        if let SinglePlayerMode::EndlessRun(run) = &self.kind {
+            if !run.is_over() && run.streak() > 0 {
+                save_run(run);
+            }
+        }
+    }
+
+    // Records the result of the finished game for the kind of game being
+    // played, such as saving the daily challenge's result.
+    //
+    // Returns if the game's status changed, such as the run's new streak and
+    // score, that the opponent was beaten, or the new record or rating.
+    pub(super) fn record_result(&mut self, state: &ttt::game::State) -> bool {
+        let result = match daily_challenge::ChallengeResult::from_state(state, self.player) {
+            Some(result) => result,
+            None => return false,
+        };
+        match &mut self.kind {
+            SinglePlayerMode::DailyChallenge(challenge) => {
+                log::info!(
+                    "Finished daily challenge {} with a {:?}.",
+                    challenge.date,
+                    result
+                );
+                let mut records = daily_challenge::ChallengeRecords::load();
+                records.record_result(challenge.date, result);
+                records.save();
+                false
+            }
+            SinglePlayerMode::EndlessRun(run) => {
+                run.record_result(result);
+                // The run is saved to the leaderboard when it ends.
+                if run.is_over() {
+                    save_run(run);
+                }
+                true
+            }
+            SinglePlayerMode::Campaign { index, is_beaten } => {
+                if result == daily_challenge::ChallengeResult::Win {
+                    let mut progress = campaign::CampaignProgress::load();
+                    if progress.record_win(*index) {
+                        log::info!(
+                            "Beat campaign opponent {}.",
+                            campaign::OPPONENTS[*index].name
+                        );
+                        progress.save();
+                    }
+                    *is_beaten = true;
+                }
+                true
+            }
+            SinglePlayerMode::LearningOpponent(record) => {
+                // The record is kept from the learning AI's side.
+                let ai_player = self.player.opposite_player();
+                if let Some(result) = daily_challenge::ChallengeResult::from_state(state, ai_player)
+                {
+                    record.add(result);
+                }
+                true
+            }
+            SinglePlayerMode::Adaptive(rating) => {
+                rating.record_result(result, self.difficulty);
+                log::info!(
+                    "Changed the player's rating to {:.0} after a {:?}.",
+                    rating.rating,
+                    result
+                );
+                rating.save();
+                true
+            }
+            SinglePlayerMode::Standard(_)
+            | SinglePlayerMode::Personality(_)
+            | SinglePlayerMode::ExternalEngine(_) => false,
+        }
+    }
+}
+
+// The kinds of single-player games, each holding the state only it needs.
+pub(super) enum SinglePlayerMode {
+    // Against the AI at the game's difficulty, with the handicaps.
+    Standard(variants::Handicaps),
+    DailyChallenge(daily_challenge::DailyChallenge),
+    EndlessRun(endless::Run),
+    // Against the campaign opponent at the index into `campaign::OPPONENTS`,
+    // noting if they have been beaten, now or in the past.
+    Campaign { index: usize, is_beaten: bool },
+    Personality(engine::Personality),
+    // Against the learning AI, keeping its record.
+    LearningOpponent(engine::LearningRecord),
+    // Against an AI whose difficulty follows the player's rating.
+    Adaptive(adaptive::PlayerRating),
+    // Against the bot running the program.
+    ExternalEngine(path::PathBuf),
+}
+
+// Adds the finished endless run to the leaderboard if it is one of the best.
+fn save_run(run: &endless::Run) {
+    let mut leaderboard = endless::Leaderboard::load();
+    let entry = endless::LeaderboardEntry::new(run, daily_challenge::ChallengeDate::today());
+    match leaderboard.add(entry) {
+        Some(rank) => {
+            log::info!("Endless run with score {} ranked {}.", run.score(), rank);
+            leaderboard.save();
+        }
+        None => log::info!("Endless run with score {} was not ranked.", run.score()),
+    }
+}
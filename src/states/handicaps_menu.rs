@@ -0,0 +1,188 @@
+use amethyst::{core::ecs, input, prelude::*};
+use contracts::*;
+
+use crate::events;
+use crate::settings;
+use crate::ui;
+use crate::variants;
+
+use super::CustomDifficultyMenu;
+
+/// Lets the player pick the handicaps used in single-player classic games.
+///
+/// Each button changes one handicap and shows its new value. Changes are
+/// saved with the player's settings straight away.
+pub struct HandicapsMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+}
+
+impl<'a, 'b> HandicapsMenu {
+    pub fn new() -> Self {
+        Self { menu: None }
+    }
+
+    // Creates the menu showing the current handicaps, replacing any existing
+    // menu.
+    fn create_menu(&mut self, world: &mut World) {
+        self.delete_menu(world);
+
+        let handicaps = world.read_resource::<settings::Settings>().handicaps;
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, "Handicaps");
+        menu.set_close_button(world, "Back", |_, _| NextState::CustomDifficultyMenu);
+        menu.add_button(
+            world,
+            &format!("Block Center: {}", on_off(handicaps.block_center)),
+            Self::on_block_center_button_click,
+        );
+        menu.add_button(
+            world,
+            &format!("Random Moves: {}", handicaps.random_opening_moves),
+            Self::on_random_moves_button_click,
+        );
+        menu.add_button(
+            world,
+            &format!("Swap Rule: {}", on_off(handicaps.swap_rule)),
+            Self::on_swap_rule_button_click,
+        );
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+    }
+
+    // Changes the handicaps, saves them, and shows their new values.
+    fn update_handicaps(
+        &mut self,
+        world: &mut World,
+        update: impl FnOnce(&mut variants::Handicaps),
+    ) -> NextState {
+        {
+            let mut settings = world.write_resource::<settings::Settings>();
+            update(&mut settings.handicaps);
+            log::info!("Changed handicaps to {:?}.", settings.handicaps);
+            settings.save();
+        }
+        self.create_menu(world);
+        NextState::None
+    }
+
+    fn on_block_center_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        self.update_handicaps(world, |handicaps| {
+            handicaps.block_center = !handicaps.block_center
+        })
+    }
+
+    fn on_random_moves_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        // Cycle through the number of moves, going back to none after the most.
+        self.update_handicaps(world, |handicaps| {
+            handicaps.random_opening_moves =
+                (handicaps.random_opening_moves + 1) % (variants::MAX_RANDOM_OPENING_MOVES + 1)
+        })
+    }
+
+    fn on_swap_rule_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        self.update_handicaps(world, |handicaps| {
+            handicaps.swap_rule = !handicaps.swap_rule
+        })
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            NextState::CustomDifficultyMenu.as_trans()
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for HandicapsMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened handicaps menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+
+        log::info!("Closed handicaps menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Gets the text shown for a handicap that is either used or not.
+fn on_off(is_on: bool) -> &'static str {
+    if is_on {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    CustomDifficultyMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::CustomDifficultyMenu => Trans::Switch(Box::new(CustomDifficultyMenu::new(
+                variants::Variant::Classic,
+            ))),
+        }
+    }
+}
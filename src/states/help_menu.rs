@@ -0,0 +1,126 @@
+use amethyst::{core::ecs, input, prelude::*};
+use contracts::*;
+
+use crate::events;
+use crate::file_io;
+use crate::ui;
+
+use super::{MainMenu, Tutorial};
+
+/// Lets the player replay the tutorial or read the player manual.
+pub struct HelpMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+}
+
+impl<'a, 'b> HelpMenu {
+    pub fn new() -> Self {
+        Self { menu: None }
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            Trans::Switch(Box::new(MainMenu::new()))
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+
+    fn on_player_manual_button_click(&mut self, _world: &mut ecs::World) -> NextState {
+        match file_io::open_player_manual() {
+            Ok(()) => log::info!("Opened player manual in the default browser."),
+            Err(e) => log::error!("Unable to open the player manual. Error details: {}", e),
+        }
+
+        NextState::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for HelpMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened help menu.");
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(data.world, "Help");
+        menu.set_close_button(data.world, "Back", |_, _| NextState::MainMenu);
+        menu.add_button(data.world, "Tutorial", |_, _| NextState::Tutorial);
+        menu.add_button(
+            data.world,
+            "Player Manual",
+            Self::on_player_manual_button_click,
+        );
+        self.menu = Some(menu);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+
+        log::info!("Closed help menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    Tutorial,
+    MainMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::Tutorial => Trans::Switch(Box::new(Tutorial::new())),
+            Self::MainMenu => Trans::Switch(Box::new(MainMenu::new())),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use amethyst::{ecs, input, prelude::*};
+use contracts::*;
+use std::collections::HashMap;
+use std::error::Error;
+use std::net::SocketAddr;
+
+use crate::components::Player;
+use crate::events;
+use crate::network;
+use crate::resources;
+use crate::settings;
+use crate::ui;
+use crate::variants::Variant;
+
+use super::{Game, MultiplayerMenu};
+
+// The most games listed, which leaves room for the button joining by address.
+const MAX_LISTED_GAMES: usize = 3;
+
+// Joining a game, giving the connection, the name of the variant, and the
+// mark the host picked.
+type Joining = network::Handshake<(network::Connection, String, Player)>;
+
+/// Lets the player join a game hosted on another computer, either one of the
+/// games announced on the local network or the game at the address saved in
+/// the settings.
+///
+/// The address is set with the `--join` command line option. The variant is
+/// picked by the host.
+pub struct JoinMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+    // The variant of the multiplayer menu to go back to.
+    variant: Variant,
+    // Listens for the announced games.
+    browser: Option<network::Browser>,
+    // The address of the game joined when each game button is pressed.
+    game_buttons: HashMap<ecs::Entity, SocketAddr>,
+    // The button that was pressed last.
+    pressed_button: Option<ecs::Entity>,
+    // Why games cannot be found or the last game could not be joined.
+    error: Option<String>,
+    // The address of the game being joined, which is finished in `update`.
+    joining: Option<(String, Joining)>,
+}
+
+impl<'a, 'b> JoinMenu {
+    /// Creates the menu, going back to the multiplayer menu of the provided
+    /// variant.
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            menu: None,
+            variant,
+            browser: None,
+            game_buttons: HashMap::new(),
+            pressed_button: None,
+            error: None,
+            joining: None,
+        }
+    }
+
+    // Creates the menu with a button for each game found, replacing any
+    // existing menu.
+    fn create_menu(&mut self, world: &mut World) {
+        self.delete_menu(world);
+
+        let games = self
+            .browser
+            .as_ref()
+            .map_or(&[][..], |browser| browser.games());
+        let address = world
+            .read_resource::<settings::Settings>()
+            .join_address
+            .clone();
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, "Join Game");
+        menu.set_close_button(world, "Back", |_, _| NextState::Back);
+        for game in games.iter().take(MAX_LISTED_GAMES) {
+            let announcement = &game.announcement;
+            let mut text = format!("{}: {}", announcement.name, announcement.variant);
+            if !announcement.is_open {
+                text.push_str(" (full)");
+            }
+            let button = menu.add_button(world, &text, Self::on_game_button_click);
+            self.game_buttons.insert(button, game.address);
+        }
+        menu.add_button(world, "Join by Address", Self::on_address_button_click);
+        let text = match (&self.joining, &self.error) {
+            (Some((address, _)), _) => format!("Joining {}...", address),
+            (None, Some(error)) => error.clone(),
+            (None, None) if games.is_empty() => {
+                format!("Looking for LAN games, or join {}.", address)
+            }
+            (None, None) => format!("Select a game, or join {}.", address),
+        };
+        menu.add_paragraph(world, &text);
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+        self.game_buttons.clear();
+    }
+
+    // Called when one of the game buttons is pressed to join the game.
+    fn on_game_button_click(&mut self, world: &mut World) -> NextState {
+        if self.joining.is_some() {
+            return NextState::None;
+        }
+        let address = match self
+            .pressed_button
+            .and_then(|button| self.game_buttons.get(&button))
+        {
+            Some(address) => *address,
+            None => return NextState::None,
+        };
+        let is_open = self.browser.as_ref().is_some_and(|browser| {
+            browser
+                .games()
+                .iter()
+                .any(|game| game.address == address && game.announcement.is_open)
+        });
+
+        if is_open {
+            self.start_joining(world, address.to_string());
+        } else {
+            let error = "someone else has joined the game";
+            log::error!("Unable to join game at {}: {}", address, error);
+            self.error = Some(format!("Unable to join: {}", error));
+            self.create_menu(world);
+        }
+        NextState::None
+    }
+
+    // Joins the game at the saved address.
+    fn on_address_button_click(&mut self, world: &mut World) -> NextState {
+        if self.joining.is_none() {
+            let address = world
+                .read_resource::<settings::Settings>()
+                .join_address
+                .clone();
+            self.start_joining(world, address);
+        }
+        NextState::None
+    }
+
+    // Starts joining the game hosted at the address, which is finished by
+    // `finish_joining`.
+    fn start_joining(&mut self, world: &mut World, address: String) {
+        log::info!("Joining game at {}.", address);
+        let host_address = address.clone();
+        let handshake = network::Handshake::start(move || network::join(&host_address));
+        self.joining = Some((address, handshake));
+        self.error = None;
+        self.create_menu(world);
+    }
+
+    // Switches to the game once it has been joined, or shows why it could
+    // not be joined.
+    fn finish_joining(&mut self, world: &mut World) -> Option<Game> {
+        let result = self.joining.as_ref()?.1.poll()?;
+        let (address, _) = self.joining.take()?;
+        let joined = result.and_then(|(connection, variant_name, player)| {
+            joined_game(world, connection, &variant_name, player).map_err(|error| error.to_string())
+        });
+        match joined {
+            Ok(game) => Some(game),
+            Err(error) => {
+                log::error!("Unable to join game at {}: {}", address, error);
+                self.error = Some(format!("Unable to join: {}", error));
+                self.create_menu(world);
+                None
+            }
+        }
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            self.as_trans(NextState::Back)
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let callback = *callback;
+                self.pressed_button = Some(ui_event.target);
+                let next_state = callback(self, data.world);
+                return self.as_trans(next_state);
+            }
+        }
+        Trans::None
+    }
+
+    // Converts the next state into a state transition.
+    fn as_trans(&mut self, next_state: NextState) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match next_state {
+            NextState::None => Trans::None,
+            NextState::Back => Trans::Switch(Box::new(MultiplayerMenu::new(self.variant))),
+        }
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for JoinMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened join menu.");
+        match network::Browser::bind() {
+            Ok(browser) => self.browser = Some(browser),
+            Err(error) => {
+                log::error!("Unable to look for LAN games: {}", error);
+                self.error = Some("Unable to look for games on the local network.".to_string());
+            }
+        }
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+        self.browser = None;
+        self.joining = None;
+
+        log::info!("Closed join menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        // Show games as they are announced and forgotten.
+        let is_changed = self.browser.as_mut().is_some_and(|browser| browser.poll());
+        if is_changed {
+            self.create_menu(data.world);
+        }
+
+        match self.finish_joining(data.world) {
+            Some(game) => Trans::Switch(Box::new(game)),
+            None => Trans::None,
+        }
+    }
+}
+
+/// Creates the game state for playing the joined game of the named variant
+/// over the connection.
+///
+/// # Errors
+/// An error is returned if the variant is a custom variant this computer does
+/// not have.
+pub(super) fn joined_game(
+    world: &World,
+    connection: network::Connection,
+    variant_name: &str,
+    player: Player,
+) -> Result<Game, Box<dyn Error>> {
+    let custom_variants = world.read_resource::<resources::CustomVariants>();
+    let variant = network::find_variant(variant_name, &custom_variants.variants)
+        .ok_or_else(|| format!("unknown variant {}", variant_name))?;
+    Ok(Game::join(variant, player, connection))
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    Back,
+}
@@ -1,14 +1,60 @@
 use amethyst::prelude::*;
+use std::{fs, path};
 
+use crate::components;
 use crate::environments::Environments;
 use crate::events;
+use crate::file_io;
+use crate::resources;
+use crate::settings;
 use crate::states;
 use crate::ui;
+use crate::variants;
 
 /// Loads the assets needed for the game.
 ///
-/// When the assets have finished loading the `Game` state switched to.
-pub struct Loading;
+/// When the assets have finished loading the `Game` state switched to, or the
+/// `Tutorial` state if the player has not been through the tutorial yet and
+/// did not ask for a difficulty or a bot to play. Asking to join a game
+/// opens the `MultiplayerMenu` instead, or the `ServerMenu` when a room is
+/// given.
+pub struct Loading {
+    // The difficulty of the first game, if given on the command line.
+    difficulty: Option<settings::DifficultyChoice>,
+    // The custom difficulty's mistake percentage, if given on the command line.
+    mistake_percent: Option<u8>,
+    // Indicates a bot should be played, if given on the command line. The
+    // bot's program is given unless the saved one should be used.
+    engine: Option<Option<path::PathBuf>>,
+    // The address of a game to join, if given on the command line.
+    join_address: Option<String>,
+    // The name to host games with, if given on the command line.
+    player_name: Option<String>,
+    // The code of a game server room to join, if given on the command line.
+    room_code: Option<String>,
+}
+
+impl Loading {
+    /// Creates the loading state, using the provided options from the command
+    /// line once loaded.
+    pub fn new(
+        difficulty: Option<settings::DifficultyChoice>,
+        mistake_percent: Option<u8>,
+        engine: Option<Option<path::PathBuf>>,
+        join_address: Option<String>,
+        player_name: Option<String>,
+        room_code: Option<String>,
+    ) -> Self {
+        Self {
+            difficulty,
+            mistake_percent,
+            engine,
+            join_address,
+            player_name,
+            room_code,
+        }
+    }
+}
 
 impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for Loading {
     fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
@@ -26,6 +72,43 @@ impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for Loading {
         let mut environments = Environments::new();
         environments.load(data.world);
         data.world.insert(Some(environments));
+
+        // Read the variants defined by data files.
+        data.world.insert(load_custom_variants());
+
+        // Solve the classic game up front so the AI and puzzles can look up
+        // the best moves without stalling the game.
+        let table = variants::PerfectPlayTable::get();
+        log::info!("Solved {} classic positions.", table.len());
+
+        // Read the player's settings, such as if they have seen the tutorial.
+        let mut settings = settings::Settings::load();
+        if let Some(percent) = self.mistake_percent {
+            log::info!("Using custom difficulty of {}% mistakes.", percent);
+            settings.custom_mistake_percent = percent;
+            settings.save();
+        }
+        if let Some(Some(program)) = &self.engine {
+            log::info!("Using bot {}.", program.display());
+            settings.external_engine = Some(program.clone());
+            settings.save();
+        }
+        if let Some(address) = &self.join_address {
+            log::info!("Using join address {}.", address);
+            settings.join_address = address.clone();
+            settings.save();
+        }
+        if let Some(name) = &self.player_name {
+            log::info!("Using player name {}.", name);
+            settings.player_name = name.clone();
+            settings.save();
+        }
+        if let Some(code) = &self.room_code {
+            log::info!("Using room code {}.", code);
+            settings.room_code = code.trim().to_uppercase();
+            settings.save();
+        }
+        data.world.insert(settings);
     }
 
     fn on_stop(&mut self, _data: StateData<'_, GameData<'a, 'b>>) {
@@ -38,8 +121,80 @@ impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for Loading {
     ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
         data.data.update(&data.world);
 
-        Trans::Switch(Box::new(states::Game::new(
-            states::GameStateOptions::default(),
-        )))
+        let settings = data.world.read_resource::<settings::Settings>();
+        if self.room_code.is_some() {
+            return Trans::Switch(Box::new(states::ServerMenu::new(
+                variants::Variant::Classic,
+            )));
+        }
+        if self.join_address.is_some() {
+            return Trans::Switch(Box::new(states::MultiplayerMenu::new(
+                variants::Variant::Classic,
+            )));
+        }
+        if self.engine.is_some() {
+            match &settings.external_engine {
+                Some(program) => {
+                    return Trans::Switch(Box::new(states::Game::external_engine(program.clone())))
+                }
+                None => log::error!("No bot has been given with --engine yet."),
+            }
+        }
+        // Asking for a difficulty means the player wants to play straight
+        // away, so the tutorial is skipped.
+        if settings.tutorial_completed || self.difficulty.is_some() {
+            let options = match self.difficulty {
+                Some(choice) => states::GameStateOptions::SinglePlayer(
+                    choice.difficulty(&settings),
+                    components::Player::X,
+                ),
+                None => states::GameStateOptions::default(),
+            };
+            Trans::Switch(Box::new(states::Game::new(
+                options,
+                variants::Variant::default(),
+            )))
+        } else {
+            Trans::Switch(Box::new(states::Tutorial::new()))
+        }
+    }
+}
+
+// Reads the custom variants from their data files.
+//
+// Files that cannot be read or have invalid rules are logged and skipped so
+// the rest of the variants can still be played.
+fn load_custom_variants() -> resources::CustomVariants {
+    let files = match file_io::custom_variant_files() {
+        Ok(files) => files,
+        Err(error) => {
+            log::error!("Unable to find the custom variant files: {}", error);
+            Vec::new()
+        }
+    };
+
+    let mut custom_variants = resources::CustomVariants::default();
+    for file in files {
+        let rules = fs::read_to_string(&file)
+            .map_err(|error| error.to_string())
+            .and_then(|text| {
+                variants::CustomRules::from_ron(&text).map_err(|error| error.to_string())
+            });
+        match rules {
+            Ok(rules) => {
+                log::info!(
+                    "Loaded custom variant {} from {}.",
+                    rules.name,
+                    file.display()
+                );
+                // The rules are needed for as long as the game runs.
+                let rules = Box::leak(Box::new(rules));
+                custom_variants
+                    .variants
+                    .push(variants::Variant::Custom(rules));
+            }
+            Err(error) => log::error!("Invalid custom variant {}: {}", file.display(), error),
+        }
     }
+    custom_variants
 }
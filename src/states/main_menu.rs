@@ -1,9 +1,9 @@
-use amethyst::{core::ecs, input, prelude::*};
+use amethyst::{input, prelude::*};
 use contracts::*;
 
 use crate::events;
-use crate::file_io;
 use crate::ui;
+use crate::variants;
 
 use super::*;
 
@@ -44,15 +44,6 @@ impl<'a, 'b> MainMenu {
         }
         Trans::None
     }
-
-    fn on_help_button_click(&mut self, _world: &mut ecs::World) -> NextState {
-        match file_io::open_player_manual() {
-            Ok(()) => log::info!("Opened player manual in the default browser."),
-            Err(e) => log::error!("Unable to open the player manual. Error details: {}", e),
-        }
-
-        NextState::None
-    }
 }
 
 impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for MainMenu {
@@ -65,9 +56,12 @@ impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for MainMenu {
         menu.add_button(data.world, "Single-player", |_, _| {
             NextState::SinglePlayerMenu
         });
-        menu.add_button(data.world, "Multiplayer", |_, _| NextState::MultiplayerGame);
+        menu.add_button(data.world, "Multiplayer", |_, _| NextState::MultiplayerMenu);
+        menu.add_button(data.world, "Variants", |_, _| NextState::VariantsMenu);
+        menu.add_button(data.world, "Puzzles", |_, _| NextState::PuzzlesMenu);
+        menu.add_button(data.world, "Statistics", |_, _| NextState::StatisticsMenu);
         menu.add_separator(data.world);
-        menu.add_button(data.world, "Help", Self::on_help_button_click);
+        menu.add_button(data.world, "Help", |_, _| NextState::HelpMenu);
         self.menu = Some(menu);
     }
 
@@ -107,9 +101,12 @@ impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for MainMenu {
 
 // Helper type for selecting the next state to transition to.
 enum NextState {
-    None,
-    MultiplayerGame,
+    MultiplayerMenu,
     SinglePlayerMenu,
+    VariantsMenu,
+    PuzzlesMenu,
+    StatisticsMenu,
+    HelpMenu,
     Quit,
 }
 
@@ -117,11 +114,14 @@ impl<'a, 'b> NextState {
     // Converts the next state variant into a state transition.
     fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
         match self {
-            Self::None => Trans::None,
-            Self::MultiplayerGame => {
-                Trans::Switch(Box::new(Game::new(GameStateOptions::Multiplayer)))
+            Self::MultiplayerMenu => {
+                Trans::Switch(Box::new(MultiplayerMenu::new(variants::Variant::Classic)))
             }
             Self::SinglePlayerMenu => Trans::Switch(Box::new(SinglePlayerMenu::new())),
+            Self::VariantsMenu => Trans::Switch(Box::new(VariantsMenu::new())),
+            Self::PuzzlesMenu => Trans::Switch(Box::new(PuzzlesMenu::new())),
+            Self::StatisticsMenu => Trans::Switch(Box::new(StatisticsMenu::new())),
+            Self::HelpMenu => Trans::Switch(Box::new(HelpMenu::new())),
             Self::Quit => Trans::Quit,
         }
     }
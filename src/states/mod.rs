@@ -1,12 +1,50 @@
 //! Holds the game's states.
 
+mod adaptive_menu;
+mod campaign_menu;
+mod challenges_menu;
+mod custom_difficulty_menu;
+mod daily_challenge_menu;
+mod endless_menu;
 mod game;
+mod handicaps_menu;
+mod help_menu;
+mod join_menu;
 mod loading;
 mod main_menu;
+mod multiplayer_menu;
+mod opponents_menu;
+mod play_mode_menu;
+mod position_editor;
+mod puzzle;
+mod puzzles_menu;
+mod server_menu;
 mod single_player_menu;
+mod statistics_menu;
+mod tutorial;
+mod variants_menu;
 
 pub use self::loading::*;
 
+use self::adaptive_menu::*;
+use self::campaign_menu::*;
+use self::challenges_menu::*;
+use self::custom_difficulty_menu::*;
+use self::daily_challenge_menu::*;
+use self::endless_menu::*;
 use self::game::*;
+use self::handicaps_menu::*;
+use self::help_menu::*;
+use self::join_menu::*;
 use self::main_menu::*;
+use self::multiplayer_menu::*;
+use self::opponents_menu::*;
+use self::play_mode_menu::*;
+use self::position_editor::*;
+use self::puzzle::*;
+use self::puzzles_menu::*;
+use self::server_menu::*;
 use self::single_player_menu::*;
+use self::statistics_menu::*;
+use self::tutorial::*;
+use self::variants_menu::*;
@@ -0,0 +1,150 @@
+use amethyst::{input, prelude::*};
+use contracts::*;
+
+use crate::events;
+use crate::ui;
+use crate::variants::Variant;
+
+use super::{Game, GameStateOptions, JoinMenu, MainMenu, PlayModeMenu, ServerMenu};
+
+/// Lets the players choose between playing on this computer and playing
+/// online, either by hosting a game, joining one, or playing in a room on a
+/// game server.
+pub struct MultiplayerMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+    variant: Variant,
+}
+
+impl<'a, 'b> MultiplayerMenu {
+    /// Creates the menu for playing the provided variant.
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            menu: None,
+            variant,
+        }
+    }
+
+    // Creates the menu, replacing any existing menu.
+    fn create_menu(&mut self, world: &mut World) {
+        self.delete_menu(world);
+
+        let title = match self.variant {
+            Variant::Classic => "Multiplayer".to_string(),
+            variant => format!("{}: Multiplayer", variant.name()),
+        };
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, &title);
+        menu.set_close_button(world, "Back", |_, _| NextState::Back);
+        menu.add_button(world, "Local", |_, _| NextState::LocalGame);
+        menu.add_button(world, "Host Game", |_, _| NextState::HostGame);
+        menu.add_button(world, "Join Game", |_, _| NextState::JoinMenu);
+        menu.add_button(world, "Game Server", |_, _| NextState::ServerMenu);
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            self.as_trans(NextState::Back)
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let callback = *callback;
+                let next_state = callback(self, data.world);
+                return self.as_trans(next_state);
+            }
+        }
+        Trans::None
+    }
+
+    // Converts the next state into a state transition.
+    fn as_trans(&mut self, next_state: NextState) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match next_state {
+            NextState::None => Trans::None,
+            NextState::LocalGame => Trans::Switch(Box::new(Game::new(
+                GameStateOptions::Multiplayer,
+                self.variant,
+            ))),
+            NextState::HostGame => Trans::Switch(Box::new(Game::host(self.variant))),
+            NextState::JoinMenu => Trans::Switch(Box::new(JoinMenu::new(self.variant))),
+            NextState::ServerMenu => Trans::Switch(Box::new(ServerMenu::new(self.variant))),
+            // The classic game's multiplayer menu is opened from the main menu.
+            NextState::Back if self.variant == Variant::Classic => {
+                Trans::Switch(Box::new(MainMenu::new()))
+            }
+            NextState::Back => Trans::Switch(Box::new(PlayModeMenu::new(self.variant))),
+        }
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for MultiplayerMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened multiplayer menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+
+        log::info!("Closed multiplayer menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    LocalGame,
+    HostGame,
+    JoinMenu,
+    ServerMenu,
+    Back,
+}
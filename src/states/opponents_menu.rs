@@ -0,0 +1,163 @@
+use amethyst::{ecs, input, prelude::*};
+use contracts::*;
+use std::collections::HashMap;
+
+use crate::engine;
+use crate::events;
+use crate::ui;
+
+use super::{ChallengesMenu, Game};
+
+/// Lets the player pick a named AI opponent for a classic game, including the
+/// learning AI.
+///
+/// Games are played on top of this state, so the player comes back here when
+/// they leave the game.
+pub struct OpponentsMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+    // The personality played when each personality button is pressed.
+    personality_buttons: HashMap<ecs::Entity, engine::Personality>,
+    // The button that was pressed last.
+    pressed_button: Option<ecs::Entity>,
+}
+
+impl<'a, 'b> OpponentsMenu {
+    pub fn new() -> Self {
+        Self {
+            menu: None,
+            personality_buttons: HashMap::new(),
+            pressed_button: None,
+        }
+    }
+
+    // Creates the menu with a button for each opponent.
+    fn create_menu(&mut self, world: &mut World) {
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, "Opponents");
+        menu.set_close_button(world, "Back", |_, _| NextState::ChallengesMenu);
+        for personality in engine::PERSONALITIES.iter() {
+            let button =
+                menu.add_button(world, personality.name, Self::on_personality_button_click);
+            self.personality_buttons.insert(button, *personality);
+        }
+        menu.add_button(world, "Learning AI", |_, _| NextState::LearningGame);
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+        self.personality_buttons.clear();
+    }
+
+    // Called when one of the personality buttons is pressed.
+    fn on_personality_button_click(&mut self, _world: &mut World) -> NextState {
+        self.pressed_button
+            .and_then(|button| self.personality_buttons.get(&button))
+            .map_or(NextState::None, |personality| {
+                NextState::PersonalityGame(*personality)
+            })
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            NextState::ChallengesMenu.as_trans()
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let callback = *callback;
+                self.pressed_button = Some(ui_event.target);
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for OpponentsMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened opponents menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+
+        log::info!("Closed opponents menu.");
+    }
+
+    fn on_pause(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.create_menu(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    PersonalityGame(engine::Personality),
+    LearningGame,
+    ChallengesMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::PersonalityGame(personality) => {
+                Trans::Push(Box::new(Game::personality(*personality)))
+            }
+            Self::LearningGame => Trans::Push(Box::new(Game::learning_opponent())),
+            Self::ChallengesMenu => Trans::Switch(Box::new(ChallengesMenu::new())),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use amethyst::{core::ecs, input, prelude::*};
+use contracts::*;
+
+use crate::events;
+use crate::ui;
+use crate::variants::Variant;
+
+use super::{MultiplayerMenu, SinglePlayerMenu, VariantsMenu};
+
+/// Lets the player choose between single-player and multiplayer for a variant.
+pub struct PlayModeMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+    variant: Variant,
+}
+
+impl<'a, 'b> PlayModeMenu {
+    /// Creates the menu for playing the provided variant.
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            menu: None,
+            variant,
+        }
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            Trans::Switch(Box::new(VariantsMenu::new()))
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+
+    fn on_single_player_button_click(&mut self, _world: &mut ecs::World) -> NextState {
+        NextState::SinglePlayerMenu(self.variant)
+    }
+
+    fn on_multiplayer_button_click(&mut self, _world: &mut ecs::World) -> NextState {
+        NextState::MultiplayerMenu(self.variant)
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for PlayModeMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!(
+            "Opened play mode menu for the {} variant.",
+            self.variant.name()
+        );
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(data.world, self.variant.name());
+        menu.set_close_button(data.world, "Back", |_, _| NextState::VariantsMenu);
+        menu.add_button(
+            data.world,
+            "Single-player",
+            Self::on_single_player_button_click,
+        );
+        menu.add_button(data.world, "Multiplayer", Self::on_multiplayer_button_click);
+        self.menu = Some(menu);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+
+        log::info!("Closed play mode menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    SinglePlayerMenu(Variant),
+    MultiplayerMenu(Variant),
+    VariantsMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::SinglePlayerMenu(variant) => {
+                Trans::Switch(Box::new(SinglePlayerMenu::with_variant(*variant)))
+            }
+            Self::MultiplayerMenu(variant) => {
+                Trans::Switch(Box::new(MultiplayerMenu::new(*variant)))
+            }
+            Self::VariantsMenu => Trans::Switch(Box::new(VariantsMenu::new())),
+        }
+    }
+}
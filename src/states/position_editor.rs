@@ -0,0 +1,377 @@
+use amethyst::{core::ecs, input, prelude::*};
+use clipboard::{ClipboardContext, ClipboardProvider};
+use contracts::*;
+use open_ttt_lib as ttt;
+use std::error::Error;
+
+use crate::components;
+use crate::environments::*;
+use crate::events;
+use crate::file_io;
+use crate::position_editor::EditedPosition;
+use crate::puzzles;
+use crate::resources;
+use crate::ui;
+use crate::variants;
+
+use super::game::*;
+use super::MainMenu;
+
+// The difficulty of the AI opponent when playing from the position.
+const AI_DIFFICULTY: ttt::ai::Difficulty = ttt::ai::Difficulty::Hard;
+
+/// Lets the player set up any classic position by clicking squares, which
+/// cycles them between empty, X, and O.
+///
+/// From the editor the position can be played against the AI, saved as a
+/// puzzle, or copied as notation. This is mainly used to reproduce reports of
+/// the AI misplaying a specific position.
+pub struct PositionEditor {
+    position: EditedPosition,
+    // The mark the local player plays as when playing from the position.
+    player: components::Player,
+    // Describes the result of the last action, such as saving the position.
+    message: Option<String>,
+    game_controls: Option<ui::GameControls<Self, NextState>>,
+}
+
+impl<'a, 'b> PositionEditor {
+    pub fn new() -> Self {
+        Self {
+            position: EditedPosition::new(),
+            player: components::Player::X,
+            message: None,
+            game_controls: None,
+        }
+    }
+
+    // Shows the board being edited along with the editor's controls.
+    fn show_editor(&mut self, world: &mut World) {
+        // An empty game is used so the environment has a board to show, the
+        // marks being edited are added separately.
+        insert_game_resources(world, resources::GameLogic::new(variants::Variant::Classic));
+        show_next_environment(world);
+        let squares: Vec<(ttt::game::Position, ttt::board::Owner)> =
+            self.position.board().iter().collect();
+        for (position, owner) in squares {
+            show_square(world, position, owner);
+        }
+
+        self.create_game_controls(world);
+    }
+
+    // Deletes the editor's controls and the environment.
+    fn hide_editor(&mut self, world: &mut World) {
+        if let Some(mut game_controls) = self.game_controls.take() {
+            game_controls.delete(world);
+        }
+        delete_current_environment(world);
+    }
+
+    // Creates the game controls, replacing any existing controls.
+    //
+    // The buttons for toggling options show the current option, so the
+    // controls are recreated whenever the position or options change.
+    fn create_game_controls(&mut self, world: &mut World) {
+        if let Some(mut game_controls) = self.game_controls.take() {
+            game_controls.delete(world);
+        }
+
+        let mut game_controls = ui::GameControls::new();
+        game_controls.set_menu_button(world, |_, _| NextState::MainMenu);
+        game_controls.add_side_button(
+            world,
+            &format!("{} moves", self.position.to_move()),
+            Self::on_toggle_to_move,
+        );
+        game_controls.add_side_button(
+            world,
+            &format!("You: {}", self.player),
+            Self::on_toggle_player,
+        );
+        game_controls.add_side_button(world, "Play", Self::on_play);
+        game_controls.add_side_button(world, "Save", Self::on_save);
+        game_controls.add_side_button(world, "Copy", Self::on_copy);
+        game_controls.add_side_button(world, "Clear", Self::on_clear);
+
+        let mut message = vec![self.position.notation()];
+        match self.position.game() {
+            Ok(_) => message.push("Legal position".to_string()),
+            Err(error) => message.push(format!("Not legal: {}", error)),
+        }
+        message.extend(self.message.clone());
+        game_controls.set_message(world, &message);
+        self.game_controls = Some(game_controls);
+    }
+
+    // Cycles the square at the position, if it is on the board.
+    fn edit_square(&mut self, world: &mut World, position: ttt::game::Position) {
+        if let Some(owner) = self.position.cycle_square(position) {
+            show_square(world, position, owner);
+            self.message = None;
+            self.create_game_controls(world);
+        }
+    }
+
+    // Handles input related events.
+    //
+    // The actions for placing marks pick the square to edit. There are no
+    // players while editing, so these actions do not create player events.
+    fn handle_input_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        input_event: &events::InputEvent<events::InputBindingTypes>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        let position = match input_event {
+            events::InputEvent::ActionPressed(events::ActionBinding::PlaceMarkAtMouse) => {
+                Some(data.world.read_resource::<resources::MousePosition>().grid)
+            }
+            events::InputEvent::ActionPressed(events::ActionBinding::PlaceMark(row, column)) => {
+                Some(ttt::game::Position {
+                    row: *row,
+                    column: *column,
+                })
+            }
+            _ => None,
+        };
+        if let Some(position) = position {
+            self.edit_square(data.world, position);
+        }
+
+        Trans::None
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            Trans::Switch(Box::new(MainMenu::new()))
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Return)
+            || input::is_key_down(window_event, input::VirtualKeyCode::NumpadEnter)
+        {
+            self.on_play(data.world).as_trans()
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(game_controls) = self.game_controls.as_mut() {
+            if let Some(callback) = game_controls.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+
+    // Called when the player wishes to change who moves next.
+    fn on_toggle_to_move(&mut self, world: &mut World) -> NextState {
+        self.position.toggle_to_move();
+        self.message = None;
+        self.create_game_controls(world);
+        NextState::None
+    }
+
+    // Called when the player wishes to change which mark they play as.
+    fn on_toggle_player(&mut self, world: &mut World) -> NextState {
+        self.player = self.player.opposite_player();
+        self.message = None;
+        self.create_game_controls(world);
+        NextState::None
+    }
+
+    // Called when the player wishes to play from the position against the AI.
+    fn on_play(&mut self, world: &mut World) -> NextState {
+        match self.position.game() {
+            Ok(game) => {
+                log::info!("Playing from position {}.", self.position.notation());
+                NextState::Play(self.player, variants::Game::Classic(game))
+            }
+            Err(error) => {
+                self.message = Some(format!("Unable to play: {}", error));
+                self.create_game_controls(world);
+                NextState::None
+            }
+        }
+    }
+
+    // Called when the player wishes to save the position as a puzzle.
+    fn on_save(&mut self, world: &mut World) -> NextState {
+        self.message = Some(match save_position(&self.position) {
+            Ok(name) => {
+                log::info!("Saved position {} as {}.", self.position.notation(), name);
+                format!("Saved as {}", name)
+            }
+            Err(error) => {
+                log::error!("Unable to save the position: {}", error);
+                format!("Unable to save: {}", error)
+            }
+        });
+        self.create_game_controls(world);
+        NextState::None
+    }
+
+    // Called when the player wishes to copy the position's notation.
+    //
+    // The notation is logged as well, so it can still be found if the
+    // clipboard is not available.
+    fn on_copy(&mut self, world: &mut World) -> NextState {
+        let notation = self.position.notation();
+        log::info!("Position notation: {}", notation);
+        self.message = Some(match copy_to_clipboard(&notation) {
+            Ok(()) => "Copied to clipboard".to_string(),
+            Err(error) => {
+                log::error!("Unable to copy the position notation: {}", error);
+                "Unable to copy, see the log".to_string()
+            }
+        });
+        self.create_game_controls(world);
+        NextState::None
+    }
+
+    // Called when the player wishes to remove every mark from the board.
+    fn on_clear(&mut self, world: &mut World) -> NextState {
+        let squares: Vec<ttt::game::Position> = self
+            .position
+            .board()
+            .iter()
+            .map(|(position, _owner)| position)
+            .collect();
+        self.position.clear();
+        for position in squares {
+            show_square(world, position, ttt::board::Owner::None);
+        }
+        self.message = None;
+        self.create_game_controls(world);
+        NextState::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for PositionEditor {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened position editor.");
+        self.show_editor(data.world);
+    }
+
+    #[post(self.game_controls.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.hide_editor(data.world);
+        log::info!("Closed position editor.");
+    }
+
+    // The editor is paused while playing from the position.
+    fn on_pause(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.hide_editor(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.message = None;
+        self.show_editor(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Input(input_event) => self.handle_input_event(data, &input_event),
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Shows the owner of the square in the current environment.
+fn show_square(world: &mut World, position: ttt::game::Position, owner: ttt::board::Owner) {
+    let player = match owner {
+        ttt::board::Owner::PlayerX => Some(components::Player::X),
+        ttt::board::Owner::PlayerO => Some(components::Player::O),
+        ttt::board::Owner::None => None,
+    };
+
+    let environments = { world.write_resource::<Option<Environments>>().take() };
+    if let Some(mut environments) = environments {
+        environments.remove_mark(world, position);
+        if let Some(player) = player {
+            let mark = components::Mark {
+                position,
+                owner: player,
+                symbol: components::Symbol::from(player),
+            };
+            environments.add_mark(world, &mark);
+        }
+        // Be sure to return the environment when done.
+        world
+            .write_resource::<Option<Environments>>()
+            .replace(environments);
+    }
+}
+
+// Adds the position as a puzzle to the saved positions file, returning the
+// name it was saved under.
+fn save_position(position: &EditedPosition) -> Result<String, Box<dyn Error>> {
+    let file = file_io::saved_positions_file()?;
+    let mut pack = if file.exists() {
+        file_io::read_ron_file(&file)?
+    } else {
+        puzzles::PuzzlePack::default()
+    };
+
+    let name = pack.unused_name("Position");
+    pack.puzzles.push(position.to_puzzle(name.clone())?);
+    file_io::write_ron_file(&file, &pack)?;
+    Ok(name)
+}
+
+// Copies the text to the system clipboard.
+fn copy_to_clipboard(text: &str) -> Result<(), Box<dyn Error>> {
+    let mut context: ClipboardContext = ClipboardProvider::new()?;
+    context.set_contents(text.to_string())
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    MainMenu,
+    // Play from the position as the given mark against the AI.
+    Play(components::Player, variants::Game),
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::MainMenu => Trans::Switch(Box::new(MainMenu::new())),
+            Self::Play(player, game) => Trans::Push(Box::new(Game::from_position(
+                GameStateOptions::SinglePlayer(AI_DIFFICULTY, *player),
+                game.clone(),
+            ))),
+        }
+    }
+}
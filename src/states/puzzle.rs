@@ -0,0 +1,354 @@
+use amethyst::{core::ecs, input, prelude::*};
+use contracts::*;
+
+use crate::components;
+use crate::environments::*;
+use crate::events;
+use crate::file_io;
+use crate::puzzles;
+use crate::resources;
+use crate::ui;
+use crate::variants;
+
+use super::game::*;
+use super::MainMenu;
+
+/// Plays the puzzles from the puzzle pack against an opponent that defends
+/// perfectly.
+///
+/// Puzzles are played in order, starting from the first one that has not been
+/// solved. Solved puzzles are saved so they are remembered the next time the
+/// game is played.
+pub struct Puzzle {
+    // The puzzles being played, `None` if the puzzle pack could not be read.
+    pack: Option<puzzles::PuzzlePack>,
+    solved: puzzles::SolvedPuzzles,
+    // The index of the puzzle being played.
+    index: usize,
+    progress: puzzles::PuzzleProgress,
+    // Entities the puzzle state owns that need deleted when the state exits.
+    owned_entities: Vec<ecs::Entity>,
+    game_controls: Option<ui::GameControls<Self, NextState>>,
+}
+
+impl<'a, 'b> Puzzle {
+    pub fn new() -> Self {
+        Self {
+            pack: None,
+            solved: puzzles::SolvedPuzzles::default(),
+            index: 0,
+            progress: puzzles::PuzzleProgress::Playing,
+            owned_entities: Vec::new(),
+            game_controls: None,
+        }
+    }
+
+    // Gets the puzzle being played.
+    fn current_puzzle(&self) -> Option<&puzzles::Puzzle> {
+        self.pack
+            .as_ref()
+            .and_then(|pack| pack.puzzles.get(self.index))
+    }
+
+    // Sets up the board and players for the current puzzle.
+    fn start_puzzle(&mut self, world: &mut World) {
+        let puzzle = match self.current_puzzle() {
+            Some(puzzle) => puzzle.clone(),
+            None => return,
+        };
+        let game = puzzle
+            .game()
+            .expect("Puzzles are checked when the pack is read.");
+        log::info!("Started puzzle {}.", puzzle.name);
+
+        self.delete_owned_entities(world);
+        self.progress = puzzles::PuzzleProgress::Playing;
+
+        // The player solves the puzzle while the AI defends.
+        let local_player = world
+            .create_entity()
+            .with(puzzle.to_move)
+            .with(components::LocalPlayer)
+            .build();
+        self.owned_entities.push(local_player);
+        let ai_player_component = components::AiPlayer::with_opponent(variants::Opponent::Perfect(
+            variants::PerfectOpponent::new(),
+        ));
+        let ai_player = world
+            .create_entity()
+            .with(puzzle.to_move.opposite_player())
+            .with(ai_player_component)
+            .build();
+        self.owned_entities.push(ai_player);
+
+        insert_game_resources(world, resources::GameLogic::with_game(game));
+        show_next_environment(world);
+        self.create_game_controls(world);
+    }
+
+    // Creates the game controls, replacing any existing controls.
+    //
+    // The status shows the puzzle's goal and, once finished, the result along
+    // with a button for moving on.
+    fn create_game_controls(&mut self, world: &mut World) {
+        if let Some(mut game_controls) = self.game_controls.take() {
+            game_controls.delete(world);
+        }
+        let (puzzle, num_puzzles) = match (self.current_puzzle(), self.pack.as_ref()) {
+            (Some(puzzle), Some(pack)) => (puzzle, pack.puzzles.len()),
+            _ => return,
+        };
+
+        let mut status = vec![
+            format!("Puzzle {} of {}", self.index + 1, num_puzzles),
+            puzzle.name.clone(),
+            puzzle.description(),
+        ];
+        let mut game_controls = ui::GameControls::new();
+        game_controls.set_menu_button(world, |_, _| NextState::MainMenu);
+        match self.progress {
+            puzzles::PuzzleProgress::Playing => {
+                if self.solved.names.contains(&puzzle.name) {
+                    status.push("Solved before".to_string());
+                }
+            }
+            puzzles::PuzzleProgress::Solved => {
+                status.push("Solved!".to_string());
+                game_controls.show_game_over_button(world, "Next Puzzle", Self::on_next_puzzle);
+            }
+            puzzles::PuzzleProgress::Failed => {
+                status.push("Not solved".to_string());
+                game_controls.show_game_over_button(world, "Try Again", Self::on_try_again);
+            }
+        }
+        game_controls.set_status(world, status);
+        self.game_controls = Some(game_controls);
+    }
+
+    // Records the result of the current puzzle and stops play.
+    fn finish_puzzle(&mut self, world: &mut World, progress: puzzles::PuzzleProgress) {
+        self.progress = progress;
+        // Removing the players stops the AI from moving, which matters when
+        // the puzzle fails before the game is over.
+        self.delete_owned_entities(world);
+
+        if progress == puzzles::PuzzleProgress::Solved {
+            if let Some(name) = self.current_puzzle().map(|puzzle| puzzle.name.clone()) {
+                log::info!("Solved puzzle {}.", name);
+                self.solved.names.insert(name);
+                save_solved_puzzles(&self.solved);
+            }
+        }
+        self.create_game_controls(world);
+    }
+
+    // Deletes all owned entities from the world.
+    fn delete_owned_entities(&mut self, world: &mut World) {
+        world
+            .delete_entities(self.owned_entities.as_slice())
+            .expect("Unable to delete puzzle state entities.");
+
+        self.owned_entities.clear();
+    }
+
+    // Updates the puzzle based on the player event.
+    fn handle_player_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        player_event: &events::PlayerEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if self.progress != puzzles::PuzzleProgress::Playing {
+            return Trans::None;
+        }
+
+        let (player, game_move, mark) = requested_move(player_event, variants::Variant::Classic);
+        if let Some(state) = do_move(data.world, player, &game_move) {
+            let progress = {
+                let game_logic = data.world.read_resource::<resources::GameLogic>();
+                self.current_puzzle()
+                    .map_or(puzzles::PuzzleProgress::Playing, |puzzle| {
+                        puzzle.progress(&game_logic.game)
+                    })
+            };
+            let outcome = match progress {
+                puzzles::PuzzleProgress::Failed => OutcomeAffinity::Loss,
+                _ => self
+                    .current_puzzle()
+                    .map_or(OutcomeAffinity::Neutral, |puzzle| {
+                        outcome_affinity(&state, puzzle.to_move)
+                    }),
+            };
+            show_move(data.world, &game_move, &mark, &state, outcome);
+
+            if progress != puzzles::PuzzleProgress::Playing {
+                self.finish_puzzle(data.world, progress);
+            }
+        }
+
+        Trans::None
+    }
+
+    fn is_continue_key_down(&self, window_event: &events::WindowEvent) -> bool {
+        input::is_key_down(window_event, input::VirtualKeyCode::Return)
+            || input::is_key_down(window_event, input::VirtualKeyCode::NumpadEnter)
+            || input::is_key_down(window_event, input::VirtualKeyCode::Space)
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            Trans::Switch(Box::new(MainMenu::new()))
+        } else if self.is_continue_key_down(window_event) {
+            let next_state = match self.progress {
+                puzzles::PuzzleProgress::Playing => NextState::None,
+                puzzles::PuzzleProgress::Solved => self.on_next_puzzle(data.world),
+                puzzles::PuzzleProgress::Failed => self.on_try_again(data.world),
+            };
+            next_state.as_trans()
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(game_controls) = self.game_controls.as_mut() {
+            if let Some(callback) = game_controls.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+
+    // Called when the player wishes to move on to the next puzzle.
+    fn on_next_puzzle(&mut self, world: &mut World) -> NextState {
+        if let Some(pack) = self.pack.as_ref() {
+            self.index = pack.next_unsolved(&self.solved, Some(self.index));
+        }
+        self.start_puzzle(world);
+        NextState::None
+    }
+
+    // Called when the player wishes to try the current puzzle again.
+    fn on_try_again(&mut self, world: &mut World) -> NextState {
+        self.start_puzzle(world);
+        NextState::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for Puzzle {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.pack = load_puzzle_pack();
+        self.solved = load_solved_puzzles();
+        if let Some(pack) = self.pack.as_ref() {
+            log::info!("Started puzzle mode with {} puzzles.", pack.puzzles.len());
+            self.index = pack.next_unsolved(&self.solved, None);
+        }
+
+        self.start_puzzle(data.world);
+    }
+
+    #[post(self.owned_entities.is_empty())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_owned_entities(data.world);
+
+        if let Some(mut game_controls) = self.game_controls.take() {
+            game_controls.delete(data.world);
+        }
+        delete_current_environment(data.world);
+
+        log::info!("Ended puzzle mode.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Player(player_event) => {
+                self.handle_player_event(data, &player_event)
+            }
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        // There is nothing to play without a puzzle pack.
+        if self.pack.is_none() {
+            Trans::Switch(Box::new(MainMenu::new()))
+        } else {
+            Trans::None
+        }
+    }
+}
+
+// Reads the puzzle pack, logging any problems with it.
+fn load_puzzle_pack() -> Option<puzzles::PuzzlePack> {
+    let file = match file_io::puzzle_pack_file() {
+        Ok(file) => file,
+        Err(error) => {
+            log::error!("Unable to find the puzzle pack file: {}", error);
+            return None;
+        }
+    };
+    let pack = std::fs::read_to_string(&file)
+        .map_err(|error| error.to_string())
+        .and_then(|text| puzzles::PuzzlePack::from_ron(&text).map_err(|error| error.to_string()));
+    match pack {
+        Ok(pack) => Some(pack),
+        Err(error) => {
+            log::error!("Invalid puzzle pack {}: {}", file.display(), error);
+            None
+        }
+    }
+}
+
+// Reads the puzzles the player has solved, logging any problems.
+//
+// No puzzles are solved the first time puzzle mode is played.
+fn load_solved_puzzles() -> puzzles::SolvedPuzzles {
+    file_io::load_or_default(file_io::solved_puzzles_file, "the solved puzzles")
+}
+
+// Saves the puzzles the player has solved, logging any problems.
+fn save_solved_puzzles(solved: &puzzles::SolvedPuzzles) {
+    file_io::save(file_io::solved_puzzles_file, solved, "the solved puzzles");
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    MainMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::MainMenu => Trans::Switch(Box::new(MainMenu::new())),
+        }
+    }
+}
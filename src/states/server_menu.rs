@@ -0,0 +1,293 @@
+use amethyst::{core::ecs, input, prelude::*};
+use contracts::*;
+use std::time::Duration;
+
+use crate::arena;
+use crate::components::Player;
+use crate::events;
+use crate::network;
+use crate::settings;
+use crate::ui;
+use crate::variants::Variant;
+
+use super::{joined_game, Game, MultiplayerMenu};
+
+// The time controls rooms can be created with, as a label and the base time
+// and increment in seconds. The first is untimed.
+const TIME_CONTROLS: [(&str, u64, u64); 4] = [
+    ("Untimed", 0, 0),
+    ("1 min", 60, 0),
+    ("3 min + 2 s", 180, 2),
+    ("10 min", 600, 0),
+];
+
+/// Lets the players create a room on a game server, or join a room with its
+/// code.
+///
+/// The server is the address saved in the settings, which is set with the
+/// `--join` command line option, and the room code is set with `--room`.
+/// Rooms are created for the menu's variant and the picked time control.
+pub struct ServerMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+    variant: Variant,
+    // The index of the time control rooms are created with.
+    time_control: usize,
+    // Why the last room could not be created or joined, if it could not.
+    error: Option<String>,
+    // The room being created or joined, which is finished in `update`.
+    pending_room: Option<PendingRoom>,
+}
+
+// A room being created or joined on the game server.
+enum PendingRoom {
+    // Creating a room, giving the connection and the room's code.
+    Create(network::Handshake<(network::Connection, String)>),
+    // Joining the room with the code, giving the connection, the name of the
+    // variant, and the mark the server picked.
+    Join {
+        code: String,
+        handshake: network::Handshake<(network::Connection, String, Player)>,
+    },
+}
+
+impl<'a, 'b> ServerMenu {
+    /// Creates the menu for playing the provided variant.
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            menu: None,
+            variant,
+            time_control: 0,
+            error: None,
+            pending_room: None,
+        }
+    }
+
+    // Creates the menu, replacing any existing menu.
+    fn create_menu(&mut self, world: &mut World) {
+        self.delete_menu(world);
+
+        let title = match self.variant {
+            Variant::Classic => "Game Server".to_string(),
+            variant => format!("{}: Game Server", variant.name()),
+        };
+        let text = match (&self.pending_room, &self.error) {
+            (Some(PendingRoom::Create(_)), _) => "Creating room...".to_string(),
+            (Some(PendingRoom::Join { code, .. }), _) => format!("Joining room {}...", code),
+            (None, Some(error)) => error.clone(),
+            (None, None) => {
+                let settings = world.read_resource::<settings::Settings>();
+                if settings.room_code.is_empty() {
+                    format!("Server {}", settings.join_address)
+                } else {
+                    format!(
+                        "Server {}, joins room {}",
+                        settings.join_address, settings.room_code
+                    )
+                }
+            }
+        };
+        let (time_control_label, _, _) = TIME_CONTROLS[self.time_control];
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, &title);
+        menu.set_close_button(world, "Back", |_, _| NextState::Back);
+        menu.add_button(world, "Create Room", Self::on_create_button_click);
+        menu.add_button(
+            world,
+            &format!("Time: {}", time_control_label),
+            Self::on_time_button_click,
+        );
+        menu.add_button(world, "Join Room", Self::on_join_button_click);
+        menu.add_paragraph(world, &text);
+        self.menu = Some(menu);
+    }
+
+    // Deletes the menu, if it exists.
+    fn delete_menu(&mut self, world: &mut World) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(world);
+        }
+    }
+
+    // Gets the time control rooms are created with, or `None` if untimed.
+    fn time_control(&self) -> Option<arena::TimeControl> {
+        match TIME_CONTROLS[self.time_control] {
+            (_, 0, _) => None,
+            (_, base, increment) => Some(arena::TimeControl {
+                base: Duration::from_secs(base),
+                increment: Duration::from_secs(increment),
+            }),
+        }
+    }
+
+    // Starts creating a room on the server to wait in for another player.
+    fn on_create_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        if self.pending_room.is_some() {
+            return NextState::None;
+        }
+        let address = world
+            .read_resource::<settings::Settings>()
+            .join_address
+            .clone();
+        log::info!("Creating room on game server {}.", address);
+        let (variant, time_control) = (self.variant, self.time_control());
+        self.pending_room = Some(PendingRoom::Create(network::Handshake::start(move || {
+            network::create_room(&address, variant, time_control)
+        })));
+        self.error = None;
+        self.create_menu(world);
+        NextState::None
+    }
+
+    fn on_time_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        if self.pending_room.is_some() {
+            return NextState::None;
+        }
+        // Cycle through the time controls, going back to untimed after the
+        // longest.
+        self.time_control = (self.time_control + 1) % TIME_CONTROLS.len();
+        self.create_menu(world);
+        NextState::None
+    }
+
+    // Starts joining the room with the saved code.
+    fn on_join_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        if self.pending_room.is_some() {
+            return NextState::None;
+        }
+        let (address, code) = {
+            let settings = world.read_resource::<settings::Settings>();
+            (settings.join_address.clone(), settings.room_code.clone())
+        };
+        if code.is_empty() {
+            self.error = Some("Give a room code with --room first.".to_string());
+        } else {
+            log::info!("Joining room {} on game server {}.", code, address);
+            let room_code = code.clone();
+            let handshake =
+                network::Handshake::start(move || network::join_room(&address, &room_code));
+            self.pending_room = Some(PendingRoom::Join { code, handshake });
+            self.error = None;
+        }
+        self.create_menu(world);
+        NextState::None
+    }
+
+    // Creates the game once the room has been created or joined, or shows
+    // why it could not be.
+    fn finish_pending_room(&mut self, world: &mut ecs::World) -> Option<Game> {
+        let game = match self.pending_room.as_ref()? {
+            PendingRoom::Create(handshake) => handshake
+                .poll()?
+                .map(|(connection, code)| {
+                    log::info!("Created room {}.", code);
+                    Game::room(self.variant, connection, code)
+                })
+                .map_err(|error| format!("Unable to create a room: {}", error)),
+            PendingRoom::Join { handshake, .. } => handshake
+                .poll()?
+                .and_then(|(connection, variant_name, player)| {
+                    joined_game(world, connection, &variant_name, player)
+                        .map_err(|error| error.to_string())
+                })
+                .map_err(|error| format!("Unable to join: {}", error)),
+        };
+        self.pending_room = None;
+        match game {
+            Ok(game) => Some(game),
+            Err(error) => {
+                log::error!("{}", error);
+                self.error = Some(error);
+                self.create_menu(world);
+                None
+            }
+        }
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            self.as_trans(NextState::Back)
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let callback = *callback;
+                let next_state = callback(self, data.world);
+                return self.as_trans(next_state);
+            }
+        }
+        Trans::None
+    }
+
+    // Converts the next state into a state transition.
+    fn as_trans(&mut self, next_state: NextState) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match next_state {
+            NextState::None => Trans::None,
+            NextState::Back => Trans::Switch(Box::new(MultiplayerMenu::new(self.variant))),
+        }
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for ServerMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened game server menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        self.delete_menu(data.world);
+        self.pending_room = None;
+
+        log::info!("Closed game server menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        match self.finish_pending_room(data.world) {
+            Some(game) => Trans::Switch(Box::new(game)),
+            None => Trans::None,
+        }
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    Back,
+}
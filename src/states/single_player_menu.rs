@@ -4,27 +4,41 @@ use open_ttt_lib as ttt;
 
 use crate::components::Player;
 use crate::events;
+use crate::settings;
 use crate::ui;
+use crate::variants::{Handicaps, Variant};
 
-use super::{Game, GameStateOptions, MainMenu};
+use super::{ChallengesMenu, CustomDifficultyMenu, Game, GameStateOptions, MainMenu, PlayModeMenu};
 
 /// Shows the single-player option UI widgets.
 pub struct SinglePlayerMenu {
     menu: Option<ui::Menu<Self, NextState>>,
     // The mark the player wishes to use.
     selected_player: Player,
+    // The variant to play.
+    variant: Variant,
 }
 
 impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for SinglePlayerMenu {
     fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
         log::info!("Opened single-player menu.");
 
+        let title = match self.variant {
+            Variant::Classic => "Single Player".to_string(),
+            variant => format!("{}: Single Player", variant.name()),
+        };
+
         let mut menu = ui::Menu::new();
-        menu.set_title(data.world, "Single Player");
+        menu.set_title(data.world, &title);
         menu.set_close_button(data.world, "Back", Self::on_back_button_click);
         menu.add_button(data.world, "Easy", Self::on_easy_button_click);
         menu.add_button(data.world, "Medium", Self::on_medium_button_click);
         menu.add_button(data.world, "Hard", Self::on_hard_button_click);
+        menu.add_button(data.world, "Custom", Self::on_custom_button_click);
+        // Challenges pick their own variant and difficulty.
+        if self.variant == Variant::Classic {
+            menu.add_button(data.world, "Challenges", |_, _| NextState::ChallengesMenu);
+        }
         self.menu = Some(menu);
     }
 
@@ -64,22 +78,28 @@ impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for SinglePlayerMenu {
 
 impl<'a, 'b> SinglePlayerMenu {
     pub fn new() -> Self {
+        Self::with_variant(Variant::Classic)
+    }
+
+    /// Creates the menu for starting a single-player game of the provided variant.
+    pub fn with_variant(variant: Variant) -> Self {
         Self {
             menu: None,
             selected_player: Player::X,
+            variant,
         }
     }
 
     // Handles window related events.
     fn handle_window_event(
         &mut self,
-        _data: StateData<'_, GameData<'a, 'b>>,
+        data: StateData<'_, GameData<'a, 'b>>,
         window_event: &events::WindowEvent,
     ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
         if input::is_close_requested(window_event) {
             Trans::Quit
         } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
-            Trans::Switch(Box::new(MainMenu::new()))
+            self.on_back_button_click(data.world).as_trans()
         } else {
             Trans::None
         }
@@ -100,27 +120,62 @@ impl<'a, 'b> SinglePlayerMenu {
         Trans::None
     }
 
-    fn on_easy_button_click(&mut self, _world: &mut ecs::World) -> NextState {
-        NextState::SinglePlayerGame(ttt::ai::Difficulty::Easy, self.selected_player)
+    fn on_easy_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        NextState::SinglePlayerGame(
+            self.variant,
+            ttt::ai::Difficulty::Easy,
+            self.selected_player,
+            self.handicaps(world),
+        )
+    }
+
+    fn on_medium_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        NextState::SinglePlayerGame(
+            self.variant,
+            ttt::ai::Difficulty::Medium,
+            self.selected_player,
+            self.handicaps(world),
+        )
+    }
+
+    fn on_hard_button_click(&mut self, world: &mut ecs::World) -> NextState {
+        NextState::SinglePlayerGame(
+            self.variant,
+            ttt::ai::Difficulty::Hard,
+            self.selected_player,
+            self.handicaps(world),
+        )
     }
 
-    fn on_medium_button_click(&mut self, _world: &mut ecs::World) -> NextState {
-        NextState::SinglePlayerGame(ttt::ai::Difficulty::Medium, self.selected_player)
+    fn on_custom_button_click(&mut self, _world: &mut ecs::World) -> NextState {
+        NextState::CustomDifficultyMenu(self.variant)
     }
 
-    fn on_hard_button_click(&mut self, _world: &mut ecs::World) -> NextState {
-        NextState::SinglePlayerGame(ttt::ai::Difficulty::Hard, self.selected_player)
+    // Gets the handicaps the player picked, which are only used in classic
+    // games.
+    fn handicaps(&self, world: &ecs::World) -> Handicaps {
+        match self.variant {
+            Variant::Classic => world.read_resource::<settings::Settings>().handicaps,
+            _ => Handicaps::default(),
+        }
     }
 
     fn on_back_button_click(&mut self, _world: &mut ecs::World) -> NextState {
-        NextState::MainMenu
+        // Variants are picked from the play mode menu, so return there.
+        match self.variant {
+            Variant::Classic => NextState::MainMenu,
+            variant => NextState::PlayModeMenu(variant),
+        }
     }
 }
 
 // Helper type for selecting the next state to transition to.
 enum NextState {
     None,
-    SinglePlayerGame(ttt::ai::Difficulty, Player),
+    SinglePlayerGame(Variant, ttt::ai::Difficulty, Player, Handicaps),
+    PlayModeMenu(Variant),
+    ChallengesMenu,
+    CustomDifficultyMenu(Variant),
     MainMenu,
 }
 
@@ -129,10 +184,19 @@ impl<'a, 'b> NextState {
     fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
         match self {
             Self::None => Trans::None,
-            Self::SinglePlayerGame(difficulty, player) => {
-                let game_state = Game::new(GameStateOptions::SinglePlayer(*difficulty, *player));
+            Self::SinglePlayerGame(variant, difficulty, player, handicaps) => {
+                let game_state = Game::new(
+                    GameStateOptions::SinglePlayer(*difficulty, *player),
+                    *variant,
+                )
+                .with_handicaps(*handicaps);
                 Trans::Switch(Box::new(game_state))
             }
+            Self::PlayModeMenu(variant) => Trans::Switch(Box::new(PlayModeMenu::new(*variant))),
+            Self::ChallengesMenu => Trans::Switch(Box::new(ChallengesMenu::new())),
+            Self::CustomDifficultyMenu(variant) => {
+                Trans::Switch(Box::new(CustomDifficultyMenu::new(*variant)))
+            }
             Self::MainMenu => Trans::Switch(Box::new(MainMenu::new())),
         }
     }
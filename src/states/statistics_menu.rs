@@ -0,0 +1,132 @@
+use amethyst::{input, prelude::*};
+use contracts::*;
+
+use crate::adaptive;
+use crate::events;
+use crate::ui;
+
+use super::MainMenu;
+
+/// Shows the player's statistics that are saved between runs of the game,
+/// such as their adaptive difficulty rating and how it has changed.
+pub struct StatisticsMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+}
+
+impl<'a, 'b> StatisticsMenu {
+    pub fn new() -> Self {
+        Self { menu: None }
+    }
+
+    // Creates the menu, reading the saved statistics.
+    fn create_menu(&mut self, world: &mut World) {
+        let rating = adaptive::PlayerRating::load();
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(world, "Statistics");
+        menu.set_close_button(world, "Back", |_, _| NextState::MainMenu);
+        menu.add_paragraph(world, &format!("Adaptive rating: {:.0}", rating.rating));
+        menu.add_paragraph(world, &format!("Adaptive games played: {}", rating.games));
+        if !rating.recent_results.is_empty() {
+            menu.add_paragraph(
+                world,
+                &format!(
+                    "Last {} games: {}",
+                    rating.recent_results.len(),
+                    rating.recent_summary()
+                ),
+            );
+        }
+        if !rating.recent_ratings.is_empty() {
+            menu.add_paragraph(
+                world,
+                &format!("Rating history: {}", rating.recent_history()),
+            );
+        }
+        self.menu = Some(menu);
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            NextState::MainMenu.as_trans()
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for StatisticsMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened statistics menu.");
+        self.create_menu(data.world);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+
+        log::info!("Closed statistics menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    MainMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::MainMenu => Trans::Switch(Box::new(MainMenu::new())),
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use amethyst::{input, prelude::*};
+use contracts::*;
+use open_ttt_lib as ttt;
+
+use crate::components;
+use crate::events;
+use crate::resources;
+use crate::settings;
+use crate::tutorial;
+use crate::ui;
+use crate::variants;
+
+use super::game::*;
+use super::MainMenu;
+
+/// Walks new players through placing marks with the mouse and keyboard,
+/// winning, blocking, and making forks.
+///
+/// Each step starts from a scripted board with the squares the player should
+/// move in highlighted. The tutorial is started automatically the first time
+/// the game is played.
+pub struct Tutorial {
+    steps: Vec<tutorial::TutorialStep>,
+    // The index of the step being played.
+    index: usize,
+    // Indicates the player has done what the current step asks.
+    is_step_done: bool,
+    // Tells the player what they did wrong, if anything.
+    hint: Option<String>,
+    game_controls: Option<ui::GameControls<Self, NextState>>,
+}
+
+impl<'a, 'b> Tutorial {
+    pub fn new() -> Self {
+        Self {
+            steps: tutorial::tutorial_steps(),
+            index: 0,
+            is_step_done: false,
+            hint: None,
+            game_controls: None,
+        }
+    }
+
+    // Gets the step being played.
+    fn current_step(&self) -> &tutorial::TutorialStep {
+        &self.steps[self.index]
+    }
+
+    // Indicates if the current step is the last one.
+    fn is_last_step(&self) -> bool {
+        self.index + 1 >= self.steps.len()
+    }
+
+    // Sets up the board and highlighted squares for the current step.
+    fn start_step(&mut self, world: &mut World) {
+        let step = self.current_step().clone();
+        log::info!("Started tutorial step {}.", self.index + 1);
+
+        insert_game_resources(
+            world,
+            resources::GameLogic::with_game(variants::Game::Classic(step.game())),
+        );
+        show_next_environment(world);
+        world
+            .write_resource::<resources::HighlightedSquares>()
+            .positions = step.targets.clone();
+
+        // Steps that only show instructions are done straight away.
+        self.is_step_done = step.targets.is_empty();
+        self.hint = None;
+        self.create_game_controls(world);
+    }
+
+    // Creates the game controls, replacing any existing controls.
+    fn create_game_controls(&mut self, world: &mut World) {
+        if let Some(mut game_controls) = self.game_controls.take() {
+            game_controls.delete(world);
+        }
+
+        let step = self.current_step();
+        let mut instructions = step.instructions.to_string();
+        if step.device == Some(tutorial::InputDevice::Keyboard) {
+            if let Some(target) = step.targets.first() {
+                instructions.push_str(&format!(" Keys: {}", key_names(world, *target)));
+            }
+        }
+        let mut message = vec![format!(
+            "Tutorial step {} of {}",
+            self.index + 1,
+            self.steps.len()
+        )];
+        if self.is_step_done && !step.targets.is_empty() {
+            message.push("Well done!".to_string());
+        }
+        message.extend(self.hint.clone());
+
+        let mut game_controls = ui::GameControls::new();
+        game_controls.set_menu_button(world, Self::on_skip);
+        if self.is_step_done {
+            let text = if self.is_last_step() {
+                "Finish"
+            } else {
+                "Continue"
+            };
+            game_controls.add_side_button(world, text, Self::on_continue);
+        }
+        if !self.is_last_step() {
+            game_controls.add_side_button(world, "Skip", Self::on_skip);
+        }
+        game_controls.set_message(world, &message);
+        game_controls.set_overlay_text(world, &instructions);
+        self.game_controls = Some(game_controls);
+    }
+
+    // Places the player's X if it is what the current step asks for,
+    // otherwise tells the player what to do instead.
+    fn try_move(
+        &mut self,
+        world: &mut World,
+        position: ttt::game::Position,
+        device: tutorial::InputDevice,
+    ) {
+        let is_on_board = {
+            let game_logic = world.read_resource::<resources::GameLogic>();
+            game_logic.game.board().get(position).is_some()
+        };
+        if self.is_step_done || !is_on_board {
+            return;
+        }
+
+        match self.current_step().check_move(position, device) {
+            tutorial::StepCheck::Done => {
+                let player_event =
+                    events::PlayerEvent::RequestMark(components::Player::X, position);
+                let (player, game_move, mark) =
+                    requested_move(&player_event, variants::Variant::Classic);
+                if let Some(state) = do_move(world, player, &game_move) {
+                    show_move(
+                        world,
+                        &game_move,
+                        &mark,
+                        &state,
+                        outcome_affinity(&state, player),
+                    );
+                }
+                world
+                    .write_resource::<resources::HighlightedSquares>()
+                    .positions
+                    .clear();
+                self.is_step_done = true;
+                self.hint = None;
+            }
+            tutorial::StepCheck::WrongSquare => {
+                self.hint = Some("Try the highlighted square".to_string());
+            }
+            tutorial::StepCheck::WrongDevice => {
+                self.hint = Some(match device {
+                    tutorial::InputDevice::Mouse => "Use the keyboard for this step".to_string(),
+                    tutorial::InputDevice::Keyboard => "Use the mouse for this step".to_string(),
+                });
+            }
+        }
+        self.create_game_controls(world);
+    }
+
+    // Records that the player has been through the tutorial, so it is not
+    // started automatically again.
+    fn complete_tutorial(&mut self, world: &mut World) {
+        let mut settings = world.write_resource::<settings::Settings>();
+        if !settings.tutorial_completed {
+            settings.tutorial_completed = true;
+            settings.save();
+        }
+    }
+
+    // Handles input related events.
+    //
+    // There are no players during the tutorial, so the actions for placing
+    // marks are handled here. This lets the tutorial tell which device the
+    // player used.
+    fn handle_input_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        input_event: &events::InputEvent<events::InputBindingTypes>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match input_event {
+            events::InputEvent::ActionPressed(events::ActionBinding::PlaceMarkAtMouse) => {
+                let position = data.world.read_resource::<resources::MousePosition>().grid;
+                self.try_move(data.world, position, tutorial::InputDevice::Mouse);
+            }
+            events::InputEvent::ActionPressed(events::ActionBinding::PlaceMark(row, column)) => {
+                let position = ttt::game::Position {
+                    row: *row,
+                    column: *column,
+                };
+                self.try_move(data.world, position, tutorial::InputDevice::Keyboard);
+            }
+            _ => (),
+        }
+
+        Trans::None
+    }
+
+    fn is_continue_key_down(&self, window_event: &events::WindowEvent) -> bool {
+        input::is_key_down(window_event, input::VirtualKeyCode::Return)
+            || input::is_key_down(window_event, input::VirtualKeyCode::NumpadEnter)
+            || input::is_key_down(window_event, input::VirtualKeyCode::Space)
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            self.on_skip(data.world).as_trans()
+        } else if self.is_step_done && self.is_continue_key_down(window_event) {
+            self.on_continue(data.world).as_trans()
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(game_controls) = self.game_controls.as_mut() {
+            if let Some(callback) = game_controls.handle_ui_event(data.world, ui_event) {
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+
+    // Called when the player wishes to move on from a finished step.
+    fn on_continue(&mut self, world: &mut World) -> NextState {
+        if self.is_last_step() {
+            log::info!("Finished the tutorial.");
+            self.complete_tutorial(world);
+            NextState::MainMenu
+        } else {
+            self.index += 1;
+            self.start_step(world);
+            NextState::None
+        }
+    }
+
+    // Called when the player wishes to leave the tutorial early.
+    fn on_skip(&mut self, world: &mut World) -> NextState {
+        log::info!("Skipped the tutorial at step {}.", self.index + 1);
+        self.complete_tutorial(world);
+        NextState::MainMenu
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for Tutorial {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Started the tutorial.");
+        self.start_step(data.world);
+    }
+
+    #[post(self.game_controls.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        if let Some(mut game_controls) = self.game_controls.take() {
+            game_controls.delete(data.world);
+        }
+        data.world
+            .write_resource::<resources::HighlightedSquares>()
+            .positions
+            .clear();
+        delete_current_environment(data.world);
+
+        log::info!("Ended the tutorial.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Input(input_event) => self.handle_input_event(data, &input_event),
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Gets the names of the keys bound to placing a mark at the position, such
+// as "Numpad7 or Q", so the instructions match the player's key bindings.
+fn key_names(world: &World, position: ttt::game::Position) -> String {
+    let input_handler = world.read_resource::<input::InputHandler<events::InputBindingTypes>>();
+    let action = events::ActionBinding::PlaceMark(position.row, position.column);
+    let names: Vec<String> = input_handler
+        .bindings
+        .action_bindings(&action)
+        .filter_map(|buttons| match buttons {
+            [input::Button::Key(key)] => Some(format!("{:?}", key)),
+            _ => None,
+        })
+        .collect();
+    names.join(" or ")
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    MainMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::MainMenu => Trans::Switch(Box::new(MainMenu::new())),
+        }
+    }
+}
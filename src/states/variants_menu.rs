@@ -0,0 +1,162 @@
+use amethyst::{ecs, input, prelude::*};
+use contracts::*;
+use std::collections::HashMap;
+
+use crate::events;
+use crate::resources;
+use crate::ui;
+use crate::variants::Variant;
+
+use super::{MainMenu, PlayModeMenu};
+
+/// Shows the tic-tac-toe variants the player can choose from.
+///
+/// The variants defined by data files are listed after the built-in variants.
+pub struct VariantsMenu {
+    menu: Option<ui::Menu<Self, NextState>>,
+    // The variant played when each custom variant button is pressed.
+    custom_variant_buttons: HashMap<ecs::Entity, Variant>,
+    // The button that was pressed last.
+    pressed_button: Option<ecs::Entity>,
+}
+
+impl<'a, 'b> VariantsMenu {
+    pub fn new() -> Self {
+        Self {
+            menu: None,
+            custom_variant_buttons: HashMap::new(),
+            pressed_button: None,
+        }
+    }
+
+    // Called when one of the custom variant buttons is pressed.
+    fn on_custom_variant_button_click(&mut self, _world: &mut World) -> NextState {
+        self.pressed_button
+            .and_then(|button| self.custom_variant_buttons.get(&button))
+            .map_or(NextState::None, |variant| NextState::PlayModeMenu(*variant))
+    }
+
+    // Handles window related events.
+    fn handle_window_event(
+        &mut self,
+        _data: StateData<'_, GameData<'a, 'b>>,
+        window_event: &events::WindowEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if input::is_close_requested(window_event) {
+            Trans::Quit
+        } else if input::is_key_down(window_event, input::VirtualKeyCode::Escape) {
+            Trans::Switch(Box::new(MainMenu::new()))
+        } else {
+            Trans::None
+        }
+    }
+
+    // Handles UI related events.
+    fn handle_ui_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        ui_event: &events::UiEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(callback) = menu.handle_ui_event(data.world, ui_event) {
+                let callback = *callback;
+                self.pressed_button = Some(ui_event.target);
+                let next_state = callback(self, data.world);
+                return next_state.as_trans();
+            }
+        }
+        Trans::None
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>, events::StateEvent> for VariantsMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        log::info!("Opened variants menu.");
+
+        let mut menu = ui::Menu::new();
+        menu.set_title(data.world, "Variants");
+        menu.set_close_button(data.world, "Back", |_, _| NextState::MainMenu);
+        menu.add_button(data.world, Variant::Numerical.name(), |_, _| {
+            NextState::PlayModeMenu(Variant::Numerical)
+        });
+        menu.add_button(data.world, Variant::ThreeMensMorris.name(), |_, _| {
+            NextState::PlayModeMenu(Variant::ThreeMensMorris)
+        });
+        menu.add_button(data.world, Variant::Notakto.name(), |_, _| {
+            NextState::PlayModeMenu(Variant::Notakto)
+        });
+        menu.add_button(data.world, Variant::OrderAndChaos.name(), |_, _| {
+            NextState::PlayModeMenu(Variant::OrderAndChaos)
+        });
+
+        let custom_variants = data
+            .world
+            .read_resource::<resources::CustomVariants>()
+            .variants
+            .clone();
+        if !custom_variants.is_empty() {
+            menu.add_separator(data.world);
+        }
+        for variant in custom_variants {
+            let button = menu.add_button(
+                data.world,
+                variant.name(),
+                Self::on_custom_variant_button_click,
+            );
+            self.custom_variant_buttons.insert(button, variant);
+        }
+        self.menu = Some(menu);
+    }
+
+    #[post(self.menu.is_none())]
+    fn on_stop(&mut self, data: StateData<'_, GameData<'a, 'b>>) {
+        if let Some(mut menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+        self.custom_variant_buttons.clear();
+
+        log::info!("Closed variants menu.");
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+        event: events::StateEvent,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        // Determine which sub-event handler needs to be called.
+        match event {
+            events::StateEvent::Window(window_event) => {
+                self.handle_window_event(data, &window_event)
+            }
+            events::StateEvent::Ui(ui_event) => self.handle_ui_event(data, &ui_event),
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<'_, GameData<'a, 'b>>,
+    ) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        data.data.update(&data.world);
+
+        Trans::None
+    }
+}
+
+// Helper type for selecting the next state to transition to.
+enum NextState {
+    None,
+    PlayModeMenu(Variant),
+    MainMenu,
+}
+
+impl<'a, 'b> NextState {
+    // Converts the next state variant into a state transition.
+    fn as_trans(&self) -> Trans<GameData<'a, 'b>, events::StateEvent> {
+        match self {
+            Self::None => Trans::None,
+            Self::PlayModeMenu(variant) => Trans::Switch(Box::new(PlayModeMenu::new(*variant))),
+            Self::MainMenu => Trans::Switch(Box::new(MainMenu::new())),
+        }
+    }
+}
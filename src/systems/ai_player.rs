@@ -1,14 +1,21 @@
 use amethyst::{
     core::{shrev::EventChannel, timing::Time},
     derive::SystemDesc,
-    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, Write},
+    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
 };
+use std::sync::Arc;
 
 use crate::components::*;
+use crate::engine;
 use crate::events::PlayerEvent;
 use crate::resources::*;
+use crate::variants;
 
 /// Generates player events for the AI opponents.
+///
+/// The AI's moves are searched for on the `SearchWorkers`, so slow engines do
+/// not hold up the game's frames. The move is made once the search has
+/// finished and the AI's move delay has passed.
 #[derive(SystemDesc)]
 pub struct AiPlayerSystem;
 
@@ -16,27 +23,76 @@ impl<'a> System<'a> for AiPlayerSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         ReadStorage<'a, Player>,
-        ReadStorage<'a, AiPlayer>,
+        WriteStorage<'a, AiPlayer>,
         Read<'a, GameLogic>,
         Read<'a, Time>,
+        Read<'a, SearchWorkers>,
         Write<'a, EventChannel<PlayerEvent>>,
     );
 
-    fn run(&mut self, (players, ai_players, game_logic, time, mut channel): Self::SystemData) {
-        for (player, ai_player) in (&players, &ai_players).join() {
-            // Check to see if is the player's turn, if not skip the player so CPU cycles are not
-            // spent evaluating positions that will not be used.
-            if game_logic.is_players_move(player)
-                && sufficient_delay_since_last_move(ai_player, &game_logic, &time)
-            {
-                if let Some(position) = ai_player.ai_opponent.get_move(&game_logic.game) {
-                    channel.single_write(PlayerEvent::RequestMark(*player, position));
+    fn run(
+        &mut self,
+        (players, mut ai_players, game_logic, time, search_workers, mut channel): Self::SystemData,
+    ) {
+        for (player, ai_player) in (&players, &mut ai_players).join() {
+            // Check to see if is the player's turn, if not cancel any search so CPU cycles are
+            // not spent evaluating positions that will not be used.
+            if !game_logic.is_players_move(player) {
+                ai_player.pending_move = None;
+                continue;
+            }
+            pick_move_delay(ai_player, &game_logic);
+            if ai_player.pending_move.is_none() {
+                start_search(ai_player, &game_logic.game, &search_workers);
+            }
+            if sufficient_delay_since_last_move(ai_player, &game_logic, &time) {
+                if let Some(game_move) = take_move(ai_player) {
+                    channel.single_write(PlayerEvent::new(*player, game_move));
                 }
             }
         }
     }
 }
 
+// Starts searching for the AI's next move on the search workers.
+fn start_search(ai_player: &mut AiPlayer, game: &variants::Game, search_workers: &SearchWorkers) {
+    let (pending_move, job) =
+        engine::PendingMove::new(Arc::clone(&ai_player.ai_opponent), game.clone());
+    ai_player.pending_move = Some(pending_move);
+    search_workers.spawn(job);
+}
+
+// Takes the AI's move once its search has finished.
+//
+// When the AI has no move to make the finished search is kept until the next
+// turn, so a new search is not started every frame.
+fn take_move(ai_player: &mut AiPlayer) -> Option<variants::Move> {
+    match ai_player.pending_move.as_ref().and_then(|p| p.take()) {
+        Some(Some(game_move)) => {
+            ai_player.pending_move = None;
+            Some(game_move)
+        }
+        Some(None) => {
+            log::warn!("The AI opponent has no move to make.");
+            None
+        }
+        None => None,
+    }
+}
+
+// Picks how long the AI thinks about its move, once per turn.
+//
+// Every move, including the first move of a restarted game, updates the time
+// of the last move, so a new turn also cancels any search left over from the
+// previous one.
+fn pick_move_delay(ai_player: &mut AiPlayer, game_logic: &GameLogic) {
+    if ai_player.move_delay_picked_at != Some(game_logic.last_move_time) {
+        ai_player.move_delay = ai_player.think_time.sample(&mut rand::thread_rng());
+        ai_player.move_delay_picked_at = Some(game_logic.last_move_time);
+        ai_player.pending_move = None;
+    }
+}
+
 // Indicates if sufficient time has elapsed since the lsat move.
 fn sufficient_delay_since_last_move(
     ai_player: &AiPlayer,
@@ -51,9 +107,63 @@ mod tests {
     use super::*;
     use open_ttt_lib as ttt;
 
+    #[test]
+    fn pick_move_delay_when_new_turn_should_pick_from_think_time() {
+        let mut ai_player = AiPlayer::new(variants::Variant::Classic, ttt::ai::Difficulty::None);
+        let mut game_logic = GameLogic::default();
+        ai_player.think_time = ThinkTime::fixed(2.0);
+        ai_player.move_delay_picked_at = Some(core::time::Duration::from_secs(1));
+        game_logic.last_move_time = core::time::Duration::from_secs(3);
+
+        pick_move_delay(&mut ai_player, &game_logic);
+
+        assert_eq!(ai_player.move_delay, core::time::Duration::from_secs(2));
+        assert_eq!(
+            ai_player.move_delay_picked_at,
+            Some(core::time::Duration::from_secs(3))
+        );
+    }
+
+    #[test]
+    fn pick_move_delay_when_new_turn_should_cancel_pending_move() {
+        let mut ai_player = AiPlayer::new(variants::Variant::Classic, ttt::ai::Difficulty::None);
+        let mut game_logic = GameLogic::default();
+        let game = variants::Game::new(variants::Variant::Classic);
+        let (pending_move, _job) =
+            engine::PendingMove::new(Arc::clone(&ai_player.ai_opponent), game);
+        ai_player.pending_move = Some(pending_move);
+        ai_player.move_delay_picked_at = Some(core::time::Duration::from_secs(1));
+        game_logic.last_move_time = core::time::Duration::from_secs(3);
+
+        pick_move_delay(&mut ai_player, &game_logic);
+
+        assert!(ai_player.pending_move.is_none());
+    }
+
+    #[test]
+    fn take_move_when_no_move_should_keep_finished_search() {
+        let mut ai_player = AiPlayer::new(variants::Variant::Classic, ttt::ai::Difficulty::None);
+        let mut game = variants::Game::new(variants::Variant::Classic);
+        for (row, column) in &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+            game.do_move(&variants::Move::Mark(ttt::game::Position {
+                row: *row,
+                column: *column,
+            }));
+        }
+        let (pending_move, job) =
+            engine::PendingMove::new(Arc::clone(&ai_player.ai_opponent), game);
+        ai_player.pending_move = Some(pending_move);
+        job();
+
+        let game_move = take_move(&mut ai_player);
+
+        assert_eq!(game_move, None);
+        assert!(ai_player.pending_move.is_some());
+    }
+
     #[test]
     fn sufficient_delay_since_last_move_when_same_delay_should_return_true() {
-        let mut ai_player = AiPlayer::new(ttt::ai::Difficulty::None);
+        let mut ai_player = AiPlayer::new(variants::Variant::Classic, ttt::ai::Difficulty::None);
         let time = Time::default();
         let mut game_logic = GameLogic::default();
 
@@ -69,7 +179,7 @@ mod tests {
 
     #[test]
     fn sufficient_delay_since_last_move_when_less_delay_should_return_false() {
-        let mut ai_player = AiPlayer::new(ttt::ai::Difficulty::None);
+        let mut ai_player = AiPlayer::new(variants::Variant::Classic, ttt::ai::Difficulty::None);
         let mut time = Time::default();
         let mut game_logic = GameLogic::default();
 
@@ -0,0 +1,38 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::prelude::{Read, System, SystemData, Write},
+    renderer::{debug_drawing::DebugLines, palette::Srgba},
+};
+use open_ttt_lib as ttt;
+
+use crate::math::*;
+use crate::resources::*;
+
+// The size of the cross, relative to the square size. This is smaller than
+// the highlights so they can be seen around it.
+const CROSS_SIZE_FACTOR: f32 = 0.6;
+
+/// Draws a cross in each blocked square that is still free.
+///
+/// Once a mark is placed in the square, the cross is no longer drawn so it
+/// does not cover the mark.
+#[derive(SystemDesc)]
+pub struct BlockedSquaresSystem;
+
+impl<'a> System<'a> for BlockedSquaresSystem {
+    type SystemData = (Write<'a, DebugLines>, Read<'a, GameLogic>, Read<'a, Grids>);
+
+    fn run(&mut self, (mut debug_lines, game_logic, grids): Self::SystemData) {
+        for (_player, position) in &game_logic.blocked_squares {
+            if game_logic.game.board().get(*position) != Some(ttt::board::Owner::None) {
+                continue;
+            }
+
+            let square = grids.position_to_square(*position);
+            let cross = Square::new(square.center(), square.size() * CROSS_SIZE_FACTOR);
+            let color = Srgba::new(0.5, 0.5, 0.5, 1.0);
+            debug_lines.draw_line(cross.bottom_left(), cross.top_right(), color);
+            debug_lines.draw_line(cross.top_left(), cross.bottom_right(), color);
+        }
+    }
+}
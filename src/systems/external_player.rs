@@ -0,0 +1,109 @@
+use amethyst::{
+    core::{shrev::EventChannel, timing::Time},
+    derive::SystemDesc,
+    ecs::prelude::{Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
+};
+use std::sync::Arc;
+
+use crate::components::*;
+use crate::engine;
+use crate::events::PlayerEvent;
+use crate::resources::*;
+
+/// Generates player events for bots running as separate programs.
+///
+/// The bot is sent the position at the start of each of its turns, and its
+/// reply is turned into a request to place a mark. The bot is asked again
+/// after an illegal move, and if it runs out of time or makes too many
+/// illegal moves the built-in AI makes the move instead.
+#[derive(SystemDesc)]
+pub struct ExternalPlayerSystem;
+
+impl<'a> System<'a> for ExternalPlayerSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadStorage<'a, Player>,
+        WriteStorage<'a, ExternalPlayer>,
+        Read<'a, GameLogic>,
+        Read<'a, Time>,
+        Read<'a, SearchWorkers>,
+        Write<'a, EventChannel<PlayerEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (players, mut external_players, game_logic, time, search_workers, mut channel): Self::SystemData,
+    ) {
+        for (player, external_player) in (&players, &mut external_players).join() {
+            if !game_logic.is_players_move(player) {
+                continue;
+            }
+            let time_left = engine::time_left(time.absolute_time() - game_logic.last_move_time);
+            if external_player.requested_at != Some(game_logic.last_move_time) {
+                external_player.requested_at = Some(game_logic.last_move_time);
+                external_player.num_illegal_replies = 0;
+                external_player.fallback_move = None;
+                request_move(external_player, &game_logic, time_left);
+            }
+
+            // Once the built-in AI has taken over, the bot's replies are
+            // ignored for the rest of the turn.
+            if let Some(fallback_move) = external_player.fallback_move.as_ref() {
+                if let Some(Some(game_move)) = fallback_move.take() {
+                    channel.single_write(PlayerEvent::new(*player, game_move));
+                }
+                continue;
+            }
+
+            if let Some((row, column)) = external_player.engine.poll_move() {
+                // Squares off the board are illegal moves, like taken ones.
+                let position = engine::reply_position(&game_logic.game, row, column)
+                    .filter(|position| game_logic.game.can_move(*position));
+                if let Some(position) = position {
+                    channel.single_write(PlayerEvent::RequestMark(*player, position));
+                    continue;
+                }
+                log::warn!("The bot tried to move to {} {}.", row, column);
+                external_player.num_illegal_replies += 1;
+                if external_player.num_illegal_replies >= engine::MAX_ILLEGAL_REPLIES {
+                    log::warn!("The bot made too many illegal moves, so the computer moves.");
+                    start_fallback_search(external_player, &game_logic, &search_workers);
+                } else if time_left > core::time::Duration::from_secs(0) {
+                    // Ask again so the bot gets another chance.
+                    request_move(external_player, &game_logic, time_left);
+                }
+            } else if time_left == core::time::Duration::from_secs(0) {
+                log::warn!("The bot ran out of time, so the computer moves.");
+                start_fallback_search(external_player, &game_logic, &search_workers);
+            }
+        }
+    }
+}
+
+// Asks the bot for its move in the current game.
+fn request_move(
+    external_player: &mut ExternalPlayer,
+    game_logic: &GameLogic,
+    time_left: core::time::Duration,
+) {
+    if let Err(error) = external_player
+        .engine
+        .request_move(&game_logic.game, time_left)
+    {
+        log::error!("Unable to ask the bot for its move: {}", error);
+    }
+}
+
+// Starts the built-in AI searching for the move the bot failed to make.
+fn start_fallback_search(
+    external_player: &mut ExternalPlayer,
+    game_logic: &GameLogic,
+    search_workers: &SearchWorkers,
+) {
+    let (pending_move, job) = engine::PendingMove::new(
+        Arc::clone(&external_player.fallback_engine),
+        game_logic.game.clone(),
+    );
+    external_player.fallback_move = Some(pending_move);
+    search_workers.spawn(job);
+}
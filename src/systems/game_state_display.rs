@@ -7,6 +7,7 @@ use open_ttt_lib as ttt;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::variants;
 
 /// Updates UI text based on the state of the game.
 #[derive(SystemDesc)]
@@ -17,28 +18,127 @@ impl<'a> System<'a> for GameStateDisplaySystem {
     type SystemData = (
         WriteStorage<'a, ui::UiText>,
         ReadStorage<'a, GameStateText>,
+        ReadStorage<'a, AiPlayer>,
         Read<'a, GameLogic>,
+        Read<'a, NumberPicker>,
+        Read<'a, SelectedMark>,
     );
 
-    fn run(&mut self, (mut ui_text, game_state_text_components, game_logic): Self::SystemData) {
+    fn run(
+        &mut self,
+        (
+            mut ui_text,
+            game_state_text_components,
+            ai_players,
+            game_logic,
+            number_picker,
+            selected_mark,
+        ): Self::SystemData,
+    ) {
+        // Let the player know the AI is working on its move.
+        let is_ai_thinking = (&ai_players,)
+            .join()
+            .any(|(ai_player,)| ai_player.pending_move.is_some());
         for (state_text_component,) in (&game_state_text_components,).join() {
             if let Some(text) = ui_text.get_mut(state_text_component.ui_text) {
                 // Use vertical bars to separate each part of the text.
                 let mut text_parts = state_text_component.extra_information.clone();
-                let state_text = game_state_text(&game_logic.game).to_string();
+                let state_text = game_state_text(&game_logic.game);
                 text_parts.push(state_text);
+                match &game_logic.game {
+                    variants::Game::Numerical(game) => {
+                        text_parts.extend(numerical_text(game, &number_picker))
+                    }
+                    variants::Game::ThreeMensMorris(game) => {
+                        text_parts.extend(three_mens_morris_text(game, &selected_mark))
+                    }
+                    variants::Game::OrderAndChaos(game) => {
+                        text_parts.extend(order_and_chaos_text(game))
+                    }
+                    variants::Game::Custom(game) => text_parts.push(custom_text(game)),
+                    variants::Game::Classic(_) | variants::Game::Notakto(_) => (),
+                }
+                if is_ai_thinking {
+                    text_parts.push("Thinking...".to_string());
+                }
                 text.text = text_parts.join(" | ");
             }
         }
     }
 }
 
-fn game_state_text(game: &ttt::game::Game) -> &str {
+// Gets text describing which numbers the current player can place.
+fn numerical_text(game: &variants::NumericalGame, number_picker: &NumberPicker) -> Vec<String> {
+    if game.state().is_game_over() {
+        return Vec::new();
+    }
+
+    let parity = if game.is_odd_players_turn() {
+        "Odd numbers"
+    } else {
+        "Even numbers"
+    };
+    let selection = match number_picker.selected {
+        Some(number) => format!("Placing {}", number),
+        None => "Pick a number".to_string(),
+    };
+    vec![parity.to_string(), selection]
+}
+
+// Gets text describing if the current player is placing or moving marks.
+fn three_mens_morris_text(
+    game: &variants::ThreeMensMorrisGame,
+    selected_mark: &SelectedMark,
+) -> Option<String> {
+    if game.state().is_game_over() {
+        None
+    } else if game.is_placing() {
+        Some("Place a mark".to_string())
+    } else if selected_mark.position.is_some() {
+        Some("Pick where to move".to_string())
+    } else {
+        Some("Pick a mark to move".to_string())
+    }
+}
+
+// Gets text describing which role each player has and how to place an O.
+fn order_and_chaos_text(game: &variants::OrderAndChaosGame) -> Vec<String> {
+    let (x_role, o_role) = if game.is_x_order() {
+        (variants::Role::Order, variants::Role::Chaos)
+    } else {
+        (variants::Role::Chaos, variants::Role::Order)
+    };
+    let mut text = vec![
+        format!("X plays {}", x_role.name()),
+        format!("O plays {}", o_role.name()),
+    ];
+    if !game.state().is_game_over() {
+        text.push("Hold Shift to place O".to_string());
+    }
+    text
+}
+
+// Gets text describing the goal of a game with custom rules.
+fn custom_text(game: &variants::CustomGame) -> String {
+    let rules = game.rules();
+    let goal = if rules.misere { "Avoid" } else { "Get" };
+    let gravity = if rules.gravity { ", marks drop" } else { "" };
+    format!("{} {} in a row{}", goal, rules.win_length, gravity)
+}
+
+fn game_state_text(game: &variants::Game) -> String {
+    // Both players place X marks in notakto, so the players are numbered instead.
+    // In variants with roles, the players are named after their roles.
+    let (x_name, o_name) = match (game, game.x_role()) {
+        (variants::Game::Notakto(_), _) => ("Player 1", "Player 2"),
+        (_, Some(x_role)) => (x_role.name(), x_role.opposite_role().name()),
+        _ => ("X", "O"),
+    };
     match game.state() {
-        ttt::game::State::PlayerXMove => "X's turn",
-        ttt::game::State::PlayerOMove => "O's turn",
-        ttt::game::State::PlayerXWin(_) => "Game Over: X wins!",
-        ttt::game::State::PlayerOWin(_) => "Game Over: O wins!",
-        ttt::game::State::CatsGame => "Game Over: Cat's game",
+        ttt::game::State::PlayerXMove => format!("{}'s turn", x_name),
+        ttt::game::State::PlayerOMove => format!("{}'s turn", o_name),
+        ttt::game::State::PlayerXWin(_) => format!("Game Over: {} wins!", x_name),
+        ttt::game::State::PlayerOWin(_) => format!("Game Over: {} wins!", o_name),
+        ttt::game::State::CatsGame => "Game Over: Cat's game".to_string(),
     }
 }
@@ -0,0 +1,37 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::prelude::{Read, System, SystemData, Write},
+    renderer::{debug_drawing::DebugLines, palette::Srgba},
+};
+
+use crate::math::*;
+use crate::resources::*;
+
+// The size of the highlight, relative to the square size. This is slightly
+// smaller than the selected mark highlight so both can be seen at once.
+const HIGHLIGHT_SIZE_FACTOR: f32 = 0.8;
+
+/// Draws a box around each of the highlighted squares.
+#[derive(SystemDesc)]
+pub struct HighlightedSquaresSystem;
+
+impl<'a> System<'a> for HighlightedSquaresSystem {
+    type SystemData = (
+        Write<'a, DebugLines>,
+        Read<'a, HighlightedSquares>,
+        Read<'a, Grids>,
+    );
+
+    fn run(&mut self, (mut debug_lines, highlighted_squares, grids): Self::SystemData) {
+        for position in &highlighted_squares.positions {
+            let square = grids.position_to_square(*position);
+            let highlight = Square::new(square.center(), square.size() * HIGHLIGHT_SIZE_FACTOR);
+            debug_lines.draw_rectangle(
+                highlight.bottom_left().xy(),
+                highlight.top_right().xy(),
+                highlight.center().z,
+                Srgba::new(0.2, 0.8, 0.3, 1.0),
+            );
+        }
+    }
+}
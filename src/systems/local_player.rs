@@ -10,9 +10,17 @@ use open_ttt_lib as ttt;
 use crate::components::*;
 use crate::events::{ActionBinding, InputBindingTypes, PlayerEvent};
 use crate::resources::*;
+use crate::variants;
+
+// The size of the board the keys for placing marks cover. Larger boards are
+// played with the mouse.
+const KEYBOARD_BOARD_SIZE: i32 = 3;
 
 /// Responsible for translating mouse clicks and keyboard button presses into
 /// player events.
+///
+/// In variants where marks move once placed, the player first selects one of
+/// their marks then selects the square to move it to.
 #[derive(SystemDesc)]
 pub struct LocalPlayerSystem;
 
@@ -24,22 +32,60 @@ impl<'a> System<'a> for LocalPlayerSystem {
         Read<'a, InputHandler<InputBindingTypes>>,
         Read<'a, GameLogic>,
         Read<'a, MousePosition>,
+        Write<'a, NumberPicker>,
+        Write<'a, SelectedMark>,
+        Write<'a, BoardPicker>,
         Write<'a, EventChannel<PlayerEvent>>,
     );
 
     fn run(
         &mut self,
-        (players, local_players, input, game_logic, mouse_position, mut channel): Self::SystemData,
+        (
+            players,
+            local_players,
+            input,
+            game_logic,
+            mouse_position,
+            mut number_picker,
+            mut selected_mark,
+            mut board_picker,
+            mut channel,
+        ): Self::SystemData,
     ) {
         for (player, _) in (&players, &local_players).join() {
             // Only allow player's whose turn it is to generate player events.
             if game_logic.is_players_move(player) {
-                // Process the keyboard and mouse input for the player.
-                if let Some(player_event) = process_keyboard_input(player, &game_logic, &input) {
-                    channel.single_write(player_event);
+                if let variants::Game::Numerical(game) = &game_logic.game {
+                    process_number_selection(game, &input, &mut number_picker);
                 }
-                if let Some(player_event) = process_mouse_input(player, &mouse_position, &input) {
-                    channel.single_write(player_event);
+
+                process_board_selection(&game_logic, &input, &mut board_picker);
+                let alternate_symbol = input
+                    .action_is_down(&ActionBinding::AlternateSymbol)
+                    .unwrap_or(false);
+
+                // Process the keyboard and mouse input for the player.
+                let requested_positions = vec![
+                    process_keyboard_input(&game_logic, &input, &board_picker),
+                    process_mouse_input(&mouse_position, &input),
+                ];
+                for position in requested_positions.into_iter().flatten() {
+                    // Picking up one of the player's marks does not make a move yet.
+                    if game_logic.game.can_move_from(position) {
+                        selected_mark.position = Some(position);
+                        continue;
+                    }
+
+                    if let Some(player_event) = position_to_player_event(
+                        player,
+                        position,
+                        &game_logic,
+                        &number_picker,
+                        &selected_mark,
+                        alternate_symbol,
+                    ) {
+                        channel.single_write(player_event);
+                    }
                 }
             }
         }
@@ -47,18 +93,32 @@ impl<'a> System<'a> for LocalPlayerSystem {
 }
 
 fn process_keyboard_input(
-    player: &Player,
     game_logic: &GameLogic,
     input: &InputHandler<InputBindingTypes>,
-) -> Option<PlayerEvent> {
-    // Check if the key corresponding to a specific position is being pressed, returning
-    // a player event corresponding to that position.
-    for (position, _owner) in game_logic.game.board().iter() {
+    board_picker: &BoardPicker,
+) -> Option<ttt::game::Position> {
+    if game_logic.game.variant().board_size() != KEYBOARD_BOARD_SIZE {
+        return None;
+    }
+
+    // Check if the key corresponding to a specific position on the selected board
+    // is being pressed, returning the corresponding position.
+    let board_positions = game_logic
+        .game
+        .board()
+        .iter()
+        .map(|(position, _owner)| position)
+        .filter(|position| game_logic.game.board_index(*position) == board_picker.selected);
+    for position in board_positions {
+        let board_position = ttt::game::Position {
+            row: position.row,
+            column: position.column - board_picker.selected as i32 * variants::BOARD_COLUMNS,
+        };
         let request_move_at_position = input
-            .action_is_down(&position_to_action_binding(&position))
+            .action_is_down(&position_to_action_binding(&board_position))
             .unwrap_or(false);
         if request_move_at_position {
-            return Some(PlayerEvent::RequestMark(*player, position));
+            return Some(position);
         }
     }
 
@@ -66,21 +126,94 @@ fn process_keyboard_input(
 }
 
 fn process_mouse_input(
-    player: &Player,
     mouse_position: &MousePosition,
     input: &InputHandler<InputBindingTypes>,
-) -> Option<PlayerEvent> {
+) -> Option<ttt::game::Position> {
     // Check if the player is requesting a move be placed at the mouse cursor.
     if input
         .action_is_down(&ActionBinding::PlaceMarkAtMouse)
         .unwrap_or(false)
     {
-        Some(PlayerEvent::RequestMark(*player, mouse_position.grid))
+        Some(mouse_position.grid)
     } else {
         None
     }
 }
 
+// Updates the board picker if a key for one of the boards being played on is pressed.
+fn process_board_selection(
+    game_logic: &GameLogic,
+    input: &InputHandler<InputBindingTypes>,
+    board_picker: &mut BoardPicker,
+) {
+    for board_index in 0..game_logic.game.variant().num_boards() {
+        let select_board = input
+            .action_is_down(&ActionBinding::SelectBoard(board_index))
+            .unwrap_or(false);
+        if select_board {
+            board_picker.selected = board_index;
+        }
+    }
+}
+
+// Updates the number picker if a key for one of the player's available numbers is pressed.
+fn process_number_selection(
+    game: &variants::NumericalGame,
+    input: &InputHandler<InputBindingTypes>,
+    number_picker: &mut NumberPicker,
+) {
+    for number in game.available_numbers() {
+        let select_number = input
+            .action_is_down(&ActionBinding::SelectNumber(number))
+            .unwrap_or(false);
+        if select_number {
+            number_picker.selected = Some(number);
+        }
+    }
+}
+
+// Builds the player event for placing or moving a mark to the position.
+//
+// When placing either symbol, an X is placed unless the alternate symbol is
+// requested. None is returned if the player still needs to pick what to place
+// or which mark to move.
+fn position_to_player_event(
+    player: &Player,
+    position: ttt::game::Position,
+    game_logic: &GameLogic,
+    number_picker: &NumberPicker,
+    selected_mark: &SelectedMark,
+    alternate_symbol: bool,
+) -> Option<PlayerEvent> {
+    match &game_logic.game {
+        variants::Game::Classic(_) | variants::Game::Notakto(_) => {
+            Some(PlayerEvent::RequestMark(*player, position))
+        }
+        variants::Game::Numerical(_) => number_picker
+            .selected
+            .map(|number| PlayerEvent::RequestNumber(*player, position, number)),
+        variants::Game::ThreeMensMorris(game) if game.is_placing() => {
+            Some(PlayerEvent::RequestMark(*player, position))
+        }
+        variants::Game::ThreeMensMorris(_) => selected_mark
+            .position
+            .map(|from| PlayerEvent::RequestMove(*player, from, position)),
+        variants::Game::OrderAndChaos(_) => {
+            let symbol = if alternate_symbol {
+                ttt::board::Owner::PlayerO
+            } else {
+                ttt::board::Owner::PlayerX
+            };
+            Some(PlayerEvent::RequestSymbol(*player, position, symbol))
+        }
+        // With gravity, the mark drops down the column that was selected.
+        variants::Game::Custom(game) => Some(PlayerEvent::RequestMark(
+            *player,
+            game.landing_position(position),
+        )),
+    }
+}
+
 // Converts the provided position to an action binding.
 fn position_to_action_binding(position: &ttt::game::Position) -> ActionBinding {
     ActionBinding::PlaceMark(position.row, position.column)
@@ -99,4 +232,135 @@ mod tests {
 
         assert_eq!(actual_action_binding, expected_action_binding);
     }
+
+    #[test]
+    fn position_to_player_event_when_numerical_and_no_number_picked_should_return_none() {
+        let position = ttt::game::Position { row: 1, column: 1 };
+        let game_logic = GameLogic::new(variants::Variant::Numerical);
+        let number_picker = NumberPicker::default();
+
+        let player_event = position_to_player_event(
+            &Player::X,
+            position,
+            &game_logic,
+            &number_picker,
+            &SelectedMark::default(),
+            false,
+        );
+
+        assert_eq!(player_event, None);
+    }
+
+    #[test]
+    fn position_to_player_event_when_numerical_should_request_picked_number() {
+        let position = ttt::game::Position { row: 1, column: 1 };
+        let game_logic = GameLogic::new(variants::Variant::Numerical);
+        let number_picker = NumberPicker { selected: Some(5) };
+        let expected_event = PlayerEvent::RequestNumber(Player::X, position, 5);
+
+        let player_event = position_to_player_event(
+            &Player::X,
+            position,
+            &game_logic,
+            &number_picker,
+            &SelectedMark::default(),
+            false,
+        );
+
+        assert_eq!(player_event, Some(expected_event));
+    }
+
+    #[test]
+    fn position_to_player_event_when_three_mens_morris_and_placing_should_request_mark() {
+        let position = ttt::game::Position { row: 1, column: 1 };
+        let game_logic = GameLogic::new(variants::Variant::ThreeMensMorris);
+        let selected_mark = SelectedMark::default();
+        let expected_event = PlayerEvent::RequestMark(Player::X, position);
+
+        let player_event = position_to_player_event(
+            &Player::X,
+            position,
+            &game_logic,
+            &NumberPicker::default(),
+            &selected_mark,
+            false,
+        );
+
+        assert_eq!(player_event, Some(expected_event));
+    }
+
+    #[test]
+    fn position_to_player_event_when_three_mens_morris_and_moving_should_request_move() {
+        let from = ttt::game::Position { row: 0, column: 0 };
+        let to = ttt::game::Position { row: 1, column: 1 };
+        let mut game_logic = GameLogic::new(variants::Variant::ThreeMensMorris);
+        for (row, column) in &[(0, 0), (2, 2), (0, 1), (1, 0), (1, 2), (2, 1)] {
+            let position = ttt::game::Position {
+                row: *row,
+                column: *column,
+            };
+            game_logic.game.do_move(&variants::Move::Mark(position));
+        }
+        let selected_mark = SelectedMark {
+            position: Some(from),
+        };
+        let expected_event = PlayerEvent::RequestMove(Player::X, from, to);
+
+        let player_event = position_to_player_event(
+            &Player::X,
+            to,
+            &game_logic,
+            &NumberPicker::default(),
+            &selected_mark,
+            false,
+        );
+
+        assert_eq!(player_event, Some(expected_event));
+    }
+
+    #[test]
+    fn position_to_player_event_when_order_and_chaos_and_alternate_symbol_should_request_o() {
+        let position = ttt::game::Position { row: 4, column: 5 };
+        let game_logic = GameLogic::new(variants::Variant::OrderAndChaos);
+        let expected_event =
+            PlayerEvent::RequestSymbol(Player::O, position, ttt::board::Owner::PlayerO);
+
+        let player_event = position_to_player_event(
+            &Player::O,
+            position,
+            &game_logic,
+            &NumberPicker::default(),
+            &SelectedMark::default(),
+            true,
+        );
+
+        assert_eq!(player_event, Some(expected_event));
+    }
+
+    #[test]
+    fn position_to_player_event_when_custom_with_gravity_should_request_lowest_free_square() {
+        let rules = variants::CustomRules {
+            name: "Gravity".to_string(),
+            board_size: 4,
+            win_length: 3,
+            gravity: true,
+            misere: false,
+            starting_position: Vec::new(),
+        };
+        let variant = variants::Variant::Custom(Box::leak(Box::new(rules)));
+        let game_logic = GameLogic::new(variant);
+        let expected_event =
+            PlayerEvent::RequestMark(Player::X, ttt::game::Position { row: 0, column: 2 });
+
+        let player_event = position_to_player_event(
+            &Player::X,
+            ttt::game::Position { row: 3, column: 2 },
+            &game_logic,
+            &NumberPicker::default(),
+            &SelectedMark::default(),
+            false,
+        );
+
+        assert_eq!(player_event, Some(expected_event));
+    }
 }
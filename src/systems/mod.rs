@@ -6,16 +6,26 @@
 use amethyst::{core::bundle::SystemBundle, ecs, utils::ortho_camera::CameraOrthoSystem};
 
 mod ai_player;
+mod blocked_squares;
+mod external_player;
 mod game_state_display;
+mod highlighted_squares;
 mod local_player;
 mod mouse_hover_debug_box;
 mod mouse_raycast;
+mod remote_player;
+mod selected_mark_highlight;
 
 use self::ai_player::*;
+use self::blocked_squares::*;
+use self::external_player::*;
 use self::game_state_display::*;
+use self::highlighted_squares::*;
 use self::local_player::*;
 use self::mouse_hover_debug_box::*;
 use self::mouse_raycast::*;
+use self::remote_player::*;
+use self::selected_mark_highlight::*;
 
 /// Bundle containing the game's main systems.
 ///
@@ -30,6 +40,8 @@ impl<'a, 'b> SystemBundle<'a, 'b> for GameBundle {
         builder: &mut ecs::DispatcherBuilder<'a, 'b>,
     ) -> Result<(), amethyst::Error> {
         builder.add(AiPlayerSystem, "ai_player_system", &[]);
+        builder.add(ExternalPlayerSystem, "external_player_system", &[]);
+        builder.add(RemotePlayerSystem, "remote_player_system", &[]);
         builder.add(
             MouseRaycastSystem,
             "mouse_raycast_system",
@@ -41,6 +53,13 @@ impl<'a, 'b> SystemBundle<'a, 'b> for GameBundle {
             &["input_system", "mouse_raycast_system"],
         );
         builder.add(GameStateDisplaySystem, "game_state_display_system", &[]);
+        builder.add(
+            SelectedMarkHighlightSystem,
+            "selected_mark_highlight_system",
+            &[],
+        );
+        builder.add(HighlightedSquaresSystem, "highlighted_squares_system", &[]);
+        builder.add(BlockedSquaresSystem, "blocked_squares_system", &[]);
         Ok(())
     }
 }
@@ -17,18 +17,18 @@ impl<'a> System<'a> for MouseHoverDebugBoxSystem {
         Write<'a, DebugLines>,
         ReadStorage<'a, MouseHoverDebugBox>,
         Read<'a, MousePosition>,
-        Read<'a, Grid>,
+        Read<'a, Grids>,
         Read<'a, GameLogic>,
     );
 
     fn run(
         &mut self,
-        (mut debug_lines, mouse_hover_debug_boxes, mouse_position, grid, game_logic): Self::SystemData,
+        (mut debug_lines, mouse_hover_debug_boxes, mouse_position, grids, game_logic): Self::SystemData,
     ) {
         for (component,) in (&mouse_hover_debug_boxes,).join() {
             let show_box = should_show_hover_box(component, &mouse_position, &game_logic);
             if show_box {
-                let square = grid.position_to_square(mouse_position.grid);
+                let square = grids.position_to_square(mouse_position.grid);
                 debug_lines.draw_rectangle(
                     square.bottom_left().xy(),
                     square.top_right().xy(),
@@ -26,7 +26,7 @@ impl<'a> System<'a> for MouseRaycastSystem {
         Read<'a, ActiveCamera>,
         ReadExpect<'a, ScreenDimensions>,
         Read<'a, InputHandler<InputBindingTypes>>,
-        Read<'a, Grid>,
+        Read<'a, Grids>,
     );
 
     fn run(
@@ -39,7 +39,7 @@ impl<'a> System<'a> for MouseRaycastSystem {
             active_camera,
             screen_dimensions,
             input,
-            grid,
+            grids,
         ): Self::SystemData,
     ) {
         // Get the mouse position if its available.
@@ -54,7 +54,7 @@ impl<'a> System<'a> for MouseRaycastSystem {
                 update_mouse_position(
                     &mut mouse_position,
                     &screen_dimensions,
-                    &grid,
+                    &grids,
                     mouse,
                     camera,
                     camera_transform,
@@ -68,7 +68,7 @@ impl<'a> System<'a> for MouseRaycastSystem {
 fn update_mouse_position(
     mouse_position: &mut MousePosition,
     screen_dimensions: &ScreenDimensions,
-    grid: &Grid,
+    grids: &Grids,
     mouse: (f32, f32),
     camera: &Camera,
     camera_transform: &Transform,
@@ -79,7 +79,7 @@ fn update_mouse_position(
         screen_dimensions.diagonal(),
         &camera_transform,
     );
-    let grid_position = grid.point_to_position(world_point);
+    let grid_position = grids.point_to_position(world_point);
 
     mouse_position.screen = screen_point;
     mouse_position.world = world_point;
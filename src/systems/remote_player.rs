@@ -0,0 +1,44 @@
+use amethyst::{
+    core::shrev::EventChannel,
+    derive::SystemDesc,
+    ecs::prelude::{Join, ReadStorage, System, SystemData, Write, WriteStorage},
+};
+
+use crate::components::*;
+use crate::events::PlayerEvent;
+use crate::network::Message;
+
+/// Generates player events for players on other computers from the messages
+/// they send.
+///
+/// The moves are checked by the game state like any other player's, so a
+/// player can only make moves for their own mark on their own turn.
+#[derive(SystemDesc)]
+pub struct RemotePlayerSystem;
+
+impl<'a> System<'a> for RemotePlayerSystem {
+    type SystemData = (
+        ReadStorage<'a, Player>,
+        WriteStorage<'a, RemotePlayer>,
+        Write<'a, EventChannel<PlayerEvent>>,
+    );
+
+    fn run(&mut self, (players, mut remote_players, mut channel): Self::SystemData) {
+        for (player, remote_player) in (&players, &mut remote_players).join() {
+            for message in remote_player.connection.poll() {
+                match message {
+                    Message::Move {
+                        player: mover,
+                        game_move,
+                    } if mover == *player => {
+                        channel.single_write(PlayerEvent::new(mover, game_move.to_move()));
+                    }
+                    Message::NextGame => remote_player.is_next_game_requested = true,
+                    Message::TimeOut { player } => remote_player.timed_out_player = Some(player),
+                    Message::Refused { reason } => remote_player.connection.close(&reason),
+                    message => log::warn!("Ignored unexpected message: {:?}", message),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::prelude::{Read, System, SystemData, Write},
+    renderer::{debug_drawing::DebugLines, palette::Srgba},
+};
+
+use crate::math::*;
+use crate::resources::*;
+
+// The size of the highlight, relative to the square size.
+const HIGHLIGHT_SIZE_FACTOR: f32 = 0.9;
+
+/// Draws a box around the mark a local player has picked up to move.
+#[derive(SystemDesc)]
+pub struct SelectedMarkHighlightSystem;
+
+impl<'a> System<'a> for SelectedMarkHighlightSystem {
+    type SystemData = (
+        Write<'a, DebugLines>,
+        Read<'a, SelectedMark>,
+        Read<'a, Grids>,
+    );
+
+    fn run(&mut self, (mut debug_lines, selected_mark, grids): Self::SystemData) {
+        if let Some(position) = selected_mark.position {
+            let square = grids.position_to_square(position);
+            let highlight = Square::new(square.center(), square.size() * HIGHLIGHT_SIZE_FACTOR);
+            debug_lines.draw_rectangle(
+                highlight.bottom_left().xy(),
+                highlight.top_right().xy(),
+                highlight.center().z,
+                Srgba::new(0.9, 0.6, 0.1, 1.0),
+            );
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Holds helpers shared by the unit tests.
+use open_ttt_lib as ttt;
+
+/// Gets the position at the provided row and column.
+pub fn position(row: i32, column: i32) -> ttt::game::Position {
+    ttt::game::Position { row, column }
+}
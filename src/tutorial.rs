@@ -0,0 +1,188 @@
+//! Contains the steps of the tutorial that teaches new players the game.
+//!
+//! Each step starts from a scripted board and asks the player to place an X
+//! in one of the highlighted squares, sometimes using a particular input
+//! device. The steps build up from placing marks to winning, blocking, and
+//! making forks.
+use open_ttt_lib as ttt;
+
+use crate::variants;
+
+/// The devices the player can pick squares with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InputDevice {
+    Mouse,
+    Keyboard,
+}
+
+/// The result of checking the player's move against a tutorial step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StepCheck {
+    /// The player did what the step asked.
+    Done,
+
+    /// The player picked a square that is not highlighted.
+    WrongSquare,
+
+    /// The player picked the square with a different input device than the
+    /// step asked for.
+    WrongDevice,
+}
+
+/// A single step of the tutorial.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TutorialStep {
+    /// Explains what the step teaches and what the player should do.
+    pub instructions: &'static str,
+
+    /// The marks on the board when the step starts, top row first. X always
+    /// moves next.
+    pub position: [&'static str; 3],
+
+    /// The squares the player is asked to place their X in. Steps without
+    /// targets only show the instructions.
+    pub targets: Vec<ttt::game::Position>,
+
+    /// The device the player must use to pick the square, `None` if either
+    /// device can be used.
+    pub device: Option<InputDevice>,
+}
+
+impl TutorialStep {
+    /// Creates the game the step starts from.
+    pub fn game(&self) -> ttt::game::Game {
+        let rows: Vec<String> = self.position.iter().map(|row| row.to_string()).collect();
+        let board = variants::board_from_rows(&rows, 3).expect("Tutorial positions are valid.");
+        variants::classic_game_from_board(&board, true).expect("Tutorial positions are legal.")
+    }
+
+    /// Checks if placing an X at the position with the device does what the
+    /// step asks.
+    pub fn check_move(&self, position: ttt::game::Position, device: InputDevice) -> StepCheck {
+        if !self.targets.contains(&position) {
+            StepCheck::WrongSquare
+        } else if self.device.is_some_and(|step_device| step_device != device) {
+            StepCheck::WrongDevice
+        } else {
+            StepCheck::Done
+        }
+    }
+}
+
+/// Gets the steps of the tutorial in the order they are played.
+pub fn tutorial_steps() -> Vec<TutorialStep> {
+    let position = |row, column| ttt::game::Position { row, column };
+    vec![
+        TutorialStep {
+            instructions: "Welcome to FossXO! You play X, and the first player to get three \
+                           marks in a row wins. Click the highlighted square to place an X.",
+            position: ["...", "...", "..."],
+            targets: vec![position(1, 1)],
+            device: Some(InputDevice::Mouse),
+        },
+        TutorialStep {
+            instructions: "Squares can also be picked with the keyboard, using keys laid out \
+                           like the board. Press the key for the highlighted corner.",
+            position: ["...", ".X.", "..O"],
+            targets: vec![position(2, 0)],
+            device: Some(InputDevice::Keyboard),
+        },
+        TutorialStep {
+            instructions: "You have two in a row. Place an X in the highlighted square to \
+                           complete the line and win.",
+            position: ["X.O", ".X.", "O.."],
+            targets: vec![position(0, 2)],
+            device: None,
+        },
+        TutorialStep {
+            instructions: "O has two in a row and wins next turn unless you stop it. Block the \
+                           line by placing an X in the highlighted square.",
+            position: ["OX.", ".O.", "X.."],
+            targets: vec![position(0, 2)],
+            device: None,
+        },
+        TutorialStep {
+            instructions: "A fork makes two lines of two at once, and O can only block one of \
+                           them. Place an X in the highlighted square to make a fork.",
+            position: ["X..", "OOX", "..."],
+            targets: vec![position(2, 2)],
+            device: None,
+        },
+        TutorialStep {
+            instructions: "O blocked the top row, but your right column is still open. \
+                           Complete it to win.",
+            position: ["XOX", "OOX", "..."],
+            targets: vec![position(0, 2)],
+            device: None,
+        },
+        TutorialStep {
+            instructions: "You are ready to play! Pick Single-player from the main menu to play \
+                           the computer. The tutorial can be played again from Help.",
+            position: ["...", "...", "..."],
+            targets: Vec::new(),
+            device: None,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    #[test]
+    fn tutorial_steps_targets_should_be_free_squares_with_x_to_move() {
+        for step in tutorial_steps() {
+            let game = step.game();
+
+            assert_eq!(game.state(), ttt::game::State::PlayerXMove);
+            for target in &step.targets {
+                assert!(game.can_move(*target), "{}", step.instructions);
+            }
+        }
+    }
+
+    #[test]
+    fn tutorial_steps_winning_steps_should_win() {
+        let steps = tutorial_steps();
+
+        for index in &[2, 5] {
+            let step = &steps[*index];
+            let mut game = step.game();
+            let state = game.do_move(step.targets[0]).unwrap();
+
+            assert!(
+                matches!(state, ttt::game::State::PlayerXWin(_)),
+                "{}",
+                step.instructions
+            );
+        }
+    }
+
+    #[test]
+    fn tutorial_step_check_move_when_target_with_wrong_device_should_be_wrong_device() {
+        let step = &tutorial_steps()[0];
+
+        let check = step.check_move(position(1, 1), InputDevice::Keyboard);
+
+        assert_eq!(check, StepCheck::WrongDevice);
+    }
+
+    #[test]
+    fn tutorial_step_check_move_when_other_square_should_be_wrong_square() {
+        let step = &tutorial_steps()[0];
+
+        let check = step.check_move(position(0, 0), InputDevice::Mouse);
+
+        assert_eq!(check, StepCheck::WrongSquare);
+    }
+
+    #[test]
+    fn tutorial_step_check_move_when_any_device_allowed_should_be_done() {
+        let step = &tutorial_steps()[3];
+
+        let check = step.check_move(position(0, 2), InputDevice::Keyboard);
+
+        assert_eq!(check, StepCheck::Done);
+    }
+}
@@ -1,15 +1,31 @@
 use amethyst::ecs;
 use amethyst::prelude::*;
-use amethyst::ui::{Anchor, UiButton, UiButtonBuilder, UiEventType, UiLabelBuilder};
+use amethyst::ui::{Anchor, LineMode, UiButton, UiButtonBuilder, UiEventType, UiLabelBuilder};
 use contracts::*;
 
 use super::*;
-use crate::{components, events};
+use crate::{components, events, resources};
+
+// The width and height of the number picker buttons.
+const NUMBER_BUTTON_SIZE: f32 = 64.0;
+// Spacing between the number picker buttons.
+const NUMBER_BUTTON_MARGIN: f32 = 10.0;
+// The size of the buttons down the side of the screen, which fit between the
+// edge of the screen and the board at the minimum window size.
+const SIDE_BUTTON_WIDTH: f32 = 140.0;
+const SIDE_BUTTON_HEIGHT: f32 = 50.0;
+const SIDE_BUTTON_FONT_SIZE: f32 = 30.0;
+// Spacing between the buttons down the side of the screen.
+const SIDE_BUTTON_MARGIN: f32 = 10.0;
 
 pub struct GameControls<TData, TReturn = ()> {
     owned_entities: Vec<ecs::Entity>,
     observers: EntityObservers<TData, TReturn>,
     game_over_button: Option<UiButton>,
+    // The number picker buttons along with the number each one selects.
+    number_buttons: Vec<(UiButton, u8)>,
+    // How far below the top of the screen the next side button goes.
+    next_side_button_y_offset: f32,
 }
 
 impl<TData, TReturn> GameControls<TData, TReturn> {
@@ -18,6 +34,8 @@ impl<TData, TReturn> GameControls<TData, TReturn> {
             owned_entities: Vec::new(),
             observers: EntityObservers::new(),
             game_over_button: None,
+            number_buttons: Vec::new(),
+            next_side_button_y_offset: 100.0,
         }
     }
 
@@ -26,8 +44,10 @@ impl<TData, TReturn> GameControls<TData, TReturn> {
     /// Any previously created widgets are invalid and should no longer be used.
     #[post(self.owned_entities.is_empty())]
     #[post(self.game_over_button.is_none())]
+    #[post(self.number_buttons.is_empty())]
     pub fn delete(&mut self, world: &mut ecs::World) {
         self.hide_game_over_button(world);
+        self.hide_number_picker(world);
 
         world
             .delete_entities(self.owned_entities.as_slice())
@@ -39,13 +59,21 @@ impl<TData, TReturn> GameControls<TData, TReturn> {
     /// Handles the provided UI event.
     ///
     /// The callback associated with the event, if any, is returned for the caller to invoke.
+    /// Clicking a number picker button stores the number in the `NumberPicker` resource.
     pub fn handle_ui_event(
         &mut self,
-        _world: &mut ecs::World,
+        world: &mut ecs::World,
         ui_event: &events::UiEvent,
     ) -> Option<&fn(&mut TData, &mut ecs::World) -> TReturn> {
         match ui_event.event_type {
-            UiEventType::Click => self.observers.get(ui_event.target),
+            UiEventType::Click => {
+                if let Some(number) = self.number_for_button(ui_event.target) {
+                    world.write_resource::<resources::NumberPicker>().selected = Some(number);
+                    None
+                } else {
+                    self.observers.get(ui_event.target)
+                }
+            }
             _ => None,
         }
     }
@@ -71,10 +99,40 @@ impl<TData, TReturn> GameControls<TData, TReturn> {
         self.observers.add(button.image_entity, on_press);
     }
 
-    /// Show's the game over button.
+    /// Adds a button with the provided text below any other buttons down the
+    /// left side of the screen.
+    pub fn add_side_button(
+        &mut self,
+        world: &mut ecs::World,
+        text: &str,
+        on_press: fn(&mut TData, &mut ecs::World) -> TReturn,
+    ) {
+        let style = world.read_resource::<Style>();
+        let (_button_id, button) = UiButtonBuilder::<(), u32>::new(text)
+            .with_font(style.button.text.font.clone())
+            .with_font_size(SIDE_BUTTON_FONT_SIZE)
+            .with_text_color(style.button.text.color)
+            .with_image(style.button.normal.clone())
+            .with_hover_image(style.button.hover.clone())
+            .with_press_image(style.button.press.clone())
+            .with_anchor(Anchor::TopLeft)
+            .with_position(
+                SIDE_BUTTON_WIDTH / 2.0 + SIDE_BUTTON_MARGIN,
+                -self.next_side_button_y_offset,
+            )
+            .with_size(SIDE_BUTTON_WIDTH, SIDE_BUTTON_HEIGHT)
+            .build_from_world(&world);
+        self.next_side_button_y_offset += SIDE_BUTTON_HEIGHT + SIDE_BUTTON_MARGIN;
+
+        self.add_owned_button(&button);
+        self.observers.add(button.image_entity, on_press);
+    }
+
+    /// Show's the game over button with the provided text.
     pub fn show_game_over_button(
         &mut self,
         world: &mut ecs::World,
+        text: &str,
         on_press: fn(&mut TData, &mut ecs::World) -> TReturn,
     ) {
         // Remove any old game over buttons.
@@ -82,7 +140,7 @@ impl<TData, TReturn> GameControls<TData, TReturn> {
 
         let style = world.read_resource::<Style>();
 
-        let (_button_id, button) = UiButtonBuilder::<(), u32>::new("Play Again?")
+        let (_button_id, button) = UiButtonBuilder::<(), u32>::new(text)
             .with_font(style.button.text.font.clone())
             .with_font_size(style.button.text.font_size)
             .with_text_color(style.button.text.color)
@@ -111,9 +169,92 @@ impl<TData, TReturn> GameControls<TData, TReturn> {
         }
     }
 
+    /// Shows a row of buttons for picking which of the provided numbers to place.
+    pub fn show_number_picker(&mut self, world: &mut ecs::World, numbers: &[u8]) {
+        // Remove the buttons for any previously shown numbers.
+        self.hide_number_picker(world);
+
+        let style = world.read_resource::<Style>();
+
+        // Center the row of buttons along the bottom of the screen.
+        let spacing = NUMBER_BUTTON_SIZE + NUMBER_BUTTON_MARGIN;
+        let first_x = -spacing * (numbers.len() as f32 - 1.0) / 2.0;
+        for (index, number) in numbers.iter().enumerate() {
+            let (_button_id, button) = UiButtonBuilder::<(), u32>::new(number.to_string())
+                .with_font(style.button.text.font.clone())
+                .with_font_size(style.button.text.font_size)
+                .with_text_color(style.button.text.color)
+                .with_image(style.button.normal.clone())
+                .with_hover_image(style.button.hover.clone())
+                .with_press_image(style.button.press.clone())
+                .with_anchor(Anchor::BottomMiddle)
+                .with_position(first_x + spacing * index as f32, 50.0)
+                .with_size(NUMBER_BUTTON_SIZE, NUMBER_BUTTON_SIZE)
+                .build_from_world(&world);
+
+            self.number_buttons.push((button, *number));
+        }
+    }
+
+    /// Hides the number picker buttons.
+    pub fn hide_number_picker(&mut self, world: &mut ecs::World) {
+        for (button, _number) in self.number_buttons.drain(..) {
+            world
+                .delete_entity(button.text_entity)
+                .expect("Unable to delete number picker button.");
+            world
+                .delete_entity(button.image_entity)
+                .expect("Unable to delete number picker button.");
+        }
+    }
+
     /// Sets the status text along with extra information.
     pub fn set_status(&mut self, world: &mut ecs::World, extra_information: Vec<String>) {
-        let (_id, label) = UiLabelBuilder::<u32>::new("")
+        let ui_text = self.create_status_label(world, "");
+
+        // Create the game state entity that gets updated to reflect the state of the game.
+        self.owned_entities.push(
+            world
+                .create_entity()
+                .with(components::GameStateText {
+                    ui_text,
+                    extra_information,
+                })
+                .build(),
+        );
+    }
+
+    /// Shows the provided text where the status normally goes.
+    ///
+    /// Unlike the status, the text does not follow the state of the game. This
+    /// is used when there is no game being played, such as in the position
+    /// editor.
+    pub fn set_message(&mut self, world: &mut ecs::World, text_parts: &[String]) {
+        // Use vertical bars to separate each part of the text, like the status.
+        self.create_status_label(world, &text_parts.join(" | "));
+    }
+
+    /// Shows the text over the bottom of the screen, wrapping it onto several
+    /// lines as needed.
+    ///
+    /// This is used for longer text than fits in the status, such as the
+    /// tutorial's instructions.
+    pub fn set_overlay_text(&mut self, world: &mut ecs::World, text: &str) {
+        let (_id, label) = UiLabelBuilder::<u32>::new(text)
+            .with_anchor(Anchor::BottomMiddle)
+            .with_align(Anchor::Middle)
+            .with_line_mode(LineMode::Wrap)
+            .with_size(760.0, 80.0)
+            .with_position(0.0, 40.0)
+            .with_font_size(22.0)
+            .with_text_color([1.0, 1.0, 1.0, 1.0])
+            .build_from_world(world);
+        self.owned_entities.push(label.text_entity);
+    }
+
+    // Creates the label the status is shown in, returning its text entity.
+    fn create_status_label(&mut self, world: &mut ecs::World, text: &str) -> ecs::Entity {
+        let (_id, label) = UiLabelBuilder::<u32>::new(text)
             .with_anchor(Anchor::TopLeft)
             .with_align(Anchor::MiddleLeft)
             .with_size(600.0, 84.0)
@@ -124,17 +265,15 @@ impl<TData, TReturn> GameControls<TData, TReturn> {
             .with_text_color([1.0, 1.0, 1.0, 1.0])
             .build_from_world(world);
         self.owned_entities.push(label.text_entity);
+        label.text_entity
+    }
 
-        // Create the game state entity that gets updated to reflect the state of the game.
-        self.owned_entities.push(
-            world
-                .create_entity()
-                .with(components::GameStateText {
-                    ui_text: label.text_entity,
-                    extra_information,
-                })
-                .build(),
-        );
+    // Gets the number selected by the provided number picker button entity.
+    fn number_for_button(&self, entity: ecs::Entity) -> Option<u8> {
+        self.number_buttons
+            .iter()
+            .find(|(button, _number)| button.image_entity == entity)
+            .map(|(_button, number)| *number)
     }
 
     // Adds the entities in the provided button to the list of owned entities.
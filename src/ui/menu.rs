@@ -11,6 +11,8 @@ const CLOSE_TAB_ORDER: u32 = 100;
 const SEPARATOR_HEIGHT: f32 = 20.0;
 // Spacing between elements.
 const MARGIN: f32 = 10.0;
+// Height of a line of paragraph text.
+const PARAGRAPH_HEIGHT: f32 = 40.0;
 
 /// Allows creating Menus related widgets, provides UI event handling logic, and
 /// holds the underlying entities.
@@ -27,7 +29,7 @@ impl<TData, TReturn> Menu<TData, TReturn> {
         Self {
             owned_entities: Vec::new(),
             observers: EntityObservers::new(),
-            next_y_offset: 150.0,
+            next_y_offset: 190.0,
             next_tab_order: 1,
         }
     }
@@ -111,12 +113,15 @@ impl<TData, TReturn> Menu<TData, TReturn> {
     /// Adds a button to the menu.
     ///
     /// The order in which this method is called determines the order buttons appear in the menu.
+    ///
+    /// The entity targeted by the button's UI events is returned. This lets
+    /// buttons that share a callback be told apart.
     pub fn add_button(
         &mut self,
         world: &mut ecs::World,
         text: &str,
         on_press: fn(&mut TData, &mut ecs::World) -> TReturn,
-    ) {
+    ) -> ecs::Entity {
         let style = world.read_resource::<Style>();
 
         let (_button_id, button) = initialize_button(text, &style)
@@ -128,6 +133,45 @@ impl<TData, TReturn> Menu<TData, TReturn> {
         self.next_tab_order += 1;
         self.add_owned_button(&button);
         self.observers.add(button.image_entity, on_press);
+        button.image_entity
+    }
+
+    /// Adds a line of text to the menu, such as information about the
+    /// buttons around it.
+    ///
+    /// Like buttons, the order in which this method is called determines the
+    /// order the text appears in the menu.
+    pub fn add_paragraph(&mut self, world: &mut ecs::World, text: &str) {
+        let (paragraph_style, button_height) = {
+            let style = world.read_resource::<Style>();
+            (style.paragraph.clone(), style.button.height)
+        };
+
+        let text = UiText::new(
+            paragraph_style.font,
+            text.to_string(),
+            paragraph_style.color,
+            paragraph_style.font_size,
+            LineMode::Single,
+            Anchor::Middle,
+        );
+
+        // Buttons are positioned by their center, so the paragraph is moved up
+        // to line up its top edge with where a button's would be.
+        let transform = UiTransform::new(
+            "paragraph".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.0,
+            self.next_y_offset + (button_height - PARAGRAPH_HEIGHT) / 2.0,
+            1.0,
+            800.0,
+            PARAGRAPH_HEIGHT,
+        );
+
+        let paragraph_entity = world.create_entity().with(text).with(transform).build();
+        self.owned_entities.push(paragraph_entity);
+        self.next_y_offset -= PARAGRAPH_HEIGHT + MARGIN;
     }
 
     /// Adds a separator between the current content.
@@ -54,7 +54,10 @@ pub fn load_style(world: &mut World) {
         hamburger_button,
         title_text,
         menu,
-        paragraph: label.clone(),
+        paragraph: TextStyle {
+            font_size: 30.0,
+            ..label.clone()
+        },
         label,
     };
 
@@ -0,0 +1,266 @@
+use contracts::*;
+use open_ttt_lib as ttt;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::*;
+
+/// Creates a classic game with the marks on the board, where the indicated
+/// player moves next.
+///
+/// Classic games always start from an empty board, so the marks are played
+/// in turn starting with the player that must have moved first.
+///
+/// # Errors
+/// An error describing the problem is returned if the board is not 3 x 3, the
+/// number of marks does not allow the player to move next, or there is
+/// already three in a row. Positions where both players have three in a row
+/// could never occur, so they are reported separately.
+pub fn classic_game_from_board(
+    board: &ttt::board::Board,
+    is_x_move: bool,
+) -> Result<ttt::game::Game, String> {
+    let size = board.size();
+    if size.rows != 3 || size.columns != 3 {
+        return Err(format!(
+            "classic games are played on a 3 x 3 board, found {} x {}",
+            size.rows, size.columns
+        ));
+    }
+
+    let x_count = count_marks(board, ttt::board::Owner::PlayerX);
+    let o_count = count_marks(board, ttt::board::Owner::PlayerO);
+    let x_started = match (is_x_move, x_count as i32 - o_count as i32) {
+        (true, 0) | (false, 1) => true,
+        (true, -1) | (false, 0) => false,
+        _ => {
+            return Err(format!(
+                "{} X and {} O marks do not allow {} to move next",
+                x_count,
+                o_count,
+                if is_x_move { "X" } else { "O" }
+            ))
+        }
+    };
+
+    let has_line = |owner| {
+        board
+            .iter()
+            .any(|(position, square)| square == owner && longest_line(board, position, 3).is_some())
+    };
+    if has_line(ttt::board::Owner::PlayerX) && has_line(ttt::board::Owner::PlayerO) {
+        return Err("both X and O have three in a row".to_string());
+    }
+
+    let mut game = ttt::game::Game::new();
+    let first_move = if x_started {
+        ttt::game::State::PlayerXMove
+    } else {
+        ttt::game::State::PlayerOMove
+    };
+    if game.state() != first_move {
+        game.start_next_game();
+    }
+
+    let marks = |owner| {
+        board
+            .iter()
+            .filter(move |(_position, square)| *square == owner)
+            .map(|(position, _square)| position)
+    };
+    let (first_marks, mut second_marks) = if x_started {
+        (
+            marks(ttt::board::Owner::PlayerX),
+            marks(ttt::board::Owner::PlayerO),
+        )
+    } else {
+        (
+            marks(ttt::board::Owner::PlayerO),
+            marks(ttt::board::Owner::PlayerX),
+        )
+    };
+    let already_won = || "already has three in a row".to_string();
+    for position in first_marks {
+        game.do_move(position).map_err(|_| already_won())?;
+        if let Some(position) = second_marks.next() {
+            game.do_move(position).map_err(|_| already_won())?;
+        }
+    }
+    if game.state().is_game_over() {
+        return Err(already_won());
+    }
+
+    Ok(game)
+}
+
+/// Scores a classic game from the point of view of the player to move,
+/// assuming both players play perfectly from here on.
+///
+/// Positive scores are wins, negative scores are losses, and zero is a cat's
+/// game. Wins and losses score one more than the number of squares still free
+/// when the game ends, so quicker wins and slower losses score higher.
+///
+/// The score is looked up in the shared `PerfectPlayTable`.
+#[pre(!game.state().is_game_over())]
+pub fn classic_score(game: &ttt::game::Game) -> i32 {
+    PerfectPlayTable::get()
+        .score(game)
+        .expect("Every game that is not over is in the table.")
+}
+
+/// AI opponent for the classic game that always plays the best move.
+///
+/// `ttt::ai::Opponent` only considers whether a move wins, loses, or draws.
+/// This opponent also takes the quickest win and, when it cannot avoid losing,
+/// holds out for as long as possible. This makes it suited to puzzles where
+/// the player must win within a number of moves.
+#[derive(Clone, Debug, Default)]
+pub struct PerfectOpponent;
+
+impl PerfectOpponent {
+    /// Creates a new perfect opponent.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Gets the position the AI opponent wishes to place its mark.
+    ///
+    /// When several moves are equally good, one of them is picked at random.
+    /// `None` is returned if the game is over.
+    pub fn get_move(&self, game: &ttt::game::Game) -> Option<ttt::game::Position> {
+        PerfectPlayTable::get()
+            .best_moves(game)
+            .choose(&mut rand::thread_rng())
+            .copied()
+    }
+}
+
+/// AI opponent for the classic game that looks up its moves in the shared
+/// `PerfectPlayTable`.
+///
+/// Like `ttt::ai::Opponent`, the opponent sometimes makes a random move based
+/// on its difficulty. Otherwise it plays one of the best moves, so it answers
+/// instantly instead of searching the rest of the game every turn.
+#[derive(Clone, Debug)]
+pub struct ClassicOpponent {
+    mistake_probability: f64,
+}
+
+impl ClassicOpponent {
+    /// Creates a new opponent with the provided difficulty.
+    pub fn new(difficulty: ttt::ai::Difficulty) -> Self {
+        Self {
+            mistake_probability: mistake_probability(difficulty),
+        }
+    }
+
+    /// Gets the position the AI opponent wishes to place its mark.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_move(&self, game: &ttt::game::Game) -> Option<ttt::game::Position> {
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.mistake_probability) {
+            let moves: Vec<ttt::game::Position> = game.free_positions().collect();
+            return moves.choose(&mut rng).copied();
+        }
+
+        PerfectPlayTable::get()
+            .best_moves(game)
+            .choose(&mut rng)
+            .copied()
+    }
+}
+
+/// Scores placing a mark at the free position from the point of view of the
+/// player placing it, scoring like `classic_score`.
+pub fn classic_move_score(game: &ttt::game::Game, position: ttt::game::Position) -> i32 {
+    PerfectPlayTable::get()
+        .move_score(game, position)
+        .expect("Only free positions are scored.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    fn board(rows: &[&str]) -> ttt::board::Board {
+        let rows: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+        board_from_rows(&rows, 3).unwrap()
+    }
+
+    #[test]
+    fn classic_game_from_board_when_o_moves_with_equal_marks_should_be_o_move() {
+        let board = board(&["X..", ".O.", "..."]);
+
+        let game = classic_game_from_board(&board, false).unwrap();
+
+        assert_eq!(game.state(), ttt::game::State::PlayerOMove);
+        assert_eq!(
+            game.board().get(position(2, 0)),
+            Some(ttt::board::Owner::PlayerX)
+        );
+        assert_eq!(
+            game.board().get(position(1, 1)),
+            Some(ttt::board::Owner::PlayerO)
+        );
+    }
+
+    #[test]
+    fn classic_game_from_board_when_too_many_x_marks_should_be_error() {
+        let board = board(&["XX.", "...", "..."]);
+
+        let result = classic_game_from_board(&board, false);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn classic_game_from_board_when_three_in_a_row_should_be_error() {
+        let board = board(&["XXX", "OO.", "..."]);
+
+        let result = classic_game_from_board(&board, false);
+
+        assert_eq!(result.unwrap_err(), "already has three in a row");
+    }
+
+    #[test]
+    fn classic_game_from_board_when_both_players_have_lines_should_be_error() {
+        let board = board(&["XXX", "OOO", "..."]);
+
+        let result = classic_game_from_board(&board, true);
+
+        assert_eq!(result.unwrap_err(), "both X and O have three in a row");
+    }
+
+    #[test]
+    fn classic_score_when_win_available_should_prefer_quickest_win() {
+        let board = board(&["XX.", "OO.", "..."]);
+        let game = classic_game_from_board(&board, true).unwrap();
+
+        let score = classic_score(&game);
+
+        // Winning now leaves four squares free.
+        assert_eq!(score, 5);
+    }
+
+    #[test]
+    fn classic_score_when_empty_board_should_be_cats_game() {
+        let game = ttt::game::Game::new();
+
+        let score = classic_score(&game);
+
+        assert_eq!(score, 0);
+    }
+
+    #[test]
+    fn perfect_opponent_get_move_when_losing_should_block() {
+        let board = board(&["XX.", ".O.", "..."]);
+        let game = classic_game_from_board(&board, false).unwrap();
+        let opponent = PerfectOpponent::new();
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, Some(position(2, 2)));
+    }
+}
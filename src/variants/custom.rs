@@ -0,0 +1,689 @@
+use contracts::*;
+use open_ttt_lib as ttt;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+use std::{error, fmt};
+
+use super::*;
+
+/// The smallest board size custom rules can use.
+pub const MIN_CUSTOM_BOARD_SIZE: i32 = 3;
+
+/// The largest board size custom rules can use.
+pub const MAX_CUSTOM_BOARD_SIZE: i32 = 9;
+
+// The most positions the AI opponent looks at when searching for a move.
+const MAX_SEARCH_POSITIONS: usize = 50_000;
+
+// The score of a line for each mark in it, raised to the number of marks.
+const LINE_SCORE_BASE: i64 = 4;
+
+// The score of a won position, before preferring quicker wins.
+const WIN_SCORE: i64 = 1_000_000_000;
+
+/// A rule set for a variant defined in a data file.
+///
+/// Custom rules are read from RON files, for example:
+/// ```text
+/// (
+///     name: "Gravity",
+///     board_size: 4,
+///     win_length: 3,
+///     gravity: true,
+///     misere: false,
+///     starting_position: [
+///         "....",
+///         "....",
+///         "..O.",
+///         ".XX.",
+///     ],
+/// )
+/// ```
+///
+/// Only the name and win length are required. The starting position lists
+/// the rows from the top of the board down, using `X`, `O`, and `.` for an
+/// empty square.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRules {
+    /// The name of the variant as shown to the player.
+    pub name: String,
+
+    /// The number of rows and columns of the board.
+    #[serde(default = "default_board_size")]
+    pub board_size: i32,
+
+    /// The number of marks in a row needed to complete a line.
+    pub win_length: i32,
+
+    /// Indicates if marks drop to the lowest free square in their column.
+    #[serde(default)]
+    pub gravity: bool,
+
+    /// Indicates if completing a line loses instead of wins.
+    #[serde(default)]
+    pub misere: bool,
+
+    /// The marks on the board when the game starts, top row first.
+    #[serde(default)]
+    pub starting_position: Vec<String>,
+}
+
+impl CustomRules {
+    /// Reads the rules from the provided RON text.
+    ///
+    /// # Errors
+    /// An error is returned if the text is not valid RON, does not have the
+    /// expected fields, or a field has a value that is not allowed. The error
+    /// indicates the field or location in the text that is wrong.
+    pub fn from_ron(text: &str) -> Result<Self, RulesError> {
+        let rules: Self = ron::de::from_str(text).map_err(RulesError::Parse)?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Checks the values of the rules' fields are allowed.
+    ///
+    /// # Errors
+    /// An error is returned for the first field with a value that is not allowed.
+    pub fn validate(&self) -> Result<(), RulesError> {
+        if self.name.trim().is_empty() {
+            return Err(RulesError::invalid("name", "must not be empty"));
+        }
+        if !(MIN_CUSTOM_BOARD_SIZE..=MAX_CUSTOM_BOARD_SIZE).contains(&self.board_size) {
+            return Err(RulesError::invalid(
+                "board_size",
+                format!(
+                    "must be from {} to {}, found {}",
+                    MIN_CUSTOM_BOARD_SIZE, MAX_CUSTOM_BOARD_SIZE, self.board_size
+                ),
+            ));
+        }
+        if !(MIN_CUSTOM_BOARD_SIZE..=self.board_size).contains(&self.win_length) {
+            return Err(RulesError::invalid(
+                "win_length",
+                format!(
+                    "must be from {} to the board size of {}, found {}",
+                    MIN_CUSTOM_BOARD_SIZE, self.board_size, self.win_length
+                ),
+            ));
+        }
+
+        self.validate_starting_position()
+    }
+
+    // Checks the starting position fits on the board and could occur in a game.
+    fn validate_starting_position(&self) -> Result<(), RulesError> {
+        const FIELD: &str = "starting_position";
+        if self.starting_position.is_empty() {
+            return Ok(());
+        }
+
+        let board = board_from_rows(&self.starting_position, self.board_size)
+            .map_err(|message| RulesError::invalid(FIELD, message))?;
+        let (x_count, o_count) = (
+            count_marks(&board, ttt::board::Owner::PlayerX),
+            count_marks(&board, ttt::board::Owner::PlayerO),
+        );
+        if x_count != o_count && x_count != o_count + 1 {
+            return Err(RulesError::invalid(
+                FIELD,
+                format!(
+                    "X must have as many marks as O or one more, found {} X and {} O",
+                    x_count, o_count
+                ),
+            ));
+        }
+        for (position, owner) in board.iter() {
+            if owner == ttt::board::Owner::None {
+                continue;
+            }
+            if longest_line(&board, position, self.win_length).is_some() {
+                return Err(RulesError::invalid(
+                    FIELD,
+                    format!("already has a line of {}", self.win_length),
+                ));
+            }
+            let below = ttt::game::Position {
+                row: position.row - 1,
+                column: position.column,
+            };
+            if self.gravity && board.get(below) == Some(ttt::board::Owner::None) {
+                return Err(RulesError::invalid(
+                    FIELD,
+                    format!(
+                        "row {} has a mark above an empty square, which gravity does not allow",
+                        self.board_size - position.row
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Builds the board with the starting position's marks.
+    //
+    // The board is empty if there is no starting position.
+    fn starting_board(&self) -> ttt::board::Board {
+        board_from_rows(&self.starting_position, self.board_size).unwrap_or_else(|_| {
+            ttt::board::Board::new(ttt::board::Size {
+                rows: self.board_size,
+                columns: self.board_size,
+            })
+        })
+    }
+}
+
+fn default_board_size() -> i32 {
+    MIN_CUSTOM_BOARD_SIZE
+}
+
+/// Errors that can occur when reading custom rules.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RulesError {
+    /// The text is not valid RON or its fields do not match the rules.
+    ///
+    /// Errors in the text include the line and column of the problem, while
+    /// missing or unknown fields include the field's name.
+    Parse(ron::de::Error),
+
+    /// A field has a value that is not allowed.
+    InvalidField {
+        /// The name of the field as written in the file.
+        field: &'static str,
+        /// Describes what is wrong with the value.
+        message: String,
+    },
+}
+
+impl RulesError {
+    // Creates an error for the field with the provided message.
+    fn invalid(field: &'static str, message: impl Into<String>) -> Self {
+        Self::InvalidField {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "{}", error),
+            Self::InvalidField { field, message } => write!(f, "`{}` {}", field, message),
+        }
+    }
+}
+
+impl error::Error for RulesError {}
+
+/// A game played with custom rules.
+///
+/// Players place their own marks like the classic game, but on a board of
+/// any size and needing lines of any length. With gravity, marks are only
+/// placed on the lowest free square of each column. With misère rules, the
+/// player who completes a line loses.
+#[derive(Clone, Debug)]
+pub struct CustomGame {
+    rules: &'static CustomRules,
+    board: ttt::board::Board,
+    state: ttt::game::State,
+    // Indicates if player X made the first move.
+    x_started: bool,
+}
+
+impl CustomGame {
+    /// Creates a new game from the rules' starting position.
+    ///
+    /// Player X goes first unless X already has an extra mark.
+    pub fn new(rules: &'static CustomRules) -> Self {
+        let board = rules.starting_board();
+        let x_started = count_marks(&board, ttt::board::Owner::PlayerX)
+            == count_marks(&board, ttt::board::Owner::PlayerO);
+        let state = if x_started {
+            ttt::game::State::PlayerXMove
+        } else {
+            ttt::game::State::PlayerOMove
+        };
+
+        Self {
+            rules,
+            board,
+            state,
+            x_started,
+        }
+    }
+
+    /// Gets the rules the game is played with.
+    pub fn rules(&self) -> &'static CustomRules {
+        self.rules
+    }
+
+    /// Gets the board, which indicates the player that owns each square.
+    pub fn board(&self) -> &ttt::board::Board {
+        &self.board
+    }
+
+    /// Gets the current state of the game.
+    ///
+    /// With misère rules the winning positions are the line the losing player
+    /// completed.
+    pub fn state(&self) -> ttt::game::State {
+        self.state.clone()
+    }
+
+    /// Indicates if a mark can be placed at the position.
+    ///
+    /// With gravity, the square below the position must already have a mark.
+    pub fn is_free(&self, position: ttt::game::Position) -> bool {
+        let below = ttt::game::Position {
+            row: position.row - 1,
+            column: position.column,
+        };
+        let is_supported = !self.rules.gravity
+            || position.row == 0
+            || self.board.get(below) != Some(ttt::board::Owner::None);
+        !self.state.is_game_over()
+            && self.board.get(position) == Some(ttt::board::Owner::None)
+            && is_supported
+    }
+
+    /// Gets the position a mark selected at the position ends up at.
+    ///
+    /// With gravity this is the lowest free square in the position's column,
+    /// otherwise the position is unchanged.
+    pub fn landing_position(&self, position: ttt::game::Position) -> ttt::game::Position {
+        if !self.rules.gravity {
+            return position;
+        }
+        (0..self.rules.board_size)
+            .map(|row| ttt::game::Position {
+                row,
+                column: position.column,
+            })
+            .find(|below| self.board.get(*below) == Some(ttt::board::Owner::None))
+            .unwrap_or(position)
+    }
+
+    /// Gets all the positions the current player can place a mark.
+    pub fn moves(&self) -> Vec<ttt::game::Position> {
+        self.board
+            .iter()
+            .map(|(position, _owner)| position)
+            .filter(|position| self.is_free(*position))
+            .collect()
+    }
+
+    /// Places a mark for the current player returning the new state of the game.
+    #[pre(self.is_free(position))]
+    pub fn do_move(&mut self, position: ttt::game::Position) -> ttt::game::State {
+        let is_x_move = self.state == ttt::game::State::PlayerXMove;
+        *self.board.get_mut(position).unwrap() = if is_x_move {
+            ttt::board::Owner::PlayerX
+        } else {
+            ttt::board::Owner::PlayerO
+        };
+
+        let is_board_full = self
+            .board
+            .iter()
+            .all(|(_position, owner)| owner != ttt::board::Owner::None);
+        self.state = match longest_line(&self.board, position, self.rules.win_length) {
+            Some(line) if is_x_move != self.rules.misere => ttt::game::State::PlayerXWin(line),
+            Some(line) => ttt::game::State::PlayerOWin(line),
+            None if is_board_full => ttt::game::State::CatsGame,
+            None if is_x_move => ttt::game::State::PlayerOMove,
+            None => ttt::game::State::PlayerXMove,
+        };
+
+        self.state()
+    }
+
+    /// Starts the next game from the starting position, alternating which
+    /// player goes first.
+    ///
+    /// If X has an extra mark in the starting position O always goes first,
+    /// as otherwise X would end up two marks ahead.
+    pub fn start_next_game(&mut self) -> ttt::game::State {
+        let x_started = !self.x_started;
+        *self = Self::new(self.rules);
+        // The new game lets X start only when both players have as many
+        // marks, which is when the players can take turns going first.
+        if self.x_started {
+            self.x_started = x_started;
+            self.state = if x_started {
+                ttt::game::State::PlayerXMove
+            } else {
+                ttt::game::State::PlayerOMove
+            };
+        }
+
+        self.state()
+    }
+}
+
+/// AI opponent for games with custom rules.
+///
+/// Since the rules are not known ahead of time, the opponent searches as many
+/// moves ahead as it can while looking at a limited number of positions. When
+/// the search stops before the end of the game, positions are scored by how
+/// close each player is to completing lines that are still open. Near the end
+/// of the game the search reaches every possible ending, so the opponent does
+/// not miss a forced win or loss.
+#[derive(Clone, Debug)]
+pub struct CustomOpponent {
+    mistake_probability: f64,
+}
+
+impl CustomOpponent {
+    /// Creates a new opponent with the provided difficulty.
+    pub fn new(difficulty: ttt::ai::Difficulty) -> Self {
+        Self {
+            mistake_probability: mistake_probability(difficulty),
+        }
+    }
+
+    /// Gets the position the AI opponent wishes to place a mark.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_move(&self, game: &CustomGame) -> Option<ttt::game::Position> {
+        let moves = game.moves();
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.mistake_probability) {
+            return moves.choose(&mut rng).copied();
+        }
+
+        let is_x_move = game.state() == ttt::game::State::PlayerXMove;
+        let depth = search_depth(moves.len());
+        let scored_moves: Vec<(ttt::game::Position, i64)> = moves
+            .iter()
+            .map(|position| {
+                let mut next_game = game.clone();
+                next_game.do_move(*position);
+                let score = -negamax(
+                    &next_game,
+                    !is_x_move,
+                    depth - 1,
+                    -WIN_SCORE * 2,
+                    WIN_SCORE * 2,
+                );
+                (*position, score)
+            })
+            .collect();
+        let best_score = scored_moves.iter().map(|(_, score)| *score).max()?;
+        let best_moves: Vec<ttt::game::Position> = scored_moves
+            .iter()
+            .filter(|(_, score)| *score == best_score)
+            .map(|(position, _)| *position)
+            .collect();
+
+        best_moves.choose(&mut rng).copied()
+    }
+}
+
+// Gets how many moves ahead to search so no more than the maximum number of
+// positions are looked at.
+//
+// With gravity the number of moves does not drop as the board fills up, so
+// this assumes every position has as many moves as the current one.
+fn search_depth(num_moves: usize) -> usize {
+    let mut depth = 1;
+    let mut positions = num_moves;
+    while depth < num_moves && positions * num_moves <= MAX_SEARCH_POSITIONS {
+        positions *= num_moves;
+        depth += 1;
+    }
+    depth
+}
+
+// Scores the game from the point of view of the player to move, searching the
+// indicated number of moves ahead.
+//
+// The player to move is provided since it is not part of the state once the
+// game is over.
+fn negamax(game: &CustomGame, is_x_move: bool, depth: usize, mut alpha: i64, beta: i64) -> i64 {
+    // Earlier wins are better, so wins with more free squares score higher.
+    let win_score = || WIN_SCORE + count_marks(&game.board, ttt::board::Owner::None) as i64;
+    match game.state() {
+        ttt::game::State::PlayerXWin(_) if is_x_move => return win_score(),
+        ttt::game::State::PlayerOWin(_) if !is_x_move => return win_score(),
+        ttt::game::State::PlayerXWin(_) | ttt::game::State::PlayerOWin(_) => return -win_score(),
+        ttt::game::State::CatsGame => return 0,
+        ttt::game::State::PlayerXMove | ttt::game::State::PlayerOMove => (),
+    }
+    if depth == 0 {
+        let score = line_score(game);
+        return if is_x_move { score } else { -score };
+    }
+
+    let mut best_score = -WIN_SCORE * 2;
+    for position in game.moves() {
+        let mut next_game = game.clone();
+        next_game.do_move(position);
+        let score = -negamax(&next_game, !is_x_move, depth - 1, -beta, -alpha);
+        best_score = best_score.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best_score
+}
+
+// Scores the open lines on the board from player X's point of view.
+//
+// Lines only one player has marks in can still be completed, and are worth
+// more the more marks they have. With misère rules completing a line loses,
+// so the score is reversed.
+pub(super) fn line_score(game: &CustomGame) -> i64 {
+    let score: i64 = windows(game.rules.board_size, game.rules.win_length)
+        .map(|window| {
+            let owners: Vec<ttt::board::Owner> = window
+                .iter()
+                .filter_map(|position| game.board.get(*position))
+                .filter(|owner| *owner != ttt::board::Owner::None)
+                .collect();
+            let x_count = owners
+                .iter()
+                .filter(|owner| **owner == ttt::board::Owner::PlayerX)
+                .count() as u32;
+            let o_count = owners.len() as u32 - x_count;
+            match (x_count, o_count) {
+                (0, 0) => 0,
+                (x_count, 0) => LINE_SCORE_BASE.pow(x_count),
+                (0, o_count) => -LINE_SCORE_BASE.pow(o_count),
+                _ => 0,
+            }
+        })
+        .sum();
+
+    if game.rules.misere {
+        -score
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+    use std::collections::HashSet;
+
+    // Reads the rules, leaking them like the game does when loading variants.
+    fn rules(text: &str) -> &'static CustomRules {
+        Box::leak(Box::new(CustomRules::from_ron(text).unwrap()))
+    }
+
+    fn invalid_field(text: &str) -> &'static str {
+        match CustomRules::from_ron(text) {
+            Err(RulesError::InvalidField { field, .. }) => field,
+            result => panic!("Expected an invalid field, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn custom_rules_from_ron_should_use_defaults_for_missing_optional_fields() {
+        let text = r#"(name: "Plain", win_length: 3)"#;
+
+        let rules = CustomRules::from_ron(text).unwrap();
+
+        assert_eq!(rules.board_size, 3);
+        assert!(!rules.gravity);
+        assert!(!rules.misere);
+        assert!(rules.starting_position.is_empty());
+    }
+
+    #[test]
+    fn custom_rules_from_ron_when_unknown_field_should_name_field() {
+        let text = r#"(name: "Typo", win_length: 3, gravty: true)"#;
+
+        let error = CustomRules::from_ron(text).unwrap_err();
+
+        assert!(error.to_string().contains("gravty"));
+    }
+
+    #[test]
+    fn custom_rules_from_ron_when_win_length_larger_than_board_should_be_invalid_win_length() {
+        let text = r#"(name: "Too long", board_size: 4, win_length: 5)"#;
+
+        let field = invalid_field(text);
+
+        assert_eq!(field, "win_length");
+    }
+
+    #[test]
+    fn custom_rules_from_ron_when_board_too_large_should_be_invalid_board_size() {
+        let text = r#"(name: "Huge", board_size: 12, win_length: 5)"#;
+
+        let field = invalid_field(text);
+
+        assert_eq!(field, "board_size");
+    }
+
+    #[test]
+    fn custom_rules_from_ron_when_starting_row_wrong_length_should_be_invalid_starting_position() {
+        let text = r#"(
+            name: "Short row",
+            win_length: 3,
+            starting_position: ["...", "..", "..."],
+        )"#;
+
+        let error = CustomRules::from_ron(text).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "`starting_position` row 2 has 2 squares, expected 3"
+        );
+    }
+
+    #[test]
+    fn custom_rules_from_ron_when_floating_mark_with_gravity_should_be_invalid_starting_position() {
+        let text = r#"(
+            name: "Floating",
+            win_length: 3,
+            gravity: true,
+            starting_position: ["...", ".X.", "..."],
+        )"#;
+
+        let field = invalid_field(text);
+
+        assert_eq!(field, "starting_position");
+    }
+
+    #[test]
+    fn custom_game_new_when_x_has_extra_mark_should_be_o_move() {
+        let rules =
+            rules(r#"(name: "Started", win_length: 3, starting_position: ["...", ".X.", "..."])"#);
+
+        let game = CustomGame::new(rules);
+
+        assert_eq!(game.state(), ttt::game::State::PlayerOMove);
+        assert_eq!(
+            game.board().get(position(1, 1)),
+            Some(ttt::board::Owner::PlayerX)
+        );
+    }
+
+    #[test]
+    fn custom_game_landing_position_with_gravity_should_be_lowest_free_square() {
+        let rules = rules(r#"(name: "Gravity", board_size: 4, win_length: 3, gravity: true)"#);
+        let mut game = CustomGame::new(rules);
+        game.do_move(position(0, 2));
+
+        let landing_position = game.landing_position(position(3, 2));
+
+        assert_eq!(landing_position, position(1, 2));
+        assert!(!game.is_free(position(3, 2)));
+    }
+
+    #[test]
+    fn custom_game_do_move_when_misere_line_should_lose() {
+        let rules = rules(r#"(name: "Misere", win_length: 3, misere: true)"#);
+        let mut game = CustomGame::new(rules);
+        for (row, column) in &[(0, 0), (1, 0), (0, 1), (2, 2)] {
+            game.do_move(position(*row, *column));
+        }
+
+        let state = game.do_move(position(0, 2));
+
+        let expected_line: HashSet<_> = (0..3).map(|column| position(0, column)).collect();
+        assert_eq!(state, ttt::game::State::PlayerOWin(expected_line));
+    }
+
+    #[test]
+    fn custom_game_start_next_game_should_let_o_go_first() {
+        let rules = rules(r#"(name: "Plain", win_length: 3)"#);
+        let mut game = CustomGame::new(rules);
+        game.do_move(position(1, 1));
+
+        let state = game.start_next_game();
+
+        assert_eq!(state, ttt::game::State::PlayerOMove);
+        assert!(game.is_free(position(1, 1)));
+    }
+
+    #[test]
+    fn custom_game_start_next_game_when_x_has_extra_mark_should_always_be_o_move() {
+        let rules =
+            rules(r#"(name: "Started", win_length: 3, starting_position: ["...", ".X.", "..."])"#);
+        let mut game = CustomGame::new(rules);
+        game.do_move(position(0, 0));
+
+        let first_state = game.start_next_game();
+        let second_state = game.start_next_game();
+
+        assert_eq!(first_state, ttt::game::State::PlayerOMove);
+        assert_eq!(second_state, ttt::game::State::PlayerOMove);
+    }
+
+    #[test]
+    fn custom_opponent_get_move_should_take_winning_move() {
+        let rules = rules(r#"(name: "Four", board_size: 4, win_length: 3)"#);
+        let mut game = CustomGame::new(rules);
+        for (row, column) in &[(0, 0), (3, 3), (0, 1), (3, 2)] {
+            game.do_move(position(*row, *column));
+        }
+        let opponent = CustomOpponent::new(ttt::ai::Difficulty::Unbeatable);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, Some(position(0, 2)));
+    }
+
+    #[test]
+    fn custom_opponent_get_move_when_game_over_should_return_none() {
+        let rules = rules(r#"(name: "Plain", win_length: 3)"#);
+        let mut game = CustomGame::new(rules);
+        for (row, column) in &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+            game.do_move(position(*row, *column));
+        }
+        let opponent = CustomOpponent::new(ttt::ai::Difficulty::Unbeatable);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, None);
+    }
+}
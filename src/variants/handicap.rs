@@ -0,0 +1,294 @@
+use open_ttt_lib as ttt;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// The most moves that can be made at random at the start of a game.
+///
+/// Five marks are needed for a line of three, so games are never over after
+/// the random moves.
+pub const MAX_RANDOM_OPENING_MOVES: u32 = 4;
+
+/// The square the computer cannot use when the center is blocked.
+pub const CENTER: ttt::game::Position = ttt::game::Position { row: 1, column: 1 };
+
+/// Options that make single-player classic games more even.
+///
+/// Handicaps are saved with the player's settings, so missing fields take
+/// their default value of no handicap.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Handicaps {
+    /// Indicates the computer, as the stronger side, cannot place a mark in
+    /// the center square. The player can still use it.
+    pub block_center: bool,
+
+    /// The number of moves made at random for both players at the start of
+    /// each game, up to `MAX_RANDOM_OPENING_MOVES`.
+    pub random_opening_moves: u32,
+
+    /// Indicates the pie rule is used: after the first mark is placed, the
+    /// second player may take it over as their own instead of moving. The
+    /// first player then moves again.
+    pub swap_rule: bool,
+}
+
+impl Handicaps {
+    /// Indicates if any handicap is used.
+    pub fn is_any(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// Gets a short description of each handicap used, for showing with the
+    /// game's status.
+    pub fn descriptions(&self) -> Vec<String> {
+        let mut descriptions = Vec::new();
+        if self.block_center {
+            descriptions.push("Center blocked".to_string());
+        }
+        match self.random_opening_moves {
+            0 => (),
+            1 => descriptions.push("1 random move".to_string()),
+            moves => descriptions.push(format!("{} random moves", moves)),
+        }
+        if self.swap_rule {
+            descriptions.push("Swap rule".to_string());
+        }
+        descriptions
+    }
+
+    /// Gets the squares the computer cannot place marks in.
+    pub fn computer_blocked_squares(&self) -> Vec<ttt::game::Position> {
+        if self.block_center {
+            vec![CENTER]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Makes the random opening moves for both players.
+    ///
+    /// Blocked squares are avoided by both players so the opening does not
+    /// depend on whose move it is.
+    pub fn play_random_opening(&self, game: &mut Game, blocked: &[ttt::game::Position]) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..self.random_opening_moves.min(MAX_RANDOM_OPENING_MOVES) {
+            let moves: Vec<Move> = game
+                .moves()
+                .into_iter()
+                .filter(|game_move| !blocked.contains(&game_move.position()))
+                .collect();
+            match moves.choose(&mut rng) {
+                Some(game_move) => game.do_move(game_move),
+                None => break,
+            };
+        }
+    }
+}
+
+/// Gives the opening mark to the second player under the swap rule.
+///
+/// The game must have exactly one mark. The player who placed it moves
+/// again, now playing against their own opening. `None` is returned if the
+/// game does not have exactly one mark.
+pub fn swap_opening(game: &ttt::game::Game) -> Option<ttt::game::Game> {
+    let marks: Vec<(ttt::game::Position, ttt::board::Owner)> = game
+        .board()
+        .iter()
+        .filter(|(_position, owner)| *owner != ttt::board::Owner::None)
+        .collect();
+    let (position, owner) = match marks.as_slice() {
+        [mark] => *mark,
+        _ => return None,
+    };
+
+    let mut board = game.board().clone();
+    let is_x_move = owner == ttt::board::Owner::PlayerX;
+    *board.get_mut(position)? = if is_x_move {
+        ttt::board::Owner::PlayerO
+    } else {
+        ttt::board::Owner::PlayerX
+    };
+    classic_game_from_board(&board, is_x_move).ok()
+}
+
+/// Indicates if the computer takes over the opening mark under the swap rule.
+///
+/// Every opening is a cat's game with perfect play, so the computer takes
+/// over the strongest opening, the center, and leaves the others.
+pub fn should_swap_opening(game: &ttt::game::Game) -> bool {
+    let marks = game
+        .board()
+        .iter()
+        .filter(|(_position, owner)| *owner != ttt::board::Owner::None)
+        .count();
+    marks == 1 && game.board().get(CENTER) != Some(ttt::board::Owner::None)
+}
+
+/// AI opponent for classic games where it cannot place marks in some squares.
+///
+/// The opponent searches every possible ending, knowing it can never use the
+/// blocked squares, and picks one of the best moves. Like the other
+/// opponents, it sometimes makes a random move instead based on its
+/// difficulty.
+#[derive(Clone, Debug)]
+pub struct HandicapOpponent {
+    mistake_probability: f64,
+    blocked: Vec<ttt::game::Position>,
+}
+
+impl HandicapOpponent {
+    /// Creates a new opponent with the provided difficulty that cannot place
+    /// marks in the blocked squares.
+    pub fn new(difficulty: ttt::ai::Difficulty, blocked: Vec<ttt::game::Position>) -> Self {
+        Self {
+            mistake_probability: mistake_probability(difficulty),
+            blocked,
+        }
+    }
+
+    /// Gets the position the AI opponent wishes to place its mark.
+    ///
+    /// `None` is returned if the game is over or every free square is blocked.
+    pub fn get_move(&self, game: &ttt::game::Game) -> Option<ttt::game::Position> {
+        if game.state().is_game_over() {
+            return None;
+        }
+        let ai_owner = match game.state() {
+            ttt::game::State::PlayerXMove => ttt::board::Owner::PlayerX,
+            _ => ttt::board::Owner::PlayerO,
+        };
+        let moves: Vec<ttt::game::Position> = game
+            .free_positions()
+            .filter(|position| !self.blocked.contains(position))
+            .collect();
+
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.mistake_probability) {
+            return moves.choose(&mut rng).copied();
+        }
+
+        let scored_moves: Vec<(ttt::game::Position, i32)> = moves
+            .iter()
+            .map(|position| (*position, self.move_score(game, *position, ai_owner)))
+            .collect();
+        let best_score = scored_moves.iter().map(|(_, score)| *score).max()?;
+        let best_moves: Vec<ttt::game::Position> = scored_moves
+            .iter()
+            .filter(|(_, score)| *score == best_score)
+            .map(|(position, _)| *position)
+            .collect();
+        best_moves.choose(&mut rng).copied()
+    }
+
+    // Scores placing a mark at the position from the point of view of the
+    // player placing it, with quicker wins scoring higher.
+    fn move_score(
+        &self,
+        game: &ttt::game::Game,
+        position: ttt::game::Position,
+        ai_owner: ttt::board::Owner,
+    ) -> i32 {
+        let mut next_game = game.clone();
+        match next_game
+            .do_move(position)
+            .expect("Only free positions are scored.")
+        {
+            ttt::game::State::PlayerXWin(_) | ttt::game::State::PlayerOWin(_) => {
+                1 + next_game.free_positions().count() as i32
+            }
+            ttt::game::State::CatsGame => 0,
+            _ => -self.score(&next_game, ai_owner),
+        }
+    }
+
+    // Scores the game from the point of view of the player to move.
+    //
+    // When the AI is to move and every free square is blocked, it cannot
+    // make progress, so the position is scored as a cat's game.
+    fn score(&self, game: &ttt::game::Game, ai_owner: ttt::board::Owner) -> i32 {
+        let is_ai_move = match game.state() {
+            ttt::game::State::PlayerXMove => ai_owner == ttt::board::Owner::PlayerX,
+            _ => ai_owner == ttt::board::Owner::PlayerO,
+        };
+        game.free_positions()
+            .filter(|position| !is_ai_move || !self.blocked.contains(position))
+            .map(|position| self.move_score(game, position, ai_owner))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    fn board(rows: &[&str]) -> ttt::board::Board {
+        let rows: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+        board_from_rows(&rows, 3).unwrap()
+    }
+
+    #[test]
+    fn handicaps_play_random_opening_should_make_moves_outside_blocked_squares() {
+        let handicaps = Handicaps {
+            random_opening_moves: MAX_RANDOM_OPENING_MOVES,
+            ..Handicaps::default()
+        };
+        let mut game = Game::new(Variant::Classic);
+
+        handicaps.play_random_opening(&mut game, &[CENTER]);
+
+        assert_eq!(
+            count_marks(game.board(), ttt::board::Owner::None),
+            9 - MAX_RANDOM_OPENING_MOVES as usize
+        );
+        assert_eq!(game.board().get(CENTER), Some(ttt::board::Owner::None));
+        assert!(!game.state().is_game_over());
+    }
+
+    #[test]
+    fn swap_opening_when_one_mark_should_give_mark_to_other_player() {
+        let game = classic_game_from_board(&board(&["X..", "...", "..."]), false).unwrap();
+
+        let swapped = swap_opening(&game).unwrap();
+
+        assert_eq!(
+            swapped.board().get(position(2, 0)),
+            Some(ttt::board::Owner::PlayerO)
+        );
+        assert_eq!(swapped.state(), ttt::game::State::PlayerXMove);
+    }
+
+    #[test]
+    fn swap_opening_when_two_marks_should_be_none() {
+        let game = classic_game_from_board(&board(&["X..", ".O.", "..."]), true).unwrap();
+
+        let swapped = swap_opening(&game);
+
+        assert!(swapped.is_none());
+    }
+
+    #[test]
+    fn should_swap_opening_when_center_opening_should_be_true() {
+        let game = classic_game_from_board(&board(&["...", ".X.", "..."]), false).unwrap();
+
+        let should_swap = should_swap_opening(&game);
+
+        assert!(should_swap);
+    }
+
+    #[test]
+    fn handicap_opponent_get_move_when_win_is_blocked_should_block_instead() {
+        // O could win in the center but cannot use it, so it must stop X's
+        // top row.
+        let game = classic_game_from_board(&board(&["XX.", "O.O", "X.."]), false).unwrap();
+        let opponent = HandicapOpponent::new(ttt::ai::Difficulty::Unbeatable, vec![CENTER]);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, Some(position(2, 2)));
+    }
+}
@@ -0,0 +1,607 @@
+//! Contains the rules for the tic-tac-toe variants the game supports.
+//!
+//! The classic game is provided by `open_ttt_lib`. The other variants
+//! implement their own rules but report their state using the same
+//! `ttt::game::State` type so the rest of the game can treat them alike.
+
+mod classic;
+mod custom;
+mod handicap;
+mod notakto;
+mod numerical;
+mod order_and_chaos;
+mod perfect_play;
+mod three_mens_morris;
+
+pub use self::classic::*;
+pub use self::custom::*;
+pub use self::handicap::*;
+pub use self::notakto::*;
+pub use self::numerical::*;
+pub use self::order_and_chaos::*;
+pub use self::perfect_play::*;
+pub use self::three_mens_morris::*;
+
+use contracts::*;
+use open_ttt_lib as ttt;
+use std::collections::HashSet;
+
+/// The number of columns each board takes up in variants played on several boards.
+///
+/// All the boards share one set of positions, as if they were placed side by
+/// side: the first board uses columns 0 to 2, the second board uses columns 3
+/// to 5, and so on.
+pub const BOARD_COLUMNS: i32 = 3;
+
+// The number of squares on a 3 x 3 board.
+const NUM_SQUARES: usize = 9;
+
+// All the rows, columns, and diagonals of a 3 x 3 board.
+const LINES: [[(i32, i32); 3]; 8] = [
+    [(0, 0), (0, 1), (0, 2)],
+    [(1, 0), (1, 1), (1, 2)],
+    [(2, 0), (2, 1), (2, 2)],
+    [(0, 0), (1, 0), (2, 0)],
+    [(0, 1), (1, 1), (2, 1)],
+    [(0, 2), (1, 2), (2, 2)],
+    [(0, 0), (1, 1), (2, 2)],
+    [(0, 2), (1, 1), (2, 0)],
+];
+
+/// The tic-tac-toe variants that can be played.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Variant {
+    /// The standard game where players place their own X or O marks.
+    #[default]
+    Classic,
+
+    /// Ron Graham's numerical tic-tac-toe where lines summing to 15 win.
+    Numerical,
+
+    /// Each player has three marks which are moved around once placed.
+    ThreeMensMorris,
+
+    /// Both players place X marks on several boards and whoever completes
+    /// three in a row on the last board loses.
+    Notakto,
+
+    /// One player tries to get five identical symbols in a row on a 6 x 6
+    /// board while the other tries to stop them.
+    OrderAndChaos,
+
+    /// A variant defined by rules loaded from a data file.
+    ///
+    /// Custom rules are loaded once when the game starts and kept for as long
+    /// as the game runs.
+    Custom(&'static CustomRules),
+}
+
+impl Variant {
+    /// Gets the name of the variant as shown to the player.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Classic => "Classic",
+            Self::Numerical => "Numerical",
+            Self::ThreeMensMorris => "Three Men's Morris",
+            Self::Notakto => "Notakto",
+            Self::OrderAndChaos => "Order and Chaos",
+            Self::Custom(rules) => &rules.name,
+        }
+    }
+
+    /// Gets the number of boards the variant is played on.
+    pub fn num_boards(&self) -> usize {
+        match self {
+            Self::Notakto => NOTAKTO_BOARDS,
+            _ => 1,
+        }
+    }
+
+    /// Gets the number of rows and columns of each board the variant is played on.
+    pub fn board_size(&self) -> i32 {
+        match self {
+            Self::OrderAndChaos => ORDER_AND_CHAOS_SIZE,
+            Self::Custom(rules) => rules.board_size,
+            _ => 3,
+        }
+    }
+}
+
+/// A move a player can make.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Move {
+    /// Places the player's own mark at the position.
+    Mark(ttt::game::Position),
+
+    /// Places the number at the position.
+    Number(ttt::game::Position, u8),
+
+    /// Moves the player's mark from the first position to the second.
+    Slide(ttt::game::Position, ttt::game::Position),
+
+    /// Places the symbol at the position, regardless of which player is moving.
+    PlaceSymbol(ttt::game::Position, ttt::board::Owner),
+}
+
+impl Move {
+    /// Gets the position the move places or moves a mark to.
+    pub fn position(&self) -> ttt::game::Position {
+        match *self {
+            Self::Mark(position)
+            | Self::Number(position, _)
+            | Self::Slide(_, position)
+            | Self::PlaceSymbol(position, _) => position,
+        }
+    }
+}
+
+/// Provides a common interface to the game logic of all the variants.
+#[derive(Clone)]
+pub enum Game {
+    Classic(ttt::game::Game),
+    Numerical(NumericalGame),
+    ThreeMensMorris(ThreeMensMorrisGame),
+    Notakto(NotaktoGame),
+    OrderAndChaos(OrderAndChaosGame),
+    Custom(CustomGame),
+}
+
+impl Game {
+    /// Creates a new game for the provided variant.
+    pub fn new(variant: Variant) -> Self {
+        match variant {
+            Variant::Classic => Self::Classic(ttt::game::Game::new()),
+            Variant::Numerical => Self::Numerical(NumericalGame::new()),
+            Variant::ThreeMensMorris => Self::ThreeMensMorris(ThreeMensMorrisGame::new()),
+            Variant::Notakto => Self::Notakto(NotaktoGame::new()),
+            Variant::OrderAndChaos => Self::OrderAndChaos(OrderAndChaosGame::new()),
+            Variant::Custom(rules) => Self::Custom(CustomGame::new(rules)),
+        }
+    }
+
+    /// Gets the variant being played.
+    pub fn variant(&self) -> Variant {
+        match self {
+            Self::Classic(_) => Variant::Classic,
+            Self::Numerical(_) => Variant::Numerical,
+            Self::ThreeMensMorris(_) => Variant::ThreeMensMorris,
+            Self::Notakto(_) => Variant::Notakto,
+            Self::OrderAndChaos(_) => Variant::OrderAndChaos,
+            Self::Custom(game) => Variant::Custom(game.rules()),
+        }
+    }
+
+    /// Gets the board, which indicates the player that owns each square.
+    ///
+    /// For variants played on several boards, this contains all the boards
+    /// as described by `BOARD_COLUMNS`.
+    pub fn board(&self) -> &ttt::board::Board {
+        match self {
+            Self::Classic(game) => game.board(),
+            Self::Numerical(game) => game.board(),
+            Self::ThreeMensMorris(game) => game.board(),
+            Self::Notakto(game) => game.board(),
+            Self::OrderAndChaos(game) => game.board(),
+            Self::Custom(game) => game.board(),
+        }
+    }
+
+    /// Gets the current state of the game.
+    pub fn state(&self) -> ttt::game::State {
+        match self {
+            Self::Classic(game) => game.state(),
+            Self::Numerical(game) => game.state(),
+            Self::ThreeMensMorris(game) => game.state(),
+            Self::Notakto(game) => game.state(),
+            Self::OrderAndChaos(game) => game.state(),
+            Self::Custom(game) => game.state(),
+        }
+    }
+
+    /// Indicates if the position is free for the current player to move into.
+    pub fn can_move(&self, position: ttt::game::Position) -> bool {
+        match self {
+            Self::Classic(game) => game.can_move(position),
+            Self::Numerical(game) => game.is_free(position),
+            Self::ThreeMensMorris(game) => game.is_free(position),
+            Self::Notakto(game) => game.is_free(position),
+            Self::OrderAndChaos(game) => game.is_free(position),
+            Self::Custom(game) => game.is_free(position),
+        }
+    }
+
+    /// Gets the role player X plays, player O plays the opposite role.
+    ///
+    /// `None` is returned for variants where players do not have roles.
+    pub fn x_role(&self) -> Option<Role> {
+        match self {
+            Self::OrderAndChaos(game) if game.is_x_order() => Some(Role::Order),
+            Self::OrderAndChaos(_) => Some(Role::Chaos),
+            _ => None,
+        }
+    }
+
+    /// Gets the index of the board the position is on.
+    ///
+    /// See `BOARD_COLUMNS` for how positions are shared between boards.
+    /// Variants played on a single board always use board 0.
+    pub fn board_index(&self, position: ttt::game::Position) -> usize {
+        match self {
+            Self::Notakto(_) => board_index(position),
+            _ => 0,
+        }
+    }
+
+    /// Gets the line of three that put the board out of play.
+    ///
+    /// `None` is returned if the board is still in play or the variant does
+    /// not take boards out of play.
+    pub fn dead_board_line(&self, board_index: usize) -> Option<HashSet<ttt::game::Position>> {
+        match self {
+            Self::Notakto(game) => game.dead_line(board_index),
+            _ => None,
+        }
+    }
+
+    /// Indicates if the current player can pick up the mark at the position
+    /// and move it elsewhere.
+    ///
+    /// This is only possible in variants where marks move once placed.
+    pub fn can_move_from(&self, position: ttt::game::Position) -> bool {
+        match self {
+            Self::ThreeMensMorris(game) => game
+                .moves()
+                .iter()
+                .any(|game_move| matches!(game_move, Move::Slide(from, _) if *from == position)),
+            _ => false,
+        }
+    }
+
+    /// Gets every move the current player can make.
+    ///
+    /// There are no moves once the game is over.
+    pub fn moves(&self) -> Vec<Move> {
+        match self {
+            Self::Classic(game) => game
+                .free_positions()
+                .filter(|position| game.can_move(*position))
+                .map(Move::Mark)
+                .collect(),
+            Self::Numerical(game) => game
+                .moves()
+                .into_iter()
+                .map(|(position, number)| Move::Number(position, number))
+                .collect(),
+            Self::ThreeMensMorris(game) => game.moves(),
+            Self::Notakto(game) => game.moves().into_iter().map(Move::Mark).collect(),
+            Self::OrderAndChaos(game) => game.moves(),
+            Self::Custom(game) => game.moves().into_iter().map(Move::Mark).collect(),
+        }
+    }
+
+    /// Indicates if the provided move is allowed by the variant's rules.
+    pub fn is_valid_move(&self, game_move: &Move) -> bool {
+        match (self, *game_move) {
+            (Self::Classic(game), Move::Mark(position)) => game.can_move(position),
+            (Self::Numerical(game), Move::Number(position, number)) => {
+                game.can_move(position, number)
+            }
+            (Self::ThreeMensMorris(game), game_move) => game.is_valid_move(&game_move),
+            (Self::Notakto(game), Move::Mark(position)) => game.is_free(position),
+            (Self::OrderAndChaos(game), game_move) => game.is_valid_move(&game_move),
+            (Self::Custom(game), Move::Mark(position)) => game.is_free(position),
+            _ => false,
+        }
+    }
+
+    /// Performs the provided move returning the new state of the game.
+    #[pre(self.is_valid_move(game_move))]
+    pub fn do_move(&mut self, game_move: &Move) -> ttt::game::State {
+        match (self, *game_move) {
+            (Self::Classic(game), Move::Mark(position)) => game
+                .do_move(position)
+                .expect("The move was checked to be valid."),
+            (Self::Numerical(game), Move::Number(position, number)) => {
+                game.do_move(position, number)
+            }
+            (Self::ThreeMensMorris(game), game_move) => game.do_move(&game_move),
+            (Self::Notakto(game), Move::Mark(position)) => game.do_move(position),
+            (Self::OrderAndChaos(game), game_move) => game.do_move(&game_move),
+            (Self::Custom(game), Move::Mark(position)) => game.do_move(position),
+            _ => unreachable!("The move was checked to be valid."),
+        }
+    }
+
+    /// Estimates how close player X is to winning, for AI engines that stop
+    /// searching before the game ends.
+    ///
+    /// Positive scores favor player X and negative scores favor player O. The
+    /// small variants can be searched to the end, so they always score 0.
+    pub fn heuristic_score(&self) -> i64 {
+        match self {
+            Self::OrderAndChaos(game) => {
+                let score = order_and_chaos::score(game);
+                if game.is_x_order() {
+                    score
+                } else {
+                    -score
+                }
+            }
+            Self::Custom(game) => custom::line_score(game),
+            _ => 0,
+        }
+    }
+
+    /// Starts the next game, alternating which player goes first.
+    pub fn start_next_game(&mut self) -> ttt::game::State {
+        match self {
+            Self::Classic(game) => game.start_next_game(),
+            Self::Numerical(game) => game.start_next_game(),
+            Self::ThreeMensMorris(game) => game.start_next_game(),
+            Self::Notakto(game) => game.start_next_game(),
+            Self::OrderAndChaos(game) => game.start_next_game(),
+            Self::Custom(game) => game.start_next_game(),
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Variant::default())
+    }
+}
+
+/// Provides a common interface to the AI opponents of all the variants.
+pub enum Opponent {
+    Classic(ClassicOpponent),
+    Numerical(NumericalOpponent),
+    ThreeMensMorris(ThreeMensMorrisOpponent),
+    Notakto(NotaktoOpponent),
+    OrderAndChaos(OrderAndChaosOpponent),
+    Custom(CustomOpponent),
+    Perfect(PerfectOpponent),
+    Handicap(HandicapOpponent),
+}
+
+impl Opponent {
+    /// Creates an opponent with the given difficulty for the provided variant.
+    pub fn new(variant: Variant, difficulty: ttt::ai::Difficulty) -> Self {
+        match variant {
+            Variant::Classic => Self::Classic(ClassicOpponent::new(difficulty)),
+            Variant::Numerical => Self::Numerical(NumericalOpponent::new(difficulty)),
+            Variant::ThreeMensMorris => {
+                Self::ThreeMensMorris(ThreeMensMorrisOpponent::new(difficulty))
+            }
+            Variant::Notakto => Self::Notakto(NotaktoOpponent::new(difficulty)),
+            Variant::OrderAndChaos => Self::OrderAndChaos(OrderAndChaosOpponent::new(difficulty)),
+            Variant::Custom(_) => Self::Custom(CustomOpponent::new(difficulty)),
+        }
+    }
+
+    /// Gets the move the AI opponent wishes to make.
+    ///
+    /// `None` is returned if the game is over or the game is of a different
+    /// variant than the opponent plays.
+    pub fn get_move(&self, game: &Game) -> Option<Move> {
+        match (self, game) {
+            (Self::Classic(opponent), Game::Classic(game)) => {
+                opponent.get_move(game).map(Move::Mark)
+            }
+            (Self::Numerical(opponent), Game::Numerical(game)) => opponent
+                .get_move(game)
+                .map(|(position, number)| Move::Number(position, number)),
+            (Self::ThreeMensMorris(opponent), Game::ThreeMensMorris(game)) => {
+                opponent.get_move(game)
+            }
+            (Self::Notakto(opponent), Game::Notakto(game)) => {
+                opponent.get_move(game).map(Move::Mark)
+            }
+            (Self::OrderAndChaos(opponent), Game::OrderAndChaos(game)) => opponent.get_move(game),
+            (Self::Custom(opponent), Game::Custom(game)) => opponent.get_move(game).map(Move::Mark),
+            (Self::Perfect(opponent), Game::Classic(game)) => {
+                opponent.get_move(game).map(Move::Mark)
+            }
+            (Self::Handicap(opponent), Game::Classic(game)) => {
+                opponent.get_move(game).map(Move::Mark)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Gets the probability an AI opponent with the given difficulty makes a mistake.
+///
+/// This lets the AI opponents for the variants match the strength of
+/// `ttt::ai::Opponent` with the same difficulty.
+pub fn mistake_probability(difficulty: ttt::ai::Difficulty) -> f64 {
+    match difficulty {
+        ttt::ai::Difficulty::None => 1.0,
+        ttt::ai::Difficulty::Easy => 0.5,
+        ttt::ai::Difficulty::Medium => 0.2,
+        ttt::ai::Difficulty::Hard => 0.05,
+        ttt::ai::Difficulty::Unbeatable => 0.0,
+        ttt::ai::Difficulty::Custom(probability) => probability.clamp(0.0, 1.0),
+    }
+}
+
+/// Gets the name of the difficulty shown to the player.
+///
+/// Custom difficulties are named by their chance of a mistake, such as
+/// "Custom 35%".
+pub fn difficulty_name(difficulty: ttt::ai::Difficulty) -> String {
+    match difficulty {
+        ttt::ai::Difficulty::None => "Random".to_string(),
+        ttt::ai::Difficulty::Easy => "Easy".to_string(),
+        ttt::ai::Difficulty::Medium => "Medium".to_string(),
+        ttt::ai::Difficulty::Hard => "Hard".to_string(),
+        ttt::ai::Difficulty::Unbeatable => "Unbeatable".to_string(),
+        ttt::ai::Difficulty::Custom(_) => {
+            format!("Custom {:.0}%", mistake_probability(difficulty) * 100.0)
+        }
+    }
+}
+
+/// Builds a square board from rows of marks, listed from the top row down.
+///
+/// Each row uses `X`, `O`, and `.` for an empty square. The board's row 0 is
+/// the bottom row, so the last row listed becomes row 0.
+///
+/// # Errors
+/// An error describing the problem is returned if the number of rows or the
+/// length of a row does not match the board size, or a row has a character
+/// that is not a mark.
+pub fn board_from_rows(rows: &[String], board_size: i32) -> Result<ttt::board::Board, String> {
+    if rows.len() != board_size as usize {
+        return Err(format!(
+            "expected {} rows, found {}",
+            board_size,
+            rows.len()
+        ));
+    }
+
+    let mut board = ttt::board::Board::new(ttt::board::Size {
+        rows: board_size,
+        columns: board_size,
+    });
+    for (index, row) in rows.iter().enumerate() {
+        let row_number = index + 1;
+        if row.chars().count() != board_size as usize {
+            return Err(format!(
+                "row {} has {} squares, expected {}",
+                row_number,
+                row.chars().count(),
+                board_size
+            ));
+        }
+        for (column, mark) in row.chars().enumerate() {
+            let owner = parse_mark(mark).ok_or_else(|| {
+                format!(
+                    "row {} has '{}', expected 'X', 'O', or '.'",
+                    row_number, mark
+                )
+            })?;
+            let position = ttt::game::Position {
+                row: board_size - 1 - index as i32,
+                column: column as i32,
+            };
+            if let Some(square) = board.get_mut(position) {
+                *square = owner;
+            }
+        }
+    }
+    Ok(board)
+}
+
+/// Lists the marks on the board as rows from the top row down, using the
+/// same format read by `board_from_rows`.
+pub fn board_to_rows(board: &ttt::board::Board) -> Vec<String> {
+    let size = board.size();
+    (0..size.rows)
+        .rev()
+        .map(|row| {
+            (0..size.columns)
+                .map(
+                    |column| match board.get(ttt::game::Position { row, column }) {
+                        Some(ttt::board::Owner::PlayerX) => 'X',
+                        Some(ttt::board::Owner::PlayerO) => 'O',
+                        _ => '.',
+                    },
+                )
+                .collect()
+        })
+        .collect()
+}
+
+// Converts a mark in a row of marks to the owner of the square.
+fn parse_mark(mark: char) -> Option<ttt::board::Owner> {
+    match mark {
+        'X' | 'x' => Some(ttt::board::Owner::PlayerX),
+        'O' | 'o' => Some(ttt::board::Owner::PlayerO),
+        '.' => Some(ttt::board::Owner::None),
+        _ => None,
+    }
+}
+
+// Counts the squares on the board with the provided owner.
+fn count_marks(board: &ttt::board::Board, owner: ttt::board::Owner) -> usize {
+    board
+        .iter()
+        .filter(|(_position, square)| *square == owner)
+        .count()
+}
+
+// Gets the index of the board the position is on for variants played on
+// several boards.
+fn board_index(position: ttt::game::Position) -> usize {
+    position.column.div_euclid(BOARD_COLUMNS).max(0) as usize
+}
+
+// The directions lines can run in as a change in row and column.
+const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+// Gets the longest line of squares with the same owner through the position,
+// if it is at least the minimum length.
+fn longest_line(
+    board: &ttt::board::Board,
+    position: ttt::game::Position,
+    min_length: i32,
+) -> Option<HashSet<ttt::game::Position>> {
+    let owner = board.get(position)?;
+    DIRECTIONS
+        .iter()
+        .map(|(row_step, column_step)| {
+            let mut line = vec![position];
+            for &direction in &[1, -1] {
+                let mut next = position;
+                loop {
+                    next = ttt::game::Position {
+                        row: next.row + row_step * direction,
+                        column: next.column + column_step * direction,
+                    };
+                    if board.get(next) != Some(owner) {
+                        break;
+                    }
+                    line.push(next);
+                }
+            }
+            line
+        })
+        .filter(|line| line.len() >= min_length as usize)
+        .max_by_key(|line| line.len())
+        .map(|line| line.into_iter().collect())
+}
+
+// Gets every line of the given length on a square board of the given size.
+fn windows(board_size: i32, length: i32) -> impl Iterator<Item = Vec<ttt::game::Position>> {
+    (0..board_size).flat_map(move |row| {
+        (0..board_size).flat_map(move |column| {
+            DIRECTIONS
+                .iter()
+                .filter_map(move |(row_step, column_step)| {
+                    let window: Vec<ttt::game::Position> = (0..length)
+                        .map(|index| ttt::game::Position {
+                            row: row + row_step * index,
+                            column: column + column_step * index,
+                        })
+                        .collect();
+                    let is_on_board = window.iter().all(|position| {
+                        (0..board_size).contains(&position.row)
+                            && (0..board_size).contains(&position.column)
+                    });
+                    if is_on_board {
+                        Some(window)
+                    } else {
+                        None
+                    }
+                })
+        })
+    })
+}
+
+// Gets all the rows, columns, and diagonals of a 3 x 3 board.
+fn lines() -> impl Iterator<Item = [ttt::game::Position; 3]> {
+    LINES.iter().map(|line| {
+        let position = |(row, column): (i32, i32)| ttt::game::Position { row, column };
+        [position(line[0]), position(line[1]), position(line[2])]
+    })
+}
@@ -0,0 +1,571 @@
+use contracts::*;
+use lazy_static::lazy_static;
+use open_ttt_lib as ttt;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+use crate::engine::{square_bit, ALL_SYMMETRIES};
+
+/// The number of boards notakto is played on.
+pub const NOTAKTO_BOARDS: usize = 3;
+
+/// Notakto, a neutral tic-tac-toe variant played on several boards at once.
+///
+/// Both players place X marks on any board that is still in play. A board is
+/// dead once it has three in a row, and no more marks can be placed on it. The
+/// player who kills the last board loses.
+///
+/// The boards share one set of positions as described by `BOARD_COLUMNS`.
+#[derive(Clone, Debug)]
+pub struct NotaktoGame {
+    board: ttt::board::Board,
+    state: ttt::game::State,
+    // Indicates if player X made the first move.
+    x_started: bool,
+}
+
+impl NotaktoGame {
+    /// Creates a new game where player X goes first.
+    pub fn new() -> Self {
+        Self {
+            board: ttt::board::Board::new(ttt::board::Size {
+                rows: 3,
+                columns: BOARD_COLUMNS * NOTAKTO_BOARDS as i32,
+            }),
+            state: ttt::game::State::PlayerXMove,
+            x_started: true,
+        }
+    }
+
+    /// Gets all the boards, laid out side by side, with every mark placed.
+    ///
+    /// All marks are X marks. The owner of each square is the player that
+    /// placed the mark.
+    pub fn board(&self) -> &ttt::board::Board {
+        &self.board
+    }
+
+    /// Gets the current state of the game.
+    ///
+    /// When the game is over the winner is the player that did not kill the
+    /// last board. There is no winning line in this case.
+    pub fn state(&self) -> ttt::game::State {
+        self.state.clone()
+    }
+
+    /// Gets the line of three that killed the board.
+    ///
+    /// `None` is returned if the board is still in play.
+    pub fn dead_line(&self, board_index: usize) -> Option<HashSet<ttt::game::Position>> {
+        let mask = self.board_mask(board_index);
+        lines()
+            .find(|line| {
+                line.iter()
+                    .all(|position| mask & square_bit(*position) != 0)
+            })
+            .map(|line| {
+                line.iter()
+                    .map(|position| to_global_position(board_index, *position))
+                    .collect()
+            })
+    }
+
+    /// Indicates if a mark can be placed at the position.
+    ///
+    /// The square must be free and on a board that is still in play.
+    pub fn is_free(&self, position: ttt::game::Position) -> bool {
+        !self.state.is_game_over()
+            && self.board.get(position) == Some(ttt::board::Owner::None)
+            && self.dead_line(board_index(position)).is_none()
+    }
+
+    /// Gets all the positions the current player can place a mark.
+    pub fn moves(&self) -> Vec<ttt::game::Position> {
+        self.board
+            .iter()
+            .map(|(position, _owner)| position)
+            .filter(|position| self.is_free(*position))
+            .collect()
+    }
+
+    /// Places a mark for the current player returning the new state of the game.
+    #[pre(self.is_free(position))]
+    pub fn do_move(&mut self, position: ttt::game::Position) -> ttt::game::State {
+        let is_x_move = self.state == ttt::game::State::PlayerXMove;
+        *self.board.get_mut(position).unwrap() = if is_x_move {
+            ttt::board::Owner::PlayerX
+        } else {
+            ttt::board::Owner::PlayerO
+        };
+
+        let is_last_board_killed =
+            (0..NOTAKTO_BOARDS).all(|board_index| self.dead_line(board_index).is_some());
+        self.state = match (is_last_board_killed, is_x_move) {
+            (true, true) => ttt::game::State::PlayerOWin(HashSet::new()),
+            (true, false) => ttt::game::State::PlayerXWin(HashSet::new()),
+            (false, true) => ttt::game::State::PlayerOMove,
+            (false, false) => ttt::game::State::PlayerXMove,
+        };
+
+        self.state()
+    }
+
+    /// Starts the next game, alternating which player goes first.
+    pub fn start_next_game(&mut self) -> ttt::game::State {
+        let x_started = !self.x_started;
+        *self = Self::new();
+        self.x_started = x_started;
+        if !x_started {
+            self.state = ttt::game::State::PlayerOMove;
+        }
+
+        self.state()
+    }
+
+    // Gets the squares of the board with a mark as a bit mask.
+    fn board_mask(&self, board_index: usize) -> u16 {
+        let mut mask = 0;
+        for row in 0..3 {
+            for column in 0..3 {
+                let local_position = ttt::game::Position { row, column };
+                let position = to_global_position(board_index, local_position);
+                let owner = self.board.get(position).unwrap_or(ttt::board::Owner::None);
+                let is_marked = owner != ttt::board::Owner::None;
+                if is_marked {
+                    mask |= square_bit(local_position);
+                }
+            }
+        }
+        mask
+    }
+
+    // Gets the bit masks of all the boards.
+    fn board_masks(&self) -> Vec<u16> {
+        (0..NOTAKTO_BOARDS)
+            .map(|board_index| self.board_mask(board_index))
+            .collect()
+    }
+}
+
+impl Default for NotaktoGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+lazy_static! {
+    // The value of every board, indexed by the board's bit mask.
+    static ref BOARD_VALUES: Vec<NotaktoValue> = board_values();
+}
+
+// The value of every board still in play, up to rotations and reflections,
+// from Plambeck and Whitehead's "The Secrets of Notakto". Each board is
+// listed a row at a time, with an X for each mark.
+const BOARD_VALUE_NAMES: [(&str, &str); 46] = [
+    ("... ... ...", "c"),
+    ("X.. ... ...", "1"),
+    (".X. ... ...", "1"),
+    ("... .X. ...", "cc"),
+    ("XX. ... ...", "ad"),
+    ("X.X ... ...", "b"),
+    (".X. X.. ...", "a"),
+    ("..X X.. ...", "b"),
+    ("X.. .X. ...", "b"),
+    (".X. .X. ...", "b"),
+    ("... X.X ...", "a"),
+    ("..X ... X..", "a"),
+    ("XX. X.. ...", "b"),
+    ("X.X X.. ...", "a"),
+    (".XX X.. ...", "d"),
+    ("XX. .X. ...", "ab"),
+    ("X.X .X. ...", "a"),
+    (".X. XX. ...", "ab"),
+    ("..X XX. ...", "a"),
+    ("X.. X.X ...", "d"),
+    (".X. X.X ...", "b"),
+    ("X.X ... X..", "ab"),
+    (".XX ... X..", "d"),
+    ("X.. ..X X..", "a"),
+    (".X. ..X X..", "1"),
+    ("XX. XX. ...", "a"),
+    ("X.X XX. ...", "b"),
+    (".XX XX. ...", "b"),
+    ("XX. X.X ...", "a"),
+    ("X.X X.X ...", "b"),
+    (".XX X.. X..", "ab"),
+    ("XX. ..X X..", "b"),
+    ("X.X ..X X..", "b"),
+    (".XX ..X X..", "a"),
+    (".X. X.X X..", "ab"),
+    ("..X X.X X..", "a"),
+    ("X.. .XX X..", "b"),
+    (".X. .XX X..", "b"),
+    (".X. X.X .X.", "a"),
+    ("X.X ... X.X", "a"),
+    (".XX X.X X..", "b"),
+    ("XX. .XX X..", "a"),
+    ("XX. X.X .X.", "b"),
+    ("X.X X.X .X.", "a"),
+    ("X.X ..X XX.", "a"),
+    (".XX X.X XX.", "a"),
+];
+
+// A value in the misère quotient of notakto, found by Plambeck and
+// Whitehead.
+//
+// The quotient is the commutative monoid generated by a, b, c, and d with the
+// relations a² = 1, b³ = b, b²c = c, c³ = ac², b²d = d, cd = ad, and d² = c²,
+// which has 18 elements. Every board has a value, and the value of a position
+// is the product of the values of its boards. The player to move loses
+// exactly when the position's value is a, b², bc, or c².
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct NotaktoValue {
+    // The powers of a, b, c, and d, kept in their simplest form.
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+}
+
+impl NotaktoValue {
+    const ONE: Self = Self {
+        a: 0,
+        b: 0,
+        c: 0,
+        d: 0,
+    };
+    const A: Self = Self { a: 1, ..Self::ONE };
+    const B: Self = Self { b: 1, ..Self::ONE };
+    const C: Self = Self { c: 1, ..Self::ONE };
+    const D: Self = Self { d: 1, ..Self::ONE };
+
+    // Creates the value with the powers, using the relations to put it in
+    // its simplest form.
+    fn new(mut a: u8, mut b: u8, mut c: u8, mut d: u8) -> Self {
+        // d² = c²
+        c += d / 2 * 2;
+        d %= 2;
+        // cd = ad
+        if d == 1 {
+            a += c;
+            c = 0;
+        }
+        // c³ = ac²
+        if c > 2 {
+            a += c - 2;
+            c = 2;
+        }
+        // b³ = b
+        if b > 2 {
+            b = 2 - b % 2;
+        }
+        // b²c = c and b²d = d
+        if b == 2 && (c > 0 || d > 0) {
+            b = 0;
+        }
+        // a² = 1
+        a %= 2;
+
+        Self { a, b, c, d }
+    }
+
+    // Gets the value written as a product of the generators, such as `ab`.
+    fn from_name(name: &str) -> Self {
+        name.chars().fold(Self::ONE, |value, generator| {
+            value
+                * match generator {
+                    '1' => Self::ONE,
+                    'a' => Self::A,
+                    'b' => Self::B,
+                    'c' => Self::C,
+                    'd' => Self::D,
+                    _ => unreachable!("Values are products of a, b, c, and d."),
+                }
+        })
+    }
+
+    // Indicates the player to move loses a position with this value.
+    fn is_losing(self) -> bool {
+        let losing_values = [
+            Self::A,
+            Self::B * Self::B,
+            Self::B * Self::C,
+            Self::C * Self::C,
+        ];
+        losing_values.contains(&self)
+    }
+}
+
+impl std::ops::Mul for NotaktoValue {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.a + other.a,
+            self.b + other.b,
+            self.c + other.c,
+            self.d + other.d,
+        )
+    }
+}
+
+/// AI opponent for notakto.
+///
+/// Plays using Plambeck and Whitehead's solution of notakto, which gives each
+/// board a value in a small commutative monoid. The opponent multiplies the
+/// values of the boards to find if the player to move loses, and tries to
+/// leave the other player in such a position. This works for any number of
+/// boards.
+///
+/// Notakto has no draws, so hard difficulty plays perfectly and can only be
+/// beaten if it starts in a losing position.
+#[derive(Clone, Debug)]
+pub struct NotaktoOpponent {
+    mistake_probability: f64,
+}
+
+impl NotaktoOpponent {
+    /// Creates a new opponent with the provided difficulty.
+    pub fn new(difficulty: ttt::ai::Difficulty) -> Self {
+        let mistake_probability = match difficulty {
+            ttt::ai::Difficulty::Hard => 0.0,
+            _ => mistake_probability(difficulty),
+        };
+
+        Self {
+            mistake_probability,
+        }
+    }
+
+    /// Gets the position the AI opponent wishes to place a mark.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_move(&self, game: &NotaktoGame) -> Option<ttt::game::Position> {
+        let moves = game.moves();
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.mistake_probability) {
+            return moves.choose(&mut rng).copied();
+        }
+
+        // Leave the other player in a losing position if possible. Otherwise,
+        // any move is as good as another.
+        let winning_moves: Vec<ttt::game::Position> = moves
+            .iter()
+            .copied()
+            .filter(|position| {
+                let mut next_game = game.clone();
+                next_game.do_move(*position);
+                position_value(&next_game.board_masks()).is_losing()
+            })
+            .collect();
+
+        winning_moves
+            .choose(&mut rng)
+            .or_else(|| moves.choose(&mut rng))
+            .copied()
+    }
+}
+
+// Gets the value of the position with the boards, which is the product of the
+// boards' values.
+fn position_value(masks: &[u16]) -> NotaktoValue {
+    masks.iter().fold(NotaktoValue::ONE, |value, mask| {
+        value * BOARD_VALUES[usize::from(*mask)]
+    })
+}
+
+// Gets the value of every board, indexed by the board's bit mask. Dead boards
+// are out of play, which is the same as having no board at all.
+fn board_values() -> Vec<NotaktoValue> {
+    let named_values: HashMap<u16, NotaktoValue> = BOARD_VALUE_NAMES
+        .iter()
+        .map(|(board, name)| (parse_board(board), NotaktoValue::from_name(name)))
+        .map(|(mask, value)| (canonical_board(mask), value))
+        .collect();
+    (0..1 << NUM_SQUARES)
+        .map(|mask| {
+            if is_dead(mask) {
+                NotaktoValue::ONE
+            } else {
+                named_values[&canonical_board(mask)]
+            }
+        })
+        .collect()
+}
+
+// Gets the bit mask of a board written a row at a time, such as `X.. .X. ...`.
+fn parse_board(board: &str) -> u16 {
+    board
+        .chars()
+        .filter(|square| *square != ' ')
+        .enumerate()
+        .filter(|(_, square)| *square == 'X')
+        .fold(0, |mask, (square, _)| mask | 1 << square)
+}
+
+// Gets the smallest mask of all the rotations and reflections of the board.
+fn canonical_board(mask: u16) -> u16 {
+    ALL_SYMMETRIES
+        .iter()
+        .map(|symmetry| symmetry.apply_to_mask(mask))
+        .min()
+        .unwrap()
+}
+
+// Indicates if the board has three in a row.
+fn is_dead(mask: u16) -> bool {
+    lines().any(|line| {
+        line.iter()
+            .all(|position| mask & square_bit(*position) != 0)
+    })
+}
+
+// Converts a position on a single board to the position shared by all boards.
+fn to_global_position(board_index: usize, position: ttt::game::Position) -> ttt::game::Position {
+    ttt::game::Position {
+        row: position.row,
+        column: board_index as i32 * BOARD_COLUMNS + position.column,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    // Fills the top row of the board, killing it.
+    fn kill_board(game: &mut NotaktoGame, board_index: usize) {
+        for column in 0..3 {
+            game.do_move(to_global_position(board_index, position(0, column)));
+        }
+    }
+
+    #[test]
+    fn notakto_game_new_should_have_all_squares_free() {
+        let game = NotaktoGame::new();
+
+        assert_eq!(game.state(), ttt::game::State::PlayerXMove);
+        assert_eq!(game.moves().len(), NUM_SQUARES * NOTAKTO_BOARDS);
+    }
+
+    #[test]
+    fn notakto_game_do_move_when_three_in_a_row_should_kill_board() {
+        let mut game = NotaktoGame::new();
+
+        kill_board(&mut game, 1);
+
+        let expected_line: HashSet<_> = vec![position(0, 3), position(0, 4), position(0, 5)]
+            .into_iter()
+            .collect();
+        assert_eq!(game.dead_line(1), Some(expected_line));
+        assert!(!game.is_free(position(1, 4)));
+        assert!(game.is_free(position(1, 1)));
+    }
+
+    #[test]
+    fn notakto_game_do_move_when_last_board_killed_should_lose() {
+        let mut game = NotaktoGame::new();
+        kill_board(&mut game, 0);
+        kill_board(&mut game, 1);
+
+        // Player X places the ninth mark, killing the last board.
+        kill_board(&mut game, 2);
+
+        assert_eq!(game.state(), ttt::game::State::PlayerOWin(HashSet::new()));
+    }
+
+    #[test]
+    fn notakto_game_start_next_game_should_let_o_go_first() {
+        let mut game = NotaktoGame::new();
+        kill_board(&mut game, 0);
+
+        let state = game.start_next_game();
+
+        assert_eq!(state, ttt::game::State::PlayerOMove);
+        assert!(game.is_free(position(0, 0)));
+    }
+
+    #[test]
+    fn canonical_board_when_rotated_should_match() {
+        let corner = square_bit(position(0, 0));
+        let other_corner = square_bit(position(2, 2));
+
+        assert_eq!(canonical_board(corner), canonical_board(other_corner));
+    }
+
+    #[test]
+    fn notakto_value_mul_should_follow_relations() {
+        let (a, b, c, d) = (
+            NotaktoValue::A,
+            NotaktoValue::B,
+            NotaktoValue::C,
+            NotaktoValue::D,
+        );
+
+        assert_eq!(a * a, NotaktoValue::ONE);
+        assert_eq!(b * b * b, b);
+        assert_eq!(b * b * c, c);
+        assert_eq!(c * c * c, a * c * c);
+        assert_eq!(b * b * d, d);
+        assert_eq!(c * d, a * d);
+        assert_eq!(d * d, c * c);
+    }
+
+    #[test]
+    fn notakto_value_is_losing_should_match_losing_set() {
+        let losing_names = ["a", "bb", "bc", "cc"];
+        let other_names = ["1", "b", "ab", "c", "ac", "d", "ad", "abb", "abc", "acc"];
+
+        assert!(losing_names
+            .iter()
+            .all(|name| NotaktoValue::from_name(name).is_losing()));
+        assert!(!other_names
+            .iter()
+            .any(|name| NotaktoValue::from_name(name).is_losing()));
+    }
+
+    #[test]
+    fn board_values_when_empty_or_center_should_be_c_or_cc() {
+        let center = square_bit(position(1, 1));
+
+        assert_eq!(BOARD_VALUES[0], NotaktoValue::C);
+        assert_eq!(
+            BOARD_VALUES[usize::from(center)],
+            NotaktoValue::from_name("cc")
+        );
+    }
+
+    #[test]
+    fn board_values_when_dead_should_be_one() {
+        let top_row = parse_board("XXX ... ...");
+
+        assert_eq!(BOARD_VALUES[usize::from(top_row)], NotaktoValue::ONE);
+    }
+
+    #[test]
+    fn notakto_opponent_get_move_when_one_empty_board_left_should_take_center() {
+        let mut game = NotaktoGame::new();
+        kill_board(&mut game, 0);
+        kill_board(&mut game, 1);
+        let opponent = NotaktoOpponent::new(ttt::ai::Difficulty::Hard);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, Some(position(1, 7)));
+    }
+
+    #[test]
+    fn notakto_opponent_get_move_when_game_over_should_return_none() {
+        let mut game = NotaktoGame::new();
+        kill_board(&mut game, 0);
+        kill_board(&mut game, 1);
+        kill_board(&mut game, 2);
+        let opponent = NotaktoOpponent::new(ttt::ai::Difficulty::Hard);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, None);
+    }
+}
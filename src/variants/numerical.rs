@@ -0,0 +1,467 @@
+use contracts::*;
+use open_ttt_lib as ttt;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+use super::*;
+
+// The value a line's numbers must add up to for the line to win.
+const WINNING_SUM: u8 = 15;
+// The largest number that can be placed on the board.
+const MAX_NUMBER: u8 = 9;
+// The number of squares on the board.
+const NUM_SQUARES: usize = 9;
+// How many moves ahead the AI looks when there are too many free squares to
+// search the rest of the game.
+const SHALLOW_SEARCH_DEPTH: usize = 4;
+// When this many or fewer squares are free the AI searches to the end of the game.
+const FULL_SEARCH_FREE_SQUARES: usize = 7;
+// Score given to a won position, before adjusting for how quickly it is reached.
+const WIN_SCORE: i32 = 100;
+
+/// Ron Graham's numerical tic-tac-toe.
+///
+/// One player owns the odd numbers 1, 3, 5, 7, and 9 while the other player
+/// owns the even numbers 2, 4, 6, and 8. Players take turns placing one of their
+/// unused numbers in a free square. The first player to complete a line of
+/// three numbers adding up to 15 wins, no matter who placed the other numbers.
+///
+/// The player with the odd numbers always goes first. Starting the next game
+/// swaps the numbers each player owns so the players alternate going first.
+#[derive(Clone, Debug)]
+pub struct NumericalGame {
+    // Indicates the player that placed the number in each square.
+    board: ttt::board::Board,
+    // The number in each square, indexed by row then column.
+    numbers: [Option<u8>; NUM_SQUARES],
+    state: ttt::game::State,
+    // True if player X owns the odd numbers.
+    x_owns_odds: bool,
+}
+
+impl NumericalGame {
+    /// Creates a new game where player X owns the odd numbers.
+    pub fn new() -> Self {
+        Self {
+            board: ttt::board::Board::new(ttt::board::Size {
+                rows: 3,
+                columns: 3,
+            }),
+            numbers: [None; NUM_SQUARES],
+            state: ttt::game::State::PlayerXMove,
+            x_owns_odds: true,
+        }
+    }
+
+    /// Gets the board, which indicates the player that placed each number.
+    pub fn board(&self) -> &ttt::board::Board {
+        &self.board
+    }
+
+    /// Gets the current state of the game.
+    pub fn state(&self) -> ttt::game::State {
+        self.state.clone()
+    }
+
+    /// Gets the number at the provided position, if any.
+    pub fn number(&self, position: ttt::game::Position) -> Option<u8> {
+        square_index(position).and_then(|index| self.numbers[index])
+    }
+
+    /// Indicates if the current player owns the odd numbers.
+    ///
+    /// If the game is over this indicates if the player who made the last
+    /// move owns the odd numbers.
+    pub fn is_odd_players_turn(&self) -> bool {
+        // The odd player goes first, so they always have placed the same
+        // number of marks as the even player when it is their turn.
+        self.num_moves() % 2 != 1
+    }
+
+    /// Gets the unused numbers available to the current player, smallest first.
+    pub fn available_numbers(&self) -> Vec<u8> {
+        if self.state.is_game_over() {
+            return Vec::new();
+        }
+
+        let first = if self.is_odd_players_turn() { 1 } else { 2 };
+        (first..=MAX_NUMBER)
+            .step_by(2)
+            .filter(|number| !self.numbers.contains(&Some(*number)))
+            .collect()
+    }
+
+    /// Indicates if the position is free for a number to be placed in it.
+    pub fn is_free(&self, position: ttt::game::Position) -> bool {
+        !self.state.is_game_over() && self.board.get(position) == Some(ttt::board::Owner::None)
+    }
+
+    /// Indicates if the current player can place the number at the position.
+    pub fn can_move(&self, position: ttt::game::Position, number: u8) -> bool {
+        self.is_free(position) && self.available_numbers().contains(&number)
+    }
+
+    /// Gets all the moves available to the current player.
+    pub fn moves(&self) -> Vec<(ttt::game::Position, u8)> {
+        let numbers = self.available_numbers();
+        self.board
+            .iter()
+            .filter(|(_position, owner)| *owner == ttt::board::Owner::None)
+            .flat_map(|(position, _owner)| numbers.iter().map(move |number| (position, *number)))
+            .collect()
+    }
+
+    /// Places the number at the position for the current player.
+    ///
+    /// The new state of the game is returned.
+    #[pre(self.can_move(position, number))]
+    pub fn do_move(&mut self, position: ttt::game::Position, number: u8) -> ttt::game::State {
+        let is_x_move = self.state == ttt::game::State::PlayerXMove;
+        let owner = if is_x_move {
+            ttt::board::Owner::PlayerX
+        } else {
+            ttt::board::Owner::PlayerO
+        };
+        *self.board.get_mut(position).unwrap() = owner;
+        self.numbers[square_index(position).unwrap()] = Some(number);
+
+        let winning_positions = self.winning_positions();
+        self.state = if !winning_positions.is_empty() {
+            if is_x_move {
+                ttt::game::State::PlayerXWin(winning_positions)
+            } else {
+                ttt::game::State::PlayerOWin(winning_positions)
+            }
+        } else if self.num_moves() == NUM_SQUARES {
+            ttt::game::State::CatsGame
+        } else if is_x_move {
+            ttt::game::State::PlayerOMove
+        } else {
+            ttt::game::State::PlayerXMove
+        };
+
+        self.state()
+    }
+
+    /// Starts the next game.
+    ///
+    /// The players swap numbers so the player who went second in the last
+    /// game owns the odd numbers and goes first.
+    pub fn start_next_game(&mut self) -> ttt::game::State {
+        let x_owns_odds = !self.x_owns_odds;
+        *self = Self::new();
+        self.x_owns_odds = x_owns_odds;
+        if !x_owns_odds {
+            self.state = ttt::game::State::PlayerOMove;
+        }
+
+        self.state()
+    }
+
+    // Gets the number of numbers that have been placed on the board.
+    fn num_moves(&self) -> usize {
+        self.numbers
+            .iter()
+            .filter(|number| number.is_some())
+            .count()
+    }
+
+    // Gets the positions of all lines whose numbers add up to the winning sum.
+    fn winning_positions(&self) -> HashSet<ttt::game::Position> {
+        let mut winning_positions = HashSet::new();
+        for line in lines() {
+            let numbers: Vec<u8> = line.iter().filter_map(|p| self.number(*p)).collect();
+            if numbers.len() == line.len() && numbers.iter().sum::<u8>() == WINNING_SUM {
+                winning_positions.extend(line.iter());
+            }
+        }
+
+        winning_positions
+    }
+}
+
+impl Default for NumericalGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// AI opponent for numerical tic-tac-toe.
+///
+/// The opponent searches ahead for winning lines using negamax with alpha-beta
+/// pruning. Near the start of the game only a few moves are searched, once
+/// enough squares are filled the opponent searches to the end of the game.
+#[derive(Clone, Debug)]
+pub struct NumericalOpponent {
+    mistake_probability: f64,
+}
+
+impl NumericalOpponent {
+    /// Creates a new opponent with the provided difficulty.
+    pub fn new(difficulty: ttt::ai::Difficulty) -> Self {
+        Self {
+            mistake_probability: mistake_probability(difficulty),
+        }
+    }
+
+    /// Gets the position and number the AI opponent wishes to play.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_move(&self, game: &NumericalGame) -> Option<(ttt::game::Position, u8)> {
+        let moves = game.moves();
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.mistake_probability) {
+            return moves.choose(&mut rng).copied();
+        }
+
+        let board = SearchBoard::from(game);
+        let depth = if board.num_free() <= FULL_SEARCH_FREE_SQUARES {
+            board.num_free()
+        } else {
+            SHALLOW_SEARCH_DEPTH
+        };
+
+        // Score each move and pick randomly between the best ones so the
+        // opponent does not always play the same game.
+        let mut best_score = i32::MIN;
+        let mut best_moves = Vec::new();
+        for (position, number) in moves {
+            let mut next_board = board;
+            next_board.place(square_index(position).unwrap(), number);
+            let score = -negamax(&next_board, depth - 1, -i32::MAX, i32::MAX);
+            if score > best_score {
+                best_score = score;
+                best_moves.clear();
+            }
+            if score == best_score {
+                best_moves.push((position, number));
+            }
+        }
+
+        best_moves.choose(&mut rng).copied()
+    }
+}
+
+// Compact copy of the board used when searching for moves.
+#[derive(Copy, Clone, Debug)]
+struct SearchBoard {
+    // The number in each square or zero for free squares.
+    squares: [u8; NUM_SQUARES],
+}
+
+impl SearchBoard {
+    fn num_free(&self) -> usize {
+        self.squares.iter().filter(|number| **number == 0).count()
+    }
+
+    fn place(&mut self, index: usize, number: u8) {
+        self.squares[index] = number;
+    }
+
+    // Gets the unused numbers for the player whose turn it is.
+    fn available_numbers(&self) -> impl Iterator<Item = u8> + '_ {
+        let first = if self.num_free() % 2 == 1 { 1 } else { 2 };
+        (first..=MAX_NUMBER)
+            .step_by(2)
+            .filter(move |number| !self.squares.contains(number))
+    }
+
+    // Indicates if any line's numbers add up to the winning sum.
+    fn has_winning_line(&self) -> bool {
+        LINES.iter().any(|line| {
+            let numbers: Vec<u8> = line
+                .iter()
+                .map(|(row, column)| self.squares[(row * 3 + column) as usize])
+                .collect();
+            !numbers.contains(&0) && numbers.iter().sum::<u8>() == WINNING_SUM
+        })
+    }
+}
+
+impl From<&NumericalGame> for SearchBoard {
+    fn from(game: &NumericalGame) -> Self {
+        let mut squares = [0; NUM_SQUARES];
+        for (square, number) in squares.iter_mut().zip(game.numbers.iter()) {
+            *square = number.unwrap_or(0);
+        }
+        Self { squares }
+    }
+}
+
+// Scores the board from the perspective of the player whose turn it is.
+//
+// Positive scores are wins, negative scores are losses, and zero is either a
+// draw or a position whose outcome is unknown within the search depth.
+fn negamax(board: &SearchBoard, depth: usize, mut alpha: i32, beta: i32) -> i32 {
+    // The previous player completing a line means the current player lost.
+    // Losses found sooner are worse than losses found later.
+    if board.has_winning_line() {
+        return -(WIN_SCORE + depth as i32);
+    }
+    if depth == 0 || board.num_free() == 0 {
+        return 0;
+    }
+
+    let numbers: Vec<u8> = board.available_numbers().collect();
+    for index in 0..NUM_SQUARES {
+        if board.squares[index] != 0 {
+            continue;
+        }
+        for number in &numbers {
+            let mut next_board = *board;
+            next_board.place(index, *number);
+            let score = -negamax(&next_board, depth - 1, -beta, -alpha);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+    }
+
+    alpha
+}
+
+// Converts the position to an index into the game's squares.
+fn square_index(position: ttt::game::Position) -> Option<usize> {
+    let in_bounds = (0..3).contains(&position.row) && (0..3).contains(&position.column);
+    if in_bounds {
+        Some((position.row * 3 + position.column) as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    #[test]
+    fn numerical_game_new_should_be_player_x_move_with_odd_numbers() {
+        let game = NumericalGame::new();
+
+        assert_eq!(game.state(), ttt::game::State::PlayerXMove);
+        assert_eq!(game.available_numbers(), vec![1, 3, 5, 7, 9]);
+    }
+
+    #[test]
+    fn numerical_game_do_move_should_give_second_player_even_numbers() {
+        let mut game = NumericalGame::new();
+
+        game.do_move(position(0, 0), 5);
+
+        assert_eq!(game.state(), ttt::game::State::PlayerOMove);
+        assert_eq!(game.available_numbers(), vec![2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn numerical_game_available_numbers_should_not_contain_used_numbers() {
+        let mut game = NumericalGame::new();
+        game.do_move(position(0, 0), 5);
+        game.do_move(position(0, 1), 4);
+
+        let numbers = game.available_numbers();
+
+        assert_eq!(numbers, vec![1, 3, 7, 9]);
+    }
+
+    #[test]
+    fn numerical_game_can_move_when_number_belongs_to_other_player_should_return_false() {
+        let game = NumericalGame::new();
+
+        let can_move = game.can_move(position(0, 0), 2);
+
+        assert!(!can_move);
+    }
+
+    #[test]
+    fn numerical_game_can_move_when_square_is_taken_should_return_false() {
+        let mut game = NumericalGame::new();
+        game.do_move(position(1, 1), 5);
+
+        let can_move = game.can_move(position(1, 1), 2);
+
+        assert!(!can_move);
+    }
+
+    #[test]
+    fn numerical_game_do_move_when_line_sums_to_15_should_win_for_player_completing_line() {
+        let mut game = NumericalGame::new();
+        game.do_move(position(0, 0), 1);
+        game.do_move(position(0, 1), 6);
+        game.do_move(position(2, 2), 9);
+
+        // O completes the top row, 1 + 6 + 8, using one of X's numbers.
+        let state = game.do_move(position(0, 2), 8);
+
+        let mut expected_positions = HashSet::new();
+        expected_positions.insert(position(0, 0));
+        expected_positions.insert(position(0, 1));
+        expected_positions.insert(position(0, 2));
+        assert_eq!(state, ttt::game::State::PlayerOWin(expected_positions));
+    }
+
+    #[test]
+    fn numerical_game_do_move_when_line_does_not_sum_to_15_should_continue() {
+        let mut game = NumericalGame::new();
+        game.do_move(position(0, 0), 1);
+        game.do_move(position(0, 1), 2);
+
+        let state = game.do_move(position(0, 2), 3);
+
+        assert_eq!(state, ttt::game::State::PlayerOMove);
+    }
+
+    #[test]
+    fn numerical_game_start_next_game_should_give_player_o_odd_numbers() {
+        let mut game = NumericalGame::new();
+        game.do_move(position(0, 0), 1);
+
+        let state = game.start_next_game();
+
+        assert_eq!(state, ttt::game::State::PlayerOMove);
+        assert_eq!(game.available_numbers(), vec![1, 3, 5, 7, 9]);
+        assert_eq!(game.number(position(0, 0)), None);
+    }
+
+    #[test]
+    fn numerical_game_moves_should_pair_free_squares_with_available_numbers() {
+        let mut game = NumericalGame::new();
+        game.do_move(position(0, 0), 1);
+
+        let moves = game.moves();
+
+        // 8 free squares and 4 even numbers.
+        assert_eq!(moves.len(), 8 * 4);
+    }
+
+    #[test]
+    fn numerical_opponent_get_move_should_take_winning_move() {
+        let mut game = NumericalGame::new();
+        game.do_move(position(0, 0), 7);
+        game.do_move(position(1, 1), 2);
+        game.do_move(position(2, 0), 3);
+        game.do_move(position(2, 2), 4);
+        // The only way for X to win is completing the left column: 7 + 5 + 3.
+        let opponent = NumericalOpponent::new(ttt::ai::Difficulty::Unbeatable);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, Some((position(1, 0), 5)));
+    }
+
+    #[test]
+    fn numerical_opponent_get_move_when_game_over_should_return_none() {
+        let mut game = NumericalGame::new();
+        game.do_move(position(0, 0), 1);
+        game.do_move(position(0, 1), 6);
+        game.do_move(position(2, 2), 9);
+        game.do_move(position(0, 2), 8);
+        let opponent = NumericalOpponent::new(ttt::ai::Difficulty::Unbeatable);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, None);
+    }
+}
@@ -0,0 +1,445 @@
+use contracts::*;
+use open_ttt_lib as ttt;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+use super::*;
+
+/// The number of rows and columns of the order and chaos board.
+pub const ORDER_AND_CHAOS_SIZE: i32 = 6;
+
+/// The number of identical symbols in a row order needs to win.
+pub const ORDER_AND_CHAOS_WIN_LENGTH: i32 = 5;
+
+// The score of a line of five for each number of identical symbols in it.
+const WINDOW_SCORES: [i64; 5] = [0, 1, 4, 16, 64];
+
+// The score of a position order has won, or chaos has won when negated.
+const WIN_SCORE: i64 = 1_000_000;
+
+/// The roles players take in order and chaos.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Tries to get five identical symbols in a row.
+    Order,
+
+    /// Tries to fill the board without five identical symbols in a row.
+    Chaos,
+}
+
+impl Role {
+    /// Gets the name of the role as shown to the player.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Order => "Order",
+            Self::Chaos => "Chaos",
+        }
+    }
+
+    /// Gets the role the other player takes.
+    pub fn opposite_role(&self) -> Self {
+        match self {
+            Self::Order => Self::Chaos,
+            Self::Chaos => Self::Order,
+        }
+    }
+}
+
+/// Order and chaos, an asymmetric tic-tac-toe variant on a 6 x 6 board.
+///
+/// Instead of owning a symbol, each player takes a role. On their turn, either
+/// player places an X or an O on any free square. Order wins by getting five or
+/// more identical symbols in a row, column, or diagonal. Chaos wins if the board
+/// is filled without this happening, so the game never ends in a draw.
+///
+/// Player X plays order and player O plays chaos in the first game. Order always
+/// moves first, and the players swap roles each game.
+#[derive(Clone, Debug)]
+pub struct OrderAndChaosGame {
+    board: ttt::board::Board,
+    state: ttt::game::State,
+    // Indicates if player X plays order.
+    x_is_order: bool,
+}
+
+impl OrderAndChaosGame {
+    /// Creates a new game where player X plays order and goes first.
+    pub fn new() -> Self {
+        Self {
+            board: ttt::board::Board::new(ttt::board::Size {
+                rows: ORDER_AND_CHAOS_SIZE,
+                columns: ORDER_AND_CHAOS_SIZE,
+            }),
+            state: ttt::game::State::PlayerXMove,
+            x_is_order: true,
+        }
+    }
+
+    /// Gets the board, which indicates the symbol placed on each square.
+    ///
+    /// Unlike other variants, `Owner::PlayerX` means an X symbol is on the square
+    /// regardless of which player placed it.
+    pub fn board(&self) -> &ttt::board::Board {
+        &self.board
+    }
+
+    /// Gets the current state of the game.
+    ///
+    /// When order wins, the winning positions are the identical symbols in a
+    /// row. When chaos wins there is no winning line.
+    pub fn state(&self) -> ttt::game::State {
+        self.state.clone()
+    }
+
+    /// Indicates if player X plays order this game.
+    pub fn is_x_order(&self) -> bool {
+        self.x_is_order
+    }
+
+    /// Gets the role of the player whose turn it is.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn current_role(&self) -> Option<Role> {
+        let is_x_move = match self.state {
+            ttt::game::State::PlayerXMove => true,
+            ttt::game::State::PlayerOMove => false,
+            _ => return None,
+        };
+        if is_x_move == self.x_is_order {
+            Some(Role::Order)
+        } else {
+            Some(Role::Chaos)
+        }
+    }
+
+    /// Indicates if a symbol can be placed at the position.
+    pub fn is_free(&self, position: ttt::game::Position) -> bool {
+        !self.state.is_game_over() && self.board.get(position) == Some(ttt::board::Owner::None)
+    }
+
+    /// Indicates if the provided move is allowed.
+    ///
+    /// Only `Move::PlaceSymbol` with an X or O symbol on a free square is allowed.
+    pub fn is_valid_move(&self, game_move: &Move) -> bool {
+        match *game_move {
+            Move::PlaceSymbol(position, symbol) => {
+                symbol != ttt::board::Owner::None && self.is_free(position)
+            }
+            _ => false,
+        }
+    }
+
+    /// Gets all the moves the current player can make.
+    pub fn moves(&self) -> Vec<Move> {
+        self.board
+            .iter()
+            .map(|(position, _owner)| position)
+            .filter(|position| self.is_free(*position))
+            .flat_map(|position| {
+                vec![
+                    Move::PlaceSymbol(position, ttt::board::Owner::PlayerX),
+                    Move::PlaceSymbol(position, ttt::board::Owner::PlayerO),
+                ]
+            })
+            .collect()
+    }
+
+    /// Performs the provided move returning the new state of the game.
+    #[pre(self.is_valid_move(game_move))]
+    pub fn do_move(&mut self, game_move: &Move) -> ttt::game::State {
+        let (position, symbol) = match *game_move {
+            Move::PlaceSymbol(position, symbol) => (position, symbol),
+            _ => unreachable!("The move was checked to be valid."),
+        };
+        *self.board.get_mut(position).unwrap() = symbol;
+
+        let is_x_move = self.state == ttt::game::State::PlayerXMove;
+        let is_board_full = self
+            .board
+            .iter()
+            .all(|(_position, owner)| owner != ttt::board::Owner::None);
+        self.state =
+            if let Some(line) = longest_line(&self.board, position, ORDER_AND_CHAOS_WIN_LENGTH) {
+                self.win_state(Role::Order, line)
+            } else if is_board_full {
+                self.win_state(Role::Chaos, HashSet::new())
+            } else if is_x_move {
+                ttt::game::State::PlayerOMove
+            } else {
+                ttt::game::State::PlayerXMove
+            };
+
+        self.state()
+    }
+
+    /// Starts the next game, swapping the players' roles.
+    ///
+    /// Order moves first, so the player who was chaos goes first.
+    pub fn start_next_game(&mut self) -> ttt::game::State {
+        let x_is_order = !self.x_is_order;
+        *self = Self::new();
+        self.x_is_order = x_is_order;
+        if !x_is_order {
+            self.state = ttt::game::State::PlayerOMove;
+        }
+
+        self.state()
+    }
+
+    // Gets the win state for the player with the role.
+    fn win_state(
+        &self,
+        role: Role,
+        winning_positions: HashSet<ttt::game::Position>,
+    ) -> ttt::game::State {
+        if (role == Role::Order) == self.x_is_order {
+            ttt::game::State::PlayerXWin(winning_positions)
+        } else {
+            ttt::game::State::PlayerOWin(winning_positions)
+        }
+    }
+}
+
+impl Default for OrderAndChaosGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// AI opponent for order and chaos that can play either role.
+///
+/// The opponent scores each move by looking at every line of five squares on
+/// the board. A line containing both symbols is useless to order, while a line
+/// with more identical symbols is closer to a win. Order picks the move with the
+/// highest score and chaos picks the move with the lowest score.
+#[derive(Clone, Debug)]
+pub struct OrderAndChaosOpponent {
+    mistake_probability: f64,
+}
+
+impl OrderAndChaosOpponent {
+    /// Creates a new opponent with the provided difficulty.
+    pub fn new(difficulty: ttt::ai::Difficulty) -> Self {
+        Self {
+            mistake_probability: mistake_probability(difficulty),
+        }
+    }
+
+    /// Gets the move the AI opponent wishes to make for the role it is playing.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_move(&self, game: &OrderAndChaosGame) -> Option<Move> {
+        let role = game.current_role()?;
+        let moves = game.moves();
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.mistake_probability) {
+            return moves.choose(&mut rng).copied();
+        }
+
+        let scored_moves: Vec<(Move, i64)> = moves
+            .iter()
+            .map(|game_move| {
+                let mut next_game = game.clone();
+                next_game.do_move(game_move);
+                let score = score(&next_game);
+                match role {
+                    Role::Order => (*game_move, score),
+                    Role::Chaos => (*game_move, -score),
+                }
+            })
+            .collect();
+        let best_score = scored_moves.iter().map(|(_, score)| *score).max()?;
+        let best_moves: Vec<Move> = scored_moves
+            .iter()
+            .filter(|(_, score)| *score == best_score)
+            .map(|(game_move, _)| *game_move)
+            .collect();
+
+        best_moves.choose(&mut rng).copied()
+    }
+}
+
+// Scores the game from order's point of view.
+pub(super) fn score(game: &OrderAndChaosGame) -> i64 {
+    match game.current_role() {
+        Some(_) => windows(ORDER_AND_CHAOS_SIZE, ORDER_AND_CHAOS_WIN_LENGTH)
+            .map(|window| {
+                let symbols: Vec<ttt::board::Owner> = window
+                    .iter()
+                    .filter_map(|position| game.board.get(*position))
+                    .filter(|owner| *owner != ttt::board::Owner::None)
+                    .collect();
+                let is_dead = symbols.iter().any(|symbol| *symbol != symbols[0]);
+                if is_dead {
+                    0
+                } else {
+                    WINDOW_SCORES[symbols.len().min(WINDOW_SCORES.len() - 1)]
+                }
+            })
+            .sum(),
+        None if game.is_x_order() => match game.state() {
+            ttt::game::State::PlayerXWin(_) => WIN_SCORE,
+            _ => -WIN_SCORE,
+        },
+        None => match game.state() {
+            ttt::game::State::PlayerOWin(_) => WIN_SCORE,
+            _ => -WIN_SCORE,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    fn x(row: i32, column: i32) -> Move {
+        Move::PlaceSymbol(position(row, column), ttt::board::Owner::PlayerX)
+    }
+
+    fn o(row: i32, column: i32) -> Move {
+        Move::PlaceSymbol(position(row, column), ttt::board::Owner::PlayerO)
+    }
+
+    // Order places four Xs along the bottom row while chaos places Os in the
+    // top right corner, ending with order to move.
+    fn four_in_a_row_game() -> OrderAndChaosGame {
+        let mut game = OrderAndChaosGame::new();
+        let moves = [
+            x(0, 0),
+            o(5, 5),
+            x(0, 1),
+            o(5, 4),
+            x(0, 2),
+            o(4, 5),
+            x(0, 3),
+            o(3, 5),
+        ];
+        for game_move in &moves {
+            game.do_move(game_move);
+        }
+        game
+    }
+
+    #[test]
+    fn windows_should_provide_all_lines_of_five() {
+        let num_windows = windows(ORDER_AND_CHAOS_SIZE, ORDER_AND_CHAOS_WIN_LENGTH).count();
+
+        // 12 rows, 12 columns, and 4 diagonals each way.
+        assert_eq!(num_windows, 32);
+    }
+
+    #[test]
+    fn order_and_chaos_game_new_should_be_orders_move() {
+        let game = OrderAndChaosGame::new();
+
+        assert_eq!(game.state(), ttt::game::State::PlayerXMove);
+        assert_eq!(game.current_role(), Some(Role::Order));
+        assert_eq!(game.moves().len(), 72);
+    }
+
+    #[test]
+    fn order_and_chaos_game_do_move_when_five_in_a_row_should_order_win() {
+        let mut game = four_in_a_row_game();
+
+        let state = game.do_move(&x(0, 4));
+
+        let expected_line: HashSet<_> = (0..5).map(|column| position(0, column)).collect();
+        assert_eq!(state, ttt::game::State::PlayerXWin(expected_line));
+    }
+
+    #[test]
+    fn order_and_chaos_game_do_move_when_chaos_completes_five_should_order_win() {
+        let mut game = four_in_a_row_game();
+        game.do_move(&o(2, 2));
+
+        // Chaos is forced to complete the line of Xs.
+        let state = game.do_move(&x(0, 4));
+
+        assert!(matches!(state, ttt::game::State::PlayerXWin(_)));
+    }
+
+    #[test]
+    fn order_and_chaos_game_do_move_when_board_full_should_chaos_win() {
+        let mut game = OrderAndChaosGame::new();
+
+        // Pairs of symbols alternate along the rows and the pattern shifts each
+        // row, so no five identical symbols are in a row.
+        for row in 0..ORDER_AND_CHAOS_SIZE {
+            for column in 0..ORDER_AND_CHAOS_SIZE {
+                if (column / 2 + row) % 2 == 0 {
+                    game.do_move(&x(row, column));
+                } else {
+                    game.do_move(&o(row, column));
+                }
+            }
+        }
+
+        assert_eq!(game.state(), ttt::game::State::PlayerOWin(HashSet::new()));
+    }
+
+    #[test]
+    fn order_and_chaos_game_is_valid_move_when_square_taken_should_return_false() {
+        let mut game = OrderAndChaosGame::new();
+        game.do_move(&x(2, 3));
+
+        assert!(!game.is_valid_move(&o(2, 3)));
+        assert!(!game.is_valid_move(&Move::Mark(position(1, 1))));
+    }
+
+    #[test]
+    fn order_and_chaos_game_start_next_game_should_swap_roles() {
+        let mut game = OrderAndChaosGame::new();
+        game.do_move(&x(2, 3));
+
+        let state = game.start_next_game();
+
+        assert_eq!(state, ttt::game::State::PlayerOMove);
+        assert!(!game.is_x_order());
+        assert_eq!(game.current_role(), Some(Role::Order));
+    }
+
+    #[test]
+    fn order_and_chaos_opponent_get_move_when_order_should_complete_five() {
+        let game = four_in_a_row_game();
+        let opponent = OrderAndChaosOpponent::new(ttt::ai::Difficulty::Unbeatable);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, Some(x(0, 4)));
+    }
+
+    #[test]
+    fn order_and_chaos_opponent_get_move_when_chaos_should_block_four() {
+        let mut game = OrderAndChaosGame::new();
+        let moves = [
+            x(0, 0),
+            o(5, 5),
+            x(0, 1),
+            o(5, 4),
+            x(0, 2),
+            o(4, 5),
+            x(0, 3),
+        ];
+        for game_move in &moves {
+            game.do_move(game_move);
+        }
+        let opponent = OrderAndChaosOpponent::new(ttt::ai::Difficulty::Unbeatable);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, Some(o(0, 4)));
+    }
+
+    #[test]
+    fn order_and_chaos_opponent_get_move_when_game_over_should_return_none() {
+        let mut game = four_in_a_row_game();
+        game.do_move(&x(0, 4));
+        let opponent = OrderAndChaosOpponent::new(ttt::ai::Difficulty::Unbeatable);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, None);
+    }
+}
@@ -0,0 +1,323 @@
+use lazy_static::lazy_static;
+use open_ttt_lib as ttt;
+use std::collections::HashMap;
+
+use super::*;
+use crate::engine::{square_bit, Symmetry, ALL_SYMMETRIES};
+
+lazy_static! {
+    static ref TABLE: PerfectPlayTable = PerfectPlayTable::new();
+}
+
+// The score and best moves of a position, from the point of view of the
+// player to move.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct TableEntry {
+    score: i8,
+    // The squares of the best moves as a bit mask, turned to match the
+    // position's key.
+    best_moves: u16,
+}
+
+/// Table of how every classic position plays out when both players play
+/// perfectly.
+///
+/// The whole classic game tree is small enough to solve once, after which
+/// scores and best moves are looked up instead of searched for. Positions are
+/// stored from the point of view of the player to move, so games started by
+/// either player share entries, and positions that are rotations or
+/// reflections of each other share a single entry.
+///
+/// Scores are the same as `classic_score`: positive scores are wins, negative
+/// scores are losses, and zero is a cat's game.
+#[derive(Clone, Debug)]
+pub struct PerfectPlayTable {
+    entries: HashMap<u32, TableEntry>,
+}
+
+impl PerfectPlayTable {
+    /// Solves every classic position to build the table.
+    ///
+    /// This takes a moment, so the table shared by the game, which is built
+    /// the first time `PerfectPlayTable::get` is called, should be used
+    /// instead.
+    pub fn new() -> Self {
+        let mut table = Self {
+            entries: HashMap::new(),
+        };
+        table.solve(0, 0);
+        table
+    }
+
+    /// Gets the table shared by the game, building it if needed.
+    pub fn get() -> &'static Self {
+        &TABLE
+    }
+
+    /// Gets the number of positions in the table, not counting positions
+    /// where the game is over.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Indicates if the table has no positions.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Scores the game from the point of view of the player to move.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn score(&self, game: &ttt::game::Game) -> Option<i32> {
+        let (mover, other) = marks(game)?;
+        self.entry(mover, other)
+            .map(|(entry, _)| i32::from(entry.score))
+    }
+
+    /// Scores placing a mark at the position from the point of view of the
+    /// player placing it.
+    ///
+    /// `None` is returned if the game is over or the position is not free.
+    pub fn move_score(&self, game: &ttt::game::Game, position: ttt::game::Position) -> Option<i32> {
+        let (mover, other) = marks(game)?;
+        let bit = square_bit(position);
+        if game.board().get(position) != Some(ttt::board::Owner::None) {
+            return None;
+        }
+        Some(self.solved_move_score(mover, other, bit))
+    }
+
+    /// Gets all the positions where placing a mark scores best for the player
+    /// to move.
+    ///
+    /// No positions are returned if the game is over.
+    pub fn best_moves(&self, game: &ttt::game::Game) -> Vec<ttt::game::Position> {
+        let best_moves = marks(game)
+            .and_then(|(mover, other)| self.entry(mover, other))
+            .map(|(entry, symmetry)| {
+                // A square is one of the best moves if it is turned onto one
+                // of the entry's best moves.
+                (0..NUM_SQUARES)
+                    .map(|square| 1 << square)
+                    .filter(|bit| symmetry.apply_to_mask(*bit) & entry.best_moves != 0)
+                    .fold(0, |mask, bit| mask | bit)
+            })
+            .unwrap_or(0);
+        game.free_positions()
+            .filter(|position| square_bit(*position) & best_moves != 0)
+            .collect()
+    }
+
+    // Looks up the position, along with the symmetry that turns it to match
+    // the entry's key.
+    fn entry(&self, mover: u16, other: u16) -> Option<(TableEntry, Symmetry)> {
+        let (key, symmetry) = canonical_key(mover, other);
+        self.entries.get(&key).map(|entry| (*entry, symmetry))
+    }
+
+    // Scores the position and every position after it, adding them to the
+    // table, and returns the position's score.
+    fn solve(&mut self, mover: u16, other: u16) -> i32 {
+        let (key, symmetry) = canonical_key(mover, other);
+        if let Some(entry) = self.entries.get(&key) {
+            return i32::from(entry.score);
+        }
+
+        let mut best_score = i32::MIN;
+        let mut best_moves = 0;
+        for bit in free_squares(mover, other) {
+            let next_mover = mover | bit;
+            let score = if has_line(next_mover) {
+                1 + free_squares(next_mover, other).count() as i32
+            } else if next_mover | other == FULL_BOARD {
+                0
+            } else {
+                -self.solve(other, next_mover)
+            };
+            if score > best_score {
+                best_score = score;
+                best_moves = 0;
+            }
+            if score == best_score {
+                best_moves |= bit;
+            }
+        }
+
+        let entry = TableEntry {
+            score: best_score as i8,
+            best_moves: symmetry.apply_to_mask(best_moves),
+        };
+        self.entries.insert(key, entry);
+        best_score
+    }
+
+    // Scores placing a mark in the free square using the solved positions.
+    fn solved_move_score(&self, mover: u16, other: u16, bit: u16) -> i32 {
+        let next_mover = mover | bit;
+        if has_line(next_mover) {
+            1 + free_squares(next_mover, other).count() as i32
+        } else if next_mover | other == FULL_BOARD {
+            0
+        } else {
+            let (entry, _) = self
+                .entry(other, next_mover)
+                .expect("Every position after a move is solved.");
+            -i32::from(entry.score)
+        }
+    }
+}
+
+impl Default for PerfectPlayTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The bit mask with every square of the board.
+const FULL_BOARD: u16 = (1 << NUM_SQUARES) - 1;
+
+// Gets the squares marked by the player to move and by the other player.
+//
+// `None` is returned if the game is over.
+fn marks(game: &ttt::game::Game) -> Option<(u16, u16)> {
+    let mover = match game.state() {
+        ttt::game::State::PlayerXMove => ttt::board::Owner::PlayerX,
+        ttt::game::State::PlayerOMove => ttt::board::Owner::PlayerO,
+        _ => return None,
+    };
+    let (mut mover_mask, mut other_mask) = (0, 0);
+    for (position, owner) in game.board().iter() {
+        if owner == mover {
+            mover_mask |= square_bit(position);
+        } else if owner != ttt::board::Owner::None {
+            other_mask |= square_bit(position);
+        }
+    }
+    Some((mover_mask, other_mask))
+}
+
+// Gets the bits of the squares neither player has marked.
+fn free_squares(mover: u16, other: u16) -> impl Iterator<Item = u16> {
+    (0..NUM_SQUARES)
+        .map(|square| 1 << square)
+        .filter(move |bit| (mover | other) & bit == 0)
+}
+
+// Indicates if the marks have three in a row.
+fn has_line(mask: u16) -> bool {
+    lines().any(|line| {
+        line.iter()
+            .all(|position| mask & square_bit(*position) != 0)
+    })
+}
+
+// Gets the smallest key of all the rotations and reflections of the position,
+// along with the symmetry that gives it.
+fn canonical_key(mover: u16, other: u16) -> (u32, Symmetry) {
+    ALL_SYMMETRIES
+        .iter()
+        .map(|symmetry| {
+            let key = u32::from(symmetry.apply_to_mask(mover))
+                | u32::from(symmetry.apply_to_mask(other)) << NUM_SQUARES;
+            (key, *symmetry)
+        })
+        .min_by_key(|(key, _)| *key)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    // A position along with the score of each move, from the point of view of
+    // the player to move.
+    type SolvedGame = (ttt::game::Game, Vec<(ttt::game::Position, i32)>);
+
+    // Scores every move of the game and of every position after it by
+    // searching every move to the end, as minimax does. Each distinct board is
+    // only solved once, but unlike the table no symmetries are used.
+    fn brute_force(game: &ttt::game::Game, solved: &mut HashMap<String, SolvedGame>) -> i32 {
+        let key = format!("{:?} {:?}", board_to_rows(game.board()), game.state());
+        if let Some((_, move_scores)) = solved.get(&key) {
+            return move_scores.iter().map(|(_, score)| *score).max().unwrap();
+        }
+
+        let mut move_scores = Vec::new();
+        for position in game.free_positions() {
+            let mut next_game = game.clone();
+            let score = match next_game.do_move(position).unwrap() {
+                ttt::game::State::PlayerXWin(_) | ttt::game::State::PlayerOWin(_) => {
+                    1 + next_game.free_positions().count() as i32
+                }
+                ttt::game::State::CatsGame => 0,
+                _ => -brute_force(&next_game, solved),
+            };
+            move_scores.push((position, score));
+        }
+        let score = move_scores.iter().map(|(_, score)| *score).max().unwrap();
+        solved.insert(key, (game.clone(), move_scores));
+        score
+    }
+
+    // Solves every position reachable in games started by either player where
+    // the game is not over.
+    fn all_games() -> Vec<SolvedGame> {
+        let mut solved = HashMap::new();
+        let mut game = ttt::game::Game::new();
+        brute_force(&game, &mut solved);
+        game.start_next_game();
+        brute_force(&game, &mut solved);
+        solved.values().cloned().collect()
+    }
+
+    #[test]
+    fn perfect_play_table_should_match_brute_force() {
+        let table = PerfectPlayTable::new();
+
+        for (game, move_scores) in all_games() {
+            let best_score = move_scores.iter().map(|(_, score)| *score).max();
+            let expected_best_moves: HashSet<ttt::game::Position> = move_scores
+                .iter()
+                .filter(|(_, score)| Some(*score) == best_score)
+                .map(|(position, _)| *position)
+                .collect();
+
+            let best_moves: HashSet<ttt::game::Position> =
+                table.best_moves(&game).into_iter().collect();
+
+            assert_eq!(table.score(&game), best_score);
+            assert_eq!(best_moves, expected_best_moves);
+            for (position, score) in move_scores {
+                assert_eq!(table.move_score(&game, position), Some(score));
+            }
+        }
+    }
+
+    #[test]
+    fn perfect_play_table_len_should_reduce_symmetric_positions() {
+        let table = PerfectPlayTable::new();
+
+        let num_games = all_games().len();
+
+        assert!(!table.is_empty());
+        assert!(table.len() * 4 < num_games);
+    }
+
+    #[test]
+    fn perfect_play_table_score_when_game_over_should_return_none() {
+        let mut game = ttt::game::Game::new();
+        for (row, column) in &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+            game.do_move(ttt::game::Position {
+                row: *row,
+                column: *column,
+            })
+            .unwrap();
+        }
+
+        let score = PerfectPlayTable::get().score(&game);
+
+        assert_eq!(score, None);
+        assert!(PerfectPlayTable::get().best_moves(&game).is_empty());
+    }
+}
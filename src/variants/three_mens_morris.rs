@@ -0,0 +1,603 @@
+use contracts::*;
+use lazy_static::lazy_static;
+use open_ttt_lib as ttt;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+
+// The number of marks each player has.
+const MARKS_PER_PLAYER: usize = 3;
+// The game is a draw once the same position occurs this many times.
+const REPETITION_LIMIT: usize = 3;
+// The number of squares on the board.
+const NUM_SQUARES: usize = 9;
+// Values used to represent the contents of a square when searching.
+const EMPTY: u8 = 0;
+const PLAYER_X: u8 = 1;
+const PLAYER_O: u8 = 2;
+
+/// Three men's morris, a tic-tac-toe variant with moving marks.
+///
+/// Each player has three marks. Players take turns placing their marks until
+/// all six are on the board. From then on, a turn consists of moving one of
+/// the player's marks to an adjacent free square. Squares are adjacent if they
+/// are next to each other in a row or column, or if one is the center square
+/// and the other is a corner.
+///
+/// The first player to get three marks in a line wins. A player who cannot
+/// move any of their marks loses. If the same position occurs three times with
+/// the same player to move the game is a draw.
+#[derive(Clone, Debug)]
+pub struct ThreeMensMorrisGame {
+    board: ttt::board::Board,
+    state: ttt::game::State,
+    // Indicates if player X made the first move.
+    x_started: bool,
+    // How many times each position has occurred, used to detect repetition.
+    position_counts: HashMap<u32, usize>,
+}
+
+impl ThreeMensMorrisGame {
+    /// Creates a new game where player X goes first.
+    pub fn new() -> Self {
+        Self {
+            board: ttt::board::Board::new(ttt::board::Size {
+                rows: 3,
+                columns: 3,
+            }),
+            state: ttt::game::State::PlayerXMove,
+            x_started: true,
+            position_counts: HashMap::new(),
+        }
+    }
+
+    /// Gets the board, which indicates the player that owns each square.
+    pub fn board(&self) -> &ttt::board::Board {
+        &self.board
+    }
+
+    /// Gets the current state of the game.
+    pub fn state(&self) -> ttt::game::State {
+        self.state.clone()
+    }
+
+    /// Indicates if the current player still has marks to place.
+    ///
+    /// Once all the player's marks are placed, they must move them instead.
+    pub fn is_placing(&self) -> bool {
+        let cells = self.cells();
+        is_placing(&cells, self.mover())
+    }
+
+    /// Indicates if the position is a free square on the board.
+    pub fn is_free(&self, position: ttt::game::Position) -> bool {
+        !self.state.is_game_over() && self.board.get(position) == Some(ttt::board::Owner::None)
+    }
+
+    /// Indicates if the current player owns the mark at the position.
+    pub fn is_current_players_mark(&self, position: ttt::game::Position) -> bool {
+        let cells = self.cells();
+        !self.state.is_game_over()
+            && square_index(position).map(|index| cells[index]) == Some(self.mover())
+    }
+
+    /// Indicates if the current player is allowed to make the provided move.
+    pub fn is_valid_move(&self, game_move: &Move) -> bool {
+        !self.state.is_game_over() && self.moves().contains(game_move)
+    }
+
+    /// Gets all the moves available to the current player.
+    pub fn moves(&self) -> Vec<Move> {
+        if self.state.is_game_over() {
+            Vec::new()
+        } else {
+            legal_moves(&self.cells(), self.mover())
+        }
+    }
+
+    /// Performs the move for the current player returning the new state of the game.
+    #[pre(self.is_valid_move(game_move))]
+    pub fn do_move(&mut self, game_move: &Move) -> ttt::game::State {
+        let mover = self.mover();
+        let cells = apply_move(&self.cells(), mover, game_move);
+        for (index, cell) in cells.iter().enumerate() {
+            *self.board.get_mut(index_to_position(index)).unwrap() = cell_to_owner(*cell);
+        }
+
+        let next_mover = opponent(mover);
+        let winning_positions = line_positions(&cells, mover);
+        self.state = if !winning_positions.is_empty() {
+            win_state(mover, winning_positions)
+        } else if legal_moves(&cells, next_mover).is_empty() {
+            // The next player cannot move, so they lose.
+            win_state(mover, HashSet::new())
+        } else if self.record_position(&cells, next_mover) >= REPETITION_LIMIT {
+            ttt::game::State::CatsGame
+        } else if next_mover == PLAYER_X {
+            ttt::game::State::PlayerXMove
+        } else {
+            ttt::game::State::PlayerOMove
+        };
+
+        self.state()
+    }
+
+    /// Starts the next game, alternating which player goes first.
+    pub fn start_next_game(&mut self) -> ttt::game::State {
+        let x_started = !self.x_started;
+        *self = Self::new();
+        self.x_started = x_started;
+        if !x_started {
+            self.state = ttt::game::State::PlayerOMove;
+        }
+
+        self.state()
+    }
+
+    // Gets the contents of each square.
+    fn cells(&self) -> [u8; NUM_SQUARES] {
+        let mut cells = [EMPTY; NUM_SQUARES];
+        for (position, owner) in self.board.iter() {
+            if let Some(index) = square_index(position) {
+                cells[index] = match owner {
+                    ttt::board::Owner::PlayerX => PLAYER_X,
+                    ttt::board::Owner::PlayerO => PLAYER_O,
+                    ttt::board::Owner::None => EMPTY,
+                };
+            }
+        }
+        cells
+    }
+
+    // Gets the player whose turn it is, or who made the last move if the game is over.
+    fn mover(&self) -> u8 {
+        match self.state {
+            ttt::game::State::PlayerXMove | ttt::game::State::PlayerXWin(_) => PLAYER_X,
+            ttt::game::State::PlayerOMove | ttt::game::State::PlayerOWin(_) => PLAYER_O,
+            ttt::game::State::CatsGame => EMPTY,
+        }
+    }
+
+    // Records another occurrence of the position, returning how many times it has occurred.
+    fn record_position(&mut self, cells: &[u8; NUM_SQUARES], mover: u8) -> usize {
+        let count = self
+            .position_counts
+            .entry(position_key(cells, mover))
+            .or_insert(0);
+        *count += 1;
+        *count
+    }
+}
+
+impl Default for ThreeMensMorrisGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// AI opponent for three men's morris.
+///
+/// Unlike classic tic-tac-toe, marks can move back and forth forever, so the
+/// game's positions form cycles instead of a tree. Every position is solved
+/// once, when the first opponent plays, using retrograde analysis: positions
+/// where the player to move has lost are found first, then the analysis works
+/// backwards to find the positions that lead to them. Positions that are never
+/// solved can only be reached by cycling forever, so they are draws.
+#[derive(Clone, Debug)]
+pub struct ThreeMensMorrisOpponent {
+    mistake_probability: f64,
+}
+
+impl ThreeMensMorrisOpponent {
+    /// Creates a new opponent with the provided difficulty.
+    pub fn new(difficulty: ttt::ai::Difficulty) -> Self {
+        Self {
+            mistake_probability: mistake_probability(difficulty),
+        }
+    }
+
+    /// Gets the move the AI opponent wishes to make.
+    ///
+    /// `None` is returned if the game is over.
+    pub fn get_move(&self, game: &ThreeMensMorrisGame) -> Option<Move> {
+        let moves = game.moves();
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.mistake_probability) {
+            return moves.choose(&mut rng).copied();
+        }
+
+        // Pick randomly from the moves with the best score so the opponent
+        // does not always play the same game.
+        let cells = game.cells();
+        let mover = game.mover();
+        let mut best_score = i32::MIN;
+        let mut best_moves = Vec::new();
+        for game_move in moves {
+            let next_cells = apply_move(&cells, mover, &game_move);
+            let score = self.score(&next_cells, opponent(mover));
+            if score > best_score {
+                best_score = score;
+                best_moves.clear();
+            }
+            if score == best_score {
+                best_moves.push(game_move);
+            }
+        }
+
+        best_moves.choose(&mut rng).copied()
+    }
+
+    // Scores the position for the player that just moved. Quick wins and
+    // slow losses are preferred.
+    fn score(&self, cells: &[u8; NUM_SQUARES], next_mover: u8) -> i32 {
+        const WIN_SCORE: i32 = 1000;
+        match OUTCOMES.get(&position_key(cells, next_mover)) {
+            Some(Outcome::Loss(plies)) => WIN_SCORE - i32::from(*plies),
+            Some(Outcome::Win(plies)) => -WIN_SCORE + i32::from(*plies),
+            None => 0,
+        }
+    }
+}
+
+// The outcome of a position for the player to move when both players play
+// perfectly, along with the number of moves until the game ends.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Outcome {
+    Win(u16),
+    Loss(u16),
+}
+
+lazy_static! {
+    // The solved outcome of each position for the player to move, which is
+    // only solved the first time it is needed.
+    static ref OUTCOMES: HashMap<u32, Outcome> = solve();
+}
+
+// Solves all positions using retrograde analysis. Draws are left out.
+fn solve() -> HashMap<u32, Outcome> {
+    // Gather every position where neither player has more than their allowed marks.
+    let mut positions = Vec::new();
+    for code in 0..3_u32.pow(NUM_SQUARES as u32) {
+        let cells = decode_cells(code);
+        if count(&cells, PLAYER_X) <= MARKS_PER_PLAYER
+            && count(&cells, PLAYER_O) <= MARKS_PER_PLAYER
+        {
+            positions.push((cells, PLAYER_X));
+            positions.push((cells, PLAYER_O));
+        }
+    }
+
+    // The player to move has lost if their opponent has a line or they cannot move.
+    let mut outcomes = HashMap::new();
+    for (cells, mover) in &positions {
+        let is_lost = !line_positions(cells, opponent(*mover)).is_empty()
+            || legal_moves(cells, *mover).is_empty();
+        if is_lost {
+            outcomes.insert(position_key(cells, *mover), Outcome::Loss(0));
+        }
+    }
+
+    // Work backwards one move at a time. Updates are applied after each pass
+    // so the number of moves to the end of the game is the shortest possible.
+    loop {
+        let mut updates = Vec::new();
+        for (cells, mover) in &positions {
+            let key = position_key(cells, *mover);
+            if outcomes.contains_key(&key) {
+                continue;
+            }
+            if let Some(outcome) = backed_up_outcome(&outcomes, cells, *mover) {
+                updates.push((key, outcome));
+            }
+        }
+
+        if updates.is_empty() {
+            break;
+        }
+        outcomes.extend(updates);
+    }
+
+    outcomes
+}
+
+// Determines the outcome of a position from the already solved outcomes of its moves.
+//
+// `None` is returned if the outcome cannot be determined yet.
+fn backed_up_outcome(
+    outcomes: &HashMap<u32, Outcome>,
+    cells: &[u8; NUM_SQUARES],
+    mover: u8,
+) -> Option<Outcome> {
+    let mut fastest_win = None;
+    let mut slowest_loss = Some(0);
+    for game_move in legal_moves(cells, mover) {
+        let next_cells = apply_move(cells, mover, &game_move);
+        match outcomes.get(&position_key(&next_cells, opponent(mover))) {
+            // The opponent losing means this position is a win.
+            Some(Outcome::Loss(plies)) => {
+                fastest_win = Some(fastest_win.map_or(plies + 1, |win: u16| win.min(plies + 1)));
+            }
+            Some(Outcome::Win(plies)) => {
+                slowest_loss = slowest_loss.map(|loss: u16| loss.max(plies + 1));
+            }
+            None => slowest_loss = None,
+        }
+    }
+
+    fastest_win
+        .map(Outcome::Win)
+        .or_else(|| slowest_loss.map(Outcome::Loss))
+}
+
+// Indicates if the mover still has marks to place.
+fn is_placing(cells: &[u8; NUM_SQUARES], mover: u8) -> bool {
+    count(cells, mover) < MARKS_PER_PLAYER
+}
+
+// Gets the moves available to the mover.
+fn legal_moves(cells: &[u8; NUM_SQUARES], mover: u8) -> Vec<Move> {
+    let free_indices = (0..NUM_SQUARES).filter(|index| cells[*index] == EMPTY);
+    if is_placing(cells, mover) {
+        free_indices
+            .map(|index| Move::Mark(index_to_position(index)))
+            .collect()
+    } else {
+        let free_indices: Vec<usize> = free_indices.collect();
+        let mut moves = Vec::new();
+        for from in (0..NUM_SQUARES).filter(|index| cells[*index] == mover) {
+            for to in free_indices.iter().filter(|to| is_adjacent(from, **to)) {
+                moves.push(Move::Slide(index_to_position(from), index_to_position(*to)));
+            }
+        }
+        moves
+    }
+}
+
+// Gets the squares after the mover makes the move.
+fn apply_move(cells: &[u8; NUM_SQUARES], mover: u8, game_move: &Move) -> [u8; NUM_SQUARES] {
+    let mut cells = *cells;
+    match *game_move {
+        Move::Mark(position) => cells[square_index(position).unwrap()] = mover,
+        Move::Slide(from, to) => {
+            cells[square_index(from).unwrap()] = EMPTY;
+            cells[square_index(to).unwrap()] = mover;
+        }
+        Move::Number(_, _) | Move::PlaceSymbol(_, _) => {
+            unreachable!("Only marks are used in three men's morris.")
+        }
+    }
+    cells
+}
+
+// Indicates if marks can move between the two squares.
+fn is_adjacent(from: usize, to: usize) -> bool {
+    const CENTER: usize = 4;
+    let (from_row, from_column) = (from / 3, from % 3);
+    let (to_row, to_column) = (to / 3, to % 3);
+    let row_distance = (from_row as i32 - to_row as i32).abs();
+    let column_distance = (from_column as i32 - to_column as i32).abs();
+    let is_orthogonal = row_distance + column_distance == 1;
+    let is_diagonal_through_center =
+        row_distance == 1 && column_distance == 1 && (from == CENTER || to == CENTER);
+    is_orthogonal || is_diagonal_through_center
+}
+
+// Gets the positions in the player's completed lines.
+fn line_positions(cells: &[u8; NUM_SQUARES], player: u8) -> HashSet<ttt::game::Position> {
+    let mut positions = HashSet::new();
+    for line in lines() {
+        let is_complete = line
+            .iter()
+            .all(|position| cells[square_index(*position).unwrap()] == player);
+        if is_complete {
+            positions.extend(line.iter());
+        }
+    }
+    positions
+}
+
+fn count(cells: &[u8; NUM_SQUARES], player: u8) -> usize {
+    cells.iter().filter(|cell| **cell == player).count()
+}
+
+fn opponent(player: u8) -> u8 {
+    if player == PLAYER_X {
+        PLAYER_O
+    } else {
+        PLAYER_X
+    }
+}
+
+fn win_state(player: u8, positions: HashSet<ttt::game::Position>) -> ttt::game::State {
+    if player == PLAYER_X {
+        ttt::game::State::PlayerXWin(positions)
+    } else {
+        ttt::game::State::PlayerOWin(positions)
+    }
+}
+
+fn cell_to_owner(cell: u8) -> ttt::board::Owner {
+    match cell {
+        PLAYER_X => ttt::board::Owner::PlayerX,
+        PLAYER_O => ttt::board::Owner::PlayerO,
+        _ => ttt::board::Owner::None,
+    }
+}
+
+// Encodes the squares and the player to move as a unique number.
+fn position_key(cells: &[u8; NUM_SQUARES], mover: u8) -> u32 {
+    let code = cells
+        .iter()
+        .rev()
+        .fold(0, |code, cell| code * 3 + u32::from(*cell));
+    code * 2 + u32::from(mover == PLAYER_X)
+}
+
+// Converts a base three number back into the contents of each square.
+fn decode_cells(mut code: u32) -> [u8; NUM_SQUARES] {
+    let mut cells = [EMPTY; NUM_SQUARES];
+    for cell in cells.iter_mut() {
+        *cell = (code % 3) as u8;
+        code /= 3;
+    }
+    cells
+}
+
+// Converts the position to an index into the game's squares.
+fn square_index(position: ttt::game::Position) -> Option<usize> {
+    let in_bounds = (0..3).contains(&position.row) && (0..3).contains(&position.column);
+    if in_bounds {
+        Some((position.row * 3 + position.column) as usize)
+    } else {
+        None
+    }
+}
+
+fn index_to_position(index: usize) -> ttt::game::Position {
+    ttt::game::Position {
+        row: (index / 3) as i32,
+        column: (index % 3) as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::position;
+
+    // Builds a game where both players have placed their marks without
+    // making a line. X is to move and can win by moving into the top right.
+    //
+    // ```text
+    // X X .
+    // O O X
+    // . . O
+    // ```
+    fn game_after_placing() -> ThreeMensMorrisGame {
+        let mut game = ThreeMensMorrisGame::new();
+        for mark in &[(0, 0), (1, 1), (0, 1), (1, 0), (1, 2), (2, 2)] {
+            game.do_move(&Move::Mark(position(mark.0, mark.1)));
+        }
+        game
+    }
+
+    #[test]
+    fn three_mens_morris_game_new_should_be_placing() {
+        let game = ThreeMensMorrisGame::new();
+
+        assert_eq!(game.state(), ttt::game::State::PlayerXMove);
+        assert!(game.is_placing());
+    }
+
+    #[test]
+    fn three_mens_morris_game_when_all_marks_placed_should_not_be_placing() {
+        let game = game_after_placing();
+
+        assert_eq!(game.state(), ttt::game::State::PlayerXMove);
+        assert!(!game.is_placing());
+    }
+
+    #[test]
+    fn three_mens_morris_game_is_valid_move_when_moving_should_not_allow_placing() {
+        let game = game_after_placing();
+
+        let is_valid = game.is_valid_move(&Move::Mark(position(2, 0)));
+
+        assert!(!is_valid);
+    }
+
+    #[test]
+    fn three_mens_morris_game_is_valid_move_when_adjacent_should_return_true() {
+        let game = game_after_placing();
+
+        let is_valid = game.is_valid_move(&Move::Slide(position(0, 1), position(0, 2)));
+
+        assert!(is_valid);
+    }
+
+    #[test]
+    fn three_mens_morris_game_is_valid_move_when_diagonal_not_through_center_should_return_false() {
+        let game = game_after_placing();
+
+        let is_valid = game.is_valid_move(&Move::Slide(position(1, 2), position(2, 1)));
+
+        assert!(!is_valid);
+    }
+
+    #[test]
+    fn three_mens_morris_game_is_valid_move_when_moving_opponents_mark_should_return_false() {
+        let game = game_after_placing();
+
+        let is_valid = game.is_valid_move(&Move::Slide(position(2, 2), position(2, 1)));
+
+        assert!(!is_valid);
+    }
+
+    #[test]
+    fn is_adjacent_when_corner_and_center_should_return_true() {
+        let is_adjacent = is_adjacent(0, 4);
+
+        assert!(is_adjacent);
+    }
+
+    #[test]
+    fn three_mens_morris_game_do_move_when_slide_completes_line_should_win() {
+        let mut game = game_after_placing();
+
+        let state = game.do_move(&Move::Slide(position(1, 2), position(0, 2)));
+
+        assert!(matches!(state, ttt::game::State::PlayerXWin(_)));
+    }
+
+    #[test]
+    fn three_mens_morris_game_do_move_when_position_occurs_three_times_should_be_cats_game() {
+        let mut game = game_after_placing();
+        let there_and_back = [
+            Move::Slide(position(0, 1), position(0, 2)),
+            Move::Slide(position(2, 2), position(2, 1)),
+            Move::Slide(position(0, 2), position(0, 1)),
+            Move::Slide(position(2, 1), position(2, 2)),
+        ];
+
+        // The position after placing occurs again at the end of each loop.
+        let mut state = game.state();
+        for game_move in there_and_back.iter().cycle().take(there_and_back.len() * 2) {
+            state = game.do_move(game_move);
+        }
+
+        assert_eq!(state, ttt::game::State::CatsGame);
+    }
+
+    #[test]
+    fn three_mens_morris_game_start_next_game_should_let_o_go_first() {
+        let mut game = game_after_placing();
+
+        let state = game.start_next_game();
+
+        assert_eq!(state, ttt::game::State::PlayerOMove);
+        assert!(game.is_free(position(0, 0)));
+    }
+
+    #[test]
+    fn three_mens_morris_opponent_get_move_should_take_winning_move() {
+        let game = game_after_placing();
+        let opponent = ThreeMensMorrisOpponent::new(ttt::ai::Difficulty::Unbeatable);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, Some(Move::Slide(position(1, 2), position(0, 2))));
+    }
+
+    #[test]
+    fn three_mens_morris_opponent_get_move_when_game_over_should_return_none() {
+        let mut game = game_after_placing();
+        game.do_move(&Move::Slide(position(1, 2), position(0, 2)));
+        let opponent = ThreeMensMorrisOpponent::new(ttt::ai::Difficulty::Unbeatable);
+
+        let ai_move = opponent.get_move(&game);
+
+        assert_eq!(ai_move, None);
+    }
+}